@@ -1,10 +1,26 @@
 // partitionfinder/capi.rs
 
-use partitioner::Partitioner;
+use euclid::Point2D;
+use partitioner::{PartitionMesh, Partitioner};
 use std::mem;
+use std::ops::ControlFlow;
+use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
 use std::slice;
-use {Bezieroid, ControlPoints, Endpoint, Path, Subpath};
+use {Bezieroid, ControlPoints, Endpoint, Path, Quad, Subpath};
 
+/// A rectangle with a plain C layout, since `euclid::Rect` doesn't promise one.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PfRectF32 {
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Returns null if the partitioner couldn't be created.
 #[no_mangle]
 pub unsafe extern fn pf_partitioner_new(endpoints: *const Endpoint,
                                         endpoint_count: u32,
@@ -15,15 +31,18 @@ pub unsafe extern fn pf_partitioner_new(endpoints: *const Endpoint,
                                         paths: *const Path,
                                         path_count: u32)
                                         -> *mut Partitioner<'static> {
-    let mut partitioner =
+    // The box is dropped during unwinding if anything panics, so nothing leaks.
+    let partitioner = panic::catch_unwind(AssertUnwindSafe(|| {
         Box::new(Partitioner::new(slice::from_raw_parts(endpoints, endpoint_count as usize),
                                   slice::from_raw_parts(control_points,
                                                         control_points_count as usize),
                                   slice::from_raw_parts(subpaths, subpath_count as usize),
-                                  slice::from_raw_parts(paths, path_count as usize)));
-    let partitioner_ptr: *mut Partitioner<'static> = &mut *partitioner;
-    mem::forget(partitioner);
-    partitioner_ptr
+                                  slice::from_raw_parts(paths, path_count as usize)))
+    }));
+    match partitioner {
+        Ok(partitioner) => Box::into_raw(partitioner),
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
@@ -32,17 +51,131 @@ pub unsafe extern fn pf_partitioner_destroy<'a>(partitioner: *mut Partitioner<'a
 }
 
 #[no_mangle]
-pub unsafe extern fn pf_partitioner_partition<'a>(partitioner: *mut Partitioner<'a>) {
-    (*partitioner).partition()
+pub unsafe extern fn pf_partitioner_enable_quads<'a>(partitioner: *mut Partitioner<'a>) {
+    (*partitioner).enable_quads()
+}
+
+/// Returns 0 on success and 1 if the sweep panicked, as it may on malformed paths. After a
+/// panic, the partitioner holds whatever primitives were produced before it.
+#[no_mangle]
+pub unsafe extern fn pf_partitioner_partition<'a>(partitioner: *mut Partitioner<'a>) -> u32 {
+    match panic::catch_unwind(AssertUnwindSafe(|| (*partitioner).partition())) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
+
+/// Calls `callback` with each bezieroid as it's produced. If `callback` returns nonzero, the sweep
+/// stops early. Returns 1 if the sweep was stopped early, 2 if it panicked, and 0 otherwise.
+#[no_mangle]
+pub unsafe extern fn pf_partitioner_partition_with<'a>(partitioner: *mut Partitioner<'a>,
+                                                       callback: extern fn(*const Bezieroid,
+                                                                           *mut c_void)
+                                                                           -> u32,
+                                                       user_data: *mut c_void)
+                                                       -> u32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        (*partitioner).partition_with(|bezieroid| {
+            if callback(&bezieroid, user_data) == 0 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })
+    }));
+    match result {
+        Ok(ControlFlow::Continue(())) => 0,
+        Ok(ControlFlow::Break(())) => 1,
+        Err(_) => 2,
+    }
 }
 
+/// Returns null, with a count of 0, if the bezieroids couldn't be retrieved.
 #[no_mangle]
 pub unsafe extern fn pf_partitioner_bezieroids<'a>(partitioner: *mut Partitioner<'a>,
                                                    out_bezieroid_count: *mut u32)
                                                    -> *const Bezieroid {
-    let bezieroids = (*partitioner).bezieroids();
+    let bezieroids = panic::catch_unwind(AssertUnwindSafe(|| {
+        let bezieroids = (*partitioner).bezieroids();
+        (bezieroids.as_ptr(), bezieroids.len())
+    }));
+    let (bezieroids, bezieroid_count) = bezieroids.unwrap_or((ptr::null(), 0));
+    if !out_bezieroid_count.is_null() {
+        *out_bezieroid_count = bezieroid_count as u32
+    }
+    bezieroids
+}
+
+/// Returns null, with a count of 0, if the quads couldn't be retrieved.
+#[no_mangle]
+pub unsafe extern fn pf_partitioner_quads<'a>(partitioner: *mut Partitioner<'a>,
+                                              out_quad_count: *mut u32)
+                                              -> *const Quad {
+    let quads = panic::catch_unwind(AssertUnwindSafe(|| {
+        let quads = (*partitioner).quads();
+        (quads.as_ptr(), quads.len())
+    }));
+    let (quads, quad_count) = quads.unwrap_or((ptr::null(), 0));
+    if !out_quad_count.is_null() {
+        *out_quad_count = quad_count as u32
+    }
+    quads
+}
+
+/// Returns null if the mesh couldn't be assembled. Otherwise, the mesh must be freed with
+/// `pf_mesh_destroy()`.
+#[no_mangle]
+pub unsafe extern fn pf_partitioner_mesh<'a>(partitioner: *mut Partitioner<'a>)
+                                             -> *mut PartitionMesh {
+    match panic::catch_unwind(AssertUnwindSafe(|| Box::new((*partitioner).mesh()))) {
+        Ok(mesh) => Box::into_raw(mesh),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern fn pf_mesh_destroy(mesh: *mut PartitionMesh) {
+    drop(mem::transmute::<*mut PartitionMesh, Box<PartitionMesh>>(mesh))
+}
+
+#[no_mangle]
+pub unsafe extern fn pf_mesh_vertices(mesh: *const PartitionMesh, out_vertex_count: *mut u32)
+                                      -> *const Point2D<f32> {
+    let vertices = &(*mesh).vertices;
+    if !out_vertex_count.is_null() {
+        *out_vertex_count = vertices.len() as u32
+    }
+    vertices.as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern fn pf_mesh_indices(mesh: *const PartitionMesh, out_index_count: *mut u32)
+                                     -> *const u32 {
+    let indices = &(*mesh).indices;
+    if !out_index_count.is_null() {
+        *out_index_count = indices.len() as u32
+    }
+    indices.as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern fn pf_mesh_bezieroids(mesh: *const PartitionMesh,
+                                        out_bezieroid_count: *mut u32)
+                                        -> *const Bezieroid {
+    let bezieroids = &(*mesh).bezieroids;
     if !out_bezieroid_count.is_null() {
         *out_bezieroid_count = bezieroids.len() as u32
     }
     bezieroids.as_ptr()
 }
+
+#[no_mangle]
+pub unsafe extern fn pf_mesh_bounds(mesh: *const PartitionMesh) -> PfRectF32 {
+    let bounds = &(*mesh).bounds;
+    PfRectF32 {
+        origin_x: bounds.origin.x,
+        origin_y: bounds.origin.y,
+        width: bounds.size.width,
+        height: bounds.size.height,
+    }
+}
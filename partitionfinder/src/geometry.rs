@@ -30,6 +30,29 @@ pub fn line_line_crossing_point(a_p0: &Point2D<f32>,
     return Some(p + r * t)
 }
 
+// Positive if the polygon winds counterclockwise in a Y-up coordinate system.
+pub fn signed_area(polygon: &[Point2D<f32>]) -> f32 {
+    let mut area = 0.0;
+    for (index, point) in polygon.iter().enumerate() {
+        let next_point = &polygon[(index + 1) % polygon.len()];
+        area += point.x * next_point.y - next_point.x * point.y
+    }
+    area * 0.5
+}
+
+// Even-odd rule; points exactly on the boundary may go either way.
+pub fn polygon_contains_point(polygon: &[Point2D<f32>], point: &Point2D<f32>) -> bool {
+    let mut inside = false;
+    for (index, a) in polygon.iter().enumerate() {
+        let b = &polygon[(index + 1) % polygon.len()];
+        if (a.y > point.y) != (b.y > point.y) &&
+                point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside
+        }
+    }
+    inside
+}
+
 // TODO(pcwalton): Implement this.
 pub fn line_cubic_bezier_crossing_point(_a_p0: &Point2D<f32>,
                                         _a_p1: &Point2D<f32>,
@@ -54,19 +77,37 @@ pub fn cubic_bezier_cubic_bezier_crossing_point(_a_p0: &Point2D<f32>,
     None
 }
 
-fn sample_cubic_bezier(t: f32,
-                       p0: &Point2D<f32>,
-                       p1: &Point2D<f32>,
-                       p2: &Point2D<f32>,
-                       p3: &Point2D<f32>)
-                       -> Point2D<f32> {
-    // TODO(pcwalton)
-    Point2D::new(0.0, 0.0)
+pub fn sample_cubic_bezier(t: f32,
+                           p0: &Point2D<f32>,
+                           p1: &Point2D<f32>,
+                           p2: &Point2D<f32>,
+                           p3: &Point2D<f32>)
+                           -> Point2D<f32> {
+    let (p0p1, p1p2, p2p3) = (p0.lerp(*p1, t), p1.lerp(*p2, t), p2.lerp(*p3, t));
+    let (p0p1p2, p1p2p3) = (p0p1.lerp(p1p2, t), p1p2.lerp(p2p3, t));
+    p0p1p2.lerp(p1p2p3, t)
+}
+
+// The number of line segments needed to approximate the curve to within `tolerance`, by Wang's
+// formula.
+pub fn cubic_bezier_segment_count(p0: &Point2D<f32>,
+                                  p1: &Point2D<f32>,
+                                  p2: &Point2D<f32>,
+                                  p3: &Point2D<f32>,
+                                  tolerance: f32)
+                                  -> u32 {
+    let (a, b) = ((*p0 - *p1) + (*p2 - *p1), (*p1 - *p2) + (*p3 - *p2));
+    let max_second_difference = f32::max(a.x.hypot(a.y), b.x.hypot(b.y));
+    let segment_count = (0.75 * max_second_difference / tolerance).sqrt().ceil();
+    if segment_count >= 1.0 {
+        segment_count as u32
+    } else {
+        1
+    }
 }
 
 pub fn solve_line_y_for_x(x: f32, a: &Point2D<f32>, b: &Point2D<f32>) -> f32 {
-    // TODO(pcwalton)
-    0.0
+    a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x)
 }
 
 pub fn solve_cubic_bezier_t_for_x(x: f32,
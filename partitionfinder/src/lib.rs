@@ -2,6 +2,8 @@
 
 extern crate bit_vec;
 extern crate euclid;
+#[cfg(feature = "lyon")]
+extern crate lyon_path;
 
 use euclid::Point2D;
 use std::u32;
@@ -10,6 +12,9 @@ pub mod capi;
 pub mod geometry;
 pub mod partitioner;
 
+#[cfg(test)]
+mod tests;
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct Bezieroid {
@@ -23,6 +28,17 @@ pub struct Bezieroid {
     pub lower_right_time: f32,
 }
 
+/// A region bounded above and below by straight edges, as emitted by a partitioner with quads
+/// enabled.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Quad {
+    pub upper_left: Point2D<f32>,
+    pub upper_right: Point2D<f32>,
+    pub lower_left: Point2D<f32>,
+    pub lower_right: Point2D<f32>,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct Endpoint {
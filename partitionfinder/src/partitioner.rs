@@ -1,20 +1,29 @@
 // partitionfinder/partitioner.rs
 
 use bit_vec::BitVec;
-use euclid::Point2D;
+use euclid::{Point2D, Rect, Transform2D, Vector2D};
 use geometry;
-use std::collections::BinaryHeap;
+#[cfg(feature = "lyon")]
+use lyon_path::PathEvent;
+use std::borrow::Cow;
+use std::collections::{BinaryHeap, HashMap};
 use std::cmp::{self, Ordering};
+use std::mem;
+use std::ops::ControlFlow;
 use std::u32;
-use {Bezieroid, ControlPoints, Endpoint, Path, Subpath};
+use {Bezieroid, ColorU8, ControlPoints, Endpoint, Path, Quad, Subpath};
 
 pub struct Partitioner<'a> {
-    endpoints: &'a [Endpoint],
-    control_points: &'a [ControlPoints],
-    subpaths: &'a [Subpath],
-    paths: &'a [Path],
+    endpoints: Cow<'a, [Endpoint]>,
+    control_points: Cow<'a, [ControlPoints]>,
+    subpaths: Cow<'a, [Subpath]>,
+    paths: Cow<'a, [Path]>,
 
     bezieroids: Vec<Bezieroid>,
+    quads: Vec<Quad>,
+    emit_quads: bool,
+    y_axis: YAxis,
+    debug_events: Option<Vec<SweepEvent>>,
 
     heap: BinaryHeap<Point>,
     visited_points: BitVec,
@@ -29,12 +38,16 @@ impl<'a> Partitioner<'a> {
                    paths: &'b [Path])
                    -> Partitioner<'b> {
         Partitioner {
-            endpoints: endpoints,
-            control_points: control_points,
-            subpaths: subpaths,
-            paths: paths,
+            endpoints: Cow::Borrowed(endpoints),
+            control_points: Cow::Borrowed(control_points),
+            subpaths: Cow::Borrowed(subpaths),
+            paths: Cow::Borrowed(paths),
 
             bezieroids: vec![],
+            quads: vec![],
+            emit_quads: false,
+            y_axis: YAxis::Down,
+            debug_events: None,
 
             heap: BinaryHeap::new(),
             visited_points: BitVec::new(),
@@ -42,11 +55,581 @@ impl<'a> Partitioner<'a> {
         }
     }
 
+    /// Creates a partitioner for a single path made of straight line segments, given as a vertex
+    /// array and a list of `(from, to)` edges indexing into it.
+    ///
+    /// The edges must form closed loops: following the edges from any vertex must eventually lead
+    /// back to it. Each loop becomes a subpath, and the loops can be listed in any order. Open
+    /// chains are rejected with `EdgeListError::OpenLoop` rather than being closed implicitly.
+    pub fn from_edges(vertices: &[Point2D<f32>], edges: &[(u32, u32)])
+                      -> Result<Partitioner<'static>, EdgeListError> {
+        if edges.iter().any(|&(from, to)| {
+            from as usize >= vertices.len() || to as usize >= vertices.len()
+        }) {
+            return Err(EdgeListError::VertexIndexOutOfBounds)
+        }
+
+        let mut edges_from_vertex: Vec<Vec<u32>> = vec![vec![]; vertices.len()];
+        for (edge_index, &(from, _)) in edges.iter().enumerate() {
+            edges_from_vertex[from as usize].push(edge_index as u32)
+        }
+
+        let mut endpoints = vec![];
+        let mut subpaths = vec![];
+        let mut visited_edges = BitVec::from_elem(edges.len(), false);
+        for first_edge_index in 0..edges.len() {
+            if visited_edges.get(first_edge_index).unwrap() {
+                continue
+            }
+
+            let subpath_index = subpaths.len() as u32;
+            subpaths.push(Subpath {
+                first_endpoint_index: endpoints.len() as u32,
+                path_index: 0,
+            });
+
+            let first_vertex_index = edges[first_edge_index].0;
+            let mut edge_index = first_edge_index as u32;
+            loop {
+                visited_edges.set(edge_index as usize, true);
+                let (from, to) = edges[edge_index as usize];
+                endpoints.push(Endpoint {
+                    position: vertices[from as usize],
+                    control_points_index: u32::MAX,
+                    subpath_index: subpath_index,
+                });
+
+                if to == first_vertex_index {
+                    break
+                }
+
+                edge_index = match edges_from_vertex[to as usize].iter().find(|&&next_edge_index| {
+                    !visited_edges.get(next_edge_index as usize).unwrap()
+                }) {
+                    Some(&next_edge_index) => next_edge_index,
+                    None => return Err(EdgeListError::OpenLoop(to)),
+                }
+            }
+        }
+
+        Ok(Partitioner::from_line_subpaths(endpoints, subpaths))
+    }
+
+    /// Creates a partitioner for a single polygon given as an outer boundary and a list of
+    /// holes, such as a GeoJSON polygon.
+    ///
+    /// The orientation of the input doesn't matter: the outer boundary is wound counterclockwise
+    /// and every hole clockwise, so the holes are cut out of the outer boundary under both the
+    /// even-odd and nonzero fill rules. Holes lying entirely outside the outer boundary would
+    /// otherwise be filled, so they're dropped, as are holes with fewer than three vertices.
+    pub fn from_polygon_with_holes(outer: &[Point2D<f32>], holes: &[&[Point2D<f32>]])
+                                   -> Partitioner<'static> {
+        let mut endpoints = vec![];
+        let mut subpaths = vec![];
+
+        push_line_subpath(&mut endpoints, &mut subpaths, outer, true);
+        for hole in holes {
+            if hole.len() < 3 ||
+                    hole.iter().all(|point| !geometry::polygon_contains_point(outer, point)) {
+                continue
+            }
+            push_line_subpath(&mut endpoints, &mut subpaths, hole, false)
+        }
+
+        Partitioner::from_line_subpaths(endpoints, subpaths)
+    }
+
+    /// Creates a partitioner for a single path described by lyon path events, so that paths built
+    /// with lyon can be partitioned directly. Requires the `lyon` feature.
+    ///
+    /// Each `Begin` starts a subpath. Quadratic curves are raised to the equivalent cubics. Every
+    /// subpath is filled as if it were closed, whether or not its `End` event closes it, and an
+    /// endpoint that merely returns to the start of its subpath is dropped in favor of the
+    /// implicit closing edge.
+    #[cfg(feature = "lyon")]
+    pub fn from_path_events<I>(events: I) -> Partitioner<'static>
+                               where I: Iterator<Item = PathEvent> {
+        let mut endpoints: Vec<Endpoint> = vec![];
+        let mut control_points = vec![];
+        let mut subpaths: Vec<Subpath> = vec![];
+
+        for event in events {
+            let (position, control_points_index) = match event {
+                PathEvent::Begin { at } => {
+                    subpaths.push(Subpath {
+                        first_endpoint_index: endpoints.len() as u32,
+                        path_index: 0,
+                    });
+                    (Point2D::new(at.x, at.y), u32::MAX)
+                }
+                PathEvent::Line { to, .. } => (Point2D::new(to.x, to.y), u32::MAX),
+                PathEvent::Quadratic { from, ctrl, to } => {
+                    let (from, ctrl, to) = (Point2D::new(from.x, from.y),
+                                            Point2D::new(ctrl.x, ctrl.y),
+                                            Point2D::new(to.x, to.y));
+                    control_points.push(ControlPoints {
+                        point1: from.lerp(ctrl, 2.0 / 3.0),
+                        point2: to.lerp(ctrl, 2.0 / 3.0),
+                    });
+                    (to, control_points.len() as u32 - 1)
+                }
+                PathEvent::Cubic { ctrl1, ctrl2, to, .. } => {
+                    control_points.push(ControlPoints {
+                        point1: Point2D::new(ctrl1.x, ctrl1.y),
+                        point2: Point2D::new(ctrl2.x, ctrl2.y),
+                    });
+                    (Point2D::new(to.x, to.y), control_points.len() as u32 - 1)
+                }
+                PathEvent::End { .. } => {
+                    // The edge back to the first endpoint is implicit, so a last endpoint on top
+                    // of the first is redundant, but a curve ending there becomes the closing
+                    // edge.
+                    let first_endpoint_index = match subpaths.last() {
+                        Some(subpath) => subpath.first_endpoint_index as usize,
+                        None => continue,
+                    };
+                    if endpoints.len() > first_endpoint_index + 1 &&
+                            endpoints[endpoints.len() - 1].position ==
+                            endpoints[first_endpoint_index].position {
+                        let last_endpoint = endpoints.pop().unwrap();
+                        endpoints[first_endpoint_index].control_points_index =
+                            last_endpoint.control_points_index
+                    }
+                    continue
+                }
+            };
+
+            // Events before the first `Begin` have no subpath to go in.
+            if subpaths.is_empty() {
+                continue
+            }
+            endpoints.push(Endpoint {
+                position: position,
+                control_points_index: control_points_index,
+                subpath_index: subpaths.len() as u32 - 1,
+            })
+        }
+
+        Partitioner::from_owned_subpaths(endpoints, control_points, subpaths)
+    }
+
+    fn from_line_subpaths(endpoints: Vec<Endpoint>, subpaths: Vec<Subpath>)
+                          -> Partitioner<'static> {
+        Partitioner::from_owned_subpaths(endpoints, vec![], subpaths)
+    }
+
+    /// Creates a partitioner that fills all the subpaths with a single opaque black path.
+    fn from_owned_subpaths(endpoints: Vec<Endpoint>,
+                           control_points: Vec<ControlPoints>,
+                           subpaths: Vec<Subpath>)
+                           -> Partitioner<'static> {
+        let paths = vec![Path {
+            first_subpath_index: 0,
+            fill_color: ColorU8 {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+        }];
+
+        Partitioner {
+            endpoints: Cow::Owned(endpoints),
+            control_points: Cow::Owned(control_points),
+            subpaths: Cow::Owned(subpaths),
+            paths: Cow::Owned(paths),
+
+            bezieroids: vec![],
+            quads: vec![],
+            emit_quads: false,
+            y_axis: YAxis::Down,
+            debug_events: None,
+
+            heap: BinaryHeap::new(),
+            visited_points: BitVec::new(),
+            active_edges: vec![],
+        }
+    }
+
+    /// Snaps near-vertical and near-horizontal straight edges to the device pixel grid, which
+    /// keeps the stems of small text crisp without full hinting.
+    ///
+    /// `device_transform` maps path coordinates to device pixels. Each edge is examined in
+    /// device space, and an edge whose slope is within `SNAP_SLOPE_THRESHOLD` of the vertical
+    /// (horizontal) has both of its endpoints moved to the nearest whole X (Y) coordinate. Curved
+    /// edges are left alone. The endpoints are modified immediately, so this must be called
+    /// before `partition()`. Does nothing if `device_transform` isn't invertible.
+    pub fn enable_pixel_snapping(&mut self, device_transform: Transform2D<f32>) {
+        let inverse_transform = match device_transform.inverse() {
+            Some(inverse_transform) => inverse_transform,
+            None => return,
+        };
+
+        let original_positions: Vec<_> = self.endpoints.iter().map(|endpoint| {
+            device_transform.transform_point(&endpoint.position)
+        }).collect();
+        let mut snapped_positions = original_positions.clone();
+
+        for endpoint_index in 0..self.endpoints.len() as u32 {
+            let next_endpoint_index = self.next_endpoint_of(endpoint_index);
+            if self.control_points_index(next_endpoint_index).is_some() {
+                continue
+            }
+
+            let (from, to) = (endpoint_index as usize, next_endpoint_index as usize);
+            let vector = original_positions[to] - original_positions[from];
+            if vector.x.abs() <= vector.y.abs() * SNAP_SLOPE_THRESHOLD {
+                let x = ((original_positions[from].x + original_positions[to].x) * 0.5).round();
+                snapped_positions[from].x = x;
+                snapped_positions[to].x = x;
+            } else if vector.y.abs() <= vector.x.abs() * SNAP_SLOPE_THRESHOLD {
+                let y = ((original_positions[from].y + original_positions[to].y) * 0.5).round();
+                snapped_positions[from].y = y;
+                snapped_positions[to].y = y;
+            }
+        }
+
+        for (endpoint, position) in self.endpoints.to_mut().iter_mut().zip(snapped_positions) {
+            endpoint.position = inverse_transform.transform_point(&position)
+        }
+    }
+
+    /// Moves endpoints that lie within `epsilon` of one another to a single shared position, so
+    /// that subpaths meant to meet at a seam really do.
+    ///
+    /// Outlines from imported geometry often have junctions that are off by a tiny amount, which
+    /// the sweep sees as two separate edges with a sliver between them. Endpoints are grouped in
+    /// order of increasing X, and each group takes the position of its first member. Control
+    /// points aren't moved. Straight edges that welding shrinks to nothing are removed along with
+    /// one of their endpoints. Like `enable_pixel_snapping()`, this modifies the endpoints
+    /// immediately, so it must be called before `partition()`.
+    pub fn weld_vertices(&mut self, epsilon: f32) {
+        let mut endpoint_indices: Vec<usize> = (0..self.endpoints.len()).collect();
+        endpoint_indices.sort_by(|&a, &b| {
+            let (a, b) = (&self.endpoints[a].position, &self.endpoints[b].position);
+            a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal)
+        });
+
+        // Canonical positions are created in order of increasing X, so only the ones at the end
+        // of the list can be within `epsilon` of the current endpoint.
+        let mut canonical_positions: Vec<Point2D<f32>> = vec![];
+        let endpoints = self.endpoints.to_mut();
+        for endpoint_index in endpoint_indices {
+            let position = endpoints[endpoint_index].position;
+            let canonical_position = canonical_positions.iter().rev().take_while(|canonical| {
+                canonical.x >= position.x - epsilon
+            }).find(|canonical| {
+                let vector = position - **canonical;
+                vector.x.hypot(vector.y) <= epsilon
+            }).cloned();
+
+            match canonical_position {
+                Some(canonical_position) => {
+                    endpoints[endpoint_index].position = canonical_position
+                }
+                None => canonical_positions.push(position),
+            }
+        }
+
+        self.remove_zero_length_edges()
+    }
+
+    /// Removes straight edges whose endpoints coincide, which have no direction for the sweep to
+    /// follow. The edge is dropped together with the endpoint that ends it.
+    fn remove_zero_length_edges(&mut self) {
+        let mut endpoints: Vec<Endpoint> = Vec::with_capacity(self.endpoints.len());
+        let mut first_endpoint_indices = Vec::with_capacity(self.subpaths.len());
+        for subpath_index in 0..(self.subpaths.len() as u32) {
+            let first_endpoint_index = self.subpaths[subpath_index as usize].first_endpoint_index;
+            let last_endpoint_index = self.last_endpoint_index_of_subpath(subpath_index);
+            let start = endpoints.len();
+            first_endpoint_indices.push(start as u32);
+
+            for endpoint in &self.endpoints[first_endpoint_index as usize..
+                                            last_endpoint_index as usize] {
+                let is_zero_length = endpoints.len() > start &&
+                    endpoint.control_points_index == u32::MAX &&
+                    endpoints[endpoints.len() - 1].position == endpoint.position;
+                if !is_zero_length {
+                    endpoints.push(*endpoint)
+                }
+            }
+
+            // The edge closing the subpath ends at its first endpoint. If it's the one with no
+            // length, the last endpoint goes, and the edge leading to it now leads to the first.
+            let end = endpoints.len();
+            if end > start + 1 && endpoints[start].control_points_index == u32::MAX &&
+                    endpoints[end - 1].position == endpoints[start].position {
+                endpoints[start].control_points_index = endpoints[end - 1].control_points_index;
+                endpoints.pop();
+            }
+        }
+
+        if endpoints.len() == self.endpoints.len() {
+            return
+        }
+        for (subpath, first_endpoint_index) in self.subpaths.to_mut()
+                                                   .iter_mut()
+                                                   .zip(first_endpoint_indices) {
+            subpath.first_endpoint_index = first_endpoint_index
+        }
+        self.endpoints = Cow::Owned(endpoints)
+    }
+
+    /// Expands the filled region outward by `amount` path units, which approximates a bold face
+    /// for fonts that lack one.
+    ///
+    /// This is only an approximation of real bolding: stems, counters, and gaps all change by
+    /// the same amount rather than as a type designer would adjust them. Curves are first
+    /// flattened to within `DILATION_FLATTENING_TOLERANCE * amount`. Then each edge is moved
+    /// along its normal, away from the filled side, so outer contours grow and holes shrink.
+    /// Adjacent edges are joined where their offsets meet, except that convex corners sharper
+    /// than `DILATION_MITER_LIMIT` allows are beveled. At concave corners the joint is pulled in
+    /// so that it never lies farther away than the shorter of the two edges is long, which keeps
+    /// the offset edges from looping over one another. Like `enable_pixel_snapping()`, this
+    /// modifies the endpoints immediately, so it must be called before `partition()`. Does
+    /// nothing unless `amount` is positive.
+    pub fn set_dilation(&mut self, amount: f32) {
+        if amount.is_nan() || amount <= 0.0 {
+            return
+        }
+
+        self.flatten_curves(amount * DILATION_FLATTENING_TOLERANCE);
+
+        let polygons: Vec<Vec<Point2D<f32>>> = (0..self.subpaths.len() as u32).map(|index| {
+            let first_endpoint_index = self.subpaths[index as usize].first_endpoint_index;
+            let last_endpoint_index = self.last_endpoint_index_of_subpath(index);
+            let mut polygon: Vec<_> = self.endpoints[first_endpoint_index as usize..
+                                                     last_endpoint_index as usize]
+                                          .iter()
+                                          .map(|endpoint| endpoint.position)
+                                          .collect();
+            polygon.dedup();
+            while polygon.len() > 1 && polygon.first() == polygon.last() {
+                polygon.pop();
+            }
+            polygon
+        }).collect();
+
+        let mut endpoints = Vec::with_capacity(self.endpoints.len());
+        for (subpath_index, polygon) in polygons.iter().enumerate() {
+            let subpath = self.subpaths[subpath_index];
+
+            // Under the even-odd rule, a subpath bounds filled area on its inside if it lies
+            // within an even number of the other subpaths of its path.
+            let enclosing_subpath_count = polygons.iter().enumerate().filter(|&(index, other)| {
+                index != subpath_index &&
+                    self.subpaths[index].path_index == subpath.path_index &&
+                    !polygon.is_empty() &&
+                    geometry::polygon_contains_point(other, &polygon[0])
+            }).count();
+            let filled_inside = enclosing_subpath_count % 2 == 0;
+
+            self.subpaths.to_mut()[subpath_index].first_endpoint_index = endpoints.len() as u32;
+            let dilated_polygon = dilate_polygon(polygon, amount, filled_inside);
+            endpoints.extend(dilated_polygon.into_iter().map(|position| {
+                Endpoint {
+                    position: position,
+                    control_points_index: u32::MAX,
+                    subpath_index: subpath_index as u32,
+                }
+            }))
+        }
+        self.endpoints = Cow::Owned(endpoints);
+    }
+
+    /// Emits the regions between two straight edges as quads instead of bezieroids, which
+    /// reduces the primitive count for outlines that are mostly straight. Bezieroids are still
+    /// emitted for regions bounded by a curve on either side.
+    ///
+    /// Quads are collected in `quads()` and aren't passed to the callback of `partition_with()`.
+    /// If that callback returns `ControlFlow::Break`, the sweep stops after the point that
+    /// produced the bezieroid it was given, so `quads()` holds only the quads emitted up to and
+    /// including that point.
+    #[inline]
+    pub fn enable_quads(&mut self) {
+        self.emit_quads = true
+    }
+
+    /// Sets the direction in which the Y axis of the path coordinates points. The default is
+    /// `YAxis::Down`, as in screen coordinates.
+    ///
+    /// The sweep orders edges by increasing Y, and each emitted primitive records which of its
+    /// two edges is on top. Setting this to `YAxis::Up` for Y-up content, such as font outlines,
+    /// flips that so that primitives are oriented correctly without having to negate every
+    /// coordinate first. It must be called before `partition()`.
+    #[inline]
+    pub fn set_y_axis(&mut self, y_axis: YAxis) {
+        self.y_axis = y_axis
+    }
+
+    /// Records every event that the sweep processes so that it can be inspected afterward with
+    /// `debug_events()`. This is meant for debugging and testing and slows partitioning down.
+    #[inline]
+    pub fn enable_debug_events(&mut self) {
+        self.debug_events = Some(vec![])
+    }
+
     pub fn partition(&mut self) {
+        let _ = self.partition_with(|_| ControlFlow::Continue(()));
+    }
+
+    /// Flattens the curves into line segments finely enough for the paths to be drawn with
+    /// `device_transform`, which maps path coordinates to device pixels, and then partitions
+    /// them.
+    ///
+    /// Every curve is split into enough segments that none strays more than
+    /// `SCREEN_FLATTENING_TOLERANCE` device pixels from the curve. The tolerance is converted to
+    /// path units by dividing it by the largest factor by which `device_transform` stretches a
+    /// vector, so glyphs that are small on screen are flattened coarsely and large ones finely.
+    /// Since the endpoints are rebuilt, `endpoints()` must be consulted to interpret the
+    /// resulting primitives.
+    pub fn partition_for_screen(&mut self, device_transform: Transform2D<f32>) {
+        let scale = f32::max(device_transform.m11.hypot(device_transform.m12),
+                             device_transform.m21.hypot(device_transform.m22));
+        if scale > 0.0 {
+            self.flatten_curves(SCREEN_FLATTENING_TOLERANCE / scale)
+        }
+        self.partition()
+    }
+
+    /// Replaces every curve with line segments that stay within `tolerance` of it.
+    fn flatten_curves(&mut self, tolerance: f32) {
+        let mut endpoints = Vec::with_capacity(self.endpoints.len());
+        let mut subpaths = Vec::with_capacity(self.subpaths.len());
+        for subpath_index in 0..self.subpaths.len() as u32 {
+            subpaths.push(Subpath {
+                first_endpoint_index: endpoints.len() as u32,
+                ..self.subpaths[subpath_index as usize]
+            });
+
+            let positions = self.flattened_subpath(subpath_index, tolerance);
+            endpoints.extend(positions.into_iter().map(|position| {
+                Endpoint {
+                    position: position,
+                    control_points_index: u32::MAX,
+                    subpath_index: subpath_index,
+                }
+            }))
+        }
+
+        self.endpoints = Cow::Owned(endpoints);
+        self.control_points = Cow::Owned(vec![]);
+        self.subpaths = Cow::Owned(subpaths);
+    }
+
+    /// Returns the positions of the vertices of the given subpath once its curves are replaced
+    /// with line segments that stay within `tolerance` of them.
+    fn flattened_subpath(&self, subpath_index: u32, tolerance: f32) -> Vec<Point2D<f32>> {
+        let mut positions = vec![];
+        let first_endpoint_index = self.subpaths[subpath_index as usize].first_endpoint_index;
+        let last_endpoint_index = self.last_endpoint_index_of_subpath(subpath_index);
+        for endpoint_index in first_endpoint_index..last_endpoint_index {
+            let position = self.endpoints[endpoint_index as usize].position;
+            if let Some(control_points_index) = self.control_points_index(endpoint_index) {
+                let control_points = &self.control_points[control_points_index as usize];
+                let prev_endpoint_index = self.prev_endpoint_of(endpoint_index);
+                let p0 = self.endpoints[prev_endpoint_index as usize].position;
+                let (p1, p2) = (control_points.point1, control_points.point2);
+
+                let segment_count =
+                    geometry::cubic_bezier_segment_count(&p0, &p1, &p2, &position, tolerance);
+                for segment_index in 1..segment_count {
+                    let t = segment_index as f32 / segment_count as f32;
+                    positions.push(geometry::sample_cubic_bezier(t, &p0, &p1, &p2, &position))
+                }
+            }
+
+            positions.push(position)
+        }
+        positions
+    }
+
+    /// Partitions the paths, calling `callback` with each bezieroid as soon as it's produced.
+    ///
+    /// The primitives and debug events of any earlier partitioning are discarded first. If
+    /// `callback` returns `ControlFlow::Break`, the sweep stops immediately and this returns
+    /// `ControlFlow::Break`. Either way, `bezieroids()` afterward contains exactly the bezieroids
+    /// that were passed to `callback`.
+    ///
+    /// Two common inputs skip the sweep, so they produce no debug events. If there are no
+    /// subpaths, nothing is emitted. If the only path has a single subpath that's an axis-aligned
+    /// rectangle, as described in `single_rectangle_bezieroid()`, it's emitted as one primitive
+    /// spanning the whole rectangle.
+    pub fn partition_with<F>(&mut self, mut callback: F) -> ControlFlow<()>
+                             where F: FnMut(Bezieroid) -> ControlFlow<()> {
+        self.bezieroids.clear();
+        self.quads.clear();
+        if let Some(ref mut debug_events) = self.debug_events {
+            debug_events.clear()
+        }
+
+        if self.subpaths.is_empty() {
+            return ControlFlow::Continue(())
+        }
+        if let Some(bezieroid) = self.single_rectangle_bezieroid() {
+            if self.emit_quads {
+                let quad = self.quad_for_bezieroid(&bezieroid);
+                self.quads.push(quad);
+                return ControlFlow::Continue(())
+            }
+            self.bezieroids.push(bezieroid);
+            return callback(bezieroid)
+        }
+
+        self.heap.clear();
+        self.active_edges.clear();
+        self.visited_points = BitVec::from_elem(self.endpoints.len() * 2, false);
+
+        let mut reported_bezieroid_count = 0;
         for path_index in (0..self.paths.len() as u32).rev() {
             self.init_heap_for_path(path_index);
-            while self.process_next_point() {}
+            loop {
+                let more_points = self.process_next_point();
+
+                while reported_bezieroid_count < self.bezieroids.len() {
+                    reported_bezieroid_count += 1;
+                    if callback(self.bezieroids[reported_bezieroid_count - 1]).is_break() {
+                        self.bezieroids.truncate(reported_bezieroid_count);
+                        return ControlFlow::Break(())
+                    }
+                }
+
+                if !more_points {
+                    break
+                }
+            }
         }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Merges adjacent primitives produced by the last `partition()` wherever doing so doesn't
+    /// change the filled shape, reducing the number of vertices that a GPU has to process.
+    ///
+    /// Quads are merged with `coalesce_quads()`. Bezieroids are merged when one continues
+    /// another along the same upper and lower edges. This takes time linear in the number of
+    /// primitives.
+    pub fn coalesce_output(&mut self) {
+        self.quads = coalesce_quads(&self.quads);
+
+        let mut bezieroids: Vec<Bezieroid> = Vec::with_capacity(self.bezieroids.len());
+        for bezieroid in &self.bezieroids {
+            if let Some(last) = bezieroids.last_mut() {
+                if last.upper_prev_endpoint == bezieroid.upper_prev_endpoint &&
+                        last.upper_next_endpoint == bezieroid.upper_next_endpoint &&
+                        last.lower_prev_endpoint == bezieroid.lower_prev_endpoint &&
+                        last.lower_next_endpoint == bezieroid.lower_next_endpoint &&
+                        last.upper_right_time == bezieroid.upper_left_time &&
+                        last.lower_right_time == bezieroid.lower_left_time {
+                    last.upper_right_time = bezieroid.upper_right_time;
+                    last.lower_right_time = bezieroid.lower_right_time;
+                    continue
+                }
+            }
+            bezieroids.push(*bezieroid)
+        }
+        self.bezieroids = bezieroids
     }
 
     #[inline]
@@ -54,6 +637,233 @@ impl<'a> Partitioner<'a> {
         &self.bezieroids
     }
 
+    #[inline]
+    pub fn quads(&self) -> &[Quad] {
+        &self.quads
+    }
+
+    /// Returns the events that the sweep processed, in order. This is empty unless
+    /// `enable_debug_events()` was called before partitioning.
+    #[inline]
+    pub fn debug_events(&self) -> &[SweepEvent] {
+        match self.debug_events {
+            None => &[],
+            Some(ref debug_events) => debug_events,
+        }
+    }
+
+    /// Assembles the results of the last `partition()` into a single mesh to hand to a renderer.
+    ///
+    /// Each bezieroid, followed by each quad, contributes its four corners to the vertices and
+    /// two triangles to the indices, so the corners of bezieroid `i` are vertices `4 * i` to
+    /// `4 * i + 3`. Bezieroid corners lie on their curves, but the curves between them may bulge
+    /// outward, which the bounds account for.
+    pub fn mesh(&self) -> PartitionMesh {
+        let corners: Vec<Quad> =
+            self.bezieroids.iter().map(|bezieroid| self.quad_for_bezieroid(bezieroid))
+                                  .chain(self.quads.iter().cloned())
+                                  .collect();
+        let mut vertices = Vec::with_capacity(corners.len() * 4);
+        for quad in &corners {
+            vertices.extend_from_slice(&[
+                quad.upper_left,
+                quad.upper_right,
+                quad.lower_left,
+                quad.lower_right,
+            ])
+        }
+
+        let indices = (0..corners.len() as u32).flat_map(|primitive_index| {
+            QUAD_TRIANGLE_INDICES.iter().map(move |index| primitive_index * 4 + index)
+        }).collect();
+
+        // A curve lies within the hull of its endpoints and control points.
+        let mut bounding_points = vertices.clone();
+        for bezieroid in &self.bezieroids {
+            for &(prev_endpoint_index, next_endpoint_index) in
+                    &[(bezieroid.upper_prev_endpoint, bezieroid.upper_next_endpoint),
+                      (bezieroid.lower_prev_endpoint, bezieroid.lower_next_endpoint)] {
+                if let Some(control_points_index) = self.control_points_index(next_endpoint_index) {
+                    let control_points = &self.control_points[control_points_index as usize];
+                    bounding_points.extend_from_slice(&[
+                        self.endpoints[prev_endpoint_index as usize].position,
+                        control_points.point1,
+                        control_points.point2,
+                        self.endpoints[next_endpoint_index as usize].position,
+                    ])
+                }
+            }
+        }
+
+        PartitionMesh {
+            bounds: Rect::from_points(&bounding_points),
+            vertices: vertices,
+            indices: indices,
+            bezieroids: self.bezieroids.clone(),
+        }
+    }
+
+    /// Returns the number of separate filled regions that the paths consist of.
+    ///
+    /// Holes don't count as regions of their own: an "O" is one region, and two disjoint
+    /// rectangles are two. A filled island inside a hole counts as a separate region. Subpaths
+    /// that share an edge, such as two rectangles side by side, fill a single region.
+    ///
+    /// Like the sweep, this uses the even-odd fill rule: a subpath starts a new region if it lies
+    /// inside an even number of the other subpaths of its path. It examines only the endpoints of
+    /// the subpaths, so subpaths that cross one another may be miscounted, and edges are only
+    /// considered shared if their endpoints match exactly.
+    pub fn region_count(&self) -> usize {
+        let mut region_count = 0;
+        for path_index in 0..self.paths.len() as u32 {
+            let first_subpath_index = self.paths[path_index as usize].first_subpath_index;
+            let last_subpath_index = self.last_subpath_index_of_path(path_index);
+            let polygons: Vec<Vec<Point2D<f32>>> =
+                (first_subpath_index..last_subpath_index).map(|subpath_index| {
+                    let first_endpoint_index =
+                        self.subpaths[subpath_index as usize].first_endpoint_index;
+                    let last_endpoint_index = self.last_endpoint_index_of_subpath(subpath_index);
+                    self.endpoints[first_endpoint_index as usize..last_endpoint_index as usize]
+                        .iter()
+                        .map(|endpoint| endpoint.position)
+                        .collect()
+                }).collect();
+
+            let filled_polygon_indices: Vec<usize> =
+                polygons.iter().enumerate().filter(|&(polygon_index, polygon)| {
+                    let point = match polygon.first() {
+                        Some(point) => point,
+                        None => return false,
+                    };
+                    let depth = polygons.iter().enumerate().filter(|&(other_index, other)| {
+                        other_index != polygon_index &&
+                            geometry::polygon_contains_point(other, point)
+                    }).count();
+                    depth % 2 == 0
+                }).map(|(polygon_index, _)| polygon_index).collect();
+
+            // Join filled subpaths that share an edge, tracking each one's representative.
+            let mut representatives: Vec<usize> = (0..filled_polygon_indices.len()).collect();
+            for (a, &a_polygon_index) in filled_polygon_indices.iter().enumerate() {
+                for (b, &b_polygon_index) in filled_polygon_indices.iter().enumerate().skip(a + 1) {
+                    if !polygons_share_edge(&polygons[a_polygon_index],
+                                            &polygons[b_polygon_index]) {
+                        continue
+                    }
+                    let (a_representative, b_representative) =
+                        (find_representative(&mut representatives, a),
+                         find_representative(&mut representatives, b));
+                    representatives[b_representative] = a_representative
+                }
+            }
+
+            region_count += (0..representatives.len()).filter(|&index| {
+                representatives[index] == index
+            }).count()
+        }
+        region_count
+    }
+
+    /// Returns the boundary of the filled region of each path as closed contours, with any
+    /// self-intersections resolved according to the even-odd rule.
+    ///
+    /// Unlike the bezieroids, which tessellate the interior, this describes the outline of what
+    /// gets filled, which is useful for stroking, offsetting, or exporting a normalized copy of
+    /// the paths. Edges are split where they cross, and the pieces with the fill on exactly one
+    /// side are kept, so overlapping parts that cancel out under the rule disappear. Outer
+    /// contours wind counterclockwise and holes clockwise, as seen with the Y axis set by
+    /// `set_y_axis()`. Curves are flattened to within `SCREEN_FLATTENING_TOLERANCE` path units,
+    /// and edges that overlap along a common line are only recognized if they coincide exactly.
+    pub fn boundary_contours(&self) -> Vec<Contour> {
+        let mut contours = vec![];
+        for path_index in 0..self.paths.len() as u32 {
+            let first_subpath_index = self.paths[path_index as usize].first_subpath_index;
+            let last_subpath_index = self.last_subpath_index_of_path(path_index);
+            let polygons: Vec<Vec<Point2D<f32>>> =
+                (first_subpath_index..last_subpath_index).map(|subpath_index| {
+                    self.flattened_subpath(subpath_index, SCREEN_FLATTENING_TOLERANCE)
+                }).collect();
+
+            let edges: Vec<(Point2D<f32>, Point2D<f32>)> = polygons.iter().flat_map(|polygon| {
+                (0..polygon.len()).map(move |index| {
+                    (polygon[index], polygon[(index + 1) % polygon.len()])
+                })
+            }).filter(|&(from, to)| from != to).collect();
+
+            // Each crossing point is computed once and shared by both edges, so that the pieces
+            // meet exactly.
+            let mut split_points = vec![vec![]; edges.len()];
+            for (a_index, a) in edges.iter().enumerate() {
+                for (b_index, b) in edges.iter().enumerate().skip(a_index + 1) {
+                    let point = geometry::line_line_crossing_point(&a.0, &a.1, &b.0, &b.1);
+                    if let Some(point) = point {
+                        split_points[a_index].push(point);
+                        split_points[b_index].push(point);
+                    }
+                }
+            }
+
+            let is_filled = |point: &Point2D<f32>| {
+                polygons.iter().filter(|polygon| {
+                    geometry::polygon_contains_point(polygon, point)
+                }).count() % 2 == 1
+            };
+
+            // Orient each boundary piece so that the fill is on its left in Y-up coordinates.
+            let mut boundary_edges = vec![];
+            for (&(from, to), mut points) in edges.iter().zip(split_points) {
+                let vector = to - from;
+                points.sort_by(|a, b| {
+                    (*a - from).dot(vector).partial_cmp(&(*b - from).dot(vector))
+                                           .unwrap_or(Ordering::Equal)
+                });
+                points.insert(0, from);
+                points.push(to);
+
+                for piece in points.windows(2) {
+                    let (from, to) = (piece[0], piece[1]);
+                    if from == to {
+                        continue
+                    }
+                    let midpoint = from.lerp(to, 0.5);
+                    let normal = Vector2D::new(from.y - to.y, to.x - from.x) *
+                        BOUNDARY_SIDE_OFFSET;
+                    match (is_filled(&(midpoint + normal)), is_filled(&(midpoint - normal))) {
+                        (true, false) => boundary_edges.push((from, to)),
+                        (false, true) => boundary_edges.push((to, from)),
+                        _ => {}
+                    }
+                }
+            }
+
+            contours.extend(chain_boundary_edges(&boundary_edges).into_iter().map(|mut points| {
+                if self.y_axis == YAxis::Down {
+                    points.reverse()
+                }
+                Contour {
+                    path_index: path_index,
+                    points: points,
+                }
+            }))
+        }
+        contours
+    }
+
+    #[inline]
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    #[inline]
+    pub fn control_points(&self) -> &[ControlPoints] {
+        &self.control_points
+    }
+
+    #[inline]
+    pub fn subpaths(&self) -> &[Subpath] {
+        &self.subpaths
+    }
+
     fn process_next_point(&mut self) -> bool {
         let point = match self.heap.peek() {
             Some(point) => *point,
@@ -68,25 +878,46 @@ impl<'a> Partitioner<'a> {
         self.mark_point_as_visited(&point);
 
         let matching_active_edges = self.find_right_point_in_active_edge_list(point.endpoint_index);
-        match point.point_type {
+        let kind = match point.point_type {
             PointType::Endpoint => {
                 match matching_active_edges.count {
-                    0 => self.process_min_endpoint(point.endpoint_index),
+                    0 => {
+                        self.process_min_endpoint(point.endpoint_index);
+                        SweepEventKind::MinEndpoint
+                    }
                     1 => {
                         self.process_regular_endpoint(point.endpoint_index,
-                                                      matching_active_edges.indices[0])
+                                                      matching_active_edges.indices[0]);
+                        SweepEventKind::RegularEndpoint
                     }
                     2 => {
                         self.process_max_endpoint(point.endpoint_index,
-                                                  matching_active_edges.indices)
+                                                  matching_active_edges.indices);
+                        SweepEventKind::MaxEndpoint
+                    }
+                    _ => {
+                        debug_assert!(false);
+                        return true
                     }
-                    _ => debug_assert!(false),
                 }
             }
             PointType::CrossingBelow => {
                 debug_assert!(matching_active_edges.count > 0);
-                self.process_crossing_point(point.position.x, matching_active_edges.indices[0])
+                self.process_crossing_point(point.position.x, matching_active_edges.indices[0]);
+                SweepEventKind::Crossing
             }
+        };
+
+        if let Some(ref mut debug_events) = self.debug_events {
+            let active_edges = self.active_edges.iter().map(|active_edge| {
+                (active_edge.prev_endpoint_index(), active_edge.next_endpoint_index())
+            }).collect();
+            debug_events.push(SweepEvent {
+                kind: kind,
+                position: point.position,
+                endpoint_index: point.endpoint_index,
+                active_edges: active_edges,
+            })
         }
 
         true
@@ -116,12 +947,12 @@ impl<'a> Partitioner<'a> {
     }
 
     fn process_regular_endpoint(&mut self, endpoint_index: u32, active_edge_index: u32) {
-        let endpoint = &self.endpoints[endpoint_index as usize];
+        let endpoint_x = self.endpoints[endpoint_index as usize].position.x;
         if self.should_fill_below_active_edge(active_edge_index) {
-            self.emit_bezieroid_below(active_edge_index, endpoint.position.x)
+            self.emit_bezieroid_below(active_edge_index, endpoint_x)
         }
         if self.should_fill_above_active_edge(active_edge_index) {
-            self.emit_bezieroid_above(active_edge_index, endpoint.position.x)
+            self.emit_bezieroid_above(active_edge_index, endpoint_x)
         }
 
         let prev_endpoint_index = self.prev_endpoint_of(endpoint_index);
@@ -151,16 +982,16 @@ impl<'a> Partitioner<'a> {
         debug_assert!(active_edge_indices[0] < active_edge_indices[1],
                       "Matching active edge indices in wrong order when processing MAX point");
 
-        let endpoint = &self.endpoints[endpoint_index as usize];
+        let endpoint_x = self.endpoints[endpoint_index as usize].position.x;
 
         if self.should_fill_above_active_edge(active_edge_indices[0]) {
-            self.emit_bezieroid_above(active_edge_indices[0], endpoint.position.x)
+            self.emit_bezieroid_above(active_edge_indices[0], endpoint_x)
         }
         if self.should_fill_above_active_edge(active_edge_indices[1]) {
-            self.emit_bezieroid_above(active_edge_indices[1], endpoint.position.x)
+            self.emit_bezieroid_above(active_edge_indices[1], endpoint_x)
         }
         if self.should_fill_below_active_edge(active_edge_indices[1]) {
-            self.emit_bezieroid_below(active_edge_indices[1], endpoint.position.x)
+            self.emit_bezieroid_below(active_edge_indices[1], endpoint_x)
         }
 
         self.heap.pop();
@@ -204,6 +1035,8 @@ impl<'a> Partitioner<'a> {
         let prev_vector = (prev_endpoint.position - endpoint.position).normalize();
         let next_vector = (next_endpoint.position - endpoint.position).normalize();
 
+        new_active_edges[0].left_endpoint_index = endpoint_index;
+        new_active_edges[1].left_endpoint_index = endpoint_index;
         if prev_vector.y <= next_vector.y {
             new_active_edges[0].right_endpoint_index = prev_endpoint_index;
             new_active_edges[1].right_endpoint_index = next_endpoint_index;
@@ -221,13 +1054,92 @@ impl<'a> Partitioner<'a> {
         }
     }
 
+    /// Returns the bezieroid covering the input if it's a single axis-aligned rectangle, which
+    /// UI backgrounds and the like commonly are.
+    ///
+    /// This is the case if there's one path with one subpath, and that subpath consists of four
+    /// straight edges that alternate between exactly horizontal and exactly vertical and enclose
+    /// a nonzero area. The bezieroid runs between the two horizontal edges, so its corners are
+    /// those of the rectangle.
+    fn single_rectangle_bezieroid(&self) -> Option<Bezieroid> {
+        if self.paths.len() != 1 || self.subpaths.len() != 1 || self.subpath_is_degenerate(0) {
+            return None
+        }
+
+        let first_endpoint_index = self.subpaths[0].first_endpoint_index;
+        let last_endpoint_index = self.last_endpoint_index_of_subpath(0);
+        if last_endpoint_index - first_endpoint_index != 4 {
+            return None
+        }
+
+        let mut horizontal_edges = vec![];
+        for endpoint_index in first_endpoint_index..last_endpoint_index {
+            if self.control_points_index(endpoint_index).is_some() {
+                return None
+            }
+            let prev_endpoint_index = self.prev_endpoint_of(endpoint_index);
+            let prev_position = self.endpoints[prev_endpoint_index as usize].position;
+            let position = self.endpoints[endpoint_index as usize].position;
+            if prev_position.y == position.y {
+                horizontal_edges.push((prev_endpoint_index, endpoint_index))
+            } else if prev_position.x != position.x {
+                return None
+            }
+        }
+
+        // Alternating edges means the horizontal ones aren't adjacent.
+        if horizontal_edges.len() != 2 || horizontal_edges[0].1 == horizontal_edges[1].0 ||
+                horizontal_edges[1].1 == horizontal_edges[0].0 {
+            return None
+        }
+
+        // Edges with greater Y are on top in a Y-up coordinate system.
+        let (mut upper_edge, mut lower_edge) = (horizontal_edges[0], horizontal_edges[1]);
+        let upper_y = self.endpoints[upper_edge.0 as usize].position.y;
+        let lower_y = self.endpoints[lower_edge.0 as usize].position.y;
+        let upper_is_below = match self.y_axis {
+            YAxis::Down => upper_y > lower_y,
+            YAxis::Up => upper_y < lower_y,
+        };
+        if upper_is_below {
+            mem::swap(&mut upper_edge, &mut lower_edge)
+        }
+
+        let left_time = |(prev_endpoint_index, next_endpoint_index): (u32, u32)| {
+            let prev_x = self.endpoints[prev_endpoint_index as usize].position.x;
+            let next_x = self.endpoints[next_endpoint_index as usize].position.x;
+            if prev_x < next_x {
+                0.0
+            } else {
+                1.0
+            }
+        };
+        let (upper_left_time, lower_left_time) = (left_time(upper_edge), left_time(lower_edge));
+
+        Some(Bezieroid {
+            upper_prev_endpoint: upper_edge.0,
+            upper_next_endpoint: upper_edge.1,
+            lower_prev_endpoint: lower_edge.0,
+            lower_next_endpoint: lower_edge.1,
+            upper_left_time: upper_left_time,
+            upper_right_time: 1.0 - upper_left_time,
+            lower_left_time: lower_left_time,
+            lower_right_time: 1.0 - lower_left_time,
+        })
+    }
+
     fn init_heap_for_path(&mut self, path_index: u32) {
         let path = &self.paths[path_index as usize];
         let first_subpath_index = path.first_subpath_index;
         let last_subpath_index = self.last_subpath_index_of_path(path_index);
         for subpath_index in first_subpath_index..last_subpath_index {
+            // Degenerate subpaths fill nothing, so leave them out of the sweep entirely.
+            if self.subpath_is_degenerate(subpath_index) {
+                continue
+            }
+
             let first_endpoint_index = self.subpaths[subpath_index as usize].first_endpoint_index;
-            let last_endpoint_index = self.last_endpoint_index_of_path(path_index);
+            let last_endpoint_index = self.last_endpoint_index_of_subpath(subpath_index);
             for endpoint_index in first_endpoint_index..last_endpoint_index {
                 match self.classify_endpoint(endpoint_index) {
                     EndpointClass::Min => {
@@ -240,6 +1152,44 @@ impl<'a> Partitioner<'a> {
         }
     }
 
+    /// Returns true if the subpath encloses no area: a single point, a line that doubles back on
+    /// itself, and so on. A curved subpath is judged by its control polygon.
+    ///
+    /// This looks for any three points that aren't collinear rather than at the net area, since
+    /// the lobes of a self-intersecting subpath such as a bowtie cancel out.
+    fn subpath_is_degenerate(&self, subpath_index: u32) -> bool {
+        let first_endpoint_index = self.subpaths[subpath_index as usize].first_endpoint_index;
+        let last_endpoint_index = self.last_endpoint_index_of_subpath(subpath_index);
+
+        let mut polygon = vec![];
+        for endpoint_index in first_endpoint_index..last_endpoint_index {
+            if let Some(control_points_index) = self.control_points_index(endpoint_index) {
+                let control_points = &self.control_points[control_points_index as usize];
+                polygon.push(control_points.point1);
+                polygon.push(control_points.point2);
+            }
+            polygon.push(self.endpoints[endpoint_index as usize].position)
+        }
+
+        if polygon.len() < 3 {
+            return true
+        }
+
+        // If every point lies on the line through the first point and the point farthest from it,
+        // all of them are collinear.
+        let origin = polygon[0];
+        let mut direction = Vector2D::zero();
+        for point in &polygon {
+            let vector = *point - origin;
+            if vector.square_length() > direction.square_length() {
+                direction = vector
+            }
+        }
+        !polygon.iter().any(|point| {
+            direction.cross(*point - origin).abs() * 0.5 >= DEGENERATE_AREA_EPSILON
+        })
+    }
+
     fn should_fill_below_active_edge(&self, active_edge_index: u32) -> bool {
         // TODO(pcwalton): Support the winding fill rule.
         active_edge_index % 2 == 0
@@ -276,14 +1226,74 @@ impl<'a> Partitioner<'a> {
                 lower_left_time: lower_active_edge.time,
                 lower_right_time: self.solve_t_for_active_edge(lower_active_edge_index, right_x),
             };
+        }
 
-            self.bezieroids.push(new_bezieroid);
+        // Edges with greater Y are on top in a Y-up coordinate system.
+        let oriented_bezieroid = match self.y_axis {
+            YAxis::Down => new_bezieroid,
+            YAxis::Up => {
+                Bezieroid {
+                    upper_prev_endpoint: new_bezieroid.lower_prev_endpoint,
+                    upper_next_endpoint: new_bezieroid.lower_next_endpoint,
+                    lower_prev_endpoint: new_bezieroid.upper_prev_endpoint,
+                    lower_next_endpoint: new_bezieroid.upper_next_endpoint,
+                    upper_left_time: new_bezieroid.lower_left_time,
+                    upper_right_time: new_bezieroid.lower_right_time,
+                    lower_left_time: new_bezieroid.upper_left_time,
+                    lower_right_time: new_bezieroid.upper_right_time,
+                }
+            }
+        };
+
+        if self.emit_quads &&
+                self.control_points_index(oriented_bezieroid.upper_next_endpoint).is_none() &&
+                self.control_points_index(oriented_bezieroid.lower_next_endpoint).is_none() {
+            let quad = self.quad_for_bezieroid(&oriented_bezieroid);
+            self.quads.push(quad)
+        } else {
+            self.bezieroids.push(oriented_bezieroid)
         }
 
         self.active_edges[upper_active_edge_index as usize].time = new_bezieroid.upper_right_time;
         self.active_edges[lower_active_edge_index as usize].time = new_bezieroid.lower_right_time;
     }
 
+    /// Returns the corners of a bezieroid. For a bezieroid whose upper and lower edges are both
+    /// straight lines, this is the quad that it's equivalent to.
+    fn quad_for_bezieroid(&self, bezieroid: &Bezieroid) -> Quad {
+        Quad {
+            upper_left: self.point_on_edge(bezieroid.upper_prev_endpoint,
+                                           bezieroid.upper_next_endpoint,
+                                           bezieroid.upper_left_time),
+            upper_right: self.point_on_edge(bezieroid.upper_prev_endpoint,
+                                            bezieroid.upper_next_endpoint,
+                                            bezieroid.upper_right_time),
+            lower_left: self.point_on_edge(bezieroid.lower_prev_endpoint,
+                                           bezieroid.lower_next_endpoint,
+                                           bezieroid.lower_left_time),
+            lower_right: self.point_on_edge(bezieroid.lower_prev_endpoint,
+                                            bezieroid.lower_next_endpoint,
+                                            bezieroid.lower_right_time),
+        }
+    }
+
+    fn point_on_edge(&self, prev_endpoint_index: u32, next_endpoint_index: u32, time: f32)
+                     -> Point2D<f32> {
+        let prev_position = self.endpoints[prev_endpoint_index as usize].position;
+        let next_position = self.endpoints[next_endpoint_index as usize].position;
+        match self.control_points_index(next_endpoint_index) {
+            None => prev_position.lerp(next_position, time),
+            Some(control_points_index) => {
+                let control_points = &self.control_points[control_points_index as usize];
+                geometry::sample_cubic_bezier(time,
+                                              &prev_position,
+                                              &control_points.point1,
+                                              &control_points.point2,
+                                              &next_position)
+            }
+        }
+    }
+
     fn already_visited_point(&self, point: &Point) -> bool {
         // FIXME(pcwalton): This makes the visited vector too big.
         let index = point.endpoint_index as usize * 2 + point.point_type as usize;
@@ -325,9 +1335,10 @@ impl<'a> Partitioner<'a> {
         let prev_point = self.create_point_from_endpoint(self.prev_endpoint_of(endpoint_index));
         let next_point = self.create_point_from_endpoint(self.next_endpoint_of(endpoint_index));
 
+        // Points to the left compare greater; see the `PartialOrd` implementation for `Point`.
         match (prev_point.cmp(&point), next_point.cmp(&point)) {
-            (Ordering::Less, Ordering::Less) => EndpointClass::Max,
-            (Ordering::Less, _) | (_, Ordering::Less) => EndpointClass::Regular,
+            (Ordering::Greater, Ordering::Greater) => EndpointClass::Max,
+            (Ordering::Greater, _) | (_, Ordering::Greater) => EndpointClass::Regular,
             (_, _) => EndpointClass::Min,
         }
     }
@@ -414,11 +1425,12 @@ impl<'a> Partitioner<'a> {
         last_active_edge_index = cmp::min(last_active_edge_index + 1,
                                           self.active_edges.len() as u32);
 
-        for (upper_active_edge_index, upper_active_edge) in
+        for (index, upper_active_edge) in
                 self.active_edges[(first_active_edge_index as usize)..
                                   (last_active_edge_index as usize - 1)]
                     .iter()
                     .enumerate() {
+            let upper_active_edge_index = first_active_edge_index as usize + index;
             let crossing_position =
                 match self.crossing_point_for_active_edge(upper_active_edge_index as u32) {
                     None => continue,
@@ -580,10 +1592,349 @@ impl<'a> Partitioner<'a> {
             None => self.subpaths.len() as u32,
         }
     }
+}
 
-    fn last_endpoint_index_of_path(&self, path_index: u32) -> u32 {
-        self.last_endpoint_index_of_subpath(self.last_subpath_index_of_path(path_index))
+/// Merges quads that share a whole side into larger quads where the result covers exactly the
+/// same area.
+///
+/// Two quads side by side merge if their upper edges continue one another in a straight line and
+/// so do their lower edges. Two quads stacked on top of each other merge if their left edges
+/// continue one another in a straight line and so do their right edges. Shared sides are found by
+/// exact comparison of their corners, which the sweep produces. Runs in expected linear time.
+pub fn coalesce_quads(quads: &[Quad]) -> Vec<Quad> {
+    let mut coalesced: Vec<Quad> = Vec::with_capacity(quads.len());
+
+    // Maps from the right and lower sides of each quad in `coalesced` to its index.
+    let mut right_sides: HashMap<[u32; 4], usize> = HashMap::new();
+    let mut lower_sides: HashMap<[u32; 4], usize> = HashMap::new();
+
+    for quad in quads {
+        let left_side = side_key(&quad.upper_left, &quad.lower_left);
+        let upper_side = side_key(&quad.upper_left, &quad.upper_right);
+
+        let mut merged_index = None;
+        if let Some(&index) = right_sides.get(&left_side) {
+            let last: &Quad = &coalesced[index];
+            if are_collinear(&last.upper_left, &last.upper_right, &quad.upper_right) &&
+                    are_collinear(&last.lower_left, &last.lower_right, &quad.lower_right) {
+                merged_index = Some((index, Quad {
+                    upper_right: quad.upper_right,
+                    lower_right: quad.lower_right,
+                    ..*last
+                }))
+            }
+        }
+        if merged_index.is_none() {
+            if let Some(&index) = lower_sides.get(&upper_side) {
+                let last: &Quad = &coalesced[index];
+                if are_collinear(&last.upper_left, &last.lower_left, &quad.lower_left) &&
+                        are_collinear(&last.upper_right, &last.lower_right, &quad.lower_right) {
+                    merged_index = Some((index, Quad {
+                        lower_left: quad.lower_left,
+                        lower_right: quad.lower_right,
+                        ..*last
+                    }))
+                }
+            }
+        }
+
+        let (index, quad) = match merged_index {
+            Some((index, merged_quad)) => {
+                let old_quad = coalesced[index];
+                right_sides.remove(&side_key(&old_quad.upper_right, &old_quad.lower_right));
+                lower_sides.remove(&side_key(&old_quad.lower_left, &old_quad.lower_right));
+                coalesced[index] = merged_quad;
+                (index, merged_quad)
+            }
+            None => {
+                coalesced.push(*quad);
+                (coalesced.len() - 1, *quad)
+            }
+        };
+        right_sides.insert(side_key(&quad.upper_right, &quad.lower_right), index);
+        lower_sides.insert(side_key(&quad.lower_left, &quad.lower_right), index);
     }
+
+    coalesced
+}
+
+fn side_key(a: &Point2D<f32>, b: &Point2D<f32>) -> [u32; 4] {
+    [a.x.to_bits(), a.y.to_bits(), b.x.to_bits(), b.y.to_bits()]
+}
+
+// Whether `c` continues the line from `a` to `b` in the same direction.
+fn are_collinear(a: &Point2D<f32>, b: &Point2D<f32>, c: &Point2D<f32>) -> bool {
+    let (ab, bc) = (*b - *a, *c - *b);
+    ab.cross(bc).abs() <= COLLINEAR_EPSILON * ab.length() * bc.length() && ab.dot(bc) >= 0.0
+}
+
+// Links directed edges that have the fill on their left into closed loops. Where several loops
+// meet at a vertex, the sharpest left turn is taken so that the loops don't cross one another.
+fn chain_boundary_edges(edges: &[(Point2D<f32>, Point2D<f32>)]) -> Vec<Vec<Point2D<f32>>> {
+    let mut used = vec![false; edges.len()];
+    let mut contours = vec![];
+    for first_edge_index in 0..edges.len() {
+        if used[first_edge_index] {
+            continue
+        }
+        used[first_edge_index] = true;
+
+        let (start, mut prev, mut current) =
+            (edges[first_edge_index].0, edges[first_edge_index].0, edges[first_edge_index].1);
+        let mut points = vec![start];
+        while current != start {
+            let incoming = current - prev;
+            let next_edge_index = (0..edges.len()).filter(|&edge_index| {
+                !used[edge_index] && edges[edge_index].0 == current
+            }).max_by(|&a, &b| {
+                let turn = |edge_index: usize| {
+                    let outgoing = edges[edge_index].1 - current;
+                    incoming.cross(outgoing).atan2(incoming.dot(outgoing))
+                };
+                turn(a).partial_cmp(&turn(b)).unwrap_or(Ordering::Equal)
+            });
+
+            match next_edge_index {
+                Some(next_edge_index) => {
+                    used[next_edge_index] = true;
+                    points.push(current);
+                    prev = current;
+                    current = edges[next_edge_index].1;
+                }
+                None => break,
+            }
+        }
+
+        // Drop the loop if it couldn't be closed, which can happen if crossings were missed.
+        if current != start {
+            continue
+        }
+
+        // Pieces of the same original edge leave collinear vertices behind.
+        let mut index = 0;
+        while index < points.len() && points.len() > 3 {
+            let prev = points[(index + points.len() - 1) % points.len()];
+            let next = points[(index + 1) % points.len()];
+            let (incoming, outgoing) = (points[index] - prev, next - points[index]);
+            if incoming.cross(outgoing).abs() <= COLLINEAR_EPSILON * incoming.length() *
+                    outgoing.length() && incoming.dot(outgoing) > 0.0 {
+                points.remove(index);
+            } else {
+                index += 1
+            }
+        }
+
+        if points.len() >= 3 {
+            contours.push(points)
+        }
+    }
+    contours
+}
+
+fn polygons_share_edge(a: &[Point2D<f32>], b: &[Point2D<f32>]) -> bool {
+    let edges_of = |polygon: &[Point2D<f32>]| {
+        (0..polygon.len()).map(|index| {
+            (polygon[index], polygon[(index + 1) % polygon.len()])
+        }).collect::<Vec<_>>()
+    };
+    let b_edges = edges_of(b);
+    edges_of(a).iter().any(|&(a_from, a_to)| {
+        b_edges.iter().any(|&(b_from, b_to)| {
+            (a_from == b_from && a_to == b_to) || (a_from == b_to && a_to == b_from)
+        })
+    })
+}
+
+fn find_representative(representatives: &mut [usize], mut index: usize) -> usize {
+    while representatives[index] != index {
+        representatives[index] = representatives[representatives[index]];
+        index = representatives[index]
+    }
+    index
+}
+
+/// The two triangles covering a primitive whose corners are, in order, its upper left, upper
+/// right, lower left, and lower right.
+const QUAD_TRIANGLE_INDICES: [u32; 6] = [0, 1, 2, 1, 3, 2];
+
+/// How far to either side of an edge `boundary_contours()` looks for the fill, as a fraction of
+/// the length of the edge.
+const BOUNDARY_SIDE_OFFSET: f32 = 1e-3;
+
+/// The largest sine of the angle between two edges at which `boundary_contours()` and
+/// `coalesce_quads()` treat them as collinear.
+const COLLINEAR_EPSILON: f32 = 1e-5;
+
+/// The greatest distance by which `set_dilation()` lets a flattened curve deviate from the
+/// original, as a fraction of the dilation amount.
+pub const DILATION_FLATTENING_TOLERANCE: f32 = 0.1;
+
+/// The longest that `set_dilation()` lets the point of a convex corner grow, as a multiple of
+/// the dilation amount, before beveling it. This is the default miter limit of SVG.
+pub const DILATION_MITER_LIMIT: f32 = 4.0;
+
+/// The greatest distance, in device pixels, by which `partition_for_screen()` lets a flattened
+/// curve deviate from the original. A quarter of a pixel is below what antialiasing can show.
+pub const SCREEN_FLATTENING_TOLERANCE: f32 = 0.25;
+
+/// The largest ratio of the minor to the major axis of an edge, in device pixels, at which
+/// `enable_pixel_snapping()` still treats the edge as axis-aligned. This is about 3 degrees.
+pub const SNAP_SLOPE_THRESHOLD: f32 = 0.05;
+
+/// Subpaths are skipped by the sweep if no three of their points form a triangle with at least
+/// this area, in square path units.
+///
+/// This is the same as `f32::approx_epsilon()`, the tolerance below which the crossing
+/// computations treat edges as parallel, so every edge of a skipped subpath would have been
+/// nearly parallel to its neighbors anyway.
+pub const DEGENERATE_AREA_EPSILON: f32 = 1e-6;
+
+/// Appends a subpath made of straight lines through `points`, reversing them if necessary so that
+/// the subpath winds counterclockwise (if `counterclockwise` is true) or clockwise.
+fn push_line_subpath(endpoints: &mut Vec<Endpoint>,
+                     subpaths: &mut Vec<Subpath>,
+                     points: &[Point2D<f32>],
+                     counterclockwise: bool) {
+    let subpath_index = subpaths.len() as u32;
+    subpaths.push(Subpath {
+        first_endpoint_index: endpoints.len() as u32,
+        path_index: 0,
+    });
+
+    let new_endpoint = |position: &Point2D<f32>| {
+        Endpoint {
+            position: *position,
+            control_points_index: u32::MAX,
+            subpath_index: subpath_index,
+        }
+    };
+    if (geometry::signed_area(points) >= 0.0) == counterclockwise {
+        endpoints.extend(points.iter().map(new_endpoint))
+    } else {
+        endpoints.extend(points.iter().rev().map(new_endpoint))
+    }
+}
+
+/// Moves each edge of `polygon` by `amount` along its normal, away from the filled side, and
+/// returns the vertices where the moved edges meet. See `Partitioner::set_dilation()`.
+fn dilate_polygon(polygon: &[Point2D<f32>], amount: f32, filled_inside: bool)
+                  -> Vec<Point2D<f32>> {
+    if polygon.len() < 3 {
+        return polygon.to_vec()
+    }
+
+    // The inside of a polygon with positive area is to the left of each edge, so the right-hand
+    // normal points outside.
+    let outward_sign = if (geometry::signed_area(polygon) > 0.0) == filled_inside {
+        1.0
+    } else {
+        -1.0
+    };
+    let edge_vector = |index: usize| polygon[(index + 1) % polygon.len()] - polygon[index];
+    let outward_normal = |vector: Vector2D<f32>| {
+        Vector2D::new(vector.y, -vector.x) * (outward_sign / vector.length())
+    };
+
+    let mut dilated = Vec::with_capacity(polygon.len());
+    for (index, point) in polygon.iter().enumerate() {
+        let prev_vector = edge_vector((index + polygon.len() - 1) % polygon.len());
+        let next_vector = edge_vector(index);
+        let (prev_normal, next_normal) = (outward_normal(prev_vector), outward_normal(next_vector));
+        let convex = prev_vector.cross(next_vector) * outward_sign >= 0.0;
+
+        // The offset edges meet `amount / cos(θ / 2)` away from the corner, where `θ` is the
+        // angle between the normals, and `1 + cos θ = 2 cos² (θ / 2)`.
+        let one_plus_cos = 1.0 + prev_normal.dot(next_normal);
+        if convex && one_plus_cos < 2.0 / (DILATION_MITER_LIMIT * DILATION_MITER_LIMIT) {
+            dilated.push(*point + prev_normal * amount);
+            dilated.push(*point + next_normal * amount);
+            continue
+        }
+        if one_plus_cos <= f32::EPSILON {
+            // The edges double back on themselves, so there's nothing to offset.
+            dilated.push(*point);
+            continue
+        }
+
+        let mut miter = (prev_normal + next_normal) * (amount / one_plus_cos);
+        if !convex {
+            let miter_length = miter.length();
+            let max_length = f32::min(prev_vector.length(), next_vector.length());
+            if miter_length > max_length {
+                miter = miter * (max_length / miter_length)
+            }
+        }
+        dilated.push(*point + miter)
+    }
+    dilated
+}
+
+/// An event processed by the sweep, as recorded when debug events are enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepEvent {
+    pub kind: SweepEventKind,
+    pub position: Point2D<f32>,
+    /// The endpoint at which the event occurred. For crossings, this is the endpoint whose edge
+    /// was found to cross another.
+    pub endpoint_index: u32,
+    /// The active edges after processing the event, from the lowest Y to the highest, each given
+    /// as the endpoints at its start and end. Under the even-odd rule that the sweep uses, the
+    /// region between an even-indexed edge and the edge after it is filled.
+    pub active_edges: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SweepEventKind {
+    /// An endpoint with both of its edges to the right, which starts two active edges.
+    MinEndpoint,
+    /// An endpoint with one edge on either side, which continues an active edge.
+    RegularEndpoint,
+    /// An endpoint with both of its edges to the left, which ends two active edges.
+    MaxEndpoint,
+    /// A point at which two active edges cross and swap places.
+    Crossing,
+}
+
+/// Everything that a renderer needs to draw the results of a partition, as returned by
+/// `Partitioner::mesh()`.
+#[derive(Debug, Clone)]
+pub struct PartitionMesh {
+    /// The corners of the bezieroids and then of the quads, four per primitive.
+    pub vertices: Vec<Point2D<f32>>,
+    /// Triangles covering each primitive, indexing into `vertices`.
+    pub indices: Vec<u32>,
+    /// The bezieroids, in the same order as their corners appear in `vertices`.
+    pub bezieroids: Vec<Bezieroid>,
+    /// A rectangle enclosing everything that the mesh covers.
+    pub bounds: Rect<f32>,
+}
+
+/// A closed loop of straight edges on the boundary of the filled region of a path, as returned
+/// by `Partitioner::boundary_contours()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub path_index: u32,
+    /// The vertices of the contour. The last vertex connects back to the first.
+    pub points: Vec<Point2D<f32>>,
+}
+
+/// The direction in which the Y axis points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YAxis {
+    /// Y increases downward, as in screen coordinates.
+    Down,
+    /// Y increases upward, as in font and mathematical coordinates.
+    Up,
+}
+
+/// Errors that can occur when building a partitioner from an edge list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeListError {
+    /// An edge referred to a vertex past the end of the vertex array.
+    VertexIndexOutOfBounds,
+    /// The edges don't form closed loops. The value is the index of the vertex at which the loop
+    /// was left open.
+    OpenLoop(u32),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -606,18 +1957,20 @@ impl PartialEq for Point {
 
 impl Eq for Point {}
 
+// `BinaryHeap` pops the greatest point first, and the sweep has to visit points from left to
+// right, so points further to the left compare greater.
 impl PartialOrd for Point {
     #[inline]
     fn partial_cmp(&self, other: &Point) -> Option<Ordering> {
-        match self.position.x.partial_cmp(&other.position.x) {
+        match other.position.x.partial_cmp(&self.position.x) {
             None | Some(Ordering::Equal) => {}
             Some(ordering) => return Some(ordering),
         }
-        match self.position.y.partial_cmp(&other.position.y) {
+        match other.position.y.partial_cmp(&self.position.y) {
             None | Some(Ordering::Equal) => {}
             Some(ordering) => return Some(ordering),
         }
-        self.endpoint_index.partial_cmp(&other.endpoint_index)
+        other.endpoint_index.partial_cmp(&self.endpoint_index)
     }
 }
 
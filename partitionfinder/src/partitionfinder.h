@@ -30,6 +30,20 @@ struct pf_bezieroid {
 
 typedef struct pf_bezieroid pf_bezieroid_t;
 
+struct pf_quad {
+    pf_point2d_f32_t upper_left, upper_right;
+    pf_point2d_f32_t lower_left, lower_right;
+};
+
+typedef struct pf_quad pf_quad_t;
+
+struct pf_rect_f32 {
+    float origin_x, origin_y;
+    float width, height;
+};
+
+typedef struct pf_rect_f32 pf_rect_f32_t;
+
 struct pf_endpoint {
     pf_point2d_f32_t position;
     uint32_t control_points_index;
@@ -61,6 +75,12 @@ struct pf_partitioner;
 
 typedef struct pf_partitioner pf_partitioner_t;
 
+struct pf_mesh;
+
+typedef struct pf_mesh pf_mesh_t;
+
+typedef uint32_t (*pf_bezieroid_callback_t)(const pf_bezieroid_t *bezieroid, void *user_data);
+
 pf_partitioner_t *pf_partitioner_new(const pf_endpoint_t *endpoints,
                                      uint32_t endpoint_count,
                                      const pf_control_points_t *control_points,
@@ -72,11 +92,31 @@ pf_partitioner_t *pf_partitioner_new(const pf_endpoint_t *endpoints,
 
 void pf_partitioner_destroy(pf_partitioner_t *partitioner);
 
-void pf_partitioner_partition(pf_partitioner_t *partitioner);
+void pf_partitioner_enable_quads(pf_partitioner_t *partitioner);
+
+uint32_t pf_partitioner_partition(pf_partitioner_t *partitioner);
+
+uint32_t pf_partitioner_partition_with(pf_partitioner_t *partitioner,
+                                       pf_bezieroid_callback_t callback,
+                                       void *user_data);
 
 const pf_bezieroid_t *pf_partitioner_bezieroids(pf_partitioner_t *partitioner,
                                                 uint32_t *out_bezieroid_count);
 
+const pf_quad_t *pf_partitioner_quads(pf_partitioner_t *partitioner, uint32_t *out_quad_count);
+
+pf_mesh_t *pf_partitioner_mesh(pf_partitioner_t *partitioner);
+
+void pf_mesh_destroy(pf_mesh_t *mesh);
+
+const pf_point2d_f32_t *pf_mesh_vertices(const pf_mesh_t *mesh, uint32_t *out_vertex_count);
+
+const uint32_t *pf_mesh_indices(const pf_mesh_t *mesh, uint32_t *out_index_count);
+
+const pf_bezieroid_t *pf_mesh_bezieroids(const pf_mesh_t *mesh, uint32_t *out_bezieroid_count);
+
+pf_rect_f32_t pf_mesh_bounds(const pf_mesh_t *mesh);
+
 #ifdef __cplusplus
 }
 #endif
@@ -0,0 +1,93 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use capi;
+use euclid::Point2D;
+use std::slice;
+use std::u32;
+use {ColorU8, ControlPoints, Endpoint, Path, Subpath};
+
+#[test]
+fn partitioning_inconsistent_input_reports_failure() {
+    // The endpoints claim to belong to a subpath that doesn't exist.
+    let endpoints: Vec<_> = [(0.0, 0.0), (2.0, 1.0), (0.0, 2.0)].iter().map(|&(x, y)| {
+        Endpoint {
+            position: Point2D::new(x, y),
+            control_points_index: u32::MAX,
+            subpath_index: 3,
+        }
+    }).collect();
+    let control_points: [ControlPoints; 0] = [];
+    let subpaths = [Subpath { first_endpoint_index: 0, path_index: 0 }];
+    let paths = [Path { first_subpath_index: 0, fill_color: ColorU8 { r: 0, g: 0, b: 0, a: 255 } }];
+
+    unsafe {
+        let partitioner = capi::pf_partitioner_new(endpoints.as_ptr(),
+                                                   endpoints.len() as u32,
+                                                   control_points.as_ptr(),
+                                                   0,
+                                                   subpaths.as_ptr(),
+                                                   subpaths.len() as u32,
+                                                   paths.as_ptr(),
+                                                   paths.len() as u32);
+        assert!(!partitioner.is_null());
+        assert_ne!(capi::pf_partitioner_partition(partitioner), 0);
+
+        let mut bezieroid_count = u32::MAX;
+        capi::pf_partitioner_bezieroids(partitioner, &mut bezieroid_count);
+        assert_ne!(bezieroid_count, u32::MAX);
+        capi::pf_partitioner_destroy(partitioner);
+    }
+}
+
+#[test]
+fn quads_and_meshes_of_a_rectangle() {
+    let endpoints: Vec<_> = [(1.0, 2.0), (1.0, 5.0), (4.0, 5.0), (4.0, 2.0)].iter().map(|&(x, y)| {
+        Endpoint {
+            position: Point2D::new(x, y),
+            control_points_index: u32::MAX,
+            subpath_index: 0,
+        }
+    }).collect();
+    let control_points: [ControlPoints; 0] = [];
+    let subpaths = [Subpath { first_endpoint_index: 0, path_index: 0 }];
+    let paths = [Path { first_subpath_index: 0, fill_color: ColorU8 { r: 0, g: 0, b: 0, a: 255 } }];
+
+    unsafe {
+        let partitioner = capi::pf_partitioner_new(endpoints.as_ptr(),
+                                                   endpoints.len() as u32,
+                                                   control_points.as_ptr(),
+                                                   0,
+                                                   subpaths.as_ptr(),
+                                                   subpaths.len() as u32,
+                                                   paths.as_ptr(),
+                                                   paths.len() as u32);
+        capi::pf_partitioner_enable_quads(partitioner);
+        assert_eq!(capi::pf_partitioner_partition(partitioner), 0);
+
+        let mut quad_count = 0;
+        let quads = capi::pf_partitioner_quads(partitioner, &mut quad_count);
+        assert_eq!(quad_count, 1);
+        assert_eq!((*quads).upper_left, Point2D::new(1.0, 2.0));
+        assert_eq!((*quads).lower_right, Point2D::new(4.0, 5.0));
+
+        let mesh = capi::pf_partitioner_mesh(partitioner);
+        assert!(!mesh.is_null());
+        let (mut vertex_count, mut index_count, mut bezieroid_count) = (0, 0, u32::MAX);
+        let vertices = capi::pf_mesh_vertices(mesh, &mut vertex_count);
+        let indices = capi::pf_mesh_indices(mesh, &mut index_count);
+        capi::pf_mesh_bezieroids(mesh, &mut bezieroid_count);
+        assert_eq!((vertex_count, index_count, bezieroid_count), (4, 6, 0));
+        assert_eq!(*vertices.offset(3), Point2D::new(4.0, 5.0));
+        assert_eq!(slice::from_raw_parts(indices, 6), [0, 1, 2, 1, 3, 2]);
+        assert_eq!(capi::pf_mesh_bounds(mesh), capi::PfRectF32 {
+            origin_x: 1.0,
+            origin_y: 2.0,
+            width: 3.0,
+            height: 3.0,
+        });
+
+        capi::pf_mesh_destroy(mesh);
+        capi::pf_partitioner_destroy(partitioner);
+    }
+}
@@ -0,0 +1,861 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use euclid::{Point2D, Rect, Transform2D};
+use geometry;
+use partitioner::{self, Contour, EdgeListError, Partitioner, SweepEventKind, YAxis};
+use std::ops::ControlFlow;
+use std::u32;
+use {ColorU8, ControlPoints, Endpoint, Path, Quad, Subpath};
+
+fn square() -> Vec<Point2D<f32>> {
+    square_at(0.0, 0.0, 1.0)
+}
+
+fn square_at(x: f32, y: f32, size: f32) -> Vec<Point2D<f32>> {
+    vec![
+        Point2D::new(x, y),
+        Point2D::new(x + size, y),
+        Point2D::new(x + size, y + size),
+        Point2D::new(x, y + size),
+    ]
+}
+
+#[test]
+fn from_edges_builds_a_closed_square() {
+    let vertices = square();
+    let edges = [(2, 3), (0, 1), (3, 0), (1, 2)];
+    let mut partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+
+    assert_eq!(partitioner.subpaths().len(), 1);
+    let positions: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        assert_eq!(endpoint.control_points_index, u32::MAX);
+        assert_eq!(endpoint.subpath_index, 0);
+        (endpoint.position.x, endpoint.position.y)
+    }).collect();
+    assert_eq!(positions, [(1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (1.0, 0.0)]);
+
+    // The square is covered by a single bezieroid between its top edge, from (0, 0) to (1, 0),
+    // and its bottom edge, from (1, 1) to (0, 1).
+    partitioner.partition();
+    let bezieroids = partitioner.bezieroids();
+    assert_eq!(bezieroids.len(), 1);
+    let bezieroid = &bezieroids[0];
+    assert_eq!((bezieroid.upper_prev_endpoint, bezieroid.upper_next_endpoint,
+                bezieroid.lower_prev_endpoint, bezieroid.lower_next_endpoint),
+               (2, 3, 0, 1));
+    assert_eq!((bezieroid.upper_left_time, bezieroid.upper_right_time,
+                bezieroid.lower_left_time, bezieroid.lower_right_time),
+               (0.0, 1.0, 1.0, 0.0));
+}
+
+#[test]
+fn from_edges_rejects_open_loops() {
+    let vertices = square();
+    let edges = [(0, 1), (1, 2), (2, 3)];
+    assert_eq!(Partitioner::from_edges(&vertices, &edges).err(),
+               Some(EdgeListError::OpenLoop(3)));
+
+    let edges = [(0, 1), (1, 4)];
+    assert_eq!(Partitioner::from_edges(&vertices, &edges).err(),
+               Some(EdgeListError::VertexIndexOutOfBounds));
+}
+
+fn signed_area(partitioner: &Partitioner, subpath_index: usize) -> f32 {
+    let subpaths = partitioner.subpaths();
+    let start = subpaths[subpath_index].first_endpoint_index as usize;
+    let end = match subpaths.get(subpath_index + 1) {
+        Some(subpath) => subpath.first_endpoint_index as usize,
+        None => partitioner.endpoints().len(),
+    };
+    let points: Vec<_> = partitioner.endpoints()[start..end].iter().map(|endpoint| {
+        endpoint.position
+    }).collect();
+    geometry::signed_area(&points)
+}
+
+#[test]
+fn from_polygon_with_holes_winds_holes_against_the_outer() {
+    let outer = square_at(0.0, 0.0, 4.0);
+    let hole = square_at(1.0, 1.0, 1.0);
+    let partitioner = Partitioner::from_polygon_with_holes(&outer, &[&hole]);
+
+    assert_eq!(partitioner.subpaths().len(), 2);
+    assert_eq!(partitioner.endpoints().len(), 8);
+    assert_eq!(signed_area(&partitioner, 0), 16.0);
+    assert_eq!(signed_area(&partitioner, 1), -1.0);
+
+    // Reversing the input makes no difference.
+    let outer: Vec<_> = outer.into_iter().rev().collect();
+    let hole: Vec<_> = hole.into_iter().rev().collect();
+    let partitioner = Partitioner::from_polygon_with_holes(&outer, &[&hole]);
+    assert_eq!(signed_area(&partitioner, 0), 16.0);
+    assert_eq!(signed_area(&partitioner, 1), -1.0);
+}
+
+#[test]
+fn from_polygon_with_holes_drops_holes_outside_the_outer() {
+    let outer = square();
+    let outside = square_at(5.0, 5.0, 1.0);
+    let partitioner = Partitioner::from_polygon_with_holes(&outer, &[&outside, &outer[0..2]]);
+
+    assert_eq!(partitioner.subpaths().len(), 1);
+    assert_eq!(partitioner.endpoints().len(), 4);
+}
+
+#[test]
+fn pixel_snapping_moves_stem_edges_onto_the_pixel_grid() {
+    // A stem 0.9 units wide that leans slightly, at a scale of 10 device pixels per unit.
+    let stem = [
+        Point2D::new(0.32, 0.04),
+        Point2D::new(1.22, 0.04),
+        Point2D::new(1.23, 2.0),
+        Point2D::new(0.33, 2.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&stem, &[]);
+    let device_transform = Transform2D::create_scale(10.0, 10.0);
+    partitioner.enable_pixel_snapping(device_transform);
+
+    for endpoint in partitioner.endpoints() {
+        let position = device_transform.transform_point(&endpoint.position);
+        assert!((position.x - position.x.round()).abs() < 0.001, "x = {}", position.x);
+        assert!((position.y - position.y.round()).abs() < 0.001, "y = {}", position.y);
+    }
+}
+
+#[test]
+fn pixel_snapping_leaves_diagonal_edges_alone() {
+    let triangle = [
+        Point2D::new(0.25, 0.25),
+        Point2D::new(4.75, 1.0),
+        Point2D::new(0.5, 4.75),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&triangle, &[]);
+    partitioner.enable_pixel_snapping(Transform2D::identity());
+
+    let positions: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        (endpoint.position.x, endpoint.position.y)
+    }).collect();
+    assert_eq!(positions, [(0.25, 0.25), (4.75, 1.0), (0.5, 4.75)]);
+}
+
+#[test]
+fn region_count_of_disjoint_rectangles() {
+    let mut vertices = square_at(0.0, 0.0, 1.0);
+    vertices.extend(square_at(3.0, 0.0, 1.0));
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (4, 5), (5, 6), (6, 7), (7, 4)];
+    let partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+    assert_eq!(partitioner.region_count(), 2);
+}
+
+#[test]
+fn region_count_ignores_holes_but_not_islands() {
+    let outer = square_at(0.0, 0.0, 9.0);
+    let hole = square_at(1.0, 1.0, 7.0);
+    let partitioner = Partitioner::from_polygon_with_holes(&outer, &[&hole]);
+    assert_eq!(partitioner.region_count(), 1);
+
+    let island = square_at(3.0, 3.0, 3.0);
+    let mut vertices = outer.clone();
+    vertices.extend(hole);
+    vertices.extend(island);
+    let edges: Vec<_> = (0..3).flat_map(|square| {
+        (0..4).map(move |corner| (square * 4 + corner, square * 4 + (corner + 1) % 4))
+    }).collect();
+    let partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+    assert_eq!(partitioner.region_count(), 2);
+}
+
+#[test]
+fn welding_joins_rectangles_along_a_misaligned_seam() {
+    let mut vertices = square_at(0.0, 0.0, 1.0);
+    vertices.extend(vec![
+        Point2D::new(1.00001, 0.0),
+        Point2D::new(2.0, 0.0),
+        Point2D::new(2.0, 1.0),
+        Point2D::new(1.0, 0.99999),
+    ]);
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (4, 5), (5, 6), (6, 7), (7, 4)];
+    let mut partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+    assert_eq!(partitioner.region_count(), 2);
+
+    partitioner.weld_vertices(0.001);
+    assert_eq!(partitioner.region_count(), 1);
+    let positions: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        (endpoint.position.x, endpoint.position.y)
+    }).collect();
+    assert_eq!(positions, [
+        (0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0),
+        (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0),
+    ]);
+}
+
+#[test]
+fn welding_removes_edges_it_shrinks_to_nothing() {
+    // The second and third points, and the last and first, weld together.
+    let polygon = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(2.0, -1.0),
+        Point2D::new(2.0005, -0.9995),
+        Point2D::new(4.0, 0.5),
+        Point2D::new(2.0, 2.0),
+        Point2D::new(0.0005, 0.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&polygon, &[]);
+    partitioner.weld_vertices(0.001);
+    let positions: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        (endpoint.position.x, endpoint.position.y)
+    }).collect();
+    assert_eq!(positions, [(0.0, 0.0), (2.0, -1.0), (4.0, 0.5), (2.0, 2.0)]);
+
+    partitioner.partition();
+    assert!(!partitioner.bezieroids().is_empty());
+    let mesh = partitioner.mesh();
+    assert!(mesh.vertices.iter().all(|vertex| !vertex.x.is_nan() && !vertex.y.is_nan()));
+    assert!(partitioner.bezieroids().iter().all(|bezieroid| {
+        [
+            bezieroid.upper_left_time,
+            bezieroid.upper_right_time,
+            bezieroid.lower_left_time,
+            bezieroid.lower_right_time,
+        ].iter().all(|time| !time.is_nan())
+    }));
+}
+
+#[test]
+fn partition_with_stops_after_break() {
+    let polygon = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(2.0, -1.0),
+        Point2D::new(4.0, 0.5),
+        Point2D::new(2.0, 2.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&polygon, &[]);
+    partitioner.partition();
+    let total_bezieroid_count = partitioner.bezieroids().len();
+    assert!(total_bezieroid_count > 2);
+
+    let mut partitioner = Partitioner::from_polygon_with_holes(&polygon, &[]);
+    let mut seen = 0;
+    let result = partitioner.partition_with(|_| {
+        seen += 1;
+        if seen == 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(result, ControlFlow::Break(()));
+    assert_eq!(seen, 2);
+    assert_eq!(partitioner.bezieroids().len(), 2);
+
+    let mut partitioner = Partitioner::from_polygon_with_holes(&polygon, &[]);
+    let mut seen = 0;
+    let result = partitioner.partition_with(|_| {
+        seen += 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(result, ControlFlow::Continue(()));
+    assert_eq!(seen, total_bezieroid_count);
+
+    // Partitioning again starts over instead of appending to the earlier output.
+    partitioner.partition();
+    assert_eq!(partitioner.bezieroids().len(), total_bezieroid_count);
+}
+
+#[test]
+fn zero_area_subpaths_are_ignored() {
+    let outer = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(2.0, -1.0),
+        Point2D::new(4.0, 0.5),
+        Point2D::new(2.0, 2.0),
+    ];
+    let spur = [Point2D::new(1.0, 0.2), Point2D::new(2.0, 0.4), Point2D::new(3.0, 0.6)];
+
+    let mut clean_partitioner = Partitioner::from_polygon_with_holes(&outer, &[]);
+    clean_partitioner.partition();
+    let mut spur_partitioner = Partitioner::from_polygon_with_holes(&outer, &[&spur]);
+    assert_eq!(spur_partitioner.subpaths().len(), 2);
+    spur_partitioner.partition();
+
+    assert_eq!(format!("{:?}", spur_partitioner.bezieroids()),
+               format!("{:?}", clean_partitioner.bezieroids()));
+}
+
+#[test]
+fn self_intersecting_subpaths_are_not_degenerate() {
+    // The two lobes of a bowtie have opposite winding, so its net area is zero.
+    let bowtie = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(4.0, 1.0),
+        Point2D::new(4.0, 0.0),
+        Point2D::new(0.0, 1.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&bowtie, &[]);
+    partitioner.partition();
+    assert_eq!(partitioner.bezieroids().len(), 4);
+}
+
+#[test]
+fn rectangles_partition_into_quads() {
+    let mut partitioner = Partitioner::from_polygon_with_holes(&square_at(0.0, 0.0, 4.0), &[]);
+    partitioner.enable_quads();
+    partitioner.partition();
+    assert!(!partitioner.quads().is_empty());
+    assert!(partitioner.bezieroids().is_empty());
+
+    // Slanted straight edges become quads too.
+    let trapezoid = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(4.0, 0.0),
+        Point2D::new(3.0, 2.0),
+        Point2D::new(1.0, 2.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&trapezoid, &[]);
+    partitioner.enable_quads();
+    partitioner.partition();
+    assert!(partitioner.bezieroids().is_empty());
+    let area: f32 = partitioner.quads().iter().map(quad_signed_area).sum();
+    assert_eq!(area, 6.0);
+}
+
+#[test]
+fn quads_stop_with_the_sweep_after_break() {
+    // A curve bounds the leftmost region, and straight edges bound everything to its right.
+    let endpoint = |x, y, control_points_index| {
+        Endpoint {
+            position: Point2D::new(x, y),
+            control_points_index: control_points_index,
+            subpath_index: 0,
+        }
+    };
+    let endpoints = [
+        endpoint(0.0, 2.0, u32::MAX),
+        endpoint(2.0, 0.0, 0),
+        endpoint(4.0, 0.0, u32::MAX),
+        endpoint(6.0, 2.0, u32::MAX),
+        endpoint(4.0, 4.0, u32::MAX),
+        endpoint(2.0, 4.0, u32::MAX),
+    ];
+    let control_points = [
+        ControlPoints { point1: Point2D::new(0.0, 1.0), point2: Point2D::new(1.0, 0.0) },
+    ];
+    let (subpaths, paths) = single_path();
+
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.enable_quads();
+    assert_eq!(partitioner.partition_with(|_| ControlFlow::Continue(())),
+               ControlFlow::Continue(()));
+    assert_eq!(partitioner.bezieroids().len(), 1);
+    assert!(!partitioner.quads().is_empty());
+
+    // The sweep stops at the point that produced the bezieroid, before reaching any quads.
+    assert_eq!(partitioner.partition_with(|_| ControlFlow::Break(())), ControlFlow::Break(()));
+    assert_eq!(partitioner.bezieroids().len(), 1);
+    assert!(partitioner.quads().is_empty());
+}
+
+#[test]
+fn a_plain_rectangle_skips_the_sweep() {
+    let rectangle = [
+        Point2D::new(1.0, 2.0),
+        Point2D::new(1.0, 5.0),
+        Point2D::new(4.0, 5.0),
+        Point2D::new(4.0, 2.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&rectangle, &[]);
+    partitioner.enable_quads();
+    partitioner.enable_debug_events();
+    partitioner.partition();
+    assert!(partitioner.debug_events().is_empty());
+    assert!(partitioner.bezieroids().is_empty());
+    assert_eq!(quad_corners(partitioner.quads()),
+               [[(1.0, 2.0), (4.0, 2.0), (1.0, 5.0), (4.0, 5.0)]]);
+
+    let mut partitioner = Partitioner::from_polygon_with_holes(&rectangle, &[]);
+    partitioner.set_y_axis(YAxis::Up);
+    partitioner.enable_debug_events();
+    partitioner.partition();
+    assert!(partitioner.debug_events().is_empty());
+    let mesh = partitioner.mesh();
+    assert_eq!(partitioner.bezieroids().len(), 1);
+    assert_eq!(quad_corners(&[Quad {
+        upper_left: mesh.vertices[0],
+        upper_right: mesh.vertices[1],
+        lower_left: mesh.vertices[2],
+        lower_right: mesh.vertices[3],
+    }]), [[(1.0, 5.0), (4.0, 5.0), (1.0, 2.0), (4.0, 2.0)]]);
+
+    // Anything else goes through the sweep.
+    let hole = square_at(0.25, 0.25, 0.5);
+    let mut partitioner = Partitioner::from_polygon_with_holes(&square(), &[&hole]);
+    partitioner.enable_debug_events();
+    partitioner.partition();
+    assert!(!partitioner.debug_events().is_empty());
+}
+
+#[test]
+fn empty_input_partitions_into_nothing() {
+    let mut partitioner = Partitioner::new(&[], &[], &[], &[]);
+    partitioner.enable_debug_events();
+    assert_eq!(partitioner.partition_with(|_| panic!()), ControlFlow::Continue(()));
+    assert!(partitioner.bezieroids().is_empty());
+    assert!(partitioner.debug_events().is_empty());
+}
+
+fn quad(upper_left: (f32, f32), upper_right: (f32, f32), lower_left: (f32, f32),
+        lower_right: (f32, f32))
+        -> Quad {
+    Quad {
+        upper_left: Point2D::new(upper_left.0, upper_left.1),
+        upper_right: Point2D::new(upper_right.0, upper_right.1),
+        lower_left: Point2D::new(lower_left.0, lower_left.1),
+        lower_right: Point2D::new(lower_right.0, lower_right.1),
+    }
+}
+
+fn quad_corners(quads: &[Quad]) -> Vec<[(f32, f32); 4]> {
+    quads.iter().map(|quad| {
+        [
+            (quad.upper_left.x, quad.upper_left.y),
+            (quad.upper_right.x, quad.upper_right.y),
+            (quad.lower_left.x, quad.lower_left.y),
+            (quad.lower_right.x, quad.lower_right.y),
+        ]
+    }).collect()
+}
+
+#[test]
+fn stacked_quads_of_a_tall_rectangle_coalesce() {
+    let quads: Vec<_> = (0..4).map(|row| {
+        let (top, bottom) = (row as f32, row as f32 + 1.0);
+        quad((0.0, top), (1.0, top), (0.0, bottom), (1.0, bottom))
+    }).collect();
+    assert_eq!(quad_corners(&partitioner::coalesce_quads(&quads)),
+               [[(0.0, 0.0), (1.0, 0.0), (0.0, 4.0), (1.0, 4.0)]]);
+}
+
+#[test]
+fn side_by_side_quads_coalesce_only_along_straight_edges() {
+    // The first two quads continue each other's upper and lower edges, but the third turns.
+    let quads = [
+        quad((0.0, 0.0), (1.0, 0.5), (0.0, 3.0), (1.0, 3.0)),
+        quad((1.0, 0.5), (2.0, 1.0), (1.0, 3.0), (2.0, 3.0)),
+        quad((2.0, 1.0), (3.0, 0.0), (2.0, 3.0), (3.0, 3.0)),
+    ];
+    assert_eq!(quad_corners(&partitioner::coalesce_quads(&quads)), [
+        [(0.0, 0.0), (2.0, 1.0), (0.0, 3.0), (2.0, 3.0)],
+        [(2.0, 1.0), (3.0, 0.0), (2.0, 3.0), (3.0, 3.0)],
+    ]);
+}
+
+#[test]
+fn coalesce_output_merges_the_quads_of_a_partitioned_path() {
+    // The vertices along the top edge split the sweep at each of them, but the pieces between
+    // them continue the same straight edges.
+    let polygon = [
+        Point2D::new(0.0, 1.0),
+        Point2D::new(1.0, 0.0),
+        Point2D::new(2.0, 0.0),
+        Point2D::new(3.0, 0.0),
+        Point2D::new(4.0, 1.0),
+        Point2D::new(3.0, 2.0),
+        Point2D::new(1.0, 2.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&polygon, &[]);
+    partitioner.enable_quads();
+    partitioner.partition();
+    let quads = partitioner.quads().to_vec();
+
+    partitioner.coalesce_output();
+    assert!(partitioner.quads().len() < quads.len());
+    assert_eq!(quad_corners(partitioner.quads()),
+               quad_corners(&partitioner::coalesce_quads(&quads)));
+}
+
+/// A circle approximated with four cubic Béziers, as `(endpoints, control points)`.
+fn circle(radius: f32) -> (Vec<Endpoint>, Vec<ControlPoints>) {
+    const K: f32 = 0.5523;
+    let (r, k) = (radius, radius * K);
+    let endpoint = |x, y, control_points_index| {
+        Endpoint {
+            position: Point2D::new(x, y),
+            control_points_index: control_points_index,
+            subpath_index: 0,
+        }
+    };
+    let endpoints = vec![
+        endpoint(r, 0.0, 3),
+        endpoint(0.0, r, 0),
+        endpoint(-r, 0.0, 1),
+        endpoint(0.0, -r, 2),
+    ];
+    let control_points = vec![
+        ControlPoints { point1: Point2D::new(r, k), point2: Point2D::new(k, r) },
+        ControlPoints { point1: Point2D::new(-k, r), point2: Point2D::new(-r, k) },
+        ControlPoints { point1: Point2D::new(-r, -k), point2: Point2D::new(-k, -r) },
+        ControlPoints { point1: Point2D::new(k, -r), point2: Point2D::new(r, -k) },
+    ];
+    (endpoints, control_points)
+}
+
+fn single_path() -> ([Subpath; 1], [Path; 1]) {
+    ([Subpath { first_endpoint_index: 0, path_index: 0 }],
+     [Path { first_subpath_index: 0, fill_color: ColorU8 { r: 0, g: 0, b: 0, a: 255 } }])
+}
+
+fn sorted_positions(partitioner: &Partitioner, subpath_index: usize) -> Vec<(f32, f32)> {
+    let first_endpoint_index = partitioner.subpaths()[subpath_index].first_endpoint_index;
+    let mut positions: Vec<_> = partitioner.endpoints()[first_endpoint_index as usize..]
+                                           .iter()
+                                           .take_while(|endpoint| {
+        endpoint.subpath_index == subpath_index as u32
+    }).map(|endpoint| (endpoint.position.x, endpoint.position.y)).collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    positions
+}
+
+#[test]
+fn dilation_grows_bounds_by_twice_the_amount() {
+    let (endpoints, control_points) = circle(10.0);
+    let (subpaths, paths) = single_path();
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.set_dilation(0.5);
+    assert!(partitioner.endpoints().len() > endpoints.len());
+
+    let positions: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        assert_eq!(endpoint.control_points_index, u32::MAX);
+        endpoint.position
+    }).collect();
+    let bounds = Rect::from_points(&positions);
+    assert!((bounds.size.width - 21.0).abs() < 0.1);
+    assert!((bounds.size.height - 21.0).abs() < 0.1);
+}
+
+#[test]
+fn dilation_miters_concave_corners_and_shrinks_holes() {
+    let l_shape = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(4.0, 0.0),
+        Point2D::new(4.0, 1.0),
+        Point2D::new(1.0, 1.0),
+        Point2D::new(1.0, 4.0),
+        Point2D::new(0.0, 4.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&l_shape, &[]);
+    partitioner.set_dilation(0.25);
+    assert_eq!(sorted_positions(&partitioner, 0), [
+        (-0.25, -0.25), (-0.25, 4.25), (1.25, 1.25), (1.25, 4.25), (4.25, -0.25), (4.25, 1.25),
+    ]);
+
+    let hole = square_at(1.0, 1.0, 2.0);
+    let mut partitioner = Partitioner::from_polygon_with_holes(&square_at(0.0, 0.0, 4.0),
+                                                               &[&hole]);
+    partitioner.set_dilation(0.25);
+    assert_eq!(sorted_positions(&partitioner, 0),
+               [(-0.25, -0.25), (-0.25, 4.25), (4.25, -0.25), (4.25, 4.25)]);
+    assert_eq!(sorted_positions(&partitioner, 1),
+               [(1.25, 1.25), (1.25, 2.75), (2.75, 1.25), (2.75, 2.75)]);
+}
+
+#[cfg(feature = "lyon")]
+#[test]
+fn lyon_path_events_match_the_equivalent_arrays() {
+    use lyon_path::PathEvent;
+    use lyon_path::math::point;
+
+    // A circle whose last curve returns to its start, which becomes the closing curve.
+    let (endpoints, control_points) = circle(1.0);
+    let mut events = vec![PathEvent::Begin { at: point(1.0, 0.0) }];
+    for (index, &endpoint_index) in [1, 2, 3, 0].iter().enumerate() {
+        let from = endpoints[(endpoint_index + 3) % 4].position;
+        let to = endpoints[endpoint_index].position;
+        let control_points = &control_points[index];
+        events.push(PathEvent::Cubic {
+            from: point(from.x, from.y),
+            ctrl1: point(control_points.point1.x, control_points.point1.y),
+            ctrl2: point(control_points.point2.x, control_points.point2.y),
+            to: point(to.x, to.y),
+        })
+    }
+    events.push(PathEvent::End { last: point(1.0, 0.0), first: point(1.0, 0.0), close: true });
+    let mut lyon_partitioner = Partitioner::from_path_events(events.into_iter());
+
+    let (subpaths, paths) = single_path();
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    assert_eq!(format!("{:?}", lyon_partitioner.endpoints()), format!("{:?}", endpoints));
+    assert_eq!(format!("{:?}", lyon_partitioner.control_points()),
+               format!("{:?}", control_points));
+
+    lyon_partitioner.partition();
+    partitioner.partition();
+    assert!(!partitioner.bezieroids().is_empty());
+    assert_eq!(format!("{:?}", lyon_partitioner.bezieroids()),
+               format!("{:?}", partitioner.bezieroids()));
+}
+
+#[cfg(feature = "lyon")]
+#[test]
+fn lyon_quadratics_become_cubics() {
+    use lyon_path::PathEvent;
+    use lyon_path::math::point;
+
+    // The line back to the start is dropped in favor of the implicit closing edge.
+    let events = vec![
+        PathEvent::Begin { at: point(0.0, 0.0) },
+        PathEvent::Line { from: point(0.0, 0.0), to: point(3.0, 0.0) },
+        PathEvent::Quadratic { from: point(3.0, 0.0), ctrl: point(3.0, 3.0), to: point(0.0, 3.0) },
+        PathEvent::Line { from: point(0.0, 3.0), to: point(0.0, 0.0) },
+        PathEvent::End { last: point(0.0, 0.0), first: point(0.0, 0.0), close: true },
+    ];
+    let partitioner = Partitioner::from_path_events(events.into_iter());
+
+    let endpoints: Vec<_> = partitioner.endpoints().iter().map(|endpoint| {
+        (endpoint.position.x, endpoint.position.y, endpoint.control_points_index)
+    }).collect();
+    assert_eq!(endpoints, [(0.0, 0.0, u32::MAX), (3.0, 0.0, u32::MAX), (0.0, 3.0, 0)]);
+
+    let control_points = partitioner.control_points();
+    assert_eq!(control_points.len(), 1);
+    assert!((control_points[0].point1 - Point2D::new(3.0, 2.0)).length() < 1e-5);
+    assert!((control_points[0].point2 - Point2D::new(2.0, 3.0)).length() < 1e-5);
+}
+
+#[test]
+fn curves_partition_into_bezieroids() {
+    let (endpoints, control_points) = circle(1.0);
+    let (subpaths, paths) = single_path();
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.enable_quads();
+    partitioner.partition();
+    assert!(!partitioner.bezieroids().is_empty());
+    assert!(partitioner.quads().is_empty());
+}
+
+#[test]
+fn mesh_agrees_with_bezieroids() {
+    let (endpoints, control_points) = circle(1.0);
+    let (subpaths, paths) = single_path();
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.partition();
+
+    let mesh = partitioner.mesh();
+    let bezieroid_count = partitioner.bezieroids().len();
+    assert_eq!(format!("{:?}", mesh.bezieroids), format!("{:?}", partitioner.bezieroids()));
+    assert_eq!(mesh.vertices.len(), bezieroid_count * 4);
+    assert_eq!(mesh.indices.len(), bezieroid_count * 6);
+    assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+    assert!(mesh.vertices.iter().all(|vertex| {
+        vertex.x >= mesh.bounds.min_x() && vertex.x <= mesh.bounds.max_x() &&
+            vertex.y >= mesh.bounds.min_y() && vertex.y <= mesh.bounds.max_y()
+    }));
+}
+
+#[test]
+fn mesh_agrees_with_quads() {
+    // The sweep can't handle vertical edges yet, so use a diamond.
+    let diamond = [
+        Point2D::new(0.0, 2.0),
+        Point2D::new(2.0, 0.0),
+        Point2D::new(4.0, 2.0),
+        Point2D::new(2.0, 4.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&diamond, &[]);
+    partitioner.enable_quads();
+    partitioner.partition();
+
+    let mesh = partitioner.mesh();
+    assert!(mesh.bezieroids.is_empty());
+    let corners: Vec<_> = partitioner.quads().iter().flat_map(|quad| {
+        vec![quad.upper_left, quad.upper_right, quad.lower_left, quad.lower_right]
+    }).collect();
+    assert_eq!(mesh.vertices, corners);
+    assert_eq!(&mesh.indices[..6], &[0, 1, 2, 1, 3, 2]);
+    assert_eq!(mesh.bounds, Rect::from_points(&corners));
+}
+
+#[test]
+fn larger_on_screen_means_finer_flattening() {
+    let (endpoints, control_points) = circle(10.0);
+    let (subpaths, paths) = single_path();
+
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.partition_for_screen(Transform2D::row_major(1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+    let endpoint_count_1x = partitioner.endpoints().len();
+    let bezieroid_count_1x = partitioner.bezieroids().len();
+    assert!(endpoint_count_1x > endpoints.len());
+    assert!(partitioner.endpoints().iter().all(|endpoint| {
+        endpoint.control_points_index == u32::MAX
+    }));
+
+    let mut partitioner = Partitioner::new(&endpoints, &control_points, &subpaths, &paths);
+    partitioner.partition_for_screen(Transform2D::row_major(2.0, 0.0, 0.0, 2.0, 0.0, 0.0));
+    assert!(partitioner.endpoints().len() > endpoint_count_1x);
+    assert!(partitioner.bezieroids().len() > bezieroid_count_1x);
+}
+
+fn donut_quads(y_axis: YAxis) -> Vec<Quad> {
+    let outer = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(3.0, -2.0),
+        Point2D::new(7.0, -1.5),
+        Point2D::new(10.0, 0.5),
+        Point2D::new(6.5, 2.5),
+        Point2D::new(2.5, 2.0),
+    ];
+    let hole = [
+        Point2D::new(3.0, 0.1),
+        Point2D::new(5.0, -0.5),
+        Point2D::new(7.0, 0.3),
+        Point2D::new(5.1, 1.0),
+    ];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&outer, &[&hole]);
+    partitioner.enable_quads();
+    partitioner.set_y_axis(y_axis);
+    partitioner.partition();
+    partitioner.quads().to_vec()
+}
+
+#[test]
+fn y_axis_orients_primitives() {
+    let down_quads = donut_quads(YAxis::Down);
+    let up_quads = donut_quads(YAxis::Up);
+    assert!(!down_quads.is_empty());
+    assert_eq!(down_quads.len(), up_quads.len());
+
+    for (down_quad, up_quad) in down_quads.iter().zip(up_quads.iter()) {
+        // On screen, the upper edge has the smaller Y.
+        assert!(down_quad.upper_left.y <= down_quad.lower_left.y);
+        assert!(down_quad.upper_right.y <= down_quad.lower_right.y);
+
+        // With Y pointing up, the upper edge has the larger Y.
+        assert_eq!(up_quad.upper_left, down_quad.lower_left);
+        assert_eq!(up_quad.upper_right, down_quad.lower_right);
+        assert_eq!(up_quad.lower_left, down_quad.upper_left);
+        assert_eq!(up_quad.lower_right, down_quad.upper_right);
+    }
+}
+
+fn quad_signed_area(quad: &Quad) -> f32 {
+    geometry::signed_area(&[quad.upper_left, quad.upper_right, quad.lower_right, quad.lower_left])
+}
+
+#[test]
+fn y_axis_preserves_the_filled_area() {
+    let triangle = [Point2D::new(0.0, 0.0), Point2D::new(2.0, -1.0), Point2D::new(4.0, 1.0)];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&triangle, &[]);
+    partitioner.enable_quads();
+    partitioner.partition();
+    let triangle_quads = partitioner.quads().to_vec();
+    assert_eq!(quad_corners(&triangle_quads), [
+        [(0.0, 0.0), (2.0, -1.0), (0.0, 0.0), (2.0, 0.5)],
+        [(2.0, -1.0), (4.0, 1.0), (2.0, 0.5), (4.0, 1.0)],
+    ]);
+
+    // The donut's quads cover the outer hexagon minus the hole, and never overlap, so each one
+    // winds the same way and their areas add up to the filled area.
+    let filled_area = 28.25 - 2.99;
+    let down_quads = donut_quads(YAxis::Down);
+    let up_quads = donut_quads(YAxis::Up);
+    assert_eq!(down_quads.len(), up_quads.len());
+    for quads in &[&triangle_quads, &down_quads] {
+        assert!(quads.iter().all(|quad| quad_signed_area(quad) >= 0.0));
+    }
+    let total_area: f32 = down_quads.iter().map(quad_signed_area).sum();
+    assert!((total_area - filled_area).abs() < 0.001, "{} != {}", total_area, filled_area);
+
+    // Flipping the Y axis flips the winding of every quad without changing its area.
+    for (down_quad, up_quad) in down_quads.iter().zip(up_quads.iter()) {
+        assert!((quad_signed_area(down_quad) + quad_signed_area(up_quad)).abs() < 0.001);
+    }
+}
+
+#[test]
+fn debug_events_of_a_triangle() {
+    let triangle = [Point2D::new(0.0, 0.0), Point2D::new(2.0, -1.0), Point2D::new(4.0, 1.0)];
+    let mut partitioner = Partitioner::from_polygon_with_holes(&triangle, &[]);
+    partitioner.partition();
+    assert!(partitioner.debug_events().is_empty());
+
+    let mut partitioner = Partitioner::from_polygon_with_holes(&triangle, &[]);
+    partitioner.enable_debug_events();
+    partitioner.partition();
+    let events = partitioner.debug_events();
+
+    let kinds: Vec<_> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(kinds, [
+        SweepEventKind::MinEndpoint,
+        SweepEventKind::RegularEndpoint,
+        SweepEventKind::MaxEndpoint,
+    ]);
+    for event in events {
+        assert_eq!(event.position, partitioner.endpoints()[event.endpoint_index as usize].position);
+    }
+
+    // The leftmost point starts both of its edges, and the last point ends them.
+    assert_eq!(events[0].position, Point2D::new(0.0, 0.0));
+    assert_eq!(events[0].active_edges.len(), 2);
+    assert_eq!(events[1].active_edges.len(), 2);
+    assert!(events[2].active_edges.is_empty());
+}
+
+// Starts each contour at its lowest vertex so that contours can be compared.
+fn normalized_contours(contours: Vec<Contour>) -> Vec<Vec<(f32, f32)>> {
+    let mut contours: Vec<Vec<(f32, f32)>> = contours.into_iter().map(|contour| {
+        let mut points: Vec<_> = contour.points.iter().map(|point| (point.x, point.y)).collect();
+        let first_index = (0..points.len()).min_by(|&a, &b| {
+            points[a].partial_cmp(&points[b]).unwrap()
+        }).unwrap();
+        points.rotate_left(first_index);
+        points
+    }).collect();
+    contours.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    contours
+}
+
+#[test]
+fn boundary_contours_of_a_figure_eight() {
+    // The edges cross at (1, 1), so under the even-odd rule the figure fills two triangles that
+    // touch at their tips.
+    let vertices = [
+        Point2D::new(0.0, 0.0),
+        Point2D::new(2.0, 2.0),
+        Point2D::new(2.0, 0.0),
+        Point2D::new(0.0, 2.0),
+    ];
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 0)];
+    let mut partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+    partitioner.set_y_axis(YAxis::Up);
+
+    let contours = partitioner.boundary_contours();
+    for contour in &contours {
+        assert!(geometry::signed_area(&contour.points) > 0.0);
+    }
+    assert_eq!(normalized_contours(contours), [
+        vec![(0.0, 0.0), (1.0, 1.0), (0.0, 2.0)],
+        vec![(1.0, 1.0), (2.0, 0.0), (2.0, 2.0)],
+    ]);
+
+    partitioner.set_y_axis(YAxis::Down);
+    for contour in &partitioner.boundary_contours() {
+        assert!(geometry::signed_area(&contour.points) < 0.0);
+    }
+}
+
+#[test]
+fn boundary_contours_wind_holes_against_outers() {
+    let mut vertices = square_at(0.0, 0.0, 3.0);
+    vertices.extend(square_at(1.0, 1.0, 1.0));
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (4, 5), (5, 6), (6, 7), (7, 4)];
+    let mut partitioner = Partitioner::from_edges(&vertices, &edges).unwrap();
+    partitioner.set_y_axis(YAxis::Up);
+
+    let contours = partitioner.boundary_contours();
+    assert_eq!(contours.len(), 2);
+    let areas: Vec<_> = contours.iter().map(|contour| {
+        geometry::signed_area(&contour.points)
+    }).collect();
+    assert!(areas.contains(&9.0));
+    assert!(areas.contains(&-1.0));
+}
@@ -12,8 +12,40 @@
 //!
 //! Consulting this table is typically the first step when rendering some text.
 
+use byteorder::{BigEndian, ByteOrder};
+use error::FontError;
+use std::cmp::{self, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::iter::FromIterator;
+use std::mem;
+use std::u16;
+
+/// The glyph ID that fonts use for characters they have no glyph for, usually drawn as a box.
+pub const MISSING_GLYPH: u16 = 0;
+
+/// The largest Unicode codepoint. Codes beyond it in malformed fonts aren't characters.
+pub const MAX_CODEPOINT: u32 = 0x10ffff;
+
+/// The number of codepoints in each page of a `DecodedCmap`.
+const BMP_PAGE_SIZE: usize = 256;
+/// The number of pages that the Basic Multilingual Plane takes up.
+const BMP_PAGE_COUNT: usize = 65536 / BMP_PAGE_SIZE;
+
+/// The magic number that begins a compiled character map, `PFCM`.
+const COMPILED_CMAP_MAGIC: u32 = ((b'P' as u32) << 24) |
+                                 ((b'F' as u32) << 16) |
+                                 ((b'C' as u32) << 8)  |
+                                  (b'M' as u32);
+/// Bumped whenever the layout of compiled character maps changes.
+const COMPILED_CMAP_VERSION: u32 = 1;
+/// The magic number, the version, and the range count.
+const COMPILED_CMAP_HEADER_SIZE: usize = 12;
+/// The starting codepoint, followed by the first and last glyph IDs.
+const COMPILED_CMAP_RANGE_SIZE: usize = 8;
+
 /// A consecutive series of Unicode codepoints.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CodepointRange {
     /// The starting code point, inclusive.
     pub start: u32,
@@ -46,6 +78,13 @@ impl CodepointRange {
             end: self.end,
         }
     }
+
+    /// Returns the fewest sorted ranges covering every character in `string`, ready to be passed
+    /// to `Font::glyph_mapping_for_codepoint_ranges()`.
+    #[inline]
+    pub fn ranges_from_str(string: &str) -> CodepointRanges {
+        string.chars().collect()
+    }
 }
 
 impl CodepointRanges {
@@ -66,7 +105,7 @@ impl CodepointRanges {
         for &ch in chars {
             match ranges.last_mut() {
                 Some(ref mut range) if range.end == ch as u32 => continue,
-                Some(ref mut range) if range.end == ch as u32 + 1 => {
+                Some(ref mut range) if range.end + 1 == ch as u32 => {
                     range.end += 1;
                     continue
                 }
@@ -81,6 +120,16 @@ impl CodepointRanges {
     }
 }
 
+/// Collects characters in any order, with duplicates, into the fewest sorted ranges covering
+/// them.
+impl FromIterator<char> for CodepointRanges {
+    fn from_iter<I>(iter: I) -> CodepointRanges where I: IntoIterator<Item = char> {
+        let mut chars: Vec<char> = iter.into_iter().collect();
+        chars.sort();
+        CodepointRanges::from_sorted_chars(&chars)
+    }
+}
+
 /// An iterator over all codepoints in a range.
 pub struct CodepointRangeIter {
     start: u32,
@@ -102,8 +151,380 @@ impl Iterator for CodepointRangeIter {
     }
 }
 
+/// A set of codepoints stored as sorted ranges, with union, intersection, and difference.
+///
+/// The ranges are kept sorted, non-overlapping, and non-adjacent, so membership queries are a
+/// binary search and set operations are a single merge pass. Font fallback can use this to find
+/// the codepoints of a run that a face doesn't cover.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CodepointRangeSet {
+    ranges: Vec<CodepointRange>,
+}
+
+impl CodepointRangeSet {
+    /// Creates an empty set.
+    #[inline]
+    pub fn new() -> CodepointRangeSet {
+        CodepointRangeSet {
+            ranges: vec![],
+        }
+    }
+
+    /// Creates a set from arbitrary codepoint ranges, which may be unsorted, overlapping, or
+    /// adjacent.
+    pub fn from_ranges(mut ranges: Vec<CodepointRange>) -> CodepointRangeSet {
+        ranges.sort_by_key(|range| range.start);
+
+        let mut coalesced: Vec<CodepointRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if range.start > range.end {
+                continue
+            }
+            if let Some(last) = coalesced.last_mut() {
+                if range.start <= last.end.saturating_add(1) {
+                    if range.end > last.end {
+                        last.end = range.end
+                    }
+                    continue
+                }
+            }
+            coalesced.push(range)
+        }
+
+        CodepointRangeSet {
+            ranges: coalesced,
+        }
+    }
+
+    /// Returns the codepoints that are in this set, `other`, or both.
+    pub fn union(&self, other: &CodepointRangeSet) -> CodepointRangeSet {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + other.ranges.len());
+        ranges.extend_from_slice(&self.ranges);
+        ranges.extend_from_slice(&other.ranges);
+        CodepointRangeSet::from_ranges(ranges)
+    }
+
+    /// Returns the codepoints that are in both this set and `other`.
+    pub fn intersection(&self, other: &CodepointRangeSet) -> CodepointRangeSet {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let (start, end) = (cmp::max(a.start, b.start), cmp::min(a.end, b.end));
+            if start <= end {
+                ranges.push(CodepointRange::new(start, end))
+            }
+            if a.end < b.end {
+                i += 1
+            } else {
+                j += 1
+            }
+        }
+
+        // The pieces are sorted and can't touch, since the inputs' ranges don't.
+        CodepointRangeSet {
+            ranges: ranges,
+        }
+    }
+
+    /// Returns the codepoints that are in this set but not in `other`, such as the characters of
+    /// a run that a font can't render.
+    pub fn difference(&self, other: &CodepointRangeSet) -> CodepointRangeSet {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for range in &self.ranges {
+            while j < other.ranges.len() && other.ranges[j].end < range.start {
+                j += 1
+            }
+
+            // Cut out each range of `other` that overlaps this one.
+            let mut start = Some(range.start);
+            for hole in other.ranges[j..].iter().take_while(|hole| hole.start <= range.end) {
+                let piece_start = match start {
+                    Some(piece_start) => piece_start,
+                    None => break,
+                };
+                if hole.start > piece_start {
+                    ranges.push(CodepointRange::new(piece_start, hole.start - 1))
+                }
+                start = if hole.end < range.end { Some(hole.end + 1) } else { None }
+            }
+            if let Some(start) = start {
+                ranges.push(CodepointRange::new(start, range.end))
+            }
+        }
+
+        CodepointRangeSet {
+            ranges: ranges,
+        }
+    }
+
+    /// Returns true if the given codepoint is in this set.
+    pub fn contains(&self, codepoint: u32) -> bool {
+        let (mut lo, mut hi) = (0, self.ranges.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if codepoint < self.ranges[mid].start {
+                hi = mid
+            } else if codepoint > self.ranges[mid].end {
+                lo = mid + 1
+            } else {
+                return true
+            }
+        }
+        false
+    }
+
+    /// Returns true if this set contains no codepoints.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the sorted, coalesced ranges that make up this set.
+    #[inline]
+    pub fn ranges(&self) -> &[CodepointRange] {
+        &self.ranges
+    }
+}
+
+impl From<CoverageSet> for CodepointRangeSet {
+    #[inline]
+    fn from(coverage: CoverageSet) -> CodepointRangeSet {
+        coverage.codepoints
+    }
+}
+
+/// The set of codepoints that a font (or several fonts) can map to glyphs.
+///
+/// This is a `CodepointRangeSet` with queries for deciding whether a font can serve as a
+/// fallback.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CoverageSet {
+    codepoints: CodepointRangeSet,
+}
+
+impl CoverageSet {
+    /// Creates an empty coverage set.
+    #[inline]
+    pub fn new() -> CoverageSet {
+        CoverageSet {
+            codepoints: CodepointRangeSet::new(),
+        }
+    }
+
+    /// Creates a coverage set from arbitrary codepoint ranges, which may be unsorted, overlapping,
+    /// or adjacent.
+    #[inline]
+    pub fn from_ranges(ranges: Vec<CodepointRange>) -> CoverageSet {
+        CoverageSet {
+            codepoints: CodepointRangeSet::from_ranges(ranges),
+        }
+    }
+
+    /// Returns the union of this set and `other`.
+    #[inline]
+    pub fn union(&self, other: &CoverageSet) -> CoverageSet {
+        CoverageSet {
+            codepoints: self.codepoints.union(&other.codepoints),
+        }
+    }
+
+    /// Returns the codepoints that are in both this set and `other`.
+    #[inline]
+    pub fn intersection(&self, other: &CoverageSet) -> CoverageSet {
+        CoverageSet {
+            codepoints: self.codepoints.intersection(&other.codepoints),
+        }
+    }
+
+    /// Returns true if the given codepoint is in this set.
+    #[inline]
+    pub fn contains(&self, codepoint: u32) -> bool {
+        self.codepoints.contains(codepoint)
+    }
+
+    /// Returns how many codepoints of the given range are in this set, for deciding whether a
+    /// font covers enough of a block to serve as a fallback for it.
+    pub fn count_in_range(&self, range: &CodepointRange) -> u32 {
+        let ranges = self.ranges();
+
+        // Skip the ranges that end before this one starts.
+        let (mut lo, mut hi) = (0, ranges.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if ranges[mid].end < range.start {
+                lo = mid + 1
+            } else {
+                hi = mid
+            }
+        }
+
+        ranges[lo..].iter().take_while(|other| other.start <= range.end).map(|other| {
+            cmp::min(other.end, range.end) - cmp::max(other.start, range.start) + 1
+        }).sum()
+    }
+
+    /// Returns true if every codepoint of the given range is in this set.
+    #[inline]
+    pub fn contains_range(&self, range: &CodepointRange) -> bool {
+        range.start > range.end || self.count_in_range(range) == range.end - range.start + 1
+    }
+
+    /// Returns true if this set contains no codepoints.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.codepoints.is_empty()
+    }
+
+    /// Returns the sorted, coalesced ranges that make up this set.
+    #[inline]
+    pub fn ranges(&self) -> &[CodepointRange] {
+        self.codepoints.ranges()
+    }
+
+    /// Returns the covered codepoints as a plain set, for combining with other codepoints.
+    #[inline]
+    pub fn codepoints(&self) -> &CodepointRangeSet {
+        &self.codepoints
+    }
+}
+
+/// A compact, approximate set of the codepoints that a font covers, for quickly ruling fonts out
+/// during fallback.
+///
+/// `maybe_contains()` never returns false for a covered codepoint, but it returns true for about
+/// 1% of uncovered codepoints (`BLOOM_BITS_PER_CODEPOINT` bits and `BLOOM_HASH_COUNT` hashes per
+/// codepoint give a false positive rate of roughly 0.8%). A positive answer must therefore be
+/// confirmed by consulting the character map, but a negative one needn't be, and most queries in
+/// a large fallback list are negative.
+#[derive(Clone, Debug)]
+pub struct CoverageBloom {
+    words: Vec<u64>,
+    mask: u32,
+}
+
+/// The number of filter bits that `CoverageBloom` allots to each covered codepoint.
+pub const BLOOM_BITS_PER_CODEPOINT: usize = 10;
+
+/// The number of bits that `CoverageBloom` sets for each codepoint.
+pub const BLOOM_HASH_COUNT: u32 = 7;
+
+impl CoverageBloom {
+    /// Builds a filter containing every codepoint in `coverage`.
+    pub fn from_coverage(coverage: &CoverageSet) -> CoverageBloom {
+        let codepoint_count = coverage.ranges().iter().map(|range| {
+            (range.end - range.start) as usize + 1
+        }).sum::<usize>();
+        let bit_count =
+            cmp::max(codepoint_count * BLOOM_BITS_PER_CODEPOINT, 64).next_power_of_two();
+
+        let mut bloom = CoverageBloom {
+            words: vec![0; bit_count / 64],
+            mask: (bit_count - 1) as u32,
+        };
+        for range in coverage.ranges() {
+            for codepoint in range.iter() {
+                let (first_hash, second_hash) = bloom_hashes(codepoint);
+                for hash_index in 0..BLOOM_HASH_COUNT {
+                    let bit = first_hash.wrapping_add(hash_index.wrapping_mul(second_hash)) &
+                        bloom.mask;
+                    bloom.words[(bit / 64) as usize] |= 1 << (bit % 64)
+                }
+            }
+        }
+        bloom
+    }
+
+    /// Returns false if the codepoint is definitely not covered, and true if it might be.
+    pub fn maybe_contains(&self, codepoint: u32) -> bool {
+        let (first_hash, second_hash) = bloom_hashes(codepoint);
+        (0..BLOOM_HASH_COUNT).all(|hash_index| {
+            let bit = first_hash.wrapping_add(hash_index.wrapping_mul(second_hash)) & self.mask;
+            self.words[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+        })
+    }
+}
+
+// Two independent hashes, combined to derive the others ("double hashing"). The second is odd so
+// that it's coprime with the power-of-two filter size.
+fn bloom_hashes(codepoint: u32) -> (u32, u32) {
+    // The SplitMix64 finalizer.
+    let mut hash = (codepoint as u64).wrapping_add(0x9e3779b97f4a7c15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^= hash >> 31;
+    (hash as u32, (hash >> 32) as u32 | 1)
+}
+
+/// The encoding record of one subtable of a font's character map.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SubtableInfo {
+    /// The platform ID (0 = Unicode, 1 = Macintosh, 3 = Microsoft).
+    pub platform_id: u16,
+    /// The platform-specific encoding ID.
+    pub encoding_id: u16,
+    /// The byte offset of the subtable from the start of the `cmap` table.
+    pub offset: u32,
+    /// The format of the subtable, or `None` if the offset points outside the `cmap` table.
+    pub format: Option<u16>,
+    /// The language field of the subtable: the Macintosh language ID plus one, or 0 if the
+    /// subtable doesn't depend on the language. `None` if the format has no such field or isn't
+    /// known.
+    pub language: Option<u32>,
+}
+
+/// The result of comparing two character map subtables against each other.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SubtableComparison {
+    /// The codepoints that the two subtables map differently, in ascending order.
+    pub mismatches: Vec<CodepointMismatch>,
+}
+
+impl SubtableComparison {
+    /// Returns true if the subtables agree on every codepoint they can both represent, which
+    /// means that either one of them is redundant.
+    #[inline]
+    pub fn is_identical(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Describes how a glyph mapping was looked up, to help diagnose missing glyphs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MappingReport {
+    /// The subtable that the codepoints were looked up in. Its `format` says how it's encoded.
+    pub subtable: SubtableInfo,
+    /// The requested codepoints that map to the missing glyph, in ascending order. A malformed
+    /// subtable makes the lookup fail instead, so these are codepoints that the font really has
+    /// no glyph for.
+    pub unmapped: Vec<CodepointRange>,
+}
+
+impl MappingReport {
+    /// Returns true if every requested codepoint has a glyph.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.unmapped.is_empty()
+    }
+}
+
+/// A codepoint that two character map subtables map to different glyphs.
+///
+/// A glyph ID of 0 means that the subtable doesn't map the codepoint at all.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CodepointMismatch {
+    /// The codepoint in question.
+    pub codepoint: u32,
+    /// The glyph that the first subtable maps the codepoint to.
+    pub glyph_a: u16,
+    /// The glyph that the second subtable maps the codepoint to.
+    pub glyph_b: u16,
+}
+
 #[doc(hidden)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GlyphRange {
     /// The starting glyph ID in the range, inclusive.
     pub start: u16,
@@ -113,15 +534,24 @@ pub struct GlyphRange {
 
 #[doc(hidden)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MappedGlyphRange {
     pub codepoint_start: u32,
     pub glyphs: GlyphRange,
 }
 
 /// A map from Unicode codepoints to glyph IDs.
+///
+/// With the `serde` feature enabled, mappings can be serialized, so that they can be cached
+/// alongside the font instead of being looked up again each time it's loaded.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GlyphMapping {
+    /// In the order they were pushed, which is the order the codepoints were requested in.
     ranges: Vec<MappedGlyphRange>,
+    /// The indices of the ranges sorted by starting codepoint, if they weren't pushed in that
+    /// order.
+    sorted_order: Option<Vec<u32>>,
 }
 
 impl GlyphMapping {
@@ -130,12 +560,33 @@ impl GlyphMapping {
     pub fn new() -> GlyphMapping {
         GlyphMapping {
             ranges: vec![],
+            sorted_order: None,
         }
     }
 
     #[doc(hidden)]
-    #[inline]
     pub fn push(&mut self, range: MappedGlyphRange) {
+        let index = self.ranges.len() as u32;
+        if self.sorted_order.is_none() {
+            match self.ranges.last() {
+                Some(last) if last.codepoint_start > range.codepoint_start => {
+                    self.sorted_order = Some((0..index).collect())
+                }
+                _ => {
+                    self.ranges.push(range);
+                    return
+                }
+            }
+        }
+
+        let ranges = &self.ranges;
+        let sorted_order = self.sorted_order.as_mut().unwrap();
+        let position = match sorted_order.binary_search_by_key(&range.codepoint_start, |&index| {
+            ranges[index as usize].codepoint_start
+        }) {
+            Ok(position) | Err(position) => position,
+        };
+        sorted_order.insert(position, index);
         self.ranges.push(range)
     }
 
@@ -171,21 +622,515 @@ impl GlyphMapping {
         }
     }
 
+    /// Returns an iterator over the `(codepoint, glyph ID)` pairs of the codepoints that the font
+    /// has a glyph for, in the order they were looked up.
+    ///
+    /// Unlike `iter()`, this skips codepoints that map to `MISSING_GLYPH`.
+    #[inline]
+    pub fn iter_pairs(&self) -> GlyphMappingPairs {
+        GlyphMappingPairs {
+            iter: self.iter(),
+        }
+    }
+
+    /// Returns the glyph that the given codepoint maps to, or `None` if the codepoint wasn't
+    /// looked up. This is the same as `glyph_for_codepoint()`.
+    #[inline]
     pub fn glyph_for(&self, codepoint: u32) -> Option<u16> {
+        self.glyph_for_codepoint(codepoint)
+    }
+
+    /// Returns the glyph that the given codepoint maps to, or `None` if the codepoint wasn't
+    /// looked up. Codepoints that were looked up but aren't in the font map to `MISSING_GLYPH`.
+    ///
+    /// This is a binary search over the ranges, even if the codepoints were looked up out of
+    /// order, so there's no need to copy the mapping into a hash map for per-character lookups.
+    pub fn glyph_for_codepoint(&self, codepoint: u32) -> Option<u16> {
+        let range = |position: usize| {
+            match self.sorted_order {
+                None => &self.ranges[position],
+                Some(ref sorted_order) => &self.ranges[sorted_order[position] as usize],
+            }
+        };
+
         let (mut lo, mut hi) = (0, self.ranges.len());
         while lo < hi {
             let mid = (lo + hi) / 2;
-            if codepoint < self.ranges[mid].codepoint_start {
+            if codepoint < range(mid).codepoint_start {
                 hi = mid
-            } else if codepoint > self.ranges[mid].codepoint_end() {
+            } else if codepoint > range(mid).codepoint_end() {
                 lo = mid + 1
             } else {
-                return Some((codepoint - self.ranges[mid].codepoint_start) as u16 +
-                            self.ranges[mid].glyphs.start)
+                return Some((codepoint - range(mid).codepoint_start) as u16 +
+                            range(mid).glyphs.start)
             }
         }
         None
     }
+
+    /// Returns the glyph IDs that this mapping refers to as a minimal sorted list of
+    /// non-overlapping, non-adjacent ranges.
+    ///
+    /// The missing glyph (glyph 0) is not included. This is the set of glyphs that a subsetter
+    /// needs to keep in order to preserve the mapping.
+    pub fn glyph_id_set(&self) -> Vec<GlyphRange> {
+        let mut glyph_ranges: Vec<GlyphRange> = self.ranges.iter().filter_map(|range| {
+            if range.glyphs.end == 0 {
+                None
+            } else {
+                Some(GlyphRange {
+                    start: cmp::max(range.glyphs.start, 1),
+                    end: range.glyphs.end,
+                })
+            }
+        }).collect();
+        glyph_ranges.sort_by_key(|range| range.start);
+
+        let mut coalesced: Vec<GlyphRange> = Vec::with_capacity(glyph_ranges.len());
+        for range in glyph_ranges {
+            if let Some(last) = coalesced.last_mut() {
+                if range.start <= last.end.saturating_add(1) {
+                    last.end = cmp::max(last.end, range.end);
+                    continue
+                }
+            }
+            coalesced.push(range)
+        }
+        coalesced
+    }
+
+    /// Combines several mappings, such as ones resolved separately for each script, into one.
+    ///
+    /// Where the mappings overlap, the first mapping in `mappings` that maps a codepoint to a
+    /// glyph other than `MISSING_GLYPH` wins; a codepoint mapped only to the missing glyph stays
+    /// that way. Ranges that continue one another in both codepoints and glyphs are coalesced.
+    pub fn merge(mappings: &[GlyphMapping]) -> GlyphMapping {
+        // Rank every range by priority, then sweep over them in codepoint order, keeping the
+        // ranges that cover the current codepoint in a heap so that the highest-priority one is
+        // on top.
+        let (found, missing): (Vec<&MappedGlyphRange>, Vec<&MappedGlyphRange>) =
+            mappings.iter().flat_map(|mapping| mapping.ranges.iter()).partition(|range| {
+                range.glyphs.start != MISSING_GLYPH || range.glyphs.end != MISSING_GLYPH
+            });
+        let mut ranges: Vec<(usize, &MappedGlyphRange)> =
+            found.into_iter().chain(missing).enumerate().collect();
+        ranges.sort_by_key(|&(priority, range)| (range.codepoint_start, priority));
+
+        let mut merged: Vec<MappedGlyphRange> = vec![];
+        let mut active = BinaryHeap::new();
+        let (mut next_range_index, mut codepoint) = (0, 0);
+        loop {
+            if active.is_empty() {
+                match ranges.get(next_range_index) {
+                    None => break,
+                    Some(&(_, range)) => codepoint = cmp::max(codepoint, range.codepoint_start),
+                }
+            }
+            while let Some(&(priority, range)) = ranges.get(next_range_index) {
+                if range.codepoint_start > codepoint {
+                    break
+                }
+                active.push(Reverse((priority, next_range_index)));
+                next_range_index += 1
+            }
+
+            let Reverse((_, winner_index)) = *active.peek().unwrap();
+            let winner = ranges[winner_index].1;
+            if winner.codepoint_end() < codepoint {
+                active.pop();
+                continue
+            }
+
+            // A range starting later may take over from the winner, so stop just before it.
+            let mut codepoint_end = winner.codepoint_end();
+            if let Some(&(_, range)) = ranges.get(next_range_index) {
+                codepoint_end = cmp::min(codepoint_end, range.codepoint_start - 1)
+            }
+            merged.push(winner.slice(codepoint, codepoint_end));
+            codepoint = match codepoint_end.checked_add(1) {
+                Some(codepoint) => codepoint,
+                None => break,
+            }
+        }
+
+        let mut coalesced: Vec<MappedGlyphRange> = Vec::with_capacity(merged.len());
+        for range in merged {
+            if let Some(last) = coalesced.last_mut() {
+                if last.codepoint_end() + 1 == range.codepoint_start &&
+                        last.glyphs.end.checked_add(1) == Some(range.glyphs.start) {
+                    last.glyphs.end = range.glyphs.end;
+                    continue
+                }
+            }
+            coalesced.push(range)
+        }
+
+        GlyphMapping {
+            ranges: coalesced,
+            sorted_order: None,
+        }
+    }
+
+    /// Returns a flat array mapping each of the `len` codepoints starting at `base` to its glyph,
+    /// so that the glyph for `codepoint` is at index `codepoint - base`.
+    ///
+    /// Codepoints that this mapping doesn't cover map to `MISSING_GLYPH`. The array costs 2 bytes
+    /// per codepoint in the block whether or not the codepoint is mapped, so this suits small,
+    /// densely populated blocks such as Latin-1 rather than all of Unicode.
+    pub fn to_dense(&self, base: u32, len: usize) -> Vec<u16> {
+        let mut dense = vec![MISSING_GLYPH; len];
+        let block_end = base as u64 + len as u64;
+        for range in &self.ranges {
+            let start = cmp::max(range.codepoint_start as u64, base as u64);
+            let end = cmp::min(range.codepoint_end() as u64 + 1, block_end);
+            for codepoint in start..end {
+                dense[(codepoint - base as u64) as usize] =
+                    range.glyphs.start + (codepoint - range.codepoint_start as u64) as u16
+            }
+        }
+        dense
+    }
+
+    /// Sorts the ranges by codepoint, drops codepoints that were looked up more than once, and
+    /// coalesces ranges that continue one another in both codepoints and glyphs.
+    ///
+    /// Lookups often produce many short ranges, one per segment in format 4 for instance, so this
+    /// saves memory when the mapping is kept around. Afterward, `iter()` visits the codepoints in
+    /// ascending order. Where ranges overlap, the one starting first wins.
+    pub fn normalize(&mut self) {
+        let mut ranges = mem::replace(&mut self.ranges, vec![]);
+        ranges.sort_by_key(|range| range.codepoint_start);
+
+        let mut normalized: Vec<MappedGlyphRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let range = match normalized.last() {
+                Some(last) if last.codepoint_end() >= range.codepoint_end() => continue,
+                Some(last) if last.codepoint_end() >= range.codepoint_start => {
+                    range.slice(last.codepoint_end() + 1, range.codepoint_end())
+                }
+                _ => range,
+            };
+            if let Some(last) = normalized.last_mut() {
+                if last.codepoint_end() + 1 == range.codepoint_start &&
+                        last.glyphs.end.checked_add(1) == Some(range.glyphs.start) {
+                    last.glyphs.end = range.glyphs.end;
+                    continue
+                }
+            }
+            normalized.push(range)
+        }
+
+        normalized.shrink_to_fit();
+        self.ranges = normalized;
+        self.sorted_order = None
+    }
+
+    /// Serializes this mapping into the format that `CompiledCmap` reads.
+    ///
+    /// Codepoints that map to the missing glyph are left out, since looking them up in the
+    /// compiled map returns the missing glyph anyway. So are codes past `MAX_CODEPOINT`, which
+    /// aren't characters.
+    pub fn compile(&self) -> Vec<u8> {
+        let mut mapping = self.clone();
+        mapping.normalize();
+        let ranges: Vec<_> = mapping.ranges.iter().filter(|range| {
+            range.codepoint_start <= MAX_CODEPOINT &&
+                (range.glyphs.start != MISSING_GLYPH || range.glyphs.end != MISSING_GLYPH)
+        }).map(|range| {
+            range.slice(range.codepoint_start, cmp::min(range.codepoint_end(), MAX_CODEPOINT))
+        }).collect();
+
+        let length = COMPILED_CMAP_HEADER_SIZE + ranges.len() * COMPILED_CMAP_RANGE_SIZE;
+        let mut bytes = vec![0; length];
+        BigEndian::write_u32(&mut bytes[0..], COMPILED_CMAP_MAGIC);
+        BigEndian::write_u32(&mut bytes[4..], COMPILED_CMAP_VERSION);
+        BigEndian::write_u32(&mut bytes[8..], ranges.len() as u32);
+        for (index, range) in ranges.iter().enumerate() {
+            let record = &mut bytes[COMPILED_CMAP_HEADER_SIZE + index * COMPILED_CMAP_RANGE_SIZE..];
+            BigEndian::write_u32(&mut record[0..], range.codepoint_start);
+            BigEndian::write_u16(&mut record[4..], range.glyphs.start);
+            BigEndian::write_u16(&mut record[6..], range.glyphs.end);
+        }
+        bytes
+    }
+
+    /// Inverts this mapping, so that the codepoints mapping to each glyph can be looked up.
+    ///
+    /// Codepoints mapped to the missing glyph are left out, as are codes past `MAX_CODEPOINT`.
+    pub fn reverse(&self) -> ReverseGlyphMapping {
+        let mut entries: Vec<(u16, u32)> = vec![];
+        for range in &self.ranges {
+            if range.codepoint_start > MAX_CODEPOINT {
+                continue
+            }
+            let codepoint_end = cmp::min(range.codepoint_end(), MAX_CODEPOINT);
+            for codepoint in range.codepoint_start..(codepoint_end + 1) {
+                let glyph_id = range.glyphs.start + (codepoint - range.codepoint_start) as u16;
+                if glyph_id != MISSING_GLYPH {
+                    entries.push((glyph_id, codepoint))
+                }
+            }
+        }
+        entries.sort();
+        ReverseGlyphMapping {
+            glyph_ids: entries.iter().map(|&(glyph_id, _)| glyph_id).collect(),
+            codepoints: entries.iter().map(|&(_, codepoint)| codepoint).collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a GlyphMapping {
+    type Item = (u32, u16);
+    type IntoIter = GlyphMappingIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> GlyphMappingIter<'a> {
+        self.iter()
+    }
+}
+
+/// Expands the mapping into one entry per codepoint. Codepoints that the font has no glyph for
+/// map to `MISSING_GLYPH`. If a codepoint was looked up more than once, the first lookup wins.
+impl From<GlyphMapping> for HashMap<u32, u16> {
+    fn from(mapping: GlyphMapping) -> HashMap<u32, u16> {
+        let mut map = HashMap::new();
+        for (codepoint, glyph_id) in &mapping {
+            map.entry(codepoint).or_insert(glyph_id);
+        }
+        map
+    }
+}
+
+/// Like the conversion to `HashMap`, but ordered by codepoint.
+impl From<GlyphMapping> for BTreeMap<u32, u16> {
+    fn from(mapping: GlyphMapping) -> BTreeMap<u32, u16> {
+        let mut map = BTreeMap::new();
+        for (codepoint, glyph_id) in &mapping {
+            map.entry(codepoint).or_insert(glyph_id);
+        }
+        map
+    }
+}
+
+/// A character map decoded ahead of time by `GlyphMapping::compile()` or
+/// `Font::compile_cmap()`, read straight from its serialized bytes.
+///
+/// The bytes can be cached on disk and memory-mapped on later runs, so that looking up
+/// characters doesn't require parsing the font at all. Lookups are a binary search over the
+/// mapped ranges.
+#[derive(Clone, Copy, Debug)]
+pub struct CompiledCmap<'a> {
+    /// The range records, sorted by starting codepoint.
+    ranges: &'a [u8],
+}
+
+impl<'a> CompiledCmap<'a> {
+    /// Checks the header of a compiled character map and prepares it for lookups.
+    ///
+    /// Returns `UnknownFormat` if the bytes aren't a compiled character map and
+    /// `UnsupportedVersion` if they were compiled by an incompatible version of this library, in
+    /// which case the cache should be rebuilt from the font.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<CompiledCmap<'a>, FontError> {
+        if bytes.len() < COMPILED_CMAP_HEADER_SIZE {
+            return Err(FontError::UnexpectedEof)
+        }
+        if BigEndian::read_u32(&bytes[0..]) != COMPILED_CMAP_MAGIC {
+            return Err(FontError::UnknownFormat)
+        }
+        if BigEndian::read_u32(&bytes[4..]) != COMPILED_CMAP_VERSION {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let range_count = BigEndian::read_u32(&bytes[8..]) as usize;
+        let ranges = &bytes[COMPILED_CMAP_HEADER_SIZE..];
+        match range_count.checked_mul(COMPILED_CMAP_RANGE_SIZE) {
+            Some(length) if length <= ranges.len() => {
+                Ok(CompiledCmap {
+                    ranges: &ranges[..length],
+                })
+            }
+            _ => Err(FontError::UnexpectedEof),
+        }
+    }
+
+    /// Returns the glyph that the given codepoint maps to, or 0 (the missing glyph) if it isn't
+    /// mapped.
+    pub fn glyph_for(&self, codepoint: u32) -> u16 {
+        // Find the last range starting at or before the codepoint.
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.range(mid).codepoint_start <= codepoint {
+                low = mid + 1
+            } else {
+                high = mid
+            }
+        }
+        if low == 0 {
+            return MISSING_GLYPH
+        }
+
+        let range = self.range(low - 1);
+        match range.glyphs.end.checked_sub(range.glyphs.start) {
+            Some(length) if codepoint - range.codepoint_start <= length as u32 => {
+                range.glyphs.start + (codepoint - range.codepoint_start) as u16
+            }
+            _ => MISSING_GLYPH,
+        }
+    }
+
+    /// Returns the number of mapped ranges.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ranges.len() / COMPILED_CMAP_RANGE_SIZE
+    }
+
+    /// Returns true if no codepoints are mapped.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Decodes the whole map back into a `GlyphMapping`.
+    pub fn to_glyph_mapping(&self) -> GlyphMapping {
+        let mut glyph_mapping = GlyphMapping::new();
+        for index in 0..self.len() {
+            glyph_mapping.push(self.range(index))
+        }
+        glyph_mapping
+    }
+
+    #[inline]
+    fn range(&self, index: usize) -> MappedGlyphRange {
+        let record = &self.ranges[index * COMPILED_CMAP_RANGE_SIZE..];
+        MappedGlyphRange {
+            codepoint_start: BigEndian::read_u32(&record[0..]),
+            glyphs: GlyphRange {
+                start: BigEndian::read_u16(&record[4..]),
+                end: BigEndian::read_u16(&record[6..]),
+            },
+        }
+    }
+}
+
+/// A character map decoded into memory once, for hot text layout paths that look up many
+/// characters.
+///
+/// The Basic Multilingual Plane is stored as a two-level table: an index of 256-codepoint
+/// pages, each either shared and empty or holding a glyph ID per codepoint. Lookups there are
+/// O(1). Codepoints above U+FFFF, which fonts map sparsely, are binary searched.
+#[derive(Clone, Debug)]
+pub struct DecodedCmap {
+    /// For each page of the BMP, the index of its first glyph ID in `pages`. Pages that map
+    /// nothing share the page at index 0, which is all missing glyphs.
+    page_offsets: Vec<u32>,
+    /// The glyph IDs of the BMP, a page at a time.
+    pages: Vec<u16>,
+    /// The mapped ranges above the BMP, sorted by starting codepoint.
+    supplementary_ranges: Vec<MappedGlyphRange>,
+}
+
+impl DecodedCmap {
+    /// Builds the lookup tables from a glyph mapping. Codepoints that the mapping doesn't cover
+    /// map to the missing glyph.
+    pub fn from_glyph_mapping(glyph_mapping: &GlyphMapping) -> DecodedCmap {
+        let mut glyph_mapping = glyph_mapping.clone();
+        glyph_mapping.normalize();
+
+        let mut decoded_cmap = DecodedCmap {
+            page_offsets: vec![0; BMP_PAGE_COUNT],
+            pages: vec![MISSING_GLYPH; BMP_PAGE_SIZE],
+            supplementary_ranges: vec![],
+        };
+        for range in &glyph_mapping.ranges {
+            if range.glyphs.start == MISSING_GLYPH && range.glyphs.end == MISSING_GLYPH {
+                continue
+            }
+
+            if range.codepoint_start <= u16::MAX as u32 {
+                let end = cmp::min(range.codepoint_end(), u16::MAX as u32);
+                for codepoint in range.codepoint_start..(end + 1) {
+                    let page = codepoint as usize / BMP_PAGE_SIZE;
+                    if decoded_cmap.page_offsets[page] == 0 {
+                        decoded_cmap.page_offsets[page] = decoded_cmap.pages.len() as u32;
+                        let pages_length = decoded_cmap.pages.len() + BMP_PAGE_SIZE;
+                        decoded_cmap.pages.resize(pages_length, MISSING_GLYPH)
+                    }
+                    let index = decoded_cmap.page_offsets[page] as usize +
+                        codepoint as usize % BMP_PAGE_SIZE;
+                    decoded_cmap.pages[index] =
+                        range.glyphs.start + (codepoint - range.codepoint_start) as u16
+                }
+            }
+            let end = cmp::min(range.codepoint_end(), MAX_CODEPOINT);
+            if end > u16::MAX as u32 {
+                let start = cmp::max(range.codepoint_start, u16::MAX as u32 + 1);
+                if start <= end {
+                    decoded_cmap.supplementary_ranges.push(range.slice(start, end))
+                }
+            }
+        }
+        decoded_cmap
+    }
+
+    /// Returns the glyph that the given codepoint maps to, or 0 (the missing glyph) if it isn't
+    /// mapped.
+    pub fn glyph_for_codepoint(&self, codepoint: u32) -> u16 {
+        let page = codepoint as usize / BMP_PAGE_SIZE;
+        if page < BMP_PAGE_COUNT {
+            return self.pages[self.page_offsets[page] as usize + codepoint as usize % BMP_PAGE_SIZE]
+        }
+
+        let (mut low, mut high) = (0, self.supplementary_ranges.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let range = &self.supplementary_ranges[mid];
+            if codepoint < range.codepoint_start {
+                high = mid
+            } else if codepoint > range.codepoint_end() {
+                low = mid + 1
+            } else {
+                return range.glyphs.start + (codepoint - range.codepoint_start) as u16
+            }
+        }
+        MISSING_GLYPH
+    }
+}
+
+/// A map from glyph IDs back to the codepoints that map to them, as built by
+/// `GlyphMapping::reverse()`.
+#[derive(Clone, Debug)]
+pub struct ReverseGlyphMapping {
+    /// Sorted, and parallel to `codepoints`.
+    glyph_ids: Vec<u16>,
+    codepoints: Vec<u32>,
+}
+
+impl ReverseGlyphMapping {
+    /// Returns the codepoints that map to the given glyph, in ascending order. This is empty if
+    /// no codepoint does, as is typical for ligatures and other glyphs reached only by shaping.
+    pub fn codepoints_for_glyph(&self, glyph_id: u16) -> &[u32] {
+        let start = lower_bound(&self.glyph_ids, glyph_id);
+        let end = match glyph_id.checked_add(1) {
+            None => self.glyph_ids.len(),
+            Some(next_glyph_id) => lower_bound(&self.glyph_ids, next_glyph_id),
+        };
+        &self.codepoints[start..end]
+    }
+}
+
+/// Returns the index of the first element of the sorted slice that isn't less than `value`.
+fn lower_bound(sorted: &[u16], value: u16) -> usize {
+    let (mut low, mut high) = (0, sorted.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if sorted[mid] < value {
+            low = mid + 1
+        } else {
+            high = mid
+        }
+    }
+    low
 }
 
 #[derive(Clone)]
@@ -250,6 +1195,24 @@ impl<'a> Iterator for GlyphMappingIter<'a> {
     }
 }
 
+/// An iterator over the codepoints of a mapping that have glyphs, returned by
+/// `GlyphMapping::iter_pairs()`.
+///
+/// Every call to `next()` returns a tuple consisting of the codepoint and glyph ID, in that order.
+#[derive(Clone)]
+pub struct GlyphMappingPairs<'a> {
+    iter: GlyphMappingIter<'a>,
+}
+
+impl<'a> Iterator for GlyphMappingPairs<'a> {
+    type Item = (u32, u16);
+
+    #[inline]
+    fn next(&mut self) -> Option<(u32, u16)> {
+        self.iter.find(|&(_, glyph_id)| glyph_id != MISSING_GLYPH)
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 struct GlyphRangesIndex {
     range_index: i32,
@@ -262,4 +1225,17 @@ impl MappedGlyphRange {
     pub fn codepoint_end(&self) -> u32 {
         self.codepoint_start + self.glyphs.end as u32 - self.glyphs.start as u32
     }
+
+    /// Returns the part of this range covering the given codepoints, inclusive, which must lie
+    /// within it.
+    fn slice(&self, codepoint_start: u32, codepoint_end: u32) -> MappedGlyphRange {
+        let glyph_start = self.glyphs.start + (codepoint_start - self.codepoint_start) as u16;
+        MappedGlyphRange {
+            codepoint_start: codepoint_start,
+            glyphs: GlyphRange {
+                start: glyph_start,
+                end: glyph_start + (codepoint_end - codepoint_start) as u16,
+            },
+        }
+    }
 }
@@ -25,9 +25,54 @@ const SFNT: u32 = ((b's' as u32) << 24) |
                   ((b'n' as u32) << 8)  |
                    (b't' as u32);
 
+/// Returns the number of fonts in the given font suitcase.
+pub fn font_count(bytes: &[u8]) -> Result<u32, FontError> {
+    let sfnt_resources = try!(SfntResources::new(bytes));
+    Ok(sfnt_resources.count)
+}
+
 impl<'a> Font<'a> {
     /// https://github.com/kreativekorp/ksfl/wiki/Macintosh-Resource-File-Format
     pub fn from_dfont_index<'b>(bytes: &'b [u8], index: u32) -> Result<Font<'b>, FontError> {
+        let sfnt_resources = try!(SfntResources::new(bytes));
+
+        // Check whether the index is in bounds.
+        if index >= sfnt_resources.count {
+            return Err(FontError::FontIndexOutOfBounds)
+        }
+
+        // Find the font we're interested in.
+        let mut reader = bytes;
+        try!(reader.jump(sfnt_resources.list_offset).map_err(FontError::eof));
+        try!(reader.jump(index as usize * (mem::size_of::<u16>() * 2 + mem::size_of::<u32>() * 2))
+                   .map_err(FontError::eof));
+        let _sfnt_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _sfnt_name_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let sfnt_data_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) &
+            0x00ffffff;
+        let _sfnt_ptr = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        // Load the resource.
+        reader = bytes;
+        try!(reader.jump(sfnt_resources.data_offset + sfnt_data_offset as usize)
+                   .map_err(FontError::eof));
+        let sfnt_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        Font::from_otf(&reader[0..sfnt_size as usize], 0)
+    }
+}
+
+/// The location of the `sfnt` resources within a Mac resource file.
+struct SfntResources {
+    /// The number of `sfnt` resources.
+    count: u32,
+    /// The offset of the `sfnt` reference list from the start of the file.
+    list_offset: usize,
+    /// The offset of the resource data from the start of the file.
+    data_offset: usize,
+}
+
+impl SfntResources {
+    fn new(bytes: &[u8]) -> Result<SfntResources, FontError> {
         let mut reader = bytes;
 
         // Read the Mac resource file header.
@@ -52,49 +97,21 @@ impl<'a> Font<'a> {
 
         // Find the 'sfnt' type.
         let type_count = (try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)) + 1) as usize;
-        let mut resource_count_and_list_offset = None;
         for _ in 0..type_count {
             let type_id = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
             let resource_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
             let resource_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
             if type_id == SFNT {
-                resource_count_and_list_offset = Some((resource_count, resource_list_offset));
-                break
-            }
-        }
-
-        // Unpack the resource count and list offset.
-        let resource_count;
-        match resource_count_and_list_offset {
-            None => return Err(FontError::Failed),
-            Some((count, resource_list_offset)) => {
-                resource_count = count;
-                reader = bytes;
-                try!(reader.jump(resource_map_offset as usize + type_list_offset as usize +
-                                 resource_list_offset as usize).map_err(FontError::eof));
+                // The resource count is stored minus one.
+                return Ok(SfntResources {
+                    count: resource_count as u32 + 1,
+                    list_offset: resource_map_offset as usize + type_list_offset as usize +
+                        resource_list_offset as usize,
+                    data_offset: resource_data_offset as usize,
+                })
             }
         }
 
-        // Check whether the index is in bounds.
-        if index >= resource_count as u32 + 1 {
-            return Err(FontError::FontIndexOutOfBounds)
-        }
-
-        // Find the font we're interested in.
-        try!(reader.jump(index as usize * (mem::size_of::<u16>() * 2 + mem::size_of::<u32>() * 2))
-                   .map_err(FontError::eof));
-        let _sfnt_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let _sfnt_name_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let sfnt_data_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) &
-            0x00ffffff;
-        let _sfnt_ptr = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
-
-        // Load the resource.
-        reader = bytes;
-        try!(reader.jump(resource_data_offset as usize + sfnt_data_offset as usize)
-                   .map_err(FontError::eof));
-        let sfnt_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
-        Font::from_otf(&reader[0..sfnt_size as usize], 0)
+        Err(FontError::Failed)
     }
 }
-
@@ -14,4 +14,6 @@ pub mod dfont;
 pub mod otf;
 pub mod ttc;
 pub mod woff;
+#[cfg(feature = "brotli-decompressor")]
+pub mod woff2;
 
@@ -16,18 +16,41 @@ use byteorder::{BigEndian, ReadBytesExt};
 use error::FontError;
 use font::{Font, FontTable};
 use std::mem;
+use tables::base::{self, BaseTable};
 use tables::cff::{self, CffTable};
 use tables::cvt;
 use tables::cmap::{self, CmapTable};
+use tables::colr::{self, ColrTable};
+use tables::cpal::{self, CpalTable};
+use tables::ebdt;
+use tables::eblc::{self, EblcTable};
 use tables::fpgm;
+use tables::fvar::{self, FvarTable};
+use tables::gasp::{self, GaspTable};
+use tables::gdef::{self, GdefTable};
 use tables::glyf::{self, GlyfTable};
+use tables::gpos::{self, GposTable};
+use tables::gsub::{self, GsubTable};
+use tables::gvar::{self, GvarTable};
+use tables::hdmx::{self, HdmxTable};
 use tables::head::{self, HeadTable};
 use tables::hhea::{self, HheaTable};
 use tables::hmtx::{self, HmtxTable};
 use tables::kern::{self, KernTable};
 use tables::loca::{self, LocaTable};
+use tables::ltsh::{self, LtshTable};
+use tables::math::{self, MathTable};
+use tables::maxp::{self, MaxpTable};
+use tables::name::{self, NameTable};
 use tables::os_2::{self, Os2Table};
+use tables::post::{self, PostTable};
 use tables::prep;
+use tables::sbix::{self, SbixTable};
+use tables::stat::{self, StatTable};
+use tables::svg::{self, SvgTable};
+use tables::vhea::{self, VheaTable};
+use tables::vmtx::{self, VmtxTable};
+use tables::vorg::{self, VorgTable};
 use util::Jump;
 
 const OTTO: u32 = ((b'O' as u32) << 24) |
@@ -35,36 +58,86 @@ const OTTO: u32 = ((b'O' as u32) << 24) |
                   ((b'T' as u32) << 8)  |
                    (b'O' as u32);
 
-pub const KNOWN_TABLE_COUNT: usize = 12;
+pub const KNOWN_TABLE_COUNT: usize = 37;
 
 pub static KNOWN_TABLES: [u32; KNOWN_TABLE_COUNT] = [
+    base::TAG,
+    ebdt::CBDT_TAG,
+    eblc::CBLC_TAG,
     cff::TAG,
+    colr::TAG,
+    cpal::TAG,
+    ebdt::TAG,
+    eblc::TAG,
+    gdef::TAG,
+    gpos::TAG,
+    gsub::TAG,
+    ltsh::TAG,
+    math::TAG,
     os_2::TAG,
+    stat::TAG,
+    svg::TAG,
+    vorg::TAG,
     cmap::TAG,
     cvt::TAG,
     fpgm::TAG,
+    fvar::TAG,
+    gasp::TAG,
     glyf::TAG,
+    gvar::TAG,
+    hdmx::TAG,
     head::TAG,
     hhea::TAG,
     hmtx::TAG,
     kern::TAG,
     loca::TAG,
+    maxp::TAG,
+    name::TAG,
+    post::TAG,
     prep::TAG,
+    sbix::TAG,
+    vhea::TAG,
+    vmtx::TAG,
 ];
 
 // This must agree with the above.
-const TABLE_INDEX_CFF:  usize = 0;
-const TABLE_INDEX_OS_2: usize = 1;
-const TABLE_INDEX_CMAP: usize = 2;
-const TABLE_INDEX_CVT:  usize = 3;
-const TABLE_INDEX_FPGM: usize = 4;
-const TABLE_INDEX_GLYF: usize = 5;
-const TABLE_INDEX_HEAD: usize = 6;
-const TABLE_INDEX_HHEA: usize = 7;
-const TABLE_INDEX_HMTX: usize = 8;
-const TABLE_INDEX_KERN: usize = 9;
-const TABLE_INDEX_LOCA: usize = 10;
-const TABLE_INDEX_PREP: usize = 11;
+const TABLE_INDEX_BASE: usize = 0;
+const TABLE_INDEX_CBDT: usize = 1;
+const TABLE_INDEX_CBLC: usize = 2;
+const TABLE_INDEX_CFF:  usize = 3;
+const TABLE_INDEX_COLR: usize = 4;
+const TABLE_INDEX_CPAL: usize = 5;
+const TABLE_INDEX_EBDT: usize = 6;
+const TABLE_INDEX_EBLC: usize = 7;
+const TABLE_INDEX_GDEF: usize = 8;
+const TABLE_INDEX_GPOS: usize = 9;
+const TABLE_INDEX_GSUB: usize = 10;
+const TABLE_INDEX_LTSH: usize = 11;
+const TABLE_INDEX_MATH: usize = 12;
+const TABLE_INDEX_OS_2: usize = 13;
+const TABLE_INDEX_STAT: usize = 14;
+const TABLE_INDEX_SVG:  usize = 15;
+const TABLE_INDEX_VORG: usize = 16;
+const TABLE_INDEX_CMAP: usize = 17;
+const TABLE_INDEX_CVT:  usize = 18;
+const TABLE_INDEX_FPGM: usize = 19;
+const TABLE_INDEX_FVAR: usize = 20;
+const TABLE_INDEX_GASP: usize = 21;
+const TABLE_INDEX_GLYF: usize = 22;
+const TABLE_INDEX_GVAR: usize = 23;
+const TABLE_INDEX_HDMX: usize = 24;
+const TABLE_INDEX_HEAD: usize = 25;
+const TABLE_INDEX_HHEA: usize = 26;
+const TABLE_INDEX_HMTX: usize = 27;
+const TABLE_INDEX_KERN: usize = 28;
+const TABLE_INDEX_LOCA: usize = 29;
+const TABLE_INDEX_MAXP: usize = 30;
+const TABLE_INDEX_NAME: usize = 31;
+const TABLE_INDEX_POST: usize = 32;
+const TABLE_INDEX_PREP: usize = 33;
+const TABLE_INDEX_SBIX: usize = 34;
+const TABLE_INDEX_VHEA: usize = 35;
+const TABLE_INDEX_VMTX: usize = 36;
 
 pub static SFNT_VERSIONS: [u32; 3] = [
     0x10000,
@@ -82,15 +155,43 @@ pub struct FontTables<'a> {
     pub os_2: Os2Table,
 
     // Optional tables.
+    pub base: Option<BaseTable<'a>>,
+    pub cblc: Option<EblcTable<'a>>,
     pub cff: Option<CffTable<'a>>,
+    pub colr: Option<ColrTable<'a>>,
+    pub cpal: Option<CpalTable<'a>>,
+    pub eblc: Option<EblcTable<'a>>,
+    pub fvar: Option<FvarTable<'a>>,
+    pub gasp: Option<GaspTable<'a>>,
+    pub gdef: Option<GdefTable<'a>>,
     pub glyf: Option<GlyfTable<'a>>,
+    pub gpos: Option<GposTable<'a>>,
+    pub gsub: Option<GsubTable<'a>>,
+    pub gvar: Option<GvarTable<'a>>,
+    pub hdmx: Option<HdmxTable<'a>>,
     pub loca: Option<LocaTable<'a>>,
+    pub ltsh: Option<LtshTable<'a>>,
+    pub math: Option<MathTable<'a>>,
+    pub maxp: Option<MaxpTable>,
     pub kern: Option<KernTable<'a>>,
+    pub name: Option<NameTable<'a>>,
+    pub post: Option<PostTable<'a>>,
+    pub sbix: Option<SbixTable<'a>>,
+    pub stat: Option<StatTable<'a>>,
+    pub svg: Option<SvgTable<'a>>,
+    pub vhea: Option<VheaTable>,
+    pub vmtx: Option<VmtxTable<'a>>,
+    pub vorg: Option<VorgTable<'a>>,
 
     // Optional tables that need no parsing.
+    pub cbdt: Option<FontTable<'a>>,
     pub cvt: Option<FontTable<'a>>,
+    pub ebdt: Option<FontTable<'a>>,
     pub fpgm: Option<FontTable<'a>>,
     pub prep: Option<FontTable<'a>>,
+
+    // Every table in the font, including the ones above, in directory order.
+    pub all: Vec<(u32, FontTable<'a>)>,
 }
 
 impl<'a> Font<'a> {
@@ -106,16 +207,33 @@ impl<'a> Font<'a> {
         let num_tables = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
         try!(reader.jump(mem::size_of::<u16>() * 3).map_err(FontError::eof));
 
-        let mut tables = [None; KNOWN_TABLE_COUNT];
+        let mut tables = Vec::with_capacity(num_tables as usize);
         for _ in 0..num_tables {
             let table_id = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
             let _checksum = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
             let offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
             let length = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+            if offset + length > bytes.len() {
+                return Err(FontError::UnexpectedEof)
+            }
 
+            tables.push((table_id, FontTable {
+                bytes: &bytes[offset..offset + length],
+            }))
+        }
+
+        Font::from_table_list(bytes, tables)
+    }
+
+    /// Creates a font from the `(tag, table)` pairs of every table in its directory.
+    #[doc(hidden)]
+    pub fn from_table_list<'b>(bytes: &'b [u8], table_list: Vec<(u32, FontTable<'b>)>)
+                               -> Result<Font<'b>, FontError> {
+        let mut tables = [None; KNOWN_TABLE_COUNT];
+        for &(tag, table) in &table_list {
             // Find the table ID in our list of known IDs, which must be sorted.
             debug_assert!(KNOWN_TABLES.windows(2).all(|w| w[0] < w[1]));
-            let slot = match KNOWN_TABLES.binary_search(&table_id) {
+            let slot = match KNOWN_TABLES.binary_search(&tag) {
                 Err(_) => continue,
                 Ok(table_index) => &mut tables[table_index],
             };
@@ -125,18 +243,9 @@ impl<'a> Font<'a> {
                 return Err(FontError::Failed)
             }
 
-            *slot = Some(FontTable {
-                bytes: &bytes[offset..offset + length],
-            })
+            *slot = Some(table)
         }
 
-        Font::from_table_list(bytes, &tables)
-    }
-
-    #[doc(hidden)]
-    pub fn from_table_list<'b>(bytes: &'b [u8],
-                               tables: &[Option<FontTable<'b>>; KNOWN_TABLE_COUNT])
-                               -> Result<Font<'b>, FontError> {
         let cff_table = match tables[TABLE_INDEX_CFF] {
             None => None,
             Some(cff_table) => Some(try!(CffTable::new(cff_table))),
@@ -157,14 +266,41 @@ impl<'a> Font<'a> {
             hmtx: HmtxTable::new(try!(tables[TABLE_INDEX_HMTX].ok_or(missing))),
             os_2: try!(Os2Table::new(try!(tables[TABLE_INDEX_OS_2].ok_or(missing)))),
 
+            base: tables[TABLE_INDEX_BASE].and_then(|table| BaseTable::new(table).ok()),
+            cblc: tables[TABLE_INDEX_CBLC].and_then(|table| EblcTable::new(table).ok()),
             cff: cff_table,
+            colr: tables[TABLE_INDEX_COLR].and_then(|table| ColrTable::new(table).ok()),
+            cpal: tables[TABLE_INDEX_CPAL].and_then(|table| CpalTable::new(table).ok()),
+            eblc: tables[TABLE_INDEX_EBLC].and_then(|table| EblcTable::new(table).ok()),
+            fvar: tables[TABLE_INDEX_FVAR].and_then(|table| FvarTable::new(table).ok()),
+            gasp: tables[TABLE_INDEX_GASP].and_then(|table| GaspTable::new(table).ok()),
+            gdef: tables[TABLE_INDEX_GDEF].and_then(|table| GdefTable::new(table).ok()),
             glyf: tables[TABLE_INDEX_GLYF].map(GlyfTable::new),
+            gpos: tables[TABLE_INDEX_GPOS].and_then(|table| GposTable::new(table).ok()),
+            gsub: tables[TABLE_INDEX_GSUB].and_then(|table| GsubTable::new(table).ok()),
+            gvar: tables[TABLE_INDEX_GVAR].and_then(|table| GvarTable::new(table).ok()),
+            hdmx: tables[TABLE_INDEX_HDMX].and_then(|table| HdmxTable::new(table).ok()),
             loca: loca_table,
+            ltsh: tables[TABLE_INDEX_LTSH].and_then(|table| LtshTable::new(table).ok()),
+            math: tables[TABLE_INDEX_MATH].and_then(|table| MathTable::new(table).ok()),
+            maxp: tables[TABLE_INDEX_MAXP].and_then(|table| MaxpTable::new(table).ok()),
             kern: tables[TABLE_INDEX_KERN].and_then(|table| KernTable::new(table).ok()),
+            name: tables[TABLE_INDEX_NAME].and_then(|table| NameTable::new(table).ok()),
+            post: tables[TABLE_INDEX_POST].and_then(|table| PostTable::new(table).ok()),
+            sbix: tables[TABLE_INDEX_SBIX].and_then(|table| SbixTable::new(table).ok()),
+            stat: tables[TABLE_INDEX_STAT].and_then(|table| StatTable::new(table).ok()),
+            svg: tables[TABLE_INDEX_SVG].and_then(|table| SvgTable::new(table).ok()),
+            vhea: tables[TABLE_INDEX_VHEA].and_then(|table| VheaTable::new(table).ok()),
+            vmtx: tables[TABLE_INDEX_VMTX].map(VmtxTable::new),
+            vorg: tables[TABLE_INDEX_VORG].and_then(|table| VorgTable::new(table).ok()),
 
+            cbdt: tables[TABLE_INDEX_CBDT],
             cvt: tables[TABLE_INDEX_CVT],
+            ebdt: tables[TABLE_INDEX_EBDT],
             fpgm: tables[TABLE_INDEX_FPGM],
             prep: tables[TABLE_INDEX_PREP],
+
+            all: table_list,
         };
 
         Ok(Font::from_tables(bytes, tables))
@@ -23,28 +23,36 @@ pub const MAGIC_NUMBER: u32 = ((b't' as u32) << 24) |
                               ((b'c' as u32) << 8)  |
                                (b'f' as u32);
 
+/// Returns the number of fonts in the given font collection.
+pub fn font_count(bytes: &[u8]) -> Result<u32, FontError> {
+    let mut reader = bytes;
+    let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+    if magic_number != MAGIC_NUMBER {
+        return Err(FontError::UnknownFormat)
+    }
+
+    let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if (major_version != 1 && major_version != 2) || minor_version != 0 {
+        return Err(FontError::UnsupportedVersion)
+    }
+
+    reader.read_u32::<BigEndian>().map_err(FontError::eof)
+}
+
 impl<'a> Font<'a> {
     /// Creates a new font from a single font within a byte buffer containing the contents of a
     /// font collection.
     pub fn from_ttc_index<'b>(bytes: &'b [u8], index: u32) -> Result<Font<'b>, FontError> {
-        let mut reader = bytes;
-        let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
-        if magic_number != MAGIC_NUMBER {
-            return Err(FontError::UnknownFormat)
-        }
-
-        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        if (major_version != 1 && major_version != 2) || minor_version != 0 {
-            return Err(FontError::UnsupportedVersion)
-        }
-
-        let num_fonts = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let num_fonts = try!(font_count(bytes));
         if index >= num_fonts {
             return Err(FontError::FontIndexOutOfBounds)
         }
 
-        try!(reader.jump(index as usize * mem::size_of::<u32>()).map_err(FontError::eof));
+        // Skip the header and move to the offset of the font we're interested in.
+        let mut reader = bytes;
+        try!(reader.jump(mem::size_of::<u32>() * 3 + index as usize * mem::size_of::<u32>())
+                   .map_err(FontError::eof));
         let table_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
         Font::from_otf(&bytes, table_offset)
     }
@@ -12,14 +12,14 @@
 //!
 //! See the specification: https://www.w3.org/TR/WOFF/
 //!
-//! TODO(pcwalton): WOFF 2.0.
+//! WOFF 2.0 files are handled by the `woff2` module.
 
 use byteorder::{BigEndian, ReadBytesExt};
-use containers::otf::{KNOWN_TABLES, KNOWN_TABLE_COUNT, SFNT_VERSIONS};
+use containers::otf::SFNT_VERSIONS;
 use error::FontError;
 use flate2::FlateReadExt;
 use font::{Font, FontTable};
-use std::io::Read;
+use std::io::{self, Read};
 use std::iter;
 use std::mem;
 use util::Jump;
@@ -62,8 +62,8 @@ impl<'a> Font<'a> {
         buffer.extend(iter::repeat(0).take(total_sfnt_size as usize));
         let mut buffer = &mut buffer[buffer_start..];
 
-        // Decompress and load tables as necessary.
-        let mut tables = [None; KNOWN_TABLE_COUNT];
+        // Decompress and load all tables.
+        let mut tables = Vec::with_capacity(num_tables as usize);
         for _ in 0..num_tables {
             let tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
             let offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
@@ -71,44 +71,46 @@ impl<'a> Font<'a> {
             let orig_length = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
             let _orig_checksum = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
 
-            // Find the table ID in our list of known IDs, which must be sorted.
-            debug_assert!(KNOWN_TABLES.windows(2).all(|w| w[0] < w[1]));
-            let slot = match KNOWN_TABLES.binary_search(&tag) {
-                Err(_) => continue,
-                Ok(table_index) => &mut tables[table_index],
-            };
-
-            // Make sure there isn't more than one copy of the table.
-            if slot.is_some() {
-                return Err(FontError::Failed)
-            }
-
             // Allocate space in the buffer.
             let comp_end = offset as usize + comp_length as usize;
             let mut temp = buffer;  // borrow check black magic
+            if orig_length as usize > temp.len() {
+                return Err(FontError::UnexpectedEof)
+            }
             let (mut dest, mut rest) = temp.split_at_mut(orig_length as usize);
             buffer = rest;
 
+            // Tables are stored uncompressed when compressing them wouldn't make them smaller, so
+            // a compressed table larger than the original is invalid.
+            if comp_end > bytes.len() {
+                return Err(FontError::UnexpectedEof)
+            }
+            if comp_length > orig_length {
+                return Err(FontError::Failed)
+            }
+
             // Decompress or copy as applicable.
-            //
-            // FIXME(pcwalton): Errors here may be zlib errors, not EOFs.
-            if comp_length != orig_length {
-                let mut table_reader = bytes;
-                try!(table_reader.jump(offset as usize).map_err(FontError::eof));
-                let mut table_reader = table_reader.zlib_decode();
-                try!(table_reader.read_exact(dest).map_err(FontError::eof));
-            } else if comp_end <= bytes.len() {
-                dest.clone_from_slice(&bytes[offset as usize..comp_end])
+            let table_bytes = &bytes[offset as usize..comp_end];
+            if comp_length < orig_length {
+                let mut table_reader = table_bytes.zlib_decode();
+                try!(table_reader.read_exact(dest).map_err(decompression_error));
             } else {
-                return Err(FontError::UnexpectedEof)
+                dest.clone_from_slice(table_bytes)
             }
 
-            *slot = Some(FontTable {
+            tables.push((tag, FontTable {
                 bytes: dest,
-            })
+            }))
         }
 
-        Font::from_table_list(bytes, &tables)
+        Font::from_table_list(bytes, tables)
     }
 }
 
+/// Converts an error from a decompressor into a font error.
+pub fn decompression_error(error: io::Error) -> FontError {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => FontError::UnexpectedEof,
+        _ => FontError::Failed,
+    }
+}
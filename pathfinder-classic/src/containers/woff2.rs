@@ -0,0 +1,711 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Web Open Font Format 2.0 (`.woff2`) files.
+//!
+//! See the specification: https://www.w3.org/TR/WOFF2/
+//!
+//! This is only available with the `brotli-decompressor` feature. WOFF 2.0 collections aren't
+//! supported.
+
+use brotli_decompressor::Decompressor;
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
+use containers::otf::SFNT_VERSIONS;
+use containers::woff;
+use error::FontError;
+use font::{Font, FontTable};
+use std::cmp;
+use std::io::Read;
+use std::mem;
+use tables::glyf;
+use tables::head::{self, HeadTable};
+use tables::hhea::{self, HheaTable};
+use tables::hmtx;
+use tables::loca;
+use tables::maxp::{self, MaxpTable};
+use util::Jump;
+
+pub const MAGIC_NUMBER: u32 = ((b'w' as u32) << 24) |
+                               ((b'O' as u32) << 16) |
+                               ((b'F' as u32) << 8) |
+                                (b'2' as u32);
+
+/// The tags that a table directory entry can refer to by index instead of spelling them out.
+static KNOWN_TAGS: [&'static [u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// The known tag index meaning that the tag follows the flags byte.
+const ARBITRARY_TAG_INDEX: u8 = 63;
+
+/// The transform version of `glyf` and `loca` tables that are stored as they are. For every other
+/// table, version 0 means that.
+const GLYF_NULL_TRANSFORM_VERSION: u8 = 3;
+
+/// The transform version of an `hmtx` table stored without the side bearings that match the
+/// glyph bounds.
+const HMTX_TRANSFORM_VERSION: u8 = 1;
+
+/// The most that the tables of a font may decompress to, in bytes. Real fonts are far smaller;
+/// this keeps a small file from inflating into gigabytes.
+const MAX_DECOMPRESSED_SIZE: u64 = 1 << 30;
+
+/// The most memory reserved for the decompressed tables before any of them have been read. The
+/// buffer grows past this only as the data actually turns up, so a directory that merely claims
+/// huge tables can't exhaust memory by itself.
+const MAX_PREALLOCATED_SIZE: usize = 1 << 24;
+
+/// The size of the header of a transformed `glyf` table, which gives the sizes of its streams.
+const TRANSFORMED_GLYF_HEADER_SIZE: usize = 36;
+
+/// Set in the option flags of a transformed `glyf` table if it ends with a bitmap of the glyphs
+/// whose contours overlap.
+const GLYF_OPTION_OVERLAP_SIMPLE_BITMAP: u16 = 1 << 0;
+
+const HMTX_FLAG_NO_PROPORTIONAL_LSBS: u8 = 1 << 0;
+const HMTX_FLAG_NO_MONOSPACED_LSBS: u8 = 1 << 1;
+
+// Simple glyph flags.
+const ON_CURVE: u8 = 1 << 0;
+const X_SHORT_VECTOR: u8 = 1 << 1;
+const Y_SHORT_VECTOR: u8 = 1 << 2;
+const X_IS_SAME_OR_POSITIVE: u8 = 1 << 4;
+const Y_IS_SAME_OR_POSITIVE: u8 = 1 << 5;
+const OVERLAP_SIMPLE: u8 = 1 << 6;
+
+// Composite glyph flags.
+const ARG_1_AND_2_ARE_WORDS: u16 = 1 << 0;
+const WE_HAVE_A_SCALE: u16 = 1 << 3;
+const MORE_COMPONENTS: u16 = 1 << 5;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 1 << 6;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 1 << 7;
+const WE_HAVE_INSTRUCTIONS: u16 = 1 << 8;
+
+/// An entry in the table directory.
+struct TableEntry {
+    tag: u32,
+    orig_length: u32,
+    transformed: bool,
+    /// The length of the table in the decompressed data, which differs from the original length
+    /// if the table is transformed.
+    length: u32,
+}
+
+/// The streams of a transformed `glyf` table that glyphs are decoded from as they're needed.
+struct GlyphStreams<'a> {
+    n_points: &'a [u8],
+    flags: &'a [u8],
+    glyphs: &'a [u8],
+    composites: &'a [u8],
+    instructions: &'a [u8],
+}
+
+/// The `glyf` and `loca` tables rebuilt from a transformed `glyf` table.
+struct ReconstructedGlyf {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    long_offsets: bool,
+    /// The minimum x coordinate of each glyph, which transformed `hmtx` tables use as the left
+    /// side bearing.
+    x_mins: Vec<i16>,
+}
+
+impl<'a> Font<'a> {
+    /// Creates a new font from a buffer containing data in the WOFF 2.0 format.
+    ///
+    /// The given buffer will be used to hold the decompressed tables, including the `glyf` and
+    /// `loca` tables rebuilt from their transformed form.
+    pub fn from_woff2<'b>(bytes: &'b [u8], buffer: &'b mut Vec<u8>)
+                          -> Result<Font<'b>, FontError> {
+        let mut reader = bytes;
+
+        // Check the magic number.
+        let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if magic_number != MAGIC_NUMBER {
+            return Err(FontError::UnknownFormat)
+        }
+
+        // Check the flavor. This rejects collections, which have the flavor `ttcf`.
+        let flavor = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if !SFNT_VERSIONS.contains(&flavor) {
+            return Err(FontError::UnknownFormat)
+        }
+
+        let _length = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let num_tables = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _reserved = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let total_sfnt_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let total_compressed_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        // Skip the version and the metadata and private data blocks.
+        try!(reader.jump(mem::size_of::<u32>() * 6).map_err(FontError::eof));
+
+        let mut entries = Vec::with_capacity(num_tables as usize);
+        for _ in 0..num_tables {
+            entries.push(try!(read_table_entry(&mut reader)))
+        }
+
+        // The compressed data directly follows the table directory.
+        if reader.len() < total_compressed_size as usize {
+            return Err(FontError::UnexpectedEof)
+        }
+        let compressed = &reader[..total_compressed_size as usize];
+
+        // The tables can't decompress to more than the font they make up.
+        let decompressed_size = entries.iter().fold(0, |size, entry| size + entry.length as u64);
+        if decompressed_size > total_sfnt_size as u64 || decompressed_size > MAX_DECOMPRESSED_SIZE {
+            return Err(FontError::Failed)
+        }
+        let decompressed_size = decompressed_size as usize;
+
+        // Read one byte more than expected so that too much data is caught without inflating all
+        // of it.
+        let mut data = Vec::with_capacity(cmp::min(decompressed_size, MAX_PREALLOCATED_SIZE));
+        try!(Decompressor::new(compressed, 4096).take(decompressed_size as u64 + 1)
+                                                .read_to_end(&mut data)
+                                                .map_err(woff::decompression_error));
+        if data.len() != decompressed_size {
+            return Err(FontError::Failed)
+        }
+
+        // Find each table in the decompressed data.
+        let mut table_data = Vec::with_capacity(entries.len());
+        let mut offset = 0;
+        for entry in &entries {
+            table_data.push(&data[offset..offset + entry.length as usize]);
+            offset += entry.length as usize
+        }
+        let find = |tag: u32| entries.iter().position(|entry| entry.tag == tag);
+
+        // Rebuild `glyf` and `loca`, which are transformed together or not at all.
+        let glyf_index = find(glyf::TAG);
+        let loca_index = find(loca::TAG);
+        let reconstructed_glyf = match (glyf_index, loca_index) {
+            (Some(glyf_index), Some(loca_index)) if entries[glyf_index].transformed => {
+                if !entries[loca_index].transformed || entries[loca_index].length != 0 {
+                    return Err(FontError::Failed)
+                }
+                let reconstructed_glyf = try!(reconstruct_glyf(table_data[glyf_index]));
+                if reconstructed_glyf.loca.len() != entries[loca_index].orig_length as usize {
+                    return Err(FontError::Failed)
+                }
+                Some(reconstructed_glyf)
+            }
+            (Some(_), None) | (None, Some(_)) => return Err(FontError::Failed),
+            _ => None,
+        };
+        if let (&Some(ref reconstructed_glyf), Some(head_index)) =
+                (&reconstructed_glyf, find(head::TAG)) {
+            let head = try!(HeadTable::new(FontTable {
+                bytes: table_data[head_index],
+            }));
+            if (head.index_to_loc_format != 0) != reconstructed_glyf.long_offsets {
+                return Err(FontError::Failed)
+            }
+        }
+
+        // Copy the tables into the buffer, 4-byte aligned, and remember where they went.
+        let buffer_start = buffer.len();
+        let mut ranges = Vec::with_capacity(entries.len());
+        for (entry, &table) in entries.iter().zip(table_data.iter()) {
+            let start = buffer.len();
+            match (entry.tag, &reconstructed_glyf) {
+                (glyf::TAG, &Some(ref reconstructed_glyf)) => {
+                    buffer.extend_from_slice(&reconstructed_glyf.glyf)
+                }
+                (loca::TAG, &Some(ref reconstructed_glyf)) => {
+                    buffer.extend_from_slice(&reconstructed_glyf.loca)
+                }
+                (hmtx::TAG, _) if entry.transformed => {
+                    let x_mins = match reconstructed_glyf {
+                        Some(ref reconstructed_glyf) => &reconstructed_glyf.x_mins,
+                        None => return Err(FontError::Failed),
+                    };
+                    let num_glyphs = match find(maxp::TAG) {
+                        None => return Err(FontError::RequiredTableMissing),
+                        Some(maxp_index) => {
+                            try!(MaxpTable::new(FontTable {
+                                bytes: table_data[maxp_index],
+                            })).num_glyphs
+                        }
+                    };
+                    let num_h_metrics = match find(hhea::TAG) {
+                        None => return Err(FontError::RequiredTableMissing),
+                        Some(hhea_index) => {
+                            try!(HheaTable::new(FontTable {
+                                bytes: table_data[hhea_index],
+                            })).number_of_h_metrics
+                        }
+                    };
+                    try!(reconstruct_hmtx(table, num_glyphs, num_h_metrics, x_mins, buffer))
+                }
+                _ if entry.transformed => return Err(FontError::UnknownFormat),
+                _ => buffer.extend_from_slice(table),
+            }
+            ranges.push((entry.tag, start - buffer_start, buffer.len() - buffer_start));
+            while buffer.len() % 4 != 0 {
+                buffer.push(0)
+            }
+        }
+
+        let buffer: &'b Vec<u8> = buffer;
+        let buffer = &buffer[buffer_start..];
+        let tables = ranges.into_iter().map(|(tag, start, end)| {
+            (tag, FontTable {
+                bytes: &buffer[start..end],
+            })
+        }).collect();
+        Font::from_table_list(bytes, tables)
+    }
+}
+
+fn read_table_entry(reader: &mut &[u8]) -> Result<TableEntry, FontError> {
+    let flags = try!(reader.read_u8().map_err(FontError::eof));
+    let tag = match flags & 0x3f {
+        ARBITRARY_TAG_INDEX => try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)),
+        tag_index => BigEndian::read_u32(KNOWN_TAGS[tag_index as usize]),
+    };
+    let transform_version = flags >> 6;
+    let transformed = match tag {
+        glyf::TAG | loca::TAG => transform_version != GLYF_NULL_TRANSFORM_VERSION,
+        hmtx::TAG if transform_version == HMTX_TRANSFORM_VERSION => true,
+        _ if transform_version == 0 => false,
+        _ => return Err(FontError::UnknownFormat),
+    };
+
+    let orig_length = try!(read_uint_base_128(reader));
+    let length = if transformed {
+        try!(read_uint_base_128(reader))
+    } else {
+        orig_length
+    };
+
+    Ok(TableEntry {
+        tag: tag,
+        orig_length: orig_length,
+        transformed: transformed,
+        length: length,
+    })
+}
+
+/// Rebuilds the `glyf` and `loca` tables from a transformed `glyf` table, in which the parts of
+/// the glyphs are split into separate streams and the points are packed more tightly.
+fn reconstruct_glyf(table: &[u8]) -> Result<ReconstructedGlyf, FontError> {
+    if table.len() < TRANSFORMED_GLYF_HEADER_SIZE {
+        return Err(FontError::UnexpectedEof)
+    }
+
+    let mut reader = table;
+    let _reserved = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let option_flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let num_glyphs = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let index_format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    // The streams follow the header in the order their sizes are listed in.
+    let mut streams = &table[TRANSFORMED_GLYF_HEADER_SIZE..];
+    let mut next_stream = |reader: &mut &[u8]| -> Result<&[u8], FontError> {
+        let size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+        if streams.len() < size {
+            return Err(FontError::UnexpectedEof)
+        }
+        let (stream, rest) = streams.split_at(size);
+        streams = rest;
+        Ok(stream)
+    };
+    let mut n_contour_stream = try!(next_stream(&mut reader));
+    let n_points_stream = try!(next_stream(&mut reader));
+    let flag_stream = try!(next_stream(&mut reader));
+    let glyph_stream = try!(next_stream(&mut reader));
+    let composite_stream = try!(next_stream(&mut reader));
+    let bbox_stream = try!(next_stream(&mut reader));
+    let instruction_stream = try!(next_stream(&mut reader));
+    let mut glyph_streams = GlyphStreams {
+        n_points: n_points_stream,
+        flags: flag_stream,
+        glyphs: glyph_stream,
+        composites: composite_stream,
+        instructions: instruction_stream,
+    };
+
+    let overlap_bitmap = if option_flags & GLYF_OPTION_OVERLAP_SIMPLE_BITMAP != 0 {
+        let size = (num_glyphs as usize + 7) / 8;
+        if streams.len() < size {
+            return Err(FontError::UnexpectedEof)
+        }
+        Some(&streams[..size])
+    } else {
+        None
+    };
+
+    // The bounding box stream starts with a bitmap of the glyphs whose bounding boxes are stored
+    // rather than computed from their points.
+    let bbox_bitmap_size = (num_glyphs as usize + 31) / 32 * 4;
+    if bbox_stream.len() < bbox_bitmap_size {
+        return Err(FontError::UnexpectedEof)
+    }
+    let (bbox_bitmap, mut bbox_values) = bbox_stream.split_at(bbox_bitmap_size);
+
+    let long_offsets = index_format != 0;
+    let mut glyf = vec![];
+    let mut loca = vec![];
+    let mut x_mins = Vec::with_capacity(num_glyphs as usize);
+    for glyph_id in 0..num_glyphs as usize {
+        try!(write_loca_offset(&mut loca, glyf.len(), long_offsets));
+
+        let bit = 0x80 >> (glyph_id % 8);
+        let has_bbox = bbox_bitmap[glyph_id / 8] & bit != 0;
+        let has_overlap = overlap_bitmap.map_or(false, |bitmap| bitmap[glyph_id / 8] & bit != 0);
+
+        let n_contours = try!(n_contour_stream.read_i16::<BigEndian>().map_err(FontError::eof));
+        let x_min = match n_contours {
+            0 => {
+                // Empty glyphs have no data at all.
+                if has_bbox {
+                    return Err(FontError::Failed)
+                }
+                0
+            }
+            -1 => {
+                // Composite glyphs must store their bounding boxes.
+                if !has_bbox {
+                    return Err(FontError::Failed)
+                }
+                let bbox = try!(read_bbox(&mut bbox_values));
+                glyf.write_i16::<BigEndian>(-1).unwrap();
+                write_bbox(&mut glyf, bbox);
+                try!(copy_composite_glyph(&mut glyf, &mut glyph_streams));
+                bbox[0]
+            }
+            n_contours if n_contours > 0 => {
+                let stored_bbox = if has_bbox {
+                    Some(try!(read_bbox(&mut bbox_values)))
+                } else {
+                    None
+                };
+                try!(reconstruct_simple_glyph(&mut glyf,
+                                              &mut glyph_streams,
+                                              n_contours as u16,
+                                              stored_bbox,
+                                              has_overlap))
+            }
+            _ => return Err(FontError::Failed),
+        };
+        x_mins.push(x_min);
+
+        let alignment = if long_offsets { 4 } else { 2 };
+        while glyf.len() % alignment != 0 {
+            glyf.push(0)
+        }
+    }
+    try!(write_loca_offset(&mut loca, glyf.len(), long_offsets));
+
+    Ok(ReconstructedGlyf {
+        glyf: glyf,
+        loca: loca,
+        long_offsets: long_offsets,
+        x_mins: x_mins,
+    })
+}
+
+/// Decodes the points of a simple glyph from the transformed streams and writes the glyph in its
+/// original form. Returns the minimum x coordinate of the glyph.
+fn reconstruct_simple_glyph(glyf: &mut Vec<u8>,
+                            streams: &mut GlyphStreams,
+                            n_contours: u16,
+                            stored_bbox: Option<[i16; 4]>,
+                            has_overlap: bool)
+                            -> Result<i16, FontError> {
+    let mut end_points = Vec::with_capacity(n_contours as usize);
+    let mut point_count = 0u32;
+    for _ in 0..n_contours {
+        point_count += try!(read_255_u16(&mut streams.n_points)) as u32;
+        if point_count == 0 || point_count > 0x10000 {
+            return Err(FontError::Failed)
+        }
+        end_points.push((point_count - 1) as u16)
+    }
+
+    // Decode the points, keeping the deltas as they are, since that's how `glyf` stores them too.
+    let mut points = Vec::with_capacity(point_count as usize);
+    let (mut x, mut y) = (0i32, 0i32);
+    let (mut x_min, mut y_min, mut x_max, mut y_max) = (i32::max_value(), i32::max_value(),
+                                                        i32::min_value(), i32::min_value());
+    for _ in 0..point_count {
+        let flag = try!(streams.flags.read_u8().map_err(FontError::eof));
+        let (dx, dy) = try!(read_triplet(flag & 0x7f, &mut streams.glyphs));
+        x = try!(x.checked_add(dx).ok_or(FontError::Failed));
+        y = try!(y.checked_add(dy).ok_or(FontError::Failed));
+        x_min = cmp::min(x_min, x);
+        y_min = cmp::min(y_min, y);
+        x_max = cmp::max(x_max, x);
+        y_max = cmp::max(y_max, y);
+        points.push((flag & 0x80 == 0, dx, dy))
+    }
+
+    let bbox = match stored_bbox {
+        Some(bbox) => bbox,
+        None => {
+            let computed_bbox = [x_min, y_min, x_max, y_max];
+            if computed_bbox.iter().any(|&value| value < -0x8000 || value > 0x7fff) {
+                return Err(FontError::Failed)
+            }
+            [x_min as i16, y_min as i16, x_max as i16, y_max as i16]
+        }
+    };
+
+    glyf.write_i16::<BigEndian>(n_contours as i16).unwrap();
+    write_bbox(glyf, bbox);
+    for &end_point in &end_points {
+        glyf.write_u16::<BigEndian>(end_point).unwrap();
+    }
+    try!(copy_instructions(glyf, streams));
+
+    let (mut flags, mut x_coords, mut y_coords) = (vec![], vec![], vec![]);
+    for (point_index, &(on_curve, dx, dy)) in points.iter().enumerate() {
+        let mut flag = if on_curve { ON_CURVE } else { 0 };
+        if point_index == 0 && has_overlap {
+            flag |= OVERLAP_SIMPLE
+        }
+        flag |= try!(write_coordinate(&mut x_coords, dx, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE));
+        flag |= try!(write_coordinate(&mut y_coords, dy, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE));
+        flags.push(flag)
+    }
+    glyf.extend_from_slice(&flags);
+    glyf.extend_from_slice(&x_coords);
+    glyf.extend_from_slice(&y_coords);
+    Ok(bbox[0])
+}
+
+/// Copies the components of a composite glyph from the composite stream, followed by its
+/// instructions, if it has any.
+fn copy_composite_glyph(glyf: &mut Vec<u8>, streams: &mut GlyphStreams)
+                        -> Result<(), FontError> {
+    let mut have_instructions = false;
+    loop {
+        let flags = try!(streams.composites.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut size = mem::size_of::<u16>();
+        size += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            size += 2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            size += 4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            size += 8
+        }
+        if streams.composites.len() < size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        glyf.write_u16::<BigEndian>(flags).unwrap();
+        glyf.extend_from_slice(&streams.composites[..size]);
+        streams.composites = &streams.composites[size..];
+
+        have_instructions = have_instructions || flags & WE_HAVE_INSTRUCTIONS != 0;
+        if flags & MORE_COMPONENTS == 0 {
+            break
+        }
+    }
+
+    if have_instructions {
+        try!(copy_instructions(glyf, streams))
+    }
+    Ok(())
+}
+
+/// Copies the instructions of a glyph, whose length is in the glyph stream, from the instruction
+/// stream, preceded by their length.
+fn copy_instructions(glyf: &mut Vec<u8>, streams: &mut GlyphStreams) -> Result<(), FontError> {
+    let instruction_length = try!(read_255_u16(&mut streams.glyphs)) as usize;
+    if streams.instructions.len() < instruction_length {
+        return Err(FontError::UnexpectedEof)
+    }
+    glyf.write_u16::<BigEndian>(instruction_length as u16).unwrap();
+    glyf.extend_from_slice(&streams.instructions[..instruction_length]);
+    streams.instructions = &streams.instructions[instruction_length..];
+    Ok(())
+}
+
+/// Rebuilds an `hmtx` table whose left side bearings were left out because they match the
+/// minimum x coordinates of the glyphs, writing it to the buffer.
+fn reconstruct_hmtx(mut reader: &[u8],
+                    num_glyphs: u16,
+                    num_h_metrics: u16,
+                    x_mins: &[i16],
+                    buffer: &mut Vec<u8>)
+                    -> Result<(), FontError> {
+    let flags = try!(reader.read_u8().map_err(FontError::eof));
+    if num_h_metrics == 0 || num_h_metrics > num_glyphs || x_mins.len() != num_glyphs as usize {
+        return Err(FontError::Failed)
+    }
+
+    let mut advance_widths = Vec::with_capacity(num_h_metrics as usize);
+    for _ in 0..num_h_metrics {
+        advance_widths.push(try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)))
+    }
+
+    let mut side_bearings = Vec::with_capacity(num_glyphs as usize);
+    for glyph_id in 0..num_glyphs {
+        let omitted = if glyph_id < num_h_metrics {
+            flags & HMTX_FLAG_NO_PROPORTIONAL_LSBS != 0
+        } else {
+            flags & HMTX_FLAG_NO_MONOSPACED_LSBS != 0
+        };
+        side_bearings.push(if omitted {
+            x_mins[glyph_id as usize]
+        } else {
+            try!(reader.read_i16::<BigEndian>().map_err(FontError::eof))
+        })
+    }
+
+    for (glyph_id, &side_bearing) in side_bearings.iter().enumerate() {
+        if let Some(&advance_width) = advance_widths.get(glyph_id) {
+            buffer.write_u16::<BigEndian>(advance_width).unwrap();
+        }
+        buffer.write_i16::<BigEndian>(side_bearing).unwrap();
+    }
+    Ok(())
+}
+
+/// Decodes the change in position to a point from its flag and the bytes that follow in the
+/// glyph stream. The flag says how many bytes there are and how the bits are split between the
+/// x and y deltas.
+fn read_triplet(flag: u8, glyph_stream: &mut &[u8]) -> Result<(i32, i32), FontError> {
+    let byte_count = match flag {
+        0...83 => 1,
+        84...119 => 2,
+        120...123 => 3,
+        _ => 4,
+    };
+    if glyph_stream.len() < byte_count {
+        return Err(FontError::UnexpectedEof)
+    }
+    let data: Vec<i32> = glyph_stream[..byte_count].iter().map(|&byte| byte as i32).collect();
+    *glyph_stream = &glyph_stream[byte_count..];
+
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_value = flag as i32;
+    Ok(match flag {
+        0...9 => (0, with_sign(flag, ((flag_value & 14) << 7) + data[0])),
+        10...19 => (with_sign(flag, (((flag_value - 10) & 14) << 7) + data[0]), 0),
+        20...83 => {
+            let high = flag_value - 20;
+            (with_sign(flag, 1 + (high & 0x30) + (data[0] >> 4)),
+             with_sign(flag >> 1, 1 + ((high & 0x0c) << 2) + (data[0] & 0x0f)))
+        }
+        84...119 => {
+            let high = flag_value - 84;
+            (with_sign(flag, 1 + ((high / 12) << 8) + data[0]),
+             with_sign(flag >> 1, 1 + (((high % 12) >> 2) << 8) + data[1]))
+        }
+        120...123 => {
+            (with_sign(flag, (data[0] << 4) + (data[1] >> 4)),
+             with_sign(flag >> 1, ((data[1] & 0x0f) << 8) + data[2]))
+        }
+        _ => {
+            (with_sign(flag, (data[0] << 8) + data[1]),
+             with_sign(flag >> 1, (data[2] << 8) + data[3]))
+        }
+    })
+}
+
+/// Writes a coordinate delta in the shortest form that `glyf` allows and returns the flags that
+/// describe that form.
+fn write_coordinate(coords: &mut Vec<u8>, delta: i32, short_flag: u8, same_or_positive_flag: u8)
+                    -> Result<u8, FontError> {
+    if delta == 0 {
+        Ok(same_or_positive_flag)
+    } else if delta > -256 && delta < 256 {
+        coords.push(delta.abs() as u8);
+        Ok(short_flag | if delta > 0 { same_or_positive_flag } else { 0 })
+    } else if delta >= -0x8000 && delta <= 0x7fff {
+        coords.write_i16::<BigEndian>(delta as i16).unwrap();
+        Ok(0)
+    } else {
+        Err(FontError::Failed)
+    }
+}
+
+/// Appends an offset to `loca`, failing if it doesn't fit in the offset format.
+fn write_loca_offset(loca: &mut Vec<u8>, offset: usize, long_offsets: bool)
+                     -> Result<(), FontError> {
+    if long_offsets {
+        if offset as u64 > u32::max_value() as u64 {
+            return Err(FontError::Failed)
+        }
+        loca.write_u32::<BigEndian>(offset as u32).unwrap()
+    } else {
+        if offset / 2 > u16::max_value() as usize {
+            return Err(FontError::Failed)
+        }
+        loca.write_u16::<BigEndian>((offset / 2) as u16).unwrap()
+    }
+    Ok(())
+}
+
+fn read_bbox(reader: &mut &[u8]) -> Result<[i16; 4], FontError> {
+    let mut bbox = [0; 4];
+    for value in &mut bbox {
+        *value = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof))
+    }
+    Ok(bbox)
+}
+
+fn write_bbox(glyf: &mut Vec<u8>, bbox: [i16; 4]) {
+    for &value in &bbox {
+        glyf.write_i16::<BigEndian>(value).unwrap();
+    }
+}
+
+/// Reads a `UIntBase128`: a big-endian number in up to five bytes of seven bits each, with the
+/// top bit set in all but the last.
+fn read_uint_base_128(reader: &mut &[u8]) -> Result<u32, FontError> {
+    let mut value = 0u32;
+    for byte_index in 0..5 {
+        let byte = try!(reader.read_u8().map_err(FontError::eof));
+
+        // Leading zeros and values that don't fit in 32 bits are invalid.
+        if (byte_index == 0 && byte == 0x80) || value & 0xfe000000 != 0 {
+            return Err(FontError::Failed)
+        }
+
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value)
+        }
+    }
+    Err(FontError::Failed)
+}
+
+/// Reads a `255UInt16`: a single byte for small values, or a code byte followed by one or two
+/// more bytes.
+fn read_255_u16(reader: &mut &[u8]) -> Result<u16, FontError> {
+    const ONE_MORE_BYTE_CODE_2: u8 = 254;
+    const ONE_MORE_BYTE_CODE_1: u8 = 255;
+    const WORD_CODE: u8 = 253;
+    const LOWEST_U_CODE: u16 = 253;
+
+    let code = try!(reader.read_u8().map_err(FontError::eof));
+    Ok(match code {
+        WORD_CODE => try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+        ONE_MORE_BYTE_CODE_1 => {
+            try!(reader.read_u8().map_err(FontError::eof)) as u16 + LOWEST_U_CODE
+        }
+        ONE_MORE_BYTE_CODE_2 => {
+            try!(reader.read_u8().map_err(FontError::eof)) as u16 + LOWEST_U_CODE * 2
+        }
+        code => code as u16,
+    })
+}
@@ -37,10 +37,16 @@ pub enum FontError {
     UnsupportedCmapEncoding,
     /// The font character map has an unsupported format.
     UnsupportedCmapFormat,
+    /// The font character map is internally inconsistent.
+    CmapMalformed,
     /// We don't support the declared version of the font header.
     UnsupportedHeadVersion,
     /// We don't support the declared version of the font's horizontal metrics.
     UnsupportedHheaVersion,
+    /// We don't support the declared version of the font's maximum profile.
+    UnsupportedMaxpVersion,
+    /// We don't support the declared version of the font's vertical metrics.
+    UnsupportedVheaVersion,
     /// We don't support the declared version of the font's OS/2 and Windows table.
     UnsupportedOs2Version,
     /// A required table is missing.
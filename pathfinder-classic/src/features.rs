@@ -0,0 +1,118 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Selection of the optional OpenType layout features applied to glyphs, such as ligatures,
+//! small capitals, or tabular figures.
+//!
+//! Features are identified by four-character tags. `FeatureSettings` maps tags to values in the
+//! same way as the CSS `font-feature-settings` property, which it can be parsed from.
+
+/// Converts a four-character feature tag such as `b"liga"` into the integer form used in fonts.
+#[inline]
+pub fn tag(name: &[u8; 4]) -> u32 {
+    ((name[0] as u32) << 24) | ((name[1] as u32) << 16) | ((name[2] as u32) << 8) |
+        (name[3] as u32)
+}
+
+/// The features that shapers enable by default in horizontal text: composition and
+/// localization, required and standard ligatures, contextual alternates, kerning, and mark
+/// positioning.
+static STANDARD_FEATURES: [&[u8; 4]; 9] = [
+    b"ccmp", b"locl", b"rlig", b"liga", b"clig", b"calt", b"kern", b"mark", b"mkmk",
+];
+
+/// The values of a set of OpenType features.
+///
+/// A value of 0 disables a feature and any other value enables it. For features that pick one
+/// of several alternate glyphs, the value is the index of the alternate, starting at 1. Features
+/// that aren't mentioned are disabled, apart from the features that the font marks as required.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct FeatureSettings {
+    values: Vec<(u32, u32)>,
+}
+
+impl FeatureSettings {
+    /// Returns settings with every feature disabled.
+    #[inline]
+    pub fn new() -> FeatureSettings {
+        FeatureSettings::default()
+    }
+
+    /// Returns settings with the features that are on by default in browsers and most shapers
+    /// enabled: `ccmp`, `locl`, `rlig`, `liga`, `clig`, `calt`, `kern`, `mark`, and `mkmk`.
+    pub fn standard() -> FeatureSettings {
+        let mut settings = FeatureSettings::new();
+        for name in STANDARD_FEATURES.iter() {
+            settings.set(tag(name), 1);
+        }
+        settings
+    }
+
+    /// Parses the value of the CSS `font-feature-settings` property, such as
+    /// `"liga" 0, "smcp", "salt" 2`, on top of these settings.
+    ///
+    /// Returns `None` if the value is malformed, in which case CSS ignores the whole declaration.
+    pub fn parse_css(&self, css: &str) -> Option<FeatureSettings> {
+        let mut settings = self.clone();
+        if css.trim() == "normal" {
+            return Some(settings)
+        }
+
+        for declaration in css.split(',') {
+            let declaration = declaration.trim();
+            let quote = match declaration.chars().next() {
+                Some(quote @ '"') | Some(quote @ '\'') => quote,
+                _ => return None,
+            };
+            let declaration = &declaration[1..];
+            let (name, value) = match declaration.find(quote) {
+                Some(end) => (&declaration.as_bytes()[..end], declaration[end + 1..].trim()),
+                None => return None,
+            };
+
+            if name.len() != 4 || !name.iter().all(|&byte| byte >= 0x20 && byte <= 0x7e) {
+                return None
+            }
+            let value = match value {
+                "" | "on" => 1,
+                "off" => 0,
+                value => match value.parse() {
+                    Ok(value) => value,
+                    Err(_) => return None,
+                },
+            };
+            settings.set(tag(&[name[0], name[1], name[2], name[3]]), value);
+        }
+        Some(settings)
+    }
+
+    /// Sets the value of the feature with the given tag, replacing any previous value.
+    pub fn set(&mut self, tag: u32, value: u32) {
+        match self.values.iter_mut().find(|&&mut (other_tag, _)| other_tag == tag) {
+            Some(setting) => setting.1 = value,
+            None => self.values.push((tag, value)),
+        }
+    }
+
+    /// Returns the value of the feature with the given tag, which is 0 if it isn't set.
+    #[inline]
+    pub fn value(&self, tag: u32) -> u32 {
+        self.values.iter()
+                   .find(|&&(other_tag, _)| other_tag == tag)
+                   .map(|&(_, value)| value)
+                   .unwrap_or(0)
+    }
+
+    /// Returns true if the feature with the given tag is enabled.
+    #[inline]
+    pub fn is_enabled(&self, tag: u32) -> bool {
+        self.value(tag) != 0
+    }
+}
@@ -11,15 +11,33 @@
 //! OpenType fonts.
 
 use byteorder::{BigEndian, ReadBytesExt};
-use charmap::{CodepointRange, GlyphMapping};
+use charmap::{CodepointRange, CoverageBloom, CoverageSet, DecodedCmap, GlyphMapping};
+use charmap::{MISSING_GLYPH, MappedGlyphRange, MappingReport};
+use charmap::{ReverseGlyphMapping, SubtableComparison, SubtableInfo};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
 use containers::dfont;
 use containers::otf::{FontTables, SFNT_VERSIONS};
 use containers::ttc;
 use containers::woff;
+#[cfg(feature = "brotli-decompressor")]
+use containers::woff2;
 use error::FontError;
+use features::FeatureSettings;
 use euclid::Point2D;
 use outline::GlyphBounds;
+use paint::{Color, ColorLayer, FOREGROUND_PALETTE_INDEX, Paint, SvgDocument};
+use stroke::{self, Path, StrokeStyle};
+use tables::cmap::CoverageRanges;
+use tables::{dsig, name, os_2};
+use tables::ebdt::EbdtTable;
+use tables::gasp;
 use tables::hmtx::HorizontalMetrics;
+use tables::vhea::VheaTable;
+use tables::vmtx::{VerticalMetrics, VmtxTable};
+
+/// The language ID of US English on the Microsoft platform.
+const MICROSOFT_LANGUAGE_ID_ENGLISH_US: u16 = 0x0409;
 
 /// A handle to a font backed by a byte buffer containing the contents of the file (`.ttf`,
 /// `.otf`), etc.
@@ -70,18 +88,42 @@ impl<'a> Font<'a> {
     /// Returns the font on success or an error on failure.
     pub fn from_collection_index<'b>(bytes: &'b [u8], index: u32, buffer: &'b mut Vec<u8>)
                                      -> Result<Font<'b>, FontError> {
+        if index >= try!(Font::collection_size(bytes)) {
+            return Err(FontError::FontIndexOutOfBounds)
+        }
+
         // Check the magic number.
         let mut reader = bytes;
         let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
         match magic_number {
             ttc::MAGIC_NUMBER => Font::from_ttc_index(bytes, index),
             woff::MAGIC_NUMBER => Font::from_woff(bytes, buffer),
+            #[cfg(feature = "brotli-decompressor")]
+            woff2::MAGIC_NUMBER => Font::from_woff2(bytes, buffer),
             dfont::MAGIC_NUMBER => Font::from_dfont_index(bytes, index),
             magic_number if SFNT_VERSIONS.contains(&magic_number) => Font::from_otf(bytes, 0),
             _ => Err(FontError::UnknownFormat),
         }
     }
 
+    /// Returns the number of fonts in a byte buffer containing the contents of a file or font
+    /// collection, which is 1 unless it's a `.ttc` or `.dfont` collection.
+    ///
+    /// Valid indices for `Font::from_collection_index()` are less than this.
+    pub fn collection_size(bytes: &[u8]) -> Result<u32, FontError> {
+        let mut reader = bytes;
+        let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        match magic_number {
+            ttc::MAGIC_NUMBER => ttc::font_count(bytes),
+            dfont::MAGIC_NUMBER => dfont::font_count(bytes),
+            woff::MAGIC_NUMBER => Ok(1),
+            #[cfg(feature = "brotli-decompressor")]
+            woff2::MAGIC_NUMBER => Ok(1),
+            magic_number if SFNT_VERSIONS.contains(&magic_number) => Ok(1),
+            _ => Err(FontError::UnknownFormat),
+        }
+    }
+
     /// Returns the glyph IDs that map to the given ranges of Unicode codepoints.
     ///
     /// The returned glyph ranges are in the same order as the codepoints.
@@ -91,6 +133,150 @@ impl<'a> Font<'a> {
         self.tables.cmap.glyph_mapping_for_codepoint_ranges(codepoint_ranges)
     }
 
+    /// Looks up the given codepoints, passing each mapped range of glyphs to `visit` in the
+    /// order the codepoints were requested in rather than building a `GlyphMapping`.
+    #[inline]
+    pub fn for_each_mapped_range<F>(&self, codepoint_ranges: &[CodepointRange], visit: F)
+                                    -> Result<(), FontError>
+                                    where F: FnMut(MappedGlyphRange) {
+        self.tables.cmap.for_each_mapped_range(codepoint_ranges, visit)
+    }
+
+    /// Like `glyph_mapping_for_codepoint_ranges()`, but also returns a report of the character
+    /// map subtable that was consulted and the codepoints that the font has no glyph for.
+    #[inline]
+    pub fn glyph_mapping_with_report(&self, codepoint_ranges: &[CodepointRange])
+                                     -> Result<(GlyphMapping, MappingReport), FontError> {
+        self.tables.cmap.glyph_mapping_with_report(codepoint_ranges)
+    }
+
+    /// Returns the glyph ID that the given character maps to, or 0 (the missing glyph) if the
+    /// font doesn't support it.
+    ///
+    /// ```
+    /// # use pathfinder::font::Font;
+    /// # use std::fs::File;
+    /// # use std::io::Read;
+    /// # let mut bytes = vec![];
+    /// # let mut file = File::open("resources/tests/nimbus-sans/NimbusSanL-Regu.ttf").unwrap();
+    /// # file.read_to_end(&mut bytes).unwrap();
+    /// # let mut buffer = vec![];
+    /// # let font = Font::new(&bytes, &mut buffer).unwrap();
+    /// let glyph_id = font.glyph_for_char('A').unwrap();
+    /// assert!(glyph_id != 0);
+    /// ```
+    #[inline]
+    pub fn glyph_for_char(&self, character: char) -> Result<u16, FontError> {
+        self.tables.cmap.glyph_for_char(character).map(|glyph_id| {
+            glyph_id.unwrap_or(MISSING_GLYPH)
+        })
+    }
+
+    /// Returns the glyph ID that the given codepoint maps to, falling back to the font's other
+    /// Unicode character map subtables if the preferred one doesn't map it.
+    ///
+    /// This is slower than a normal lookup on a miss, but copes with broken fonts whose
+    /// subtables disagree. Returns 0 (the missing glyph) if no subtable maps the codepoint.
+    #[inline]
+    pub fn glyph_for_codepoint_resilient(&self, codepoint: u32) -> Result<u16, FontError> {
+        self.tables.cmap.glyph_for_codepoint_resilient(codepoint)
+    }
+
+    /// Returns the glyph ID that the variation sequence of `base` followed by the variation
+    /// selector `selector` maps to.
+    ///
+    /// Sequences that the font doesn't give a glyph of their own use the glyph for `base`.
+    #[inline]
+    pub fn glyph_for_variation_sequence(&self, base: u32, selector: u32)
+                                        -> Result<u16, FontError> {
+        self.tables.cmap.glyph_for_variation_sequence(base, selector)
+    }
+
+    /// Returns the glyph ID for each character of `string`, in order. Unsupported characters map
+    /// to 0 (the missing glyph).
+    ///
+    /// This performs a single batched lookup, so it's faster than calling `glyph_for_char()` on
+    /// each character.
+    #[inline]
+    pub fn glyphs_for_str(&self, string: &str) -> Result<Vec<u16>, FontError> {
+        self.tables.cmap.glyphs_for_str(string)
+    }
+
+    /// Like `glyphs_for_str()`, but normalizes `string` to NFC first (or NFKC, if
+    /// `compatibility` is set), decomposing characters that the font only supports in decomposed
+    /// form. The glyphs therefore don't correspond one-to-one with the characters of `string`.
+    #[cfg(feature = "unicode-normalization")]
+    #[inline]
+    pub fn glyphs_for_normalized_str(&self, string: &str, compatibility: bool)
+                                     -> Result<Vec<u16>, FontError> {
+        self.tables.cmap.glyphs_for_normalized_str(string, compatibility)
+    }
+
+    /// Returns the set of all codepoints that this font maps to a glyph.
+    #[inline]
+    pub fn coverage(&self) -> Result<CoverageSet, FontError> {
+        self.tables.cmap.coverage()
+    }
+
+    /// Returns the codepoints that this font maps to a glyph as an iterator over maximal ranges
+    /// in ascending order, which avoids building the whole set when it's only going to be
+    /// written out, as in a fallback table.
+    #[inline]
+    pub fn coverage_ranges(&self) -> Result<CoverageRanges<'a>, FontError> {
+        self.tables.cmap.coverage_ranges()
+    }
+
+    /// Returns a map from each glyph to the codepoints that this font maps to it, for tools that
+    /// need to know which characters a glyph represents.
+    ///
+    /// This walks the whole character map, so build it once and reuse it.
+    #[inline]
+    pub fn reverse_glyph_mapping(&self) -> Result<ReverseGlyphMapping, FontError> {
+        self.tables.cmap.reverse_glyph_mapping()
+    }
+
+    /// Decodes this font's whole character map into in-memory lookup tables, for layout code
+    /// that looks up characters often enough that walking the `cmap` table each time shows up.
+    #[inline]
+    pub fn decode_cmap(&self) -> Result<DecodedCmap, FontError> {
+        self.tables.cmap.decode()
+    }
+
+    /// Decodes this font's whole character map into a compact binary blob that `CompiledCmap`
+    /// can look characters up in without parsing the font.
+    ///
+    /// Applications can cache the blob on disk, keyed by the font, to skip the character map on
+    /// warm starts.
+    #[inline]
+    pub fn compile_cmap(&self) -> Result<Vec<u8>, FontError> {
+        self.tables.cmap.compile()
+    }
+
+    /// Returns a Bloom filter over the codepoints that this font maps to a glyph.
+    ///
+    /// Building the filter walks the whole character map, but afterward it can rule out uncovered
+    /// codepoints without consulting the font. See `CoverageBloom` for its accuracy.
+    #[inline]
+    pub fn coverage_bloom(&self) -> Result<CoverageBloom, FontError> {
+        self.tables.cmap.build_bloom()
+    }
+
+    /// Returns the encoding records of every subtable in this font's character map.
+    #[inline]
+    pub fn cmap_subtables(&self) -> Result<Vec<SubtableInfo>, FontError> {
+        self.tables.cmap.subtables()
+    }
+
+    /// Reports the codepoints that two of this font's character map subtables map to different
+    /// glyphs.
+    ///
+    /// Codepoints above U+FFFF are skipped if either subtable is limited to the BMP.
+    #[inline]
+    pub fn compare_cmap_subtables(&self, a: &SubtableInfo, b: &SubtableInfo)
+                                  -> Result<SubtableComparison, FontError> {
+        self.tables.cmap.compare_subtables(a, b)
+    }
+
     /// Calls the given callback for each point in the supplied glyph's contour.
     ///
     /// This function is the primary method for accessing a glyph's outline.
@@ -112,6 +298,55 @@ impl<'a> Font<'a> {
         }
     }
 
+    /// Returns the points of the given glyph, grouped into contours.
+    ///
+    /// For a variable font, this is the outline of the default instance. It's read straight from
+    /// the outline tables without consulting the glyph variations, so it costs no more than it
+    /// would for a static font.
+    pub fn glyph_contours(&self, glyph_id: u16) -> Result<Vec<Vec<Point>>, FontError> {
+        let mut contours = vec![];
+        try!(self.for_each_point(glyph_id, |point| push_point_to_contours(&mut contours, point)));
+        Ok(contours)
+    }
+
+    /// Returns the points of the given glyph, grouped into contours, at the given normalized
+    /// variation coordinates, one per axis in `fvar` order. Missing coordinates are taken to be
+    /// zero.
+    ///
+    /// The glyph variations in `gvar` are applied to TrueType outlines. When every coordinate is
+    /// zero (the default location), this is equivalent to `glyph_contours()`, which should be
+    /// preferred as it skips examining the coordinates. The same goes for fonts whose outlines
+    /// don't vary.
+    pub fn glyph_contours_at(&self, glyph_id: u16, coords: &[f32])
+                             -> Result<Vec<Vec<Point>>, FontError> {
+        let (glyf, gvar) = match (self.tables.glyf, self.tables.gvar) {
+            (Some(glyf), Some(gvar)) if coords.iter().any(|&coord| coord != 0.0) => (glyf, gvar),
+            _ => return self.glyph_contours(glyph_id),
+        };
+        let loca = match self.tables.loca {
+            Some(ref loca) => loca,
+            None => return Err(FontError::RequiredTableMissing),
+        };
+
+        let mut contours = vec![];
+        try!(glyf.for_each_point_at(&self.tables.head, loca, &gvar, glyph_id, coords, |point| {
+            push_point_to_contours(&mut contours, point)
+        }));
+        Ok(contours)
+    }
+
+    /// Returns the given glyph as two polygonal paths in font units: the fill, which is the
+    /// glyph's outline with its curves flattened, and the outline of the glyph's stroke in the
+    /// given style, converted to a fill.
+    ///
+    /// Drawing the second path on top of the first produces outlined text.
+    pub fn glyph_fill_and_stroke(&self, glyph_id: u16, style: StrokeStyle)
+                                 -> Result<(Path, Path), FontError> {
+        let fill = try!(Path::from_glyph(self, glyph_id));
+        let stroke = stroke::stroke_to_fill(&fill, &style);
+        Ok((fill, stroke))
+    }
+
     /// Returns the boundaries of the given glyph in font units.
     #[inline]
     pub fn glyph_bounds(&self, glyph_id: u16) -> Result<GlyphBounds, FontError> {
@@ -130,6 +365,246 @@ impl<'a> Font<'a> {
         }
     }
 
+    /// Returns the advance width, left side bearing, and bounds of the given glyph in font units:
+    /// everything a layout pass needs to measure text.
+    ///
+    /// This is much cheaper than extracting the outline. For TrueType outlines, the bounds are
+    /// read from the glyph header without decoding the glyph. For CFF outlines, the CharString has
+    /// to be interpreted to find them. The bounds are `None` if the glyph has no outline, as is
+    /// the case for the space character.
+    pub fn glyph_layout_info(&self, glyph_id: u16) -> Result<GlyphLayoutInfo, FontError> {
+        let metrics = try!(self.metrics_for_glyph(glyph_id));
+        let bounds = match (self.tables.glyf, self.tables.cff) {
+            (Some(glyf), None) => {
+                let loca = match self.tables.loca {
+                    Some(ref loca) => loca,
+                    None => return Err(FontError::RequiredTableMissing),
+                };
+
+                try!(glyf.glyph_header_bounds(&self.tables.head, loca, glyph_id))
+            }
+            (None, Some(cff)) => Some(try!(cff.glyph_bounds(glyph_id))),
+            (Some(_), Some(_)) => return Err(FontError::Failed),
+            (None, None) => None,
+        };
+
+        Ok(GlyphLayoutInfo {
+            advance: metrics.advance_width,
+            lsb: metrics.lsb,
+            bounds: bounds,
+        })
+    }
+
+    /// Returns the embedded monochrome bitmap of the given glyph at the given size in pixels per
+    /// em, from the `EBLC` and `EBDT` tables.
+    ///
+    /// Fonts made of pixel art may have no outlines at all, only bitmaps. Returns `None` if the
+    /// font has no monochrome strike at that size or the strike lacks the glyph.
+    pub fn glyph_mono_bitmap(&self, glyph_id: u16, ppem: u16)
+                             -> Result<Option<MonoBitmap>, FontError> {
+        let (eblc, ebdt) = match (self.tables.eblc, self.tables.ebdt) {
+            (Some(eblc), Some(ebdt)) => (eblc, EbdtTable::new(ebdt)),
+            _ => return Ok(None),
+        };
+        match try!(eblc.locate(glyph_id, ppem)) {
+            None => Ok(None),
+            Some(location) => ebdt.mono_bitmap(&location).map(Some),
+        }
+    }
+
+    /// Returns the sizes of the strikes of monochrome and grayscale bitmaps in the `EBLC` table,
+    /// in the order the font lists them.
+    pub fn embedded_bitmap_strikes(&self) -> Result<Vec<EmbeddedStrike>, FontError> {
+        match self.tables.eblc {
+            Some(eblc) => {
+                Ok(try!(eblc.strikes()).into_iter().map(|(ppem, bit_depth)| {
+                    EmbeddedStrike {
+                        ppem: ppem,
+                        bit_depth: bit_depth,
+                    }
+                }).collect())
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the index of the strike in the list that `embedded_bitmap_strikes()` returns that
+    /// should be used to render glyphs at the given size in pixels per em, or `None` if glyphs
+    /// should be rendered from their outlines instead.
+    ///
+    /// Scaling these bitmaps would spoil them, so only strikes of exactly the size qualify.
+    /// Grayscale strikes are preferred to monochrome ones, and deeper ones to shallower ones.
+    pub fn best_strike_for_size(&self, ppem: u16) -> Result<Option<usize>, FontError> {
+        let mut best_strike: Option<(usize, u8)> = None;
+        for (strike_index, strike) in try!(self.embedded_bitmap_strikes()).iter().enumerate() {
+            if strike.ppem != ppem {
+                continue
+            }
+            match best_strike {
+                Some((_, best_bit_depth)) if best_bit_depth >= strike.bit_depth => {}
+                _ => best_strike = Some((strike_index, strike.bit_depth)),
+            }
+        }
+        Ok(best_strike.map(|(strike_index, _)| strike_index))
+    }
+
+    /// Returns the embedded bitmap of the given glyph in the strike with the given index in the
+    /// list that `embedded_bitmap_strikes()` returns, converted to one byte per pixel whatever
+    /// the bit depth of the strike. Returns `None` if the strike lacks the glyph.
+    pub fn glyph_gray_bitmap(&self, glyph_id: u16, strike_index: usize)
+                             -> Result<Option<GrayBitmap>, FontError> {
+        let (eblc, ebdt) = match (self.tables.eblc, self.tables.ebdt) {
+            (Some(eblc), Some(ebdt)) => (eblc, EbdtTable::new(ebdt)),
+            _ => return Ok(None),
+        };
+        let (_, bit_depth) = match try!(eblc.strikes()).get(strike_index) {
+            Some(&strike) => strike,
+            None => return Err(FontError::Failed),
+        };
+        match try!(eblc.locate_in_strike_index(strike_index as u32, glyph_id)) {
+            None => Ok(None),
+            Some(location) => ebdt.gray_bitmap(&location, bit_depth).map(Some),
+        }
+    }
+
+    /// Returns the sizes of the strikes of color bitmaps, in the order the font lists them.
+    ///
+    /// These come from the `sbix` table if the font has one, and from the `CBLC` table
+    /// otherwise. `CBLC` doesn't record pixel densities, so its strikes are reported at 72 pixels
+    /// per inch.
+    pub fn color_bitmap_strikes(&self) -> Result<Vec<BitmapStrike>, FontError> {
+        if let Some(sbix) = self.tables.sbix {
+            return sbix.strikes()
+        }
+        match self.tables.cblc {
+            Some(cblc) => {
+                Ok(try!(cblc.strikes()).into_iter().map(|(ppem, _)| {
+                    BitmapStrike {
+                        ppem: ppem,
+                        ppi: 72,
+                    }
+                }).collect())
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the color bitmap of the given glyph for rendering at the given size in pixels per
+    /// em, as used by Apple Color Emoji (`sbix`) and Noto Color Emoji (`CBDT`).
+    ///
+    /// The image comes from the smallest strike at least as large as the size, or the largest
+    /// strike if none is. Returns `None` if the font has no color bitmaps or that strike lacks
+    /// the glyph.
+    pub fn glyph_color_bitmap(&self, glyph_id: u16, ppem: u16)
+                              -> Result<Option<ColorBitmap<'a>>, FontError> {
+        let mut best_strike: Option<(usize, u16)> = None;
+        for (strike_index, strike) in try!(self.color_bitmap_strikes()).iter().enumerate() {
+            best_strike = match best_strike {
+                None => Some((strike_index, strike.ppem)),
+                Some((_, best_ppem)) if (best_ppem < ppem && strike.ppem > best_ppem) ||
+                        (strike.ppem >= ppem && strike.ppem < best_ppem) => {
+                    Some((strike_index, strike.ppem))
+                }
+                Some(best_strike) => Some(best_strike),
+            }
+        }
+
+        match best_strike {
+            None => Ok(None),
+            Some((strike_index, _)) => self.glyph_color_bitmap_in_strike(glyph_id, strike_index),
+        }
+    }
+
+    /// Returns the color bitmap of the given glyph in the strike with the given index in the
+    /// list that `color_bitmap_strikes()` returns, or `None` if the strike lacks the glyph.
+    pub fn glyph_color_bitmap_in_strike(&self, glyph_id: u16, strike_index: usize)
+                                        -> Result<Option<ColorBitmap<'a>>, FontError> {
+        if let Some(sbix) = self.tables.sbix {
+            // The glyph count bounds the table's offset arrays.
+            let glyph_count = try!(self.glyph_count().ok_or(FontError::RequiredTableMissing));
+            return sbix.glyph_bitmap(strike_index as u32, glyph_id, glyph_count)
+        }
+
+        let (cblc, cbdt) = match (self.tables.cblc, self.tables.cbdt) {
+            (Some(cblc), Some(cbdt)) => (cblc, EbdtTable::new(cbdt)),
+            _ => return Ok(None),
+        };
+        let (ppem, _) = match try!(cblc.strikes()).get(strike_index) {
+            Some(&strike) => strike,
+            None => return Err(FontError::Failed),
+        };
+        match try!(cblc.locate_in_strike_index(strike_index as u32, glyph_id)) {
+            None => Ok(None),
+            Some(location) => cbdt.color_bitmap(&location, ppem).map(Some),
+        }
+    }
+
+    /// Returns the root of the color paint graph of the given glyph from version 1 of the `COLR`
+    /// table, or `None` if the font doesn't describe the glyph that way.
+    ///
+    /// Paint formats beyond solid fills, linear and radial gradients, glyph outlines, transforms,
+    /// and layers aren't supported yet and result in `FontError::UnknownFormat`.
+    pub fn paint_graph(&self, glyph_id: u16) -> Result<Option<Paint>, FontError> {
+        match self.tables.colr {
+            Some(colr) => colr.paint_graph(glyph_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the layers of the given version 0 color glyph from bottom to top, with their
+    /// colors looked up in the given `CPAL` palette, or an empty list if the glyph isn't a
+    /// version 0 color glyph.
+    ///
+    /// Palette 0 is the default. Layers whose color is the current text color, or whose palette
+    /// entry is missing, have no `color`.
+    pub fn color_layers(&self, glyph_id: u16, palette: u16)
+                        -> Result<Vec<ColorLayer>, FontError> {
+        let colr = match self.tables.colr {
+            Some(colr) => colr,
+            None => return Ok(vec![]),
+        };
+
+        let mut color_layers = vec![];
+        for (layer_glyph_id, palette_index) in try!(colr.layers(glyph_id)) {
+            let color = if palette_index == FOREGROUND_PALETTE_INDEX {
+                None
+            } else {
+                try!(self.palette_color(palette, palette_index))
+            };
+            color_layers.push(ColorLayer {
+                glyph_id: layer_glyph_id,
+                palette_index: palette_index,
+                color: color,
+            })
+        }
+        Ok(color_layers)
+    }
+
+    /// Returns the number of color palettes in the font's `CPAL` table.
+    #[inline]
+    pub fn palette_count(&self) -> u16 {
+        self.tables.cpal.map_or(0, |cpal| cpal.palette_count())
+    }
+
+    /// Returns the color at `palette_index` in the given `CPAL` palette, or `None` if the font
+    /// has no such palette entry.
+    pub fn palette_color(&self, palette: u16, palette_index: u16)
+                         -> Result<Option<Color>, FontError> {
+        match self.tables.cpal {
+            Some(cpal) => cpal.color(palette, palette_index),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the SVG document in the `SVG ` table that describes the given color glyph, or
+    /// `None` if the glyph has no SVG description.
+    pub fn svg_document(&self, glyph_id: u16) -> Result<Option<SvgDocument<'a>>, FontError> {
+        match self.tables.svg {
+            Some(svg) => svg.document(glyph_id),
+            None => Ok(None),
+        }
+    }
+
     /// Returns the minimum shelf height that an atlas containing glyphs from this font will need.
     #[inline]
     pub fn shelf_height(&self, point_size: f32) -> u32 {
@@ -152,6 +627,77 @@ impl<'a> Font<'a> {
         self.tables.head.units_per_em
     }
 
+    /// Returns the number of glyphs in the font, or `None` if the font lacks a `maxp` table we
+    /// can read. Valid glyph IDs are less than this.
+    ///
+    /// Character maps in broken fonts can produce glyph IDs out of range, so check them against
+    /// this before using them to index other tables.
+    #[inline]
+    pub fn glyph_count(&self) -> Option<u16> {
+        self.tables.maxp.as_ref().map(|maxp| maxp.num_glyphs)
+    }
+
+    /// Returns the limits on the complexity of the font's TrueType outlines, or `None` if the
+    /// font has CFF outlines, whose `maxp` table doesn't record them, or no `maxp` table at all.
+    #[inline]
+    pub fn glyph_limits(&self) -> Option<GlyphLimits> {
+        self.tables.maxp.as_ref().and_then(|maxp| maxp.limits)
+    }
+
+    /// Returns the union of the bounding boxes of all glyphs in the font, in font units.
+    #[inline]
+    pub fn font_bounds(&self) -> GlyphBounds {
+        self.tables.head.max_glyph_bounds
+    }
+
+    /// Returns the style bits of the `head` table, which should agree with `is_bold()` and
+    /// `is_italic()`.
+    #[inline]
+    pub fn mac_style(&self) -> MacStyle {
+        self.tables.head.mac_style
+    }
+
+    /// Returns the smallest size, in pixels per em, at which the font is legible.
+    #[inline]
+    pub fn lowest_recommended_ppem(&self) -> u16 {
+        self.tables.head.lowest_rec_ppem
+    }
+
+    /// Returns true if the `loca` table stores 32-bit glyph offsets rather than 16-bit ones.
+    #[inline]
+    pub fn has_long_glyph_offsets(&self) -> bool {
+        self.tables.head.index_to_loc_format != 0
+    }
+
+    /// Returns the creation date recorded in the `head` table, in seconds since the Unix epoch, or
+    /// `None` if it can't be represented that way.
+    ///
+    /// Font tools fill this in inconsistently, so it shouldn't be relied upon for anything more
+    /// than display.
+    #[inline]
+    pub fn created(&self) -> Option<i64> {
+        self.tables.head.created()
+    }
+
+    /// Returns the modification date recorded in the `head` table, in seconds since the Unix
+    /// epoch, or `None` if it can't be represented that way.
+    #[inline]
+    pub fn modified(&self) -> Option<i64> {
+        self.tables.head.modified()
+    }
+
+    /// Returns the creation date as a UTC date and time, or `None` if it's out of range.
+    #[cfg(feature = "chrono")]
+    pub fn created_date_time(&self) -> Option<DateTime<Utc>> {
+        self.created().and_then(unix_timestamp_to_date_time)
+    }
+
+    /// Returns the modification date as a UTC date and time, or `None` if it's out of range.
+    #[cfg(feature = "chrono")]
+    pub fn modified_date_time(&self) -> Option<DateTime<Utc>> {
+        self.modified().and_then(unix_timestamp_to_date_time)
+    }
+
     /// Returns the horizontal metrics for the glyph with the given ID.
     ///
     /// Horizontal metrics are important for text shaping, as they specify the number of units to
@@ -161,6 +707,149 @@ impl<'a> Font<'a> {
         self.tables.hmtx.metrics_for_glyph(&self.tables.hhea, glyph_id)
     }
 
+    /// Returns the advance width of the given glyph in font units.
+    #[inline]
+    pub fn advance_width(&self, glyph_id: u16) -> Result<u16, FontError> {
+        self.tables.hmtx.advance_width(&self.tables.hhea, glyph_id)
+    }
+
+    /// Returns the left side bearing of the given glyph in font units: the distance from the
+    /// origin to the left edge of its bounding box.
+    #[inline]
+    pub fn left_side_bearing(&self, glyph_id: u16) -> Result<i16, FontError> {
+        self.tables.hmtx.left_side_bearing(&self.tables.hhea, glyph_id)
+    }
+
+    /// Returns true if the font has vertical metrics (`vhea` and `vmtx` tables), as fonts meant
+    /// for vertical CJK layout do.
+    #[inline]
+    pub fn has_vertical_metrics(&self) -> bool {
+        self.tables.vhea.is_some() && self.tables.vmtx.is_some()
+    }
+
+    /// Returns the vertical metrics of the given glyph, which describe how far to advance the pen
+    /// down after typesetting it in vertical text.
+    ///
+    /// Returns `RequiredTableMissing` if the font has no vertical metrics.
+    pub fn vertical_metrics_for_glyph(&self, glyph_id: u16)
+                                      -> Result<VerticalMetrics, FontError> {
+        let (vhea, vmtx) = try!(self.vertical_metrics_tables());
+        vmtx.metrics_for_glyph(vhea, glyph_id)
+    }
+
+    /// Returns the advance height of the given glyph in font units.
+    #[inline]
+    pub fn advance_height(&self, glyph_id: u16) -> Result<u16, FontError> {
+        let (vhea, vmtx) = try!(self.vertical_metrics_tables());
+        vmtx.advance_height(vhea, glyph_id)
+    }
+
+    /// Returns the top side bearing of the given glyph in font units: the distance from the top
+    /// of the vertical line to the top edge of its bounding box.
+    #[inline]
+    pub fn top_side_bearing(&self, glyph_id: u16) -> Result<i16, FontError> {
+        let (vhea, vmtx) = try!(self.vertical_metrics_tables());
+        vmtx.top_side_bearing(vhea, glyph_id)
+    }
+
+    /// Returns the Y coordinate of the given glyph's origin in vertical layout, in font units, as
+    /// recorded in the `VORG` table of CFF-flavored fonts.
+    ///
+    /// Returns `None` if the font has no `VORG` table, in which case the origin should be derived
+    /// from the top side bearing and the glyph bounds instead.
+    #[inline]
+    pub fn vertical_origin_y(&self, glyph_id: u16) -> Option<i16> {
+        self.tables.vorg.map(|vorg| vorg.vertical_origin_y(glyph_id))
+    }
+
+    fn vertical_metrics_tables(&self) -> Result<(&VheaTable, VmtxTable<'a>), FontError> {
+        match (self.tables.vhea.as_ref(), self.tables.vmtx) {
+            (Some(vhea), Some(vmtx)) => Ok((vhea, vmtx)),
+            _ => Err(FontError::RequiredTableMissing),
+        }
+    }
+
+    /// Looks up each of the given PostScript glyph names, as PDF and SVG content sometimes refer
+    /// to glyphs, and returns the corresponding glyph IDs.
+    ///
+    /// Names are resolved with the `post` table. Names that aren't found, and all names in fonts
+    /// whose `post` table is missing or doesn't name glyphs, map to `None`.
+    pub fn glyphs_for_names(&self, names: &[&str]) -> Result<Vec<Option<u16>>, FontError> {
+        let post = match self.tables.post {
+            Some(ref post) => post,
+            None => return Ok(vec![None; names.len()]),
+        };
+        names.iter().map(|name| post.glyph_for_name(name)).collect()
+    }
+
+    /// Looks up a single PostScript glyph name. See `glyphs_for_names()`.
+    pub fn glyph_for_name(&self, name: &str) -> Result<Option<u16>, FontError> {
+        match self.tables.post {
+            Some(ref post) => post.glyph_for_name(name),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the PostScript name of the given glyph, such as "A" or "uni0041.ss01", for
+    /// generating PDF and SVG content.
+    ///
+    /// Returns `None` if the font's `post` table is missing or doesn't name glyphs.
+    pub fn glyph_name(&self, glyph_id: u16) -> Result<Option<String>, FontError> {
+        match self.tables.post {
+            Some(ref post) => post.glyph_name(glyph_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the slant of the font in degrees counterclockwise from the vertical, so italic
+    /// fonts have negative angles. Fonts without a `post` table are assumed to be upright.
+    #[inline]
+    pub fn italic_angle(&self) -> f32 {
+        self.tables.post.as_ref().map_or(0.0, |post| post.italic_angle)
+    }
+
+    /// Returns the position of the top of the underline relative to the baseline in font units,
+    /// or `None` if the font has no `post` table.
+    #[inline]
+    pub fn underline_position(&self) -> Option<i16> {
+        self.tables.post.as_ref().map(|post| post.underline_position)
+    }
+
+    /// Returns the thickness of the underline in font units, or `None` if the font has no `post`
+    /// table.
+    #[inline]
+    pub fn underline_thickness(&self) -> Option<i16> {
+        self.tables.post.as_ref().map(|post| post.underline_thickness)
+    }
+
+    /// Returns true if every glyph in the font has the same advance width, as terminals and code
+    /// editors expect.
+    ///
+    /// This is the `isFixedPitch` flag of the `post` table. Fonts without a `post` table are
+    /// considered monospaced if all the advance widths in `hmtx` are equal, apart from zero
+    /// advances, which combining marks use.
+    pub fn is_monospaced(&self) -> Result<bool, FontError> {
+        if let Some(ref post) = self.tables.post {
+            return Ok(post.is_fixed_pitch)
+        }
+
+        let mut fixed_advance_width = None;
+        for glyph_id in 0..self.tables.hhea.number_of_h_metrics {
+            let advance_width = try!(self.metrics_for_glyph(glyph_id)).advance_width;
+            if advance_width == 0 {
+                continue
+            }
+            match fixed_advance_width {
+                None => fixed_advance_width = Some(advance_width),
+                Some(fixed_advance_width) if fixed_advance_width != advance_width => {
+                    return Ok(false)
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(true)
+    }
+
     /// Returns the kerning between the given two glyph IDs in font units.
     ///
     /// Positive values move glyphs farther apart; negative values move glyphs closer together.
@@ -170,10 +859,385 @@ impl<'a> Font<'a> {
     pub fn kerning_for_glyph_pair(&self, left_glyph_id: u16, right_glyph_id: u16) -> i16 {
         match self.tables.kern {
             None => 0,
-            Some(kern) => kern.kerning_for_glyph_pair(left_glyph_id, right_glyph_id).unwrap_or(0),
+            Some(kern) => kern.kerning(left_glyph_id, right_glyph_id),
+        }
+    }
+
+    /// Returns the class of the given glyph in the `GDEF` table: whether it's a base glyph, a
+    /// ligature, a combining mark, or a component of a ligature.
+    ///
+    /// Returns `None` if the glyph is unclassified or the font has no glyph classes.
+    pub fn glyph_class(&self, glyph_id: u16) -> Result<Option<GlyphClass>, FontError> {
+        match self.tables.gdef {
+            None => Ok(None),
+            Some(gdef) => gdef.glyph_class(glyph_id),
+        }
+    }
+
+    /// Returns the mark attachment class of the given glyph in the `GDEF` table, or 0 if it has
+    /// none.
+    pub fn mark_attachment_class(&self, glyph_id: u16) -> Result<u16, FontError> {
+        match self.tables.gdef {
+            None => Ok(0),
+            Some(gdef) => gdef.mark_attachment_class(glyph_id),
+        }
+    }
+
+    /// Returns the indices of the points in the outline of the given glyph that marks attach
+    /// to, as listed in the `GDEF` table.
+    pub fn attachment_points(&self, glyph_id: u16) -> Result<Vec<u16>, FontError> {
+        match self.tables.gdef {
+            None => Ok(vec![]),
+            Some(gdef) => gdef.attachment_points(glyph_id),
+        }
+    }
+
+    /// Returns the value of the given constant in the `MATH` table, in font units or, for the
+    /// few constants that are percentages, in percent.
+    ///
+    /// Returns `None` if the font has no `MATH` table.
+    pub fn math_constant(&self, constant: MathConstant) -> Result<Option<i16>, FontError> {
+        match self.tables.math {
+            None => Ok(None),
+            Some(math) => math.constant(constant),
+        }
+    }
+
+    /// Returns the italics correction of the given glyph in the `MATH` table: how far to move a
+    /// following superscript, or an upper limit, to the right of the glyph's advance.
+    pub fn math_italics_correction(&self, glyph_id: u16) -> Result<Option<i16>, FontError> {
+        match self.tables.math {
+            None => Ok(None),
+            Some(math) => math.italics_correction(glyph_id),
+        }
+    }
+
+    /// Returns the horizontal position, in font units from the glyph origin, over which accents
+    /// placed on top of the given glyph should be centered.
+    ///
+    /// Returns `None` if the `MATH` table doesn't say, in which case the accent should be
+    /// centered over the glyph's advance.
+    pub fn math_top_accent_attachment(&self, glyph_id: u16) -> Result<Option<i16>, FontError> {
+        match self.tables.math {
+            None => Ok(None),
+            Some(math) => math.top_accent_attachment(glyph_id),
+        }
+    }
+
+    /// Returns true if the `MATH` table marks the given glyph as an extended shape, like a large
+    /// integral sign or a tall delimiter.
+    pub fn is_math_extended_shape(&self, glyph_id: u16) -> Result<bool, FontError> {
+        match self.tables.math {
+            None => Ok(false),
+            Some(math) => math.is_extended_shape(glyph_id),
+        }
+    }
+
+    /// Returns the minimum overlap, in font units, between adjacent parts of a glyph assembly.
+    pub fn math_min_connector_overlap(&self) -> Result<u16, FontError> {
+        match self.tables.math {
+            None => Ok(0),
+            Some(math) => math.min_connector_overlap(),
+        }
+    }
+
+    /// Returns the ready-made larger versions of the given glyph that grow in the given
+    /// direction, from smallest to largest, as listed in the `MATH` table.
+    pub fn math_glyph_variants(&self, glyph_id: u16, direction: MathDirection)
+                               -> Result<Vec<MathGlyphVariant>, FontError> {
+        match self.tables.math {
+            None => Ok(vec![]),
+            Some(math) => math.glyph_variants(glyph_id, direction),
+        }
+    }
+
+    /// Returns the parts from which the given glyph can be stretched to any size in the given
+    /// direction, for when none of its variants is large enough.
+    pub fn math_glyph_assembly(&self, glyph_id: u16, direction: MathDirection)
+                               -> Result<Option<GlyphAssembly>, FontError> {
+        match self.tables.math {
+            None => Ok(None),
+            Some(math) => math.glyph_assembly(glyph_id, direction),
+        }
+    }
+
+    /// Returns the positions of the baselines, such as `romn`, `hang`, and `ideo`, of the given
+    /// script, as listed in the `BASE` table.
+    ///
+    /// The baselines of the default script (`DFLT`) are returned if the table doesn't list the
+    /// given script. Returns `None` if the font doesn't say where the baselines are.
+    pub fn script_baselines(&self, axis: BaselineAxis, script_tag: u32)
+                            -> Result<Option<ScriptBaselines>, FontError> {
+        match self.tables.base {
+            None => Ok(None),
+            Some(base) => base.baselines(axis, script_tag),
+        }
+    }
+
+    /// Returns the position of the baseline with the given tag in the given script, in font units
+    /// along the given axis.
+    pub fn baseline(&self, axis: BaselineAxis, script_tag: u32, baseline_tag: u32)
+                    -> Result<Option<i16>, FontError> {
+        Ok(try!(self.script_baselines(axis, script_tag)).and_then(|baselines| {
+            baselines.position(baseline_tag)
+        }))
+    }
+
+    /// Returns the minimum and maximum extents of glyphs in the given language of the given
+    /// script, as listed in the `BASE` table, for computing line heights.
+    ///
+    /// The extents of the script as a whole are returned if the language has none of its own.
+    pub fn baseline_extents(&self, axis: BaselineAxis, script_tag: u32, language_tag: u32)
+                            -> Result<Option<BaselineExtents>, FontError> {
+        match self.tables.base {
+            None => Ok(None),
+            Some(base) => base.extents(axis, script_tag, language_tag),
+        }
+    }
+
+    /// Applies the single and ligature substitutions of the enabled features in the `GSUB` table
+    /// to the glyphs in place, so that, for example, "f" followed by "i" becomes the "fi"
+    /// ligature with the `liga` feature.
+    ///
+    /// Returns the cluster map: for each resulting glyph, the index of the first original glyph
+    /// it stands for. Fonts without a `GSUB` table leave the glyphs untouched.
+    pub fn substitute_glyphs(&self, glyph_ids: &mut Vec<u16>, features: &FeatureSettings)
+                             -> Result<Vec<usize>, FontError> {
+        match self.tables.gsub {
+            None => Ok((0..glyph_ids.len()).collect()),
+            Some(gsub) => gsub.substitute(glyph_ids, features),
+        }
+    }
+
+    /// Returns the adjustments that the `GPOS` table makes to the positions of two adjacent
+    /// glyphs through the pair adjustment lookups of the enabled features.
+    ///
+    /// Ordinary kerning is the `kern` feature. Unlike `kerning_for_glyph_pair()`, this finds the
+    /// kerning of modern fonts, which no longer have a `kern` table. No adjustments are returned
+    /// if the font has no `GPOS` table.
+    pub fn pair_adjustment(&self,
+                           first_glyph_id: u16,
+                           second_glyph_id: u16,
+                           features: &FeatureSettings)
+                           -> Result<PairAdjustment, FontError> {
+        match self.tables.gpos {
+            None => Ok(PairAdjustment::default()),
+            Some(gpos) => gpos.pair_adjustment(first_glyph_id, second_glyph_id, features),
+        }
+    }
+
+    /// Returns, for each of the given glyphs, how far to move it so that combining marks sit on
+    /// the glyphs they attach to, according to the mark-to-base and mark-to-mark attachment
+    /// lookups of the enabled features (usually `mark` and `mkmk`) in the `GPOS` table.
+    ///
+    /// The adjustments are relative to the positions the glyphs would have if the pen advanced by
+    /// the advance widths of `metrics_for_glyph()`. Only the placements are adjusted; glyphs that
+    /// aren't marks, and all glyphs in fonts without a `GPOS` table, aren't moved.
+    pub fn mark_placements(&self, glyph_ids: &[u16], features: &FeatureSettings)
+                           -> Result<Vec<PositionAdjustment>, FontError> {
+        let gpos = match self.tables.gpos {
+            None => return Ok(vec![PositionAdjustment::default(); glyph_ids.len()]),
+            Some(gpos) => gpos,
+        };
+        let mut advance_widths = Vec::with_capacity(glyph_ids.len());
+        for &glyph_id in glyph_ids {
+            advance_widths.push(try!(self.advance_width(glyph_id)))
+        }
+        gpos.mark_placements(glyph_ids, &advance_widths, features)
+    }
+
+    /// Returns the grid-fitted advance width of the given glyph in whole pixels at the given size
+    /// in pixels per em, as recorded in the `hdmx` table.
+    ///
+    /// Use this to match the layout of the hinted renderer at small sizes. Returns `None` if the
+    /// font has no device metrics for that size, in which case the advance width from
+    /// `metrics_for_glyph()` should be scaled instead.
+    #[inline]
+    pub fn device_advance_width(&self, glyph_id: u16, ppem: u8) -> Option<u8> {
+        // The records are padded, so the table can't tell where the glyphs end by itself.
+        match self.glyph_count() {
+            Some(glyph_count) if glyph_id < glyph_count => {}
+            _ => return None,
+        }
+        self.tables.hdmx.and_then(|hdmx| hdmx.advance(glyph_id, ppem))
+    }
+
+    /// Returns the advance width of the given glyph in whole pixels at the given size in pixels
+    /// per em, for laying out hinted text.
+    ///
+    /// This is the width from `device_advance_width()` when the font has one for the size, and
+    /// the advance width from `metrics_for_glyph()` scaled and rounded to the nearest pixel
+    /// otherwise.
+    pub fn pixel_advance_width(&self, glyph_id: u16, ppem: u8) -> Result<u16, FontError> {
+        if let Some(advance_width) = self.device_advance_width(glyph_id, ppem) {
+            return Ok(advance_width as u16)
+        }
+
+        let advance_width = try!(self.advance_width(glyph_id)) as u32;
+        let units_per_em = self.units_per_em() as u32;
+        Ok(((advance_width * ppem as u32 + units_per_em / 2) / units_per_em) as u16)
+    }
+
+    /// Returns the size in pixels per em at and above which the advance width of the given glyph
+    /// scales linearly, as recorded in the `LTSH` table.
+    ///
+    /// At or above this size, the scaled advance width from `metrics_for_glyph()` is exact and
+    /// `device_advance_width()` needn't be consulted. Returns `None` if the font has no `LTSH`
+    /// table or the glyph is out of range.
+    #[inline]
+    pub fn linear_threshold(&self, glyph_id: u16) -> Option<u8> {
+        self.tables.ltsh.and_then(|ltsh| ltsh.linear_threshold(glyph_id))
+    }
+
+    /// Returns how glyphs should be rendered at the given size in pixels per em, according to the
+    /// font's `gasp` table.
+    ///
+    /// Fonts without a `gasp` table, or whose table doesn't cover the size, get grayscale
+    /// antialiasing with no grid fitting.
+    pub fn recommended_rendering(&self, ppem: u16) -> RenderingHint {
+        let behavior = match self.tables.gasp.and_then(|gasp| gasp.behavior(ppem)) {
+            None => {
+                return RenderingHint {
+                    gridfit: false,
+                    antialias: AntialiasMode::Grayscale,
+                }
+            }
+            Some(behavior) => behavior,
+        };
+
+        // Symmetric smoothing is ClearType's antialiasing in the direction parallel to the
+        // subpixels, which is what subpixel antialiasing needs.
+        let antialias = if behavior.contains(gasp::SYMMETRIC_SMOOTHING) {
+            AntialiasMode::Subpixel
+        } else if behavior.contains(gasp::DO_GRAY) {
+            AntialiasMode::Grayscale
+        } else {
+            AntialiasMode::None
+        };
+
+        RenderingHint {
+            gridfit: behavior.intersects(gasp::GRIDFIT | gasp::SYMMETRIC_GRIDFIT),
+            antialias: antialias,
+        }
+    }
+
+    /// Returns the string with the given name ID (family name, copyright notice, etc.) from the
+    /// `name` table, in the language with the given ID if available and in English otherwise.
+    ///
+    /// Language IDs are platform-specific; for example, 0x409 is US English on Windows. See the
+    /// OpenType specification for the full list.
+    ///
+    /// Returns `None` if the font has no such name in a decodable encoding.
+    #[inline]
+    pub fn localized_name(&self, name_id: u16, language_id: u16) -> Option<String> {
+        match self.tables.name {
+            None => None,
+            Some(name) => name.get_localized(name_id, language_id),
+        }
+    }
+
+    /// Returns the English name of the font family, such as "Source Sans Pro", for grouping
+    /// fonts in a font picker.
+    ///
+    /// The typographic family name is preferred to the legacy family name, which fonts with more
+    /// than four styles split by weight ("Source Sans Pro Light").
+    pub fn family_name(&self) -> Option<String> {
+        self.localized_name(name::NAME_ID_TYPOGRAPHIC_FAMILY, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+            .or_else(|| self.localized_name(name::NAME_ID_FAMILY, MICROSOFT_LANGUAGE_ID_ENGLISH_US))
+    }
+
+    /// Returns the English name of the style of the font within its family, such as "Bold
+    /// Italic".
+    ///
+    /// Like `family_name()`, this prefers the typographic subfamily name.
+    pub fn subfamily_name(&self) -> Option<String> {
+        self.localized_name(name::NAME_ID_TYPOGRAPHIC_SUBFAMILY,
+                            MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+            .or_else(|| {
+                self.localized_name(name::NAME_ID_SUBFAMILY, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+            })
+    }
+
+    /// Returns the English full name of the font, such as "Source Sans Pro Bold Italic".
+    #[inline]
+    pub fn full_name(&self) -> Option<String> {
+        self.localized_name(name::NAME_ID_FULL_NAME, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+    }
+
+    /// Returns the PostScript name of the font, such as "SourceSansPro-BoldIt", which uniquely
+    /// identifies it.
+    #[inline]
+    pub fn postscript_name(&self) -> Option<String> {
+        self.localized_name(name::NAME_ID_POSTSCRIPT_NAME, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+    }
+
+    /// Returns the named instances ("Regular", "Bold", "Condensed Light", and so on) of a
+    /// variable font, in the order the font lists them.
+    ///
+    /// Returns an empty vector if the font isn't a variable font.
+    pub fn named_instances(&self) -> Vec<NamedInstance> {
+        match self.tables.fvar {
+            None => vec![],
+            Some(fvar) => fvar.named_instances(),
+        }
+    }
+
+    /// Returns the English names of the named instances of a variable font, in the order the font
+    /// lists them.
+    ///
+    /// Instances whose names can't be found in the `name` table are omitted.
+    pub fn instance_names(&self) -> Vec<String> {
+        self.named_instances().iter().filter_map(|instance| {
+            self.localized_name(instance.name_id, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+        }).collect()
+    }
+
+    /// Returns the design axes of the font family that the `STAT` table describes, in the order
+    /// that axis values refer to them by.
+    pub fn design_axes(&self) -> Result<Vec<DesignAxis>, FontError> {
+        match self.tables.stat {
+            None => Ok(vec![]),
+            Some(stat) => stat.design_axes(),
+        }
+    }
+
+    /// Returns the named positions along the design axes that the `STAT` table lists.
+    pub fn axis_values(&self) -> Result<Vec<AxisValue>, FontError> {
+        match self.tables.stat {
+            None => Ok(vec![]),
+            Some(stat) => stat.axis_values(),
         }
     }
 
+    /// Returns the English names of the positions along the design axis with the given tag, such
+    /// as "Light" and "Bold" on `wght`, with their values, in the order the font lists them.
+    ///
+    /// Axis values that combine more than one axis and values whose names can't be found in the
+    /// `name` table are omitted.
+    pub fn axis_value_names(&self, axis_tag: u32) -> Result<Vec<(f32, String)>, FontError> {
+        let axes = try!(self.design_axes());
+        let axis_index = match axes.iter().position(|axis| axis.tag == axis_tag) {
+            None => return Ok(vec![]),
+            Some(axis_index) => axis_index as u16,
+        };
+        Ok(try!(self.axis_values()).iter().filter_map(|value| {
+            if value.locations.len() != 1 || value.locations[0].0 != axis_index {
+                return None
+            }
+            let position = value.locations[0].1;
+            self.localized_name(value.name_id, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+                .map(|name| (position, name))
+        }).collect())
+    }
+
+    /// Returns the English name, such as "Regular", to use for a style whose axis value names
+    /// are all elidable, if the `STAT` table gives one.
+    pub fn elided_fallback_name(&self) -> Option<String> {
+        self.tables.stat.and_then(|stat| stat.elided_fallback_name_id()).and_then(|name_id| {
+            self.localized_name(name_id, MICROSOFT_LANGUAGE_ID_ENGLISH_US)
+        })
+    }
+
     /// Returns the distance from the baseline to the top of the text box in font units.
     ///
     /// The following expression computes the baseline-to-baseline height:
@@ -201,6 +1265,95 @@ impl<'a> Font<'a> {
         self.tables.os_2.typo_line_gap
     }
 
+    /// Returns the height of lowercase letters such as "x" above the baseline in font units.
+    ///
+    /// Fonts with a version 0 or 1 `OS/2` table don't record this.
+    #[inline]
+    pub fn x_height(&self) -> Option<i16> {
+        self.tables.os_2.x_height
+    }
+
+    /// Returns the height of flat capital letters such as "H" above the baseline in font units.
+    ///
+    /// Fonts with a version 0 or 1 `OS/2` table don't record this.
+    #[inline]
+    pub fn cap_height(&self) -> Option<i16> {
+        self.tables.os_2.cap_height
+    }
+
+    /// Returns the thickness of the strikeout stroke in font units.
+    #[inline]
+    pub fn strikeout_size(&self) -> i16 {
+        self.tables.os_2.strikeout_size
+    }
+
+    /// Returns the position of the top of the strikeout stroke above the baseline in font units.
+    #[inline]
+    pub fn strikeout_position(&self) -> i16 {
+        self.tables.os_2.strikeout_position
+    }
+
+    /// Returns the weight of the font, from 1 to 1000, on the same scale as the CSS
+    /// `font-weight` property: 400 is regular and 700 is bold.
+    #[inline]
+    pub fn weight_class(&self) -> u16 {
+        self.tables.os_2.weight_class
+    }
+
+    /// Returns the width of the font, from 1 (ultra-condensed) to 9 (ultra-expanded), where 5 is
+    /// normal. These are the nine keywords of the CSS `font-stretch` property in order.
+    #[inline]
+    pub fn width_class(&self) -> u16 {
+        self.tables.os_2.width_class
+    }
+
+    /// Returns true if the font is italic.
+    #[inline]
+    pub fn is_italic(&self) -> bool {
+        self.tables.os_2.fs_selection.contains(os_2::ITALIC)
+    }
+
+    /// Returns true if the font is oblique: a slanted version of an upright design, rather than
+    /// a true italic.
+    #[inline]
+    pub fn is_oblique(&self) -> bool {
+        self.tables.os_2.fs_selection.contains(os_2::OBLIQUE)
+    }
+
+    /// Returns true if the font is marked bold.
+    ///
+    /// This is the legacy style-linking bit; `weight_class()` is more precise.
+    #[inline]
+    pub fn is_bold(&self) -> bool {
+        self.tables.os_2.fs_selection.contains(os_2::BOLD)
+    }
+
+    /// Returns true if the font asks for `ascender()`, `descender()`, and `line_gap()` to be used
+    /// for line spacing in preference to any platform-specific metrics.
+    #[inline]
+    pub fn use_typo_metrics(&self) -> bool {
+        self.tables.os_2.fs_selection.contains(os_2::USE_TYPO_METRICS)
+    }
+
+    /// Returns the Unicode blocks that the font claims to cover, as a 128-bit field in four
+    /// 32-bit words, lowest bits first.
+    ///
+    /// Bit assignments are listed in the `OS/2` table specification. The field is zero in fonts
+    /// with a version 0 `OS/2` table.
+    #[inline]
+    pub fn unicode_ranges(&self) -> [u32; 4] {
+        self.tables.os_2.unicode_ranges
+    }
+
+    /// Returns the code pages that the font claims to be functional for, as a 64-bit field in two
+    /// 32-bit words, lowest bits first.
+    ///
+    /// Fonts with a version 0 `OS/2` table don't record this.
+    #[inline]
+    pub fn code_page_ranges(&self) -> Option<[u32; 2]> {
+        self.tables.os_2.code_page_ranges
+    }
+
     /// Returns the Control Value Table of the font.
     #[inline]
     pub fn control_value_table(&self) -> &[u8] {
@@ -227,6 +1380,99 @@ impl<'a> Font<'a> {
             Some(prep) => prep.bytes,
         }
     }
+
+    /// Returns the raw contents of the table with the given tag, if the font has one.
+    ///
+    /// This works for any table, including ones that Pathfinder doesn't otherwise understand.
+    /// Tags are big-endian four-character codes, so `DSIG` is `0x44534947`.
+    pub fn table_bytes(&self, tag: u32) -> Option<&'a [u8]> {
+        self.tables.all.iter().find(|&&(table_tag, _)| table_tag == tag).map(|&(_, table)| {
+            table.bytes
+        })
+    }
+
+    /// Returns true if the font carries a digital signature (a `DSIG` table).
+    ///
+    /// The signature is not verified.
+    #[inline]
+    pub fn has_signature(&self) -> bool {
+        self.signature_bytes().is_some()
+    }
+
+    /// Returns the raw contents of the font's `DSIG` table, if present.
+    #[inline]
+    pub fn signature_bytes(&self) -> Option<&'a [u8]> {
+        self.table_bytes(dsig::TAG)
+    }
+}
+
+/// A handle to a font file that may contain more than one font (`.ttc`, `.dfont`).
+///
+/// Single-font files (`.ttf`, `.otf`) are treated as collections containing one font.
+#[derive(Clone, Copy, Debug)]
+pub struct FontCollection<'a> {
+    bytes: &'a [u8],
+    font_count: u32,
+}
+
+impl<'a> FontCollection<'a> {
+    /// Creates a new font collection from a byte buffer containing the contents of a file.
+    ///
+    /// WOFF files are not supported here, since they must be decompressed; use `Font::new()` for
+    /// those.
+    pub fn new<'b>(bytes: &'b [u8]) -> Result<FontCollection<'b>, FontError> {
+        let mut reader = bytes;
+        let magic_number = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let font_count = match magic_number {
+            ttc::MAGIC_NUMBER => try!(ttc::font_count(bytes)),
+            dfont::MAGIC_NUMBER => try!(dfont::font_count(bytes)),
+            magic_number if SFNT_VERSIONS.contains(&magic_number) => 1,
+            _ => return Err(FontError::UnknownFormat),
+        };
+
+        Ok(FontCollection {
+            bytes: bytes,
+            font_count: font_count,
+        })
+    }
+
+    /// Returns the number of fonts in this collection.
+    #[inline]
+    pub fn len(&self) -> u32 {
+        self.font_count
+    }
+
+    /// Returns true if this collection contains no fonts.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.font_count == 0
+    }
+
+    /// Returns the font at the given index.
+    pub fn font(&self, index: u32) -> Result<Font<'a>, FontError> {
+        if index >= self.font_count {
+            return Err(FontError::FontIndexOutOfBounds)
+        }
+
+        let mut reader = self.bytes;
+        match try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) {
+            ttc::MAGIC_NUMBER => Font::from_ttc_index(self.bytes, index),
+            dfont::MAGIC_NUMBER => Font::from_dfont_index(self.bytes, index),
+            _ => Font::from_otf(self.bytes, 0),
+        }
+    }
+
+    /// Returns the set of codepoints that at least one font in this collection maps to a glyph.
+    ///
+    /// This answers the question "can any face in this file render this character?"
+    pub fn combined_coverage(&self) -> Result<CoverageSet, FontError> {
+        let mut coverage = CoverageSet::new();
+        for index in 0..self.font_count {
+            let font = try!(self.font(index));
+            coverage = coverage.union(&try!(font.coverage()))
+        }
+        Ok(coverage)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -244,6 +1490,445 @@ pub struct Point {
     pub kind: PointKind,
 }
 
+/// A 1-bit-per-pixel glyph image, as returned by `Font::glyph_mono_bitmap()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MonoBitmap {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The rows of the image from top to bottom, each padded to a whole number of bytes. The most
+    /// significant bit of each byte is the leftmost pixel, and set bits are inked.
+    pub data: Vec<u8>,
+}
+
+/// A glyph image with one byte of coverage per pixel, as returned by `Font::glyph_gray_bitmap()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GrayBitmap {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The rows of the image from top to bottom, without padding. 0 is uninked and 255 is fully
+    /// inked.
+    pub data: Vec<u8>,
+}
+
+/// The size of a strike of embedded bitmaps, as returned by `Font::embedded_bitmap_strikes()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EmbeddedStrike {
+    /// The size in pixels per em that the images were drawn for.
+    pub ppem: u16,
+    /// The number of bits per pixel: 1 for monochrome strikes, and 2, 4, or 8 for grayscale ones.
+    pub bit_depth: u8,
+}
+
+/// The size of a strike of color bitmaps, as returned by `Font::color_bitmap_strikes()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BitmapStrike {
+    /// The size in pixels per em that the images were drawn for.
+    pub ppem: u16,
+    /// The pixel density in pixels per inch that the images were drawn for.
+    pub ppi: u16,
+}
+
+/// A color glyph image, as returned by `Font::glyph_color_bitmap()`.
+///
+/// Images from `CBDT` tables are always PNGs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorBitmap<'a> {
+    /// The format of `data`.
+    pub format: BitmapFormat,
+    /// The size of the strike in pixels per em. The image needs scaling when rendering at other
+    /// sizes.
+    pub ppem: u16,
+    /// The horizontal position of the left edge of the image relative to the glyph origin, in
+    /// pixels.
+    pub origin_x: i16,
+    /// The vertical position of the bottom edge of the image relative to the glyph origin, in
+    /// pixels.
+    pub origin_y: i16,
+    /// The encoded image, to be handed to an image decoder.
+    pub data: &'a [u8],
+}
+
+/// The encoding of a `ColorBitmap`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BitmapFormat {
+    Png,
+    Jpeg,
+    Tiff,
+}
+
+/// The metrics of a glyph needed to lay it out, as returned by `Font::glyph_layout_info()`.
+#[derive(Clone, Copy, Debug)]
+pub struct GlyphLayoutInfo {
+    /// The advance width in font units.
+    pub advance: u16,
+
+    /// The left side bearing in font units.
+    pub lsb: i16,
+
+    /// The bounding box in font units, or `None` if the glyph has no outline.
+    pub bounds: Option<GlyphBounds>,
+}
+
+/// How glyphs should be rendered at a particular size, as returned by
+/// `Font::recommended_rendering()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RenderingHint {
+    /// Whether the glyphs should be hinted.
+    pub gridfit: bool,
+
+    /// How the glyphs should be antialiased.
+    pub antialias: AntialiasMode,
+}
+
+/// The kind of antialiasing to render glyphs with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AntialiasMode {
+    /// No antialiasing: every pixel is either fully covered or not at all.
+    None,
+    /// Antialiasing using coverage of whole pixels.
+    Grayscale,
+    /// Antialiasing using coverage of the individual red, green, and blue subpixels.
+    Subpixel,
+}
+
+/// A predefined set of design coordinates in a variable font, such as "Bold" or "Condensed
+/// Light".
+#[derive(Clone, PartialEq, Debug)]
+pub struct NamedInstance {
+    /// The ID of the instance's subfamily name in the `name` table.
+    pub name_id: u16,
+
+    /// The coordinate along each of the font's variation axes, in axis order.
+    pub coords: Vec<f32>,
+
+    /// The ID of the instance's PostScript name in the `name` table, if it has one.
+    pub postscript_name_id: Option<u16>,
+}
+
+/// A design axis of a font family, as listed in the `STAT` table.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DesignAxis {
+    /// The tag of the axis, such as `wght`.
+    pub tag: u32,
+
+    /// The ID of the axis's name in the `name` table.
+    pub name_id: u16,
+
+    /// Where names of values on this axis go relative to those of other axes when a style name
+    /// is built from them. Lower values come first.
+    pub ordering: u16,
+}
+
+/// A named position along one or more design axes, as listed in the `STAT` table.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AxisValue {
+    /// The ID of the position's name, such as "Bold", in the `name` table.
+    pub name_id: u16,
+
+    /// Whether the name may be left out of style names and whether it describes an older font.
+    pub flags: AxisValueFlags,
+
+    /// The index of each axis in `Font::design_axes()` and the position along it.
+    pub locations: Vec<(u16, f32)>,
+
+    /// The range of positions along the axis that the name applies to, if it isn't a single
+    /// position.
+    pub range: Option<(f32, f32)>,
+
+    /// The position of the style linked to this one, such as Bold for Regular, if any.
+    pub linked_value: Option<f32>,
+}
+
+bitflags! {
+    /// The flags of an axis value in the `STAT` table.
+    ///
+    /// `AXIS_VALUE_OLDER_SIBLING_FONT_ATTRIBUTE` marks a value that describes a font kept for
+    /// compatibility with older fonts in the family. `AXIS_VALUE_ELIDABLE_NAME` marks a value
+    /// whose name, such as "Regular", is normally left out of style names.
+    pub flags AxisValueFlags: u16 {
+        const AXIS_VALUE_OLDER_SIBLING_FONT_ATTRIBUTE = 1 << 0,
+        const AXIS_VALUE_ELIDABLE_NAME = 1 << 1,
+    }
+}
+
+/// The class of a glyph in the layout tables, as returned by `Font::glyph_class()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GlyphClass {
+    /// A single character, spacing glyph.
+    Base,
+    /// A multiple character, spacing glyph.
+    Ligature,
+    /// A non-spacing combining glyph.
+    Mark,
+    /// A part of a single character, spacing glyph.
+    Component,
+}
+
+/// Upper bounds on the complexity of the font's TrueType outlines, as recorded in the `maxp`
+/// table. Returned by `Font::glyph_limits()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GlyphLimits {
+    /// The most points in a simple glyph.
+    pub max_points: u16,
+    /// The most contours in a simple glyph.
+    pub max_contours: u16,
+    /// The most points in a composite glyph.
+    pub max_composite_points: u16,
+    /// The most contours in a composite glyph.
+    pub max_composite_contours: u16,
+    /// The most components referenced at the top level of a composite glyph.
+    pub max_component_elements: u16,
+    /// How deeply composite glyphs nest. A composite made of simple glyphs has a depth of 1.
+    pub max_component_depth: u16,
+}
+
+/// A constant in the `MATH` table, as passed to `Font::math_constant()`.
+///
+/// The constants are in the order that the table stores them in. Lengths are in font units.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MathConstant {
+    /// The scale of the first level of superscripts and subscripts, in percent.
+    ScriptPercentScaleDown,
+    /// The scale of the second level of superscripts and subscripts, in percent.
+    ScriptScriptPercentScaleDown,
+    /// The minimum height of a subformula for its delimiters to be stretched.
+    DelimitedSubFormulaMinHeight,
+    /// The minimum height of large operators like integrals in display style.
+    DisplayOperatorMinHeight,
+    /// The white space to leave between lines of a multiline formula.
+    MathLeading,
+    /// The height of the math axis, on which fraction bars and operators are centered.
+    AxisHeight,
+    /// The largest height of a base that accents needn't be moved up for.
+    AccentBaseHeight,
+    /// The height of a base above which flattened accents are used.
+    FlattenedAccentBaseHeight,
+    /// The standard drop of a subscript's baseline.
+    SubscriptShiftDown,
+    /// The highest that the top of a subscript may be.
+    SubscriptTopMax,
+    /// The minimum drop of a subscript's baseline below the bottom of its base.
+    SubscriptBaselineDropMin,
+    /// The standard rise of a superscript's baseline.
+    SuperscriptShiftUp,
+    /// The standard rise of a superscript's baseline in cramped style.
+    SuperscriptShiftUpCramped,
+    /// The lowest that the bottom of a superscript may be.
+    SuperscriptBottomMin,
+    /// The maximum drop of a superscript's baseline below the top of its base.
+    SuperscriptBaselineDropMax,
+    /// The minimum gap between a superscript and a subscript.
+    SubSuperscriptGapMin,
+    /// The highest the bottom of a superscript may be raised to make room for a subscript.
+    SuperscriptBottomMaxWithSubscript,
+    /// The space to add after a superscript or subscript.
+    SpaceAfterScript,
+    /// The minimum gap between an upper limit and its operator.
+    UpperLimitGapMin,
+    /// The minimum rise of an upper limit's baseline above its operator.
+    UpperLimitBaselineRiseMin,
+    /// The minimum gap between a lower limit and its operator.
+    LowerLimitGapMin,
+    /// The minimum drop of a lower limit's baseline below its operator.
+    LowerLimitBaselineDropMin,
+    /// The standard rise of the top element of a stack.
+    StackTopShiftUp,
+    /// The standard rise of the top element of a stack in display style.
+    StackTopDisplayStyleShiftUp,
+    /// The standard drop of the bottom element of a stack.
+    StackBottomShiftDown,
+    /// The standard drop of the bottom element of a stack in display style.
+    StackBottomDisplayStyleShiftDown,
+    /// The minimum gap between the elements of a stack.
+    StackGapMin,
+    /// The minimum gap between the elements of a stack in display style.
+    StackDisplayStyleGapMin,
+    /// The standard rise of an element above a stretched glyph.
+    StretchStackTopShiftUp,
+    /// The standard drop of an element below a stretched glyph.
+    StretchStackBottomShiftDown,
+    /// The minimum gap between a stretched glyph and the element above it.
+    StretchStackGapAboveMin,
+    /// The minimum gap between a stretched glyph and the element below it.
+    StretchStackGapBelowMin,
+    /// The standard rise of a numerator.
+    FractionNumeratorShiftUp,
+    /// The standard rise of a numerator in display style.
+    FractionNumeratorDisplayStyleShiftUp,
+    /// The standard drop of a denominator.
+    FractionDenominatorShiftDown,
+    /// The standard drop of a denominator in display style.
+    FractionDenominatorDisplayStyleShiftDown,
+    /// The minimum gap between a numerator and the fraction bar.
+    FractionNumeratorGapMin,
+    /// The minimum gap between a numerator and the fraction bar in display style.
+    FractionNumDisplayStyleGapMin,
+    /// The thickness of the fraction bar.
+    FractionRuleThickness,
+    /// The minimum gap between a denominator and the fraction bar.
+    FractionDenominatorGapMin,
+    /// The minimum gap between a denominator and the fraction bar in display style.
+    FractionDenomDisplayStyleGapMin,
+    /// The horizontal gap between the two halves of a skewed fraction.
+    SkewedFractionHorizontalGap,
+    /// The vertical gap between the two halves of a skewed fraction.
+    SkewedFractionVerticalGap,
+    /// The gap between an overbar and the top of its base.
+    OverbarVerticalGap,
+    /// The thickness of an overbar.
+    OverbarRuleThickness,
+    /// The extra white space to leave above an overbar.
+    OverbarExtraAscender,
+    /// The gap between an underbar and the bottom of its base.
+    UnderbarVerticalGap,
+    /// The thickness of an underbar.
+    UnderbarRuleThickness,
+    /// The extra white space to leave below an underbar.
+    UnderbarExtraDescender,
+    /// The gap between a radical's overbar and the top of its radicand.
+    RadicalVerticalGap,
+    /// The gap between a radical's overbar and the top of its radicand in display style.
+    RadicalDisplayStyleVerticalGap,
+    /// The thickness of a radical's overbar.
+    RadicalRuleThickness,
+    /// The extra white space to leave above a radical's overbar.
+    RadicalExtraAscender,
+    /// The space before the degree of a radical.
+    RadicalKernBeforeDegree,
+    /// The space after the degree of a radical, which is usually negative.
+    RadicalKernAfterDegree,
+    /// The rise of the bottom of a radical's degree, in percent of the radical's height.
+    RadicalDegreeBottomRaisePercent,
+}
+
+/// The direction in which a math glyph is stretched.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MathDirection {
+    /// Taller, as for parentheses and radicals.
+    Vertical,
+    /// Wider, as for over- and underbraces and arrows.
+    Horizontal,
+}
+
+/// A larger version of a math glyph, as returned by `Font::math_glyph_variants()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MathGlyphVariant {
+    /// The glyph ID of the variant.
+    pub glyph_id: u16,
+    /// The size of the variant in the direction it grows in, in font units.
+    pub advance: u16,
+}
+
+/// The parts from which a math glyph of any size is built, as returned by
+/// `Font::math_glyph_assembly()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GlyphAssembly {
+    /// The italics correction of the assembled glyph.
+    pub italics_correction: i16,
+    /// The parts, from bottom to top or left to right.
+    pub parts: Vec<GlyphPart>,
+}
+
+/// One part of a `GlyphAssembly`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GlyphPart {
+    /// The glyph ID of the part.
+    pub glyph_id: u16,
+    /// The length of the straight section at the start of the part that may overlap the part
+    /// before it.
+    pub start_connector_length: u16,
+    /// The length of the straight section at the end of the part that may overlap the part
+    /// after it.
+    pub end_connector_length: u16,
+    /// The size of the part in the direction of growth.
+    pub full_advance: u16,
+    /// Whether the part may be repeated, or left out, to change the size of the assembly.
+    pub is_extender: bool,
+}
+
+/// The direction of text that a set of baselines in the `BASE` table applies to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BaselineAxis {
+    /// Baselines for horizontal text, which are heights above the alphabetic baseline.
+    Horizontal,
+    /// Baselines for vertical text, which are horizontal positions.
+    Vertical,
+}
+
+/// The baselines of a script, as returned by `Font::script_baselines()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptBaselines {
+    /// The tag of the baseline that glyphs of the script sit on.
+    pub default_baseline: u32,
+    /// The tag and the position, in font units, of each baseline.
+    pub baselines: Vec<(u32, i16)>,
+}
+
+impl ScriptBaselines {
+    /// Returns the position of the baseline with the given tag, if there is one.
+    pub fn position(&self, baseline_tag: u32) -> Option<i16> {
+        self.baselines.iter().find(|&&(tag, _)| tag == baseline_tag).map(|&(_, position)| position)
+    }
+}
+
+/// How far the glyphs of a script or language extend, as returned by
+/// `Font::baseline_extents()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BaselineExtents {
+    /// The lowest (or, for vertical text, leftmost) extent, in font units.
+    pub min: Option<i16>,
+    /// The highest (or, for vertical text, rightmost) extent, in font units.
+    pub max: Option<i16>,
+}
+
+bitflags! {
+    /// The style bits of the `head` table, as returned by `Font::mac_style()`.
+    pub flags MacStyle: u16 {
+        const MAC_STYLE_BOLD = 1 << 0,
+        const MAC_STYLE_ITALIC = 1 << 1,
+        const MAC_STYLE_UNDERLINE = 1 << 2,
+        const MAC_STYLE_OUTLINE = 1 << 3,
+        const MAC_STYLE_SHADOW = 1 << 4,
+        const MAC_STYLE_CONDENSED = 1 << 5,
+        const MAC_STYLE_EXTENDED = 1 << 6,
+    }
+}
+
+/// An adjustment to the position of a glyph and to the advance of the pen after it, in font
+/// units, as made by a `GPOS` lookup.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct PositionAdjustment {
+    /// How far to move the glyph horizontally.
+    pub x_placement: i16,
+
+    /// How far to move the glyph vertically.
+    pub y_placement: i16,
+
+    /// How much to add to the horizontal advance.
+    pub x_advance: i16,
+
+    /// How much to add to the vertical advance.
+    pub y_advance: i16,
+}
+
+/// The adjustments to a pair of adjacent glyphs, as returned by `Font::pair_adjustment()`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct PairAdjustment {
+    /// The adjustment to the first glyph. Kerning is usually an adjustment to its advance.
+    pub first: PositionAdjustment,
+
+    /// The adjustment to the second glyph.
+    pub second: PositionAdjustment,
+}
+
 /// The type of point.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum PointKind {
@@ -257,3 +1942,14 @@ pub enum PointKind {
     SecondCubicControl,
 }
 
+fn push_point_to_contours(contours: &mut Vec<Vec<Point>>, point: &Point) {
+    if point.index_in_contour == 0 {
+        contours.push(vec![])
+    }
+    contours.last_mut().unwrap().push(*point)
+}
+
+#[cfg(feature = "chrono")]
+fn unix_timestamp_to_date_time(seconds: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0)
+}
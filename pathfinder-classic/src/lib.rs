@@ -75,7 +75,11 @@
 
 #[macro_use]
 extern crate bitflags;
+#[cfg(feature = "brotli-decompressor")]
+extern crate brotli_decompressor;
 extern crate byteorder;
+#[cfg(feature = "chrono")]
+extern crate chrono;
 extern crate compute_shader;
 extern crate euclid;
 extern crate flate2;
@@ -86,21 +90,30 @@ extern crate num_traits;
 #[cfg(test)]
 #[macro_use]
 extern crate quickcheck;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 #[cfg(test)]
 extern crate test;
+#[cfg(feature = "unicode-normalization")]
+extern crate unicode_normalization;
 
 pub mod atlas;
 pub mod charmap;
 pub mod coverage;
 pub mod error;
+pub mod features;
 pub mod font;
 pub mod hinting;
 pub mod outline;
+pub mod paint;
 pub mod rasterizer;
 pub mod shaper;
+pub mod stroke;
 pub mod typesetter;
 
 mod containers;
+mod encoding;
 mod rect_packer;
 mod tables;
 mod util;
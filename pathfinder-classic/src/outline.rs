@@ -304,7 +304,7 @@ impl GlyphPixelBounds {
 }
 
 /// The boundaries of a glyph in font units.
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
 pub struct GlyphBounds {
     pub left: i32,
     pub bottom: i32,
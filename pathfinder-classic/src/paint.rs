@@ -0,0 +1,158 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Color glyphs described as graphs of paint operations.
+//!
+//! Version 1 of the `COLR` table, used by modern emoji and icon fonts, describes each color glyph
+//! as a tree of fills, glyph outlines that clip them, transforms, and layers. A renderer walks the
+//! tree from the root returned by `Font::paint_graph()`.
+//!
+//! Version 0 color glyphs are simpler: a stack of glyph outlines, each filled with a single color
+//! from a `CPAL` palette, as returned by `Font::color_layers()`.
+//!
+//! Fonts may instead describe color glyphs as SVG documents, returned by `Font::svg_document()`,
+//! which need an SVG renderer.
+
+use error::FontError;
+use euclid::{Matrix2D, Point2D};
+use flate2::FlateReadExt;
+use std::io::Read;
+
+/// The palette index that stands for the current text color rather than a palette entry.
+pub const FOREGROUND_PALETTE_INDEX: u16 = 0xffff;
+
+/// A color from a `CPAL` palette. The components aren't premultiplied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// A layer of a version 0 color glyph: an outline filled with a single color.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorLayer {
+    /// The glyph whose outline is filled.
+    pub glyph_id: u16,
+    /// The index of the fill color in the palette, or `FOREGROUND_PALETTE_INDEX`.
+    pub palette_index: u16,
+    /// The color at `palette_index` in the selected palette, or `None` for the current text
+    /// color.
+    pub color: Option<Color>,
+}
+
+/// A node in the paint graph of a color glyph. Coordinates are in font units.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Paint {
+    /// Paints each layer in turn, from bottom to top.
+    Layers(Vec<Paint>),
+
+    /// Fills with a single color from the palette.
+    Solid {
+        palette_index: u16,
+        /// Multiplied with the alpha of the palette entry.
+        alpha: f32,
+    },
+
+    /// Fills with a gradient along the line from `p0` to `p1`. The gradient is skewed so that
+    /// its lines of constant color are parallel to the line from `p0` to `p2`.
+    LinearGradient {
+        color_line: ColorLine,
+        p0: Point2D<i16>,
+        p1: Point2D<i16>,
+        p2: Point2D<i16>,
+    },
+
+    /// Fills with a gradient between the circle around `c0` with radius `r0` and the circle
+    /// around `c1` with radius `r1`.
+    RadialGradient {
+        color_line: ColorLine,
+        c0: Point2D<i16>,
+        r0: u16,
+        c1: Point2D<i16>,
+        r1: u16,
+    },
+
+    /// Fills the outline of the given glyph with `paint`.
+    Glyph {
+        glyph_id: u16,
+        paint: Box<Paint>,
+    },
+
+    /// Applies `transform` to `paint`.
+    Transform {
+        transform: Matrix2D<f32>,
+        paint: Box<Paint>,
+    },
+}
+
+/// The colors of a gradient and what happens past its ends.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorLine {
+    pub extend: Extend,
+    /// The stops in the order they're stored in the font, which needn't be sorted by offset.
+    pub stops: Vec<ColorStop>,
+}
+
+/// A color at a position along a gradient.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorStop {
+    /// The position along the gradient, where 0 is the start and 1 is the end.
+    pub offset: f32,
+    pub palette_index: u16,
+    /// Multiplied with the alpha of the palette entry.
+    pub alpha: f32,
+}
+
+/// How a gradient continues past its first and last stops.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Extend {
+    /// The colors of the first and last stops continue indefinitely.
+    Pad,
+    /// The gradient repeats.
+    Repeat,
+    /// The gradient repeats, reversing direction each time.
+    Reflect,
+}
+
+/// An SVG document from the `SVG ` table, describing one or more color glyphs.
+///
+/// The element that draws glyph N has the ID `glyphN`. Its coordinates are in font units, with
+/// the y axis pointing down from the baseline.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SvgDocument<'a> {
+    /// The document as stored in the font, which may be gzip-compressed.
+    pub bytes: &'a [u8],
+    /// The first glyph that the document describes.
+    pub start_glyph_id: u16,
+    /// The last glyph that the document describes, inclusive.
+    pub end_glyph_id: u16,
+}
+
+impl<'a> SvgDocument<'a> {
+    /// Returns true if the document is gzip-compressed.
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        self.bytes.starts_with(&[0x1f, 0x8b])
+    }
+
+    /// Returns the text of the document, decompressing it if necessary.
+    pub fn decompress(&self) -> Result<Vec<u8>, FontError> {
+        if !self.is_compressed() {
+            return Ok(self.bytes.to_vec())
+        }
+
+        let mut decoder = try!(self.bytes.gz_decode().map_err(|_| FontError::Failed));
+        let mut document = vec![];
+        try!(decoder.read_to_end(&mut document).map_err(|_| FontError::Failed));
+        Ok(document)
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion of glyph outlines to polygons and of strokes to fills.
+//!
+//! The paths produced here consist solely of closed polygonal contours, which makes them suitable
+//! for handing to a partitioner.
+
+use error::FontError;
+use euclid::Point2D;
+use font::{Font, PointKind};
+
+/// The number of line segments that each curve is approximated with.
+const CURVE_SUBDIVISIONS: u32 = 8;
+
+/// Joins sharper than this ratio of miter length to stroke width are beveled.
+const MITER_LIMIT: f32 = 4.0;
+
+/// How a path should be stroked.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StrokeStyle {
+    /// The width of the stroke, in the units of the path.
+    pub width: f32,
+}
+
+impl StrokeStyle {
+    /// Creates a new stroke style with the given width.
+    #[inline]
+    pub fn new(width: f32) -> StrokeStyle {
+        StrokeStyle {
+            width: width,
+        }
+    }
+}
+
+/// A path made of closed polygonal contours, to be filled with the nonzero winding rule.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Path {
+    /// The contours. The last point of each contour implicitly connects back to the first.
+    pub contours: Vec<Vec<Point2D<f32>>>,
+}
+
+impl Path {
+    /// Creates an empty path.
+    #[inline]
+    pub fn new() -> Path {
+        Path {
+            contours: vec![],
+        }
+    }
+
+    /// Returns the outline of the given glyph in font units, with curves flattened.
+    pub fn from_glyph(font: &Font, glyph_id: u16) -> Result<Path, FontError> {
+        let mut path = Path::new();
+        let mut control_points = vec![];
+        try!(font.for_each_point(glyph_id, |point| {
+            let position = Point2D::new(point.position.x as f32, point.position.y as f32);
+            if point.index_in_contour == 0 {
+                path.contours.push(vec![position]);
+                control_points.clear();
+                return
+            }
+
+            let contour = path.contours.last_mut().unwrap();
+            if point.kind != PointKind::OnCurve {
+                control_points.push(position);
+                return
+            }
+
+            let from = *contour.last().unwrap();
+            match control_points.len() {
+                1 => flatten_quad(contour, &from, &control_points[0], &position),
+                2 => {
+                    flatten_cubic(contour, &from, &control_points[0], &control_points[1], &position)
+                }
+                _ => contour.push(position),
+            }
+            control_points.clear()
+        }));
+
+        // Contours are explicitly closed by repeating their first point, which we don't need.
+        for contour in &mut path.contours {
+            if contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+        }
+        path.contours.retain(|contour| contour.len() > 2);
+        Ok(path)
+    }
+
+    /// Returns true if this path has no contours.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+}
+
+/// Converts the stroke of `path` into a path that can be filled.
+///
+/// Each contour becomes a ring made of two contours offset by half the stroke width on either
+/// side, wound in opposite directions. Joins are mitered, falling back to bevels for sharp
+/// corners. A stroke of zero or negative width produces an empty path.
+pub fn stroke_to_fill(path: &Path, style: &StrokeStyle) -> Path {
+    let mut stroke = Path::new();
+    if !(style.width > 0.0) {
+        return stroke
+    }
+
+    let half_width = style.width * 0.5;
+    for contour in &path.contours {
+        let mut points = contour.clone();
+        points.dedup();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            continue
+        }
+
+        stroke.contours.push(offset_contour(&points, half_width));
+        let mut inner = offset_contour(&points, -half_width);
+        inner.reverse();
+        stroke.contours.push(inner);
+    }
+    stroke
+}
+
+/// Offsets a closed contour with no repeated points by `distance` along the left-hand normals of
+/// its edges.
+fn offset_contour(points: &[Point2D<f32>], distance: f32) -> Vec<Point2D<f32>> {
+    let mut offset = Vec::with_capacity(points.len());
+    for (index, point) in points.iter().enumerate() {
+        let prev = &points[(index + points.len() - 1) % points.len()];
+        let next = &points[(index + 1) % points.len()];
+        let (prev_normal, next_normal) = (left_normal(prev, point), left_normal(point, next));
+
+        let miter = prev_normal + next_normal;
+        let miter_length = (miter.x * miter.x + miter.y * miter.y).sqrt();
+        let cos_half_angle = if miter_length > 0.0 {
+            (miter.x * next_normal.x + miter.y * next_normal.y) / miter_length
+        } else {
+            0.0
+        };
+
+        if cos_half_angle * MITER_LIMIT > 1.0 {
+            offset.push(*point + miter * (distance / (miter_length * cos_half_angle)))
+        } else {
+            offset.push(*point + prev_normal * distance);
+            offset.push(*point + next_normal * distance);
+        }
+    }
+    offset
+}
+
+fn left_normal(from: &Point2D<f32>, to: &Point2D<f32>) -> Point2D<f32> {
+    let vector = *to - *from;
+    let length = (vector.x * vector.x + vector.y * vector.y).sqrt();
+    Point2D::new(-vector.y / length, vector.x / length)
+}
+
+fn flatten_quad(contour: &mut Vec<Point2D<f32>>,
+                p0: &Point2D<f32>,
+                p1: &Point2D<f32>,
+                p2: &Point2D<f32>) {
+    for step in 1..(CURVE_SUBDIVISIONS + 1) {
+        let t = step as f32 / CURVE_SUBDIVISIONS as f32;
+        let u = 1.0 - t;
+        contour.push(*p0 * (u * u) + *p1 * (2.0 * u * t) + *p2 * (t * t))
+    }
+}
+
+fn flatten_cubic(contour: &mut Vec<Point2D<f32>>,
+                 p0: &Point2D<f32>,
+                 p1: &Point2D<f32>,
+                 p2: &Point2D<f32>,
+                 p3: &Point2D<f32>) {
+    for step in 1..(CURVE_SUBDIVISIONS + 1) {
+        let t = step as f32 / CURVE_SUBDIVISIONS as f32;
+        let u = 1.0 - t;
+        contour.push(*p0 * (u * u * u) + *p1 * (3.0 * u * u * t) + *p2 * (3.0 * u * t * t) +
+                     *p3 * (t * t * t))
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{BaselineAxis, BaselineExtents, FontTable, ScriptBaselines};
+use std::mem;
+use tables::layout;
+use util::Jump;
+
+pub const TAG: u32 = ((b'B' as u32) << 24) |
+                      ((b'A' as u32) << 16) |
+                      ((b'S' as u32) << 8)  |
+                       (b'E' as u32);
+
+/// The script whose baselines apply to scripts that the table doesn't list.
+const DEFAULT_SCRIPT_TAG: u32 = ((b'D' as u32) << 24) |
+                                ((b'F' as u32) << 16) |
+                                ((b'L' as u32) << 8)  |
+                                 (b'T' as u32);
+
+/// The size of a `BaseScriptRecord` or `BaseLangSysRecord`: a tag and a 16-bit offset.
+const TAG_RECORD_SIZE: usize = 6;
+
+/// The baseline table, which gives the positions of the baselines of each script so that text
+/// in different scripts and fonts can be lined up.
+#[derive(Clone, Copy)]
+pub struct BaseTable<'a> {
+    table: FontTable<'a>,
+    horiz_axis_offset: u16,
+    vert_axis_offset: u16,
+}
+
+impl<'a> BaseTable<'a> {
+    pub fn new(table: FontTable) -> Result<BaseTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let horiz_axis_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let vert_axis_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        Ok(BaseTable {
+            table: table,
+            horiz_axis_offset: horiz_axis_offset,
+            vert_axis_offset: vert_axis_offset,
+        })
+    }
+
+    /// Returns the baselines of the given script, or of the default script if the table doesn't
+    /// list it. Returns `None` if neither has baselines.
+    pub fn baselines(&self, axis: BaselineAxis, script_tag: u32)
+                     -> Result<Option<ScriptBaselines>, FontError> {
+        let axis_table = match try!(self.axis(axis)) {
+            None => return Ok(None),
+            Some(axis_table) => axis_table,
+        };
+        let script = match try!(script(axis_table, script_tag)) {
+            None => return Ok(None),
+            Some(script) => script,
+        };
+        let base_values_offset =
+            try!((&mut &script[..]).read_u16::<BigEndian>().map_err(FontError::eof));
+        if base_values_offset == 0 {
+            return Ok(None)
+        }
+
+        // The tags of the baselines are listed once for the whole axis.
+        let mut reader = axis_table;
+        let base_tag_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if base_tag_list_offset == 0 {
+            return Ok(None)
+        }
+        let mut tags = try!(layout::at(axis_table, base_tag_list_offset as usize));
+        let tag_count = try!(tags.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let base_values = try!(layout::at(script, base_values_offset as usize));
+        let mut reader = base_values;
+        let default_baseline_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let coord_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if coord_count != tag_count || default_baseline_index >= tag_count {
+            return Err(FontError::Failed)
+        }
+
+        let mut default_baseline = 0;
+        let mut baselines = Vec::with_capacity(coord_count as usize);
+        for baseline_index in 0..coord_count {
+            let tag = try!(tags.read_u32::<BigEndian>().map_err(FontError::eof));
+            let coord_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if baseline_index == default_baseline_index {
+                default_baseline = tag
+            }
+            baselines.push((tag, try!(coordinate(base_values, coord_offset))))
+        }
+
+        Ok(Some(ScriptBaselines {
+            default_baseline: default_baseline,
+            baselines: baselines,
+        }))
+    }
+
+    /// Returns the extents of glyphs in the given language of the given script, falling back to
+    /// the extents for the script as a whole. Scripts that the table doesn't list get the
+    /// extents of the default script.
+    pub fn extents(&self, axis: BaselineAxis, script_tag: u32, language_tag: u32)
+                   -> Result<Option<BaselineExtents>, FontError> {
+        let axis_table = match try!(self.axis(axis)) {
+            None => return Ok(None),
+            Some(axis_table) => axis_table,
+        };
+        let script = match try!(script(axis_table, script_tag)) {
+            None => return Ok(None),
+            Some(script) => script,
+        };
+
+        let mut reader = script;
+        let _base_values_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut min_max_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let lang_sys_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        for _ in 0..lang_sys_count {
+            let tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if tag == language_tag {
+                min_max_offset = offset;
+                break
+            }
+        }
+        if min_max_offset == 0 {
+            return Ok(None)
+        }
+
+        let min_max = try!(layout::at(script, min_max_offset as usize));
+        let mut reader = min_max;
+        let min_coord_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let max_coord_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        Ok(Some(BaselineExtents {
+            min: try!(optional_coordinate(min_max, min_coord_offset)),
+            max: try!(optional_coordinate(min_max, max_coord_offset)),
+        }))
+    }
+
+    fn axis(&self, axis: BaselineAxis) -> Result<Option<&'a [u8]>, FontError> {
+        let offset = match axis {
+            BaselineAxis::Horizontal => self.horiz_axis_offset,
+            BaselineAxis::Vertical => self.vert_axis_offset,
+        };
+        if offset == 0 {
+            return Ok(None)
+        }
+        layout::at(self.table.bytes, offset as usize).map(Some)
+    }
+}
+
+/// Finds the `BaseScript` table of the given script in an axis table, or that of the default
+/// script if the given script isn't listed.
+fn script(axis_table: &[u8], script_tag: u32) -> Result<Option<&[u8]>, FontError> {
+    let mut reader = axis_table;
+    try!(reader.jump(mem::size_of::<u16>()).map_err(FontError::eof));
+    let base_script_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if base_script_list_offset == 0 {
+        return Ok(None)
+    }
+
+    let base_script_list = try!(layout::at(axis_table, base_script_list_offset as usize));
+    let mut reader = base_script_list;
+    let script_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if reader.len() < script_count as usize * TAG_RECORD_SIZE {
+        return Err(FontError::UnexpectedEof)
+    }
+
+    let mut default_script_offset = None;
+    for _ in 0..script_count {
+        let tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if tag == script_tag {
+            return layout::at(base_script_list, offset as usize).map(Some)
+        }
+        if tag == DEFAULT_SCRIPT_TAG {
+            default_script_offset = Some(offset)
+        }
+    }
+
+    match default_script_offset {
+        None => Ok(None),
+        Some(offset) => layout::at(base_script_list, offset as usize).map(Some),
+    }
+}
+
+/// Reads the coordinate of a `BaseCoord` table.
+///
+/// All three formats start with the coordinate. The glyph contour point of format 2 and the
+/// device table of format 3 only refine it for hinted rendering, so they're ignored.
+fn coordinate(table: &[u8], offset: u16) -> Result<i16, FontError> {
+    let mut reader = try!(layout::at(table, offset as usize));
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if format < 1 || format > 3 {
+        return Err(FontError::UnknownFormat)
+    }
+    reader.read_i16::<BigEndian>().map_err(FontError::eof)
+}
+
+fn optional_coordinate(table: &[u8], offset: u16) -> Result<Option<i16>, FontError> {
+    if offset == 0 {
+        return Ok(None)
+    }
+    coordinate(table, offset).map(Some)
+}
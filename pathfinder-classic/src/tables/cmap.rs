@@ -8,13 +8,23 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use byteorder::{BigEndian, ReadBytesExt};
-use charmap::{CodepointRange, GlyphMapping, GlyphRange, MappedGlyphRange};
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
+use charmap::{CodepointMismatch, CodepointRange, CoverageBloom, CoverageSet, DecodedCmap};
+use charmap::{GlyphMapping, MappingReport};
+use charmap::{GlyphRange, MAX_CODEPOINT, MISSING_GLYPH, MappedGlyphRange, ReverseGlyphMapping};
+use charmap::{SubtableComparison, SubtableInfo};
+#[cfg(feature = "unicode-normalization")]
+use charmap::CodepointRanges;
 use error::FontError;
 use font::FontTable;
 use std::cmp;
 use std::mem;
+use std::vec;
 use std::u16;
+use std::u8;
+use std::u32;
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::{self, UnicodeNormalization};
 use util::Jump;
 
 pub const TAG: u32 = ((b'c' as u32) << 24) |
@@ -23,91 +33,1010 @@ pub const TAG: u32 = ((b'c' as u32) << 24) |
                        (b'p' as u32);
 
 const PLATFORM_ID_UNICODE: u16 = 0;
+const PLATFORM_ID_MACINTOSH: u16 = 1;
 const PLATFORM_ID_MICROSOFT: u16 = 3;
 
+const MACINTOSH_ENCODING_ID_ROMAN: u16 = 0;
+
+const UNICODE_ENCODING_ID_FULL_REPERTOIRE: u16 = 4;
+const UNICODE_ENCODING_ID_VARIATION_SEQUENCES: u16 = 5;
+
+const MICROSOFT_ENCODING_ID_SYMBOL: u16 = 0;
 const MICROSOFT_ENCODING_ID_UNICODE_BMP: u16 = 1;
 const MICROSOFT_ENCODING_ID_UNICODE_UCS4: u16 = 10;
 
+const FORMAT_BYTE_ENCODING_TABLE: u16 = 0;
+const FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE: u16 = 2;
 const FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES: u16 = 4;
+const FORMAT_TRIMMED_TABLE_MAPPING: u16 = 6;
+const FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE: u16 = 8;
+const FORMAT_TRIMMED_ARRAY: u16 = 10;
 const FORMAT_SEGMENTED_COVERAGE: u16 = 12;
+const FORMAT_MANY_TO_ONE_RANGE_MAPPINGS: u16 = 13;
+const FORMAT_UNICODE_VARIATION_SEQUENCES: u16 = 14;
 
-const MISSING_GLYPH: u16 = 0;
+/// The size of a mapping in the non-default UVS table of a format 14 subtable: a 24-bit
+/// codepoint followed by a glyph ID.
+const UVS_MAPPING_SIZE: usize = 5;
 
-#[derive(Clone, Copy)]
+/// The UTF-16 surrogate code points, which are reserved and never map to characters.
+const FIRST_SURROGATE: u32 = 0xd800;
+const LAST_SURROGATE: u32 = 0xdfff;
+/// The high surrogates come first, followed by the low ones.
+const FIRST_LOW_SURROGATE: u32 = 0xdc00;
+
+/// Symbol subtables conventionally map the private use codepoints starting here, with the low
+/// byte giving the character's code in the font's own symbol encoding.
+const SYMBOL_PRIVATE_USE_BASE: u32 = 0xf000;
+
+/// The size of the array of subheader keys that begins a format 2 subtable, one per byte value.
+const SUB_HEADER_KEYS_SIZE: usize = 256 * 2;
+
+/// The size of the bitmap in a format 8 subtable, one bit per 16-bit value.
+const IS32_SIZE: usize = 65536 / 8;
+
+#[derive(Clone)]
 pub struct CmapTable<'a> {
     table: FontTable<'a>,
+    /// The subtable to use, if one was chosen explicitly via `with_subtable()`.
+    subtable: Option<SubtableInfo>,
+    /// The indices of the format 4 segments in order of end code, if `with_repaired_segments()`
+    /// found them out of order.
+    segment_order: Option<Vec<u16>>,
+    /// Whether lookups in a symbol subtable fall back to the private use area, as set by
+    /// `with_symbol_remapping()`.
+    symbol_remapping: bool,
 }
 
 impl<'a> CmapTable<'a> {
     pub fn new(table: FontTable) -> CmapTable {
         CmapTable {
             table: table,
+            subtable: None,
+            segment_order: None,
+            symbol_remapping: false,
         }
     }
 
-    pub fn glyph_mapping_for_codepoint_ranges(&self, codepoint_ranges: &[CodepointRange])
-                                              -> Result<GlyphMapping, FontError> {
+    /// Returns the encoding records of every subtable in this character map, in table order.
+    pub fn subtables(&self) -> Result<Vec<SubtableInfo>, FontError> {
         let mut cmap_reader = self.table.bytes;
-
-        // Check version.
         if try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof)) != 0 {
             return Err(FontError::UnsupportedCmapVersion)
         }
 
         let num_tables = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-
-        // Check platform ID and encoding.
-        // TODO(pcwalton): Handle more.
-        let mut table_found = false;
+        let mut subtables = Vec::with_capacity(num_tables as usize);
         for _ in 0..num_tables {
             let platform_id = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
             let encoding_id = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
             let offset = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
-            match (platform_id, encoding_id) {
-                (PLATFORM_ID_UNICODE, _) |
-                (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_BMP) |
-                (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_UCS4) => {
-                    // Move to the mapping table.
-                    cmap_reader = self.table.bytes;
-                    try!(cmap_reader.jump(offset as usize).map_err(FontError::eof));
-                    table_found = true;
-                    break
-                }
-                _ => {}
+
+            // A record pointing outside the table shouldn't hide the others.
+            let subtable = self.table.bytes.get(offset as usize..);
+            let format = subtable.and_then(|mut subtable_reader| {
+                subtable_reader.read_u16::<BigEndian>().ok()
+            });
+            let language = match (subtable, format) {
+                (Some(subtable), Some(format)) => read_subtable_language(subtable, format),
+                _ => None,
+            };
+
+            subtables.push(SubtableInfo {
+                platform_id: platform_id,
+                encoding_id: encoding_id,
+                offset: offset,
+                format: format,
+                language: language,
+            })
+        }
+        Ok(subtables)
+    }
+
+    /// Returns a view of this character map that performs all lookups using the given subtable
+    /// instead of the one chosen by default.
+    pub fn with_subtable(&self, subtable: &SubtableInfo) -> CmapTable<'a> {
+        CmapTable {
+            table: self.table,
+            subtable: Some(*subtable),
+            segment_order: None,
+            symbol_remapping: self.symbol_remapping,
+        }
+    }
+
+    /// Returns a view of this character map that remaps codepoints up to U+00FF into the private
+    /// use area when the subtable in use is a Microsoft Symbol (3, 0) subtable.
+    ///
+    /// Symbol fonts such as Wingdings map their glyphs to U+F020 through U+F0FF, but text written
+    /// for them usually uses the plain ASCII and Latin-1 codepoints, so other engines look up
+    /// U+F000 plus the codepoint when the codepoint itself isn't mapped. Lookups in other kinds
+    /// of subtables are unaffected.
+    pub fn with_symbol_remapping(&self) -> CmapTable<'a> {
+        CmapTable {
+            table: self.table,
+            subtable: self.subtable,
+            segment_order: self.segment_order.clone(),
+            symbol_remapping: true,
+        }
+    }
+
+    /// Returns a view of this character map that performs all lookups using the subtable whose
+    /// platform and encoding IDs come earliest in `preferences`. For example, `[(3, 10), (3, 1)]`
+    /// prefers UCS-4 to the BMP, and `[(3, 0)]` picks the Microsoft Symbol subtable.
+    ///
+    /// If no subtable matches any of the preferences, the view chooses one as usual.
+    pub fn with_preferred_subtable(&self, preferences: &[(u16, u16)])
+                                   -> Result<CmapTable<'a>, FontError> {
+        let subtables = try!(self.subtables());
+        for &(platform_id, encoding_id) in preferences {
+            if let Some(subtable) = subtables.iter().find(|subtable| {
+                subtable.platform_id == platform_id && subtable.encoding_id == encoding_id
+            }) {
+                return Ok(self.with_subtable(subtable))
+            }
+        }
+        Ok(self.clone())
+    }
+
+    /// Returns a view of this character map that performs all lookups using a subtable whose
+    /// language field is `language`. Localized legacy fonts can carry a Macintosh subtable for
+    /// each language.
+    ///
+    /// The language field holds the Macintosh language ID plus one, or 0 for a subtable that
+    /// doesn't depend on the language, as subtables for other platforms never do. Of the matching
+    /// subtables, the view uses the one that would be chosen by default, or else the first. If
+    /// none match, the view chooses a subtable as usual.
+    pub fn with_preferred_language(&self, language: u32) -> Result<CmapTable<'a>, FontError> {
+        let matching: Vec<_> = try!(self.subtables()).into_iter().filter(|subtable| {
+            subtable.language == Some(language)
+        }).collect();
+        match default_subtable(&matching).or_else(|| matching.first().cloned()) {
+            Some(subtable) => Ok(self.with_subtable(&subtable)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// Returns a view of this character map whose lookups cope with format 4 segments that
+    /// aren't sorted by end code, as they are in some broken fonts.
+    ///
+    /// Lookups binary search the segments, so they miss codepoints in segments that are out of
+    /// place. This scans the segments once, and if any are out of order, it allocates an index
+    /// of the segments sorted by end code for later lookups to search instead. Subtables that
+    /// are already sorted, and subtables in other formats, are used as they are.
+    pub fn with_repaired_segments(&self) -> Result<CmapTable<'a>, FontError> {
+        let mut cmap_reader = try!(self.mapping_table());
+        let format = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if format != FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES {
+            return Ok(self.clone())
+        }
+
+        let seg_count = try!(read_segment_mapping_header(&mut cmap_reader));
+        let mut end_codes = Vec::with_capacity(seg_count as usize);
+        for _ in 0..seg_count {
+            end_codes.push(try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof)))
+        }
+        if end_codes.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return Ok(self.clone())
+        }
+
+        let mut segment_order: Vec<u16> = (0..seg_count).collect();
+        segment_order.sort_by_key(|&segment_index| end_codes[segment_index as usize]);
+        Ok(CmapTable {
+            table: self.table,
+            subtable: self.subtable,
+            segment_order: Some(segment_order),
+            symbol_remapping: self.symbol_remapping,
+        })
+    }
+
+    /// Reports every codepoint that the two subtables map to different glyphs.
+    ///
+    /// Codepoints that only one of the subtables is able to represent (those above U+FFFF when
+    /// one of them is in format 4) are not compared, so a BMP subtable and a full-repertoire
+    /// subtable that agree on the BMP compare as identical.
+    pub fn compare_subtables(&self, a: &SubtableInfo, b: &SubtableInfo)
+                             -> Result<SubtableComparison, FontError> {
+        let (a, b) = (self.with_subtable(a), self.with_subtable(b));
+
+        let max_codepoint = cmp::min(try!(a.max_representable_codepoint()),
+                                     try!(b.max_representable_codepoint()));
+        let codepoint_ranges: Vec<_> =
+            try!(a.coverage()).union(&try!(b.coverage()))
+                              .ranges()
+                              .iter()
+                              .filter(|range| range.start <= max_codepoint)
+                              .map(|range| {
+                                  CodepointRange::new(range.start,
+                                                      cmp::min(range.end, max_codepoint))
+                              })
+                              .collect();
+
+        // Both mappings contain exactly one entry per requested codepoint, in the same order.
+        let a_mapping = try!(a.glyph_mapping_for_codepoint_ranges(&codepoint_ranges));
+        let b_mapping = try!(b.glyph_mapping_for_codepoint_ranges(&codepoint_ranges));
+        let mismatches = a_mapping.iter().zip(b_mapping.iter()).filter_map(|(a, b)| {
+            if a.1 == b.1 {
+                None
+            } else {
+                Some(CodepointMismatch {
+                    codepoint: a.0,
+                    glyph_a: a.1,
+                    glyph_b: b.1,
+                })
+            }
+        }).collect();
+
+        Ok(SubtableComparison {
+            mismatches: mismatches,
+        })
+    }
+
+    pub fn glyph_mapping_for_codepoint_ranges(&self, codepoint_ranges: &[CodepointRange])
+                                              -> Result<GlyphMapping, FontError> {
+        self.glyph_mapping_for_codepoint_ranges_using(codepoint_ranges, SegmentSearch::Branchless)
+    }
+
+    /// Like `glyph_mapping_for_codepoint_ranges()`, but also reports which subtable was used and
+    /// which of the codepoints it has no glyph for.
+    pub fn glyph_mapping_with_report(&self, codepoint_ranges: &[CodepointRange])
+                                     -> Result<(GlyphMapping, MappingReport), FontError> {
+        let subtable = try!(self.mapping_subtable());
+        let glyph_mapping = try!(self.glyph_mapping_for_codepoint_ranges(codepoint_ranges));
+
+        let mut unmapped = vec![];
+        for (codepoint, glyph_id) in glyph_mapping.iter() {
+            if glyph_id == MISSING_GLYPH {
+                unmapped.push(CodepointRange::new(codepoint, codepoint))
             }
         }
+        let report = MappingReport {
+            subtable: subtable,
+            unmapped: CoverageSet::from_ranges(unmapped).ranges().to_vec(),
+        };
+        Ok((glyph_mapping, report))
+    }
 
-        if !table_found {
-            return Err(FontError::UnsupportedCmapEncoding)
+    /// Like `glyph_mapping_for_codepoint_ranges()`, but uses the given binary search
+    /// implementation for format 12 subtables. This exists so the two can be compared.
+    pub fn glyph_mapping_for_codepoint_ranges_using(&self,
+                                                    codepoint_ranges: &[CodepointRange],
+                                                    search: SegmentSearch)
+                                                    -> Result<GlyphMapping, FontError> {
+        let subtable = try!(self.mapping_subtable());
+        let cmap_reader = try!(self.subtable_reader(&subtable));
+        if self.remaps_symbols(&subtable) {
+            let mut glyph_mapping = GlyphMapping::new();
+            try!(self.for_each_mapped_range(codepoint_ranges, |range| glyph_mapping.push(range)));
+            return Ok(glyph_mapping)
         }
+        if !is_macintosh_roman_subtable(&subtable) {
+            return self.glyph_mapping_for_codes(cmap_reader, codepoint_ranges, search)
+        }
+
+        // Look up every byte, then translate the requested codepoints into bytes.
+        let byte_ranges = [CodepointRange::new(0, u8::MAX as u32)];
+        let byte_mapping = try!(self.glyph_mapping_for_codes(cmap_reader, &byte_ranges, search));
+        Ok(glyph_mapping_for_codepoints(codepoint_ranges, |codepoint| {
+            macintosh_roman_byte_for_codepoint(codepoint).and_then(|byte| {
+                byte_mapping.glyph_for(byte as u32)
+            }).unwrap_or(MISSING_GLYPH)
+        }))
+    }
 
+    /// Looks up codes in the encoding of the subtable that `cmap_reader` points to.
+    fn glyph_mapping_for_codes(&self,
+                               cmap_reader: &[u8],
+                               codepoint_ranges: &[CodepointRange],
+                               search: SegmentSearch)
+                               -> Result<GlyphMapping, FontError> {
         // Check the mapping table format.
+        let mut format_reader = cmap_reader;
+        let format = try!(format_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // Format 2 codes belong to a legacy multibyte encoding rather than Unicode, so the
+        // surrogate range means nothing to them.
+        if format == FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE {
+            let mut glyph_mapping = GlyphMapping::new();
+            try!(self.visit_codes(cmap_reader, codepoint_ranges, search, &mut |range| {
+                glyph_mapping.push(range)
+            }));
+            return Ok(glyph_mapping)
+        }
+
+        let mut glyph_mapping = GlyphMapping::new();
+        for range in codepoint_ranges {
+            try!(self.visit_codes_outside_surrogates(cmap_reader, *range, search, &mut |range| {
+                glyph_mapping.push(range)
+            }))
+        }
+        Ok(glyph_mapping)
+    }
+
+    /// Looks up a range of codes like `visit_codes()`, except that surrogates aren't characters,
+    /// so they map to the missing glyph no matter what the font says. Everything is visited in
+    /// codepoint order.
+    fn visit_codes_outside_surrogates<V>(&self,
+                                         cmap_reader: &[u8],
+                                         range: CodepointRange,
+                                         search: SegmentSearch,
+                                         visit: &mut V)
+                                         -> Result<(), FontError>
+                                         where V: FnMut(MappedGlyphRange) {
+        if range.start < FIRST_SURROGATE {
+            let end = cmp::min(range.end, FIRST_SURROGATE - 1);
+            try!(self.visit_codes(cmap_reader, &[CodepointRange::new(range.start, end)], search,
+                                  visit))
+        }
+        let surrogates = CodepointRange::new(cmp::max(range.start, FIRST_SURROGATE),
+                                             cmp::min(range.end, LAST_SURROGATE));
+        for codepoint in surrogates.iter() {
+            visit(MappedGlyphRange {
+                codepoint_start: codepoint,
+                glyphs: GlyphRange {
+                    start: MISSING_GLYPH,
+                    end: MISSING_GLYPH,
+                },
+            })
+        }
+        if range.end > LAST_SURROGATE {
+            let start = cmp::max(range.start, LAST_SURROGATE + 1);
+            try!(self.visit_codes(cmap_reader, &[CodepointRange::new(start, range.end)], search,
+                                  visit))
+        }
+        Ok(())
+    }
+
+    /// Looks up the given codepoints and passes each mapped range to `visit` as it's found,
+    /// instead of collecting them into a `GlyphMapping`.
+    ///
+    /// The ranges arrive in the order the codepoints were requested in, including surrogates,
+    /// which map to the missing glyph. Unlike a `GlyphMapping`, nothing is allocated per range,
+    /// so callers can feed large lookups straight into their own structures.
+    pub fn for_each_mapped_range<F>(&self, codepoint_ranges: &[CodepointRange], mut visit: F)
+                                    -> Result<(), FontError>
+                                    where F: FnMut(MappedGlyphRange) {
+        let subtable = try!(self.mapping_subtable());
+        let cmap_reader = try!(self.subtable_reader(&subtable));
+        let search = SegmentSearch::Branchless;
+        if is_macintosh_roman_subtable(&subtable) {
+            let byte_ranges = [CodepointRange::new(0, u8::MAX as u32)];
+            let byte_mapping = try!(self.glyph_mapping_for_codes(cmap_reader,
+                                                                 &byte_ranges,
+                                                                 search));
+            visit_codepoints(codepoint_ranges, |codepoint| {
+                macintosh_roman_byte_for_codepoint(codepoint).and_then(|byte| {
+                    byte_mapping.glyph_for(byte as u32)
+                }).unwrap_or(MISSING_GLYPH)
+            }, &mut visit);
+            return Ok(())
+        }
+
+        let mut format_reader = cmap_reader;
+        let format = try!(format_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if format == FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE {
+            return self.visit_codes(cmap_reader, codepoint_ranges, search, &mut visit)
+        }
+
+        // The low codepoints of symbol subtables may be remapped, so look them all up front.
+        let mut symbol_mapping = None;
+        if self.remaps_symbols(&subtable) {
+            symbol_mapping = Some(try!(self.remapped_symbol_mapping(cmap_reader, search)))
+        }
+
+        for range in codepoint_ranges {
+            let mut range = *range;
+            if let Some(ref symbol_mapping) = symbol_mapping {
+                if range.start <= u8::MAX as u32 {
+                    let end = cmp::min(range.end, u8::MAX as u32);
+                    visit_codepoints(&[CodepointRange::new(range.start, end)], |codepoint| {
+                        symbol_mapping.glyph_for(codepoint).unwrap_or(MISSING_GLYPH)
+                    }, &mut visit);
+                    if range.end <= u8::MAX as u32 {
+                        continue
+                    }
+                    range.start = u8::MAX as u32 + 1
+                }
+            }
+
+            try!(self.visit_codes_outside_surrogates(cmap_reader, range, search, &mut visit))
+        }
+        Ok(())
+    }
+
+    /// Looks up codes in the encoding of the subtable that `cmap_reader` points to, passing each
+    /// mapped range to `visit`. Surrogates are looked up like any other code.
+    fn visit_codes<V>(&self,
+                      mut cmap_reader: &[u8],
+                      codepoint_ranges: &[CodepointRange],
+                      search: SegmentSearch,
+                      visit: &mut V)
+                      -> Result<(), FontError>
+                      where V: FnMut(MappedGlyphRange) {
+        let format = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        match format {
+            FORMAT_BYTE_ENCODING_TABLE | FORMAT_TRIMMED_TABLE_MAPPING | FORMAT_TRIMMED_ARRAY => {
+                let glyph_id_array = try!(GlyphIdArray::read(format, cmap_reader));
+                visit_codepoints(codepoint_ranges,
+                                 |codepoint| glyph_id_array.glyph_for(codepoint),
+                                 visit);
+                Ok(())
+            }
+            FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE => {
+                let table = try!(HighByteMappingTable::read(cmap_reader));
+                visit_codepoints(codepoint_ranges, |codepoint| table.glyph_for(codepoint), visit);
+                Ok(())
+            }
+            FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE => {
+                let table = try!(MixedCoverageTable::read(cmap_reader));
+                let mut result = Ok(());
+                visit_codepoints(codepoint_ranges, |codepoint| {
+                    table.glyph_for(codepoint, search).unwrap_or_else(|error| {
+                        result = Err(error);
+                        MISSING_GLYPH
+                    })
+                }, visit);
+                result
+            }
+            FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES => {
+                self.visit_codepoint_ranges_segment_mapping_format(cmap_reader,
+                                                                   codepoint_ranges,
+                                                                   visit)
+            }
+            FORMAT_SEGMENTED_COVERAGE | FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                self.visit_codepoint_ranges_segmented_coverage(
+                    cmap_reader,
+                    codepoint_ranges,
+                    search,
+                    format == FORMAT_MANY_TO_ONE_RANGE_MAPPINGS,
+                    visit)
+            }
+            _ => Err(FontError::UnsupportedCmapFormat),
+        }
+    }
+
+    /// Returns the glyph that the given character maps to, or `None` if it isn't mapped.
+    ///
+    /// This looks the character up in the subtable directly instead of building a glyph mapping,
+    /// so it's cheap enough for one-off lookups.
+    pub fn glyph_for_char(&self, character: char) -> Result<Option<u16>, FontError> {
+        match try!(self.glyph_for_codepoint(character as u32)) {
+            MISSING_GLYPH => Ok(None),
+            glyph_id => Ok(Some(glyph_id)),
+        }
+    }
+
+    /// Returns the glyph that the given codepoint maps to, consulting every Unicode subtable if
+    /// necessary. This copes with broken fonts that only populate some of their subtables.
+    ///
+    /// The selected subtable (the one `with_subtable()` chose, or else the preferred Unicode
+    /// subtable) is consulted first. If it doesn't map the codepoint, the other Unicode subtables
+    /// (platform 0, and platform 3 with encoding 1 or 10) are tried in the order they appear in
+    /// the table, skipping any in formats we can't read. Returns 0 (the missing glyph) if none of
+    /// them map the codepoint.
+    pub fn glyph_for_codepoint_resilient(&self, codepoint: u32) -> Result<u16, FontError> {
+        let primary_result = self.glyph_for_codepoint(codepoint);
+        match primary_result {
+            Ok(MISSING_GLYPH) | Err(_) => {}
+            Ok(glyph_id) => return Ok(glyph_id),
+        }
+
+        for subtable in try!(self.subtables()) {
+            if !is_unicode_subtable(&subtable) {
+                continue
+            }
+            match self.with_subtable(&subtable).glyph_for_codepoint(codepoint) {
+                Ok(MISSING_GLYPH) | Err(_) => {}
+                Ok(glyph_id) => return Ok(glyph_id),
+            }
+        }
+
+        primary_result
+    }
+
+    fn glyph_for_codepoint(&self, codepoint: u32) -> Result<u16, FontError> {
+        let subtable = try!(self.mapping_subtable());
+        let cmap_reader = try!(self.subtable_reader(&subtable));
+        if self.remaps_symbols(&subtable) && codepoint <= u8::MAX as u32 {
+            return match try!(self.glyph_for_code(cmap_reader, codepoint)) {
+                MISSING_GLYPH => self.glyph_for_code(cmap_reader,
+                                                     SYMBOL_PRIVATE_USE_BASE + codepoint),
+                glyph_id => Ok(glyph_id),
+            }
+        }
+        if !is_macintosh_roman_subtable(&subtable) {
+            return self.glyph_for_code(cmap_reader, codepoint)
+        }
+        match macintosh_roman_byte_for_codepoint(codepoint) {
+            Some(byte) => self.glyph_for_code(cmap_reader, byte as u32),
+            None => Ok(MISSING_GLYPH),
+        }
+    }
+
+    /// Looks up a single code in the encoding of the subtable that `cmap_reader` points to. This
+    /// agrees with `glyph_mapping_for_codes()`, but reads only what the lookup needs.
+    fn glyph_for_code(&self, mut cmap_reader: &[u8], code: u32) -> Result<u16, FontError> {
+        let format = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if format != FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE &&
+                code >= FIRST_SURROGATE && code <= LAST_SURROGATE {
+            return Ok(MISSING_GLYPH)
+        }
+
+        match format {
+            FORMAT_BYTE_ENCODING_TABLE | FORMAT_TRIMMED_TABLE_MAPPING | FORMAT_TRIMMED_ARRAY => {
+                Ok(try!(GlyphIdArray::read(format, cmap_reader)).glyph_for(code))
+            }
+            FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE => {
+                Ok(try!(HighByteMappingTable::read(cmap_reader)).glyph_for(code))
+            }
+            FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE => {
+                let table = try!(MixedCoverageTable::read(cmap_reader));
+                table.glyph_for(code, SegmentSearch::Branchless)
+            }
+            FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES => {
+                let seg_count = try!(read_segment_mapping_header(&mut cmap_reader));
+                if cmap_reader.len() < mem::size_of::<u16>() * (4 * seg_count as usize + 1) {
+                    return Err(FontError::UnexpectedEof)
+                }
+                if code > u16::MAX as u32 {
+                    return Ok(MISSING_GLYPH)
+                }
+
+                // Binary search to find the segment, as `glyph_mapping_for_codes()` does.
+                let (mut low, mut high) = (0, seg_count);
+                while low < high {
+                    let mid = (low + high) / 2;
+                    let segment_index = match self.segment_order {
+                        Some(ref segment_order) => segment_order[mid as usize],
+                        None => mid,
+                    };
+                    let segment = FormatFourSegment::read(cmap_reader, seg_count, segment_index);
+                    if code > segment.end_code {
+                        low = mid + 1
+                    } else if code < segment.start_code {
+                        high = mid
+                    } else {
+                        return Ok(segment.glyph_for(cmap_reader, code))
+                    }
+                }
+                Ok(MISSING_GLYPH)
+            }
+            FORMAT_SEGMENTED_COVERAGE | FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+                let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+                let num_groups = try!(cmap_reader.read_u32::<BigEndian>()
+                                                 .map_err(FontError::eof));
+                let groups_size = num_groups as usize * mem::size_of::<[u32; 3]>();
+                if cmap_reader.len() < groups_size {
+                    return Err(FontError::UnexpectedEof)
+                }
+                match find_segment_branchless(&cmap_reader[..groups_size], code) {
+                    None => Ok(MISSING_GLYPH),
+                    Some(segment) if format == FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                        Ok(segment.start_glyph_id as u16)
+                    }
+                    Some(segment) => {
+                        let glyph_id = try!(segment.start_glyph_id
+                                                   .checked_add(code - segment.start_char_code)
+                                                   .ok_or(FontError::Failed));
+                        Ok(glyph_id as u16)
+                    }
+                }
+            }
+            _ => Err(FontError::UnsupportedCmapFormat),
+        }
+    }
+
+    /// Returns the glyph that `base` followed by the variation selector `selector` maps to, as
+    /// with CJK ideographic variants and the emoji and text presentation selectors.
+    ///
+    /// The font's format 14 subtable lists the sequences whose glyph differs from the one `base`
+    /// maps to on its own. Any other sequence, including every sequence in a font without such a
+    /// subtable, uses the usual glyph for `base`.
+    pub fn glyph_for_variation_sequence(&self, base: u32, selector: u32)
+                                        -> Result<u16, FontError> {
+        match try!(self.non_default_glyph_for_variation_sequence(base, selector)) {
+            Some(glyph_id) => Ok(glyph_id),
+            None => self.glyph_for_codepoint(base),
+        }
+    }
+
+    /// Looks a variation sequence up in the non-default UVS table of the format 14 subtable, if
+    /// there is one.
+    fn non_default_glyph_for_variation_sequence(&self, base: u32, selector: u32)
+                                                -> Result<Option<u16>, FontError> {
+        let subtable = match try!(self.subtables()).into_iter().find(|subtable| {
+            subtable.platform_id == PLATFORM_ID_UNICODE &&
+                subtable.encoding_id == UNICODE_ENCODING_ID_VARIATION_SEQUENCES
+        }) {
+            None => return Ok(None),
+            Some(subtable) => subtable,
+        };
+
+        let mut subtable_reader = self.table.bytes;
+        try!(subtable_reader.jump(subtable.offset as usize).map_err(FontError::eof));
+        let subtable_start = subtable_reader;
+        let format = try!(subtable_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if format != FORMAT_UNICODE_VARIATION_SEQUENCES {
+            return Err(FontError::UnsupportedCmapFormat)
+        }
+        let _length = try!(subtable_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let num_var_selector_records = try!(subtable_reader.read_u32::<BigEndian>()
+                                                           .map_err(FontError::eof));
+
+        // There are only a handful of selector records, so a linear search suffices. The default
+        // UVS table needn't be consulted, since the sequences it lists use the usual glyph.
+        let mut non_default_uvs_offset = 0;
+        for _ in 0..num_var_selector_records {
+            let var_selector = try!(subtable_reader.read_u24::<BigEndian>()
+                                                   .map_err(FontError::eof));
+            let _default_uvs_offset = try!(subtable_reader.read_u32::<BigEndian>()
+                                                          .map_err(FontError::eof));
+            let offset = try!(subtable_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            if var_selector == selector {
+                non_default_uvs_offset = offset;
+                break
+            }
+        }
+        if non_default_uvs_offset == 0 {
+            return Ok(None)
+        }
+
+        let mut uvs_reader = subtable_start;
+        try!(uvs_reader.jump(non_default_uvs_offset as usize).map_err(FontError::eof));
+        let num_uvs_mappings = try!(uvs_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let mappings_size = num_uvs_mappings as usize * UVS_MAPPING_SIZE;
+        if uvs_reader.len() < mappings_size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        // The mappings are sorted by codepoint.
+        let (mut low, mut high) = (0, num_uvs_mappings as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            let mapping = &uvs_reader[(mid * UVS_MAPPING_SIZE)..];
+            let unicode_value = BigEndian::read_u24(mapping);
+            if base < unicode_value {
+                high = mid
+            } else if base > unicode_value {
+                low = mid + 1
+            } else {
+                return Ok(Some(BigEndian::read_u16(&mapping[3..])))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the glyph for each character of `string`, in order, using 0 (the missing glyph)
+    /// for unmapped characters.
+    pub fn glyphs_for_str(&self, string: &str) -> Result<Vec<u16>, FontError> {
+        let codepoint_ranges = CodepointRange::ranges_from_str(string);
+        let glyph_mapping =
+            try!(self.glyph_mapping_for_codepoint_ranges(&codepoint_ranges.ranges));
+        Ok(string.chars().map(|character| {
+            glyph_mapping.glyph_for(character as u32).unwrap_or(MISSING_GLYPH)
+        }).collect())
+    }
+
+    /// Returns the glyphs for `string` after normalizing it to NFC, or to NFKC if
+    /// `compatibility` is set, so that text maps the same way whether it arrived precomposed or
+    /// decomposed.
+    ///
+    /// Characters of the normalized text that the font has no glyph for are decomposed again, and
+    /// if the font maps every character of the decomposition, their glyphs are used instead. So
+    /// the result has one glyph per character of the normalized and decomposed text, not of
+    /// `string` itself.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn glyphs_for_normalized_str(&self, string: &str, compatibility: bool)
+                                     -> Result<Vec<u16>, FontError> {
+        let decompose = |character: char, decomposition: &mut Vec<char>| {
+            decomposition.clear();
+            if compatibility {
+                unicode_normalization::char::decompose_compatible(character, |character| {
+                    decomposition.push(character)
+                })
+            } else {
+                unicode_normalization::char::decompose_canonical(character, |character| {
+                    decomposition.push(character)
+                })
+            }
+        };
+
+        let normalized: Vec<char> = if compatibility {
+            string.nfkc().collect()
+        } else {
+            string.nfc().collect()
+        };
+
+        // Look up the decompositions along with the normalized text, in one batch.
+        let mut chars = normalized.clone();
+        let mut decomposition = vec![];
+        for &character in &normalized {
+            decompose(character, &mut decomposition);
+            chars.extend_from_slice(&decomposition)
+        }
+        let codepoint_ranges: CodepointRanges = chars.into_iter().collect();
+        let glyph_mapping =
+            try!(self.glyph_mapping_for_codepoint_ranges(&codepoint_ranges.ranges));
+        let glyph_for = |character: char| {
+            glyph_mapping.glyph_for(character as u32).unwrap_or(MISSING_GLYPH)
+        };
+
+        let mut glyphs = Vec::with_capacity(normalized.len());
+        for &character in &normalized {
+            let glyph_id = glyph_for(character);
+            if glyph_id == MISSING_GLYPH {
+                decompose(character, &mut decomposition);
+                if decomposition.len() > 1 &&
+                        decomposition.iter().all(|&part| glyph_for(part) != MISSING_GLYPH) {
+                    glyphs.extend(decomposition.iter().map(|&part| glyph_for(part)));
+                    continue
+                }
+            }
+            glyphs.push(glyph_id)
+        }
+        Ok(glyphs)
+    }
+
+    /// Returns the set of all codepoints that this character map maps to a glyph other than the
+    /// missing glyph.
+    pub fn coverage(&self) -> Result<CoverageSet, FontError> {
+        let subtable = try!(self.mapping_subtable());
+        let coverage = try!(self.coverage_of_codes(try!(self.subtable_reader(&subtable))));
+        if self.remaps_symbols(&subtable) {
+            let private_use = CoverageSet::from_ranges(vec![
+                CodepointRange::new(SYMBOL_PRIVATE_USE_BASE,
+                                    SYMBOL_PRIVATE_USE_BASE + u8::MAX as u32),
+            ]);
+            let remapped = coverage.intersection(&private_use).ranges().iter().map(|range| {
+                CodepointRange::new(range.start - SYMBOL_PRIVATE_USE_BASE,
+                                    range.end - SYMBOL_PRIVATE_USE_BASE)
+            }).collect();
+            return Ok(coverage.union(&CoverageSet::from_ranges(remapped)))
+        }
+        if !is_macintosh_roman_subtable(&subtable) {
+            return Ok(coverage)
+        }
+        Ok(CoverageSet::from_ranges(macintosh_roman_ranges_to_unicode(coverage.ranges())))
+    }
+
+    /// Returns the codes that the subtable `cmap_reader` points to maps, in its own encoding.
+    fn coverage_of_codes(&self, mut cmap_reader: &[u8]) -> Result<CoverageSet, FontError> {
         let format = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut ranges = vec![];
         match format {
+            FORMAT_BYTE_ENCODING_TABLE | FORMAT_TRIMMED_TABLE_MAPPING | FORMAT_TRIMMED_ARRAY => {
+                let glyph_id_array = try!(GlyphIdArray::read(format, cmap_reader));
+                for index in 0..glyph_id_array.len() {
+                    if glyph_id_array.glyph_at(index) != MISSING_GLYPH {
+                        push_codepoint(&mut ranges, glyph_id_array.first_code + index as u32)
+                    }
+                }
+            }
+            FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE => {
+                let table = try!(HighByteMappingTable::read(cmap_reader));
+                let mut next_code = 0;
+                while let Some(code) = table.next_mapped_code(next_code) {
+                    push_codepoint(&mut ranges, code);
+                    next_code = code + 1
+                }
+            }
+            FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE => {
+                let table = try!(MixedCoverageTable::read(cmap_reader));
+                for group_index in 0..table.group_count() {
+                    table.push_group_ranges(group_index, &mut ranges)
+                }
+            }
+            FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES => {
+                try!(self.coverage_segment_mapping_format(cmap_reader, &mut ranges))
+            }
+            FORMAT_SEGMENTED_COVERAGE | FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                try!(self.coverage_segmented_coverage(cmap_reader,
+                                                      &mut ranges,
+                                                      format == FORMAT_MANY_TO_ONE_RANGE_MAPPINGS))
+            }
+            _ => return Err(FontError::UnsupportedCmapFormat),
+        }
+
+        Ok(CoverageSet::from_ranges(ranges))
+    }
+
+    /// Returns the same codepoints as `coverage()`, as maximal ranges of consecutive codepoints
+    /// in ascending order, decoding the mapping table as the ranges are requested instead of
+    /// collecting them all first.
+    ///
+    /// The subtable header is checked up front. Since the ranges come straight from the
+    /// subtable, its segments must be sorted, as the specification requires. Format 4 glyph IDs
+    /// that lie outside the table are treated as missing.
+    pub fn coverage_ranges(&self) -> Result<CoverageRanges<'a>, FontError> {
+        let subtable = try!(self.mapping_subtable());
+        let mut cmap_reader = try!(self.subtable_reader(&subtable));
+
+        // A Macintosh Roman subtable maps at most 256 codes, and they don't translate to
+        // ascending codepoints, so it's simplest to translate them all up front. Remapped symbol
+        // subtables are small too.
+        if is_macintosh_roman_subtable(&subtable) || self.remaps_symbols(&subtable) {
+            return Ok(CoverageRanges {
+                subtable: CoverageSubtable::Translated {
+                    ranges: try!(self.coverage()).ranges().to_vec().into_iter(),
+                },
+                pending: None,
+            })
+        }
+
+        let format = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let subtable = match format {
+            FORMAT_BYTE_ENCODING_TABLE | FORMAT_TRIMMED_TABLE_MAPPING | FORMAT_TRIMMED_ARRAY => {
+                CoverageSubtable::GlyphIdArray {
+                    array: try!(GlyphIdArray::read(format, cmap_reader)),
+                    next_index: 0,
+                }
+            }
+            FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE => {
+                CoverageSubtable::HighByteMapping {
+                    table: try!(HighByteMappingTable::read(cmap_reader)),
+                    next_code: 0,
+                }
+            }
+            FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE => {
+                CoverageSubtable::MixedCoverage {
+                    table: try!(MixedCoverageTable::read(cmap_reader)),
+                    next_group_index: 0,
+                    group_ranges: vec![].into_iter(),
+                }
+            }
             FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES => {
-                self.glyph_mapping_for_codepoint_ranges_segment_mapping_format(cmap_reader,
-                                                                               codepoint_ranges)
+                let seg_count = try!(read_segment_mapping_header(&mut cmap_reader));
+                if cmap_reader.len() < mem::size_of::<u16>() * (4 * seg_count as usize + 1) {
+                    return Err(FontError::UnexpectedEof)
+                }
+                CoverageSubtable::SegmentMapping {
+                    arrays: cmap_reader,
+                    seg_count: seg_count,
+                    segment_index: 0,
+                    next_code: 0,
+                }
+            }
+            FORMAT_SEGMENTED_COVERAGE | FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+                let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+                let num_groups = try!(cmap_reader.read_u32::<BigEndian>()
+                                                 .map_err(FontError::eof));
+                let groups_size = num_groups as usize * mem::size_of::<[u32; 3]>();
+                if cmap_reader.len() < groups_size {
+                    return Err(FontError::UnexpectedEof)
+                }
+                CoverageSubtable::SegmentedCoverage {
+                    groups: &cmap_reader[..groups_size],
+                    many_to_one: format == FORMAT_MANY_TO_ONE_RANGE_MAPPINGS,
+                }
+            }
+            _ => return Err(FontError::UnsupportedCmapFormat),
+        };
+
+        Ok(CoverageRanges {
+            subtable: subtable,
+            pending: None,
+        })
+    }
+
+    /// Returns a map from each glyph to the codepoints that this table maps to it.
+    pub fn reverse_glyph_mapping(&self) -> Result<ReverseGlyphMapping, FontError> {
+        let coverage = try!(self.coverage());
+        let glyph_mapping = try!(self.glyph_mapping_for_codepoint_ranges(coverage.ranges()));
+        Ok(glyph_mapping.reverse())
+    }
+
+    /// Decodes every mapping in this table into lookup tables that no longer refer to the font.
+    pub fn decode(&self) -> Result<DecodedCmap, FontError> {
+        let coverage = try!(self.coverage());
+        let glyph_mapping = try!(self.glyph_mapping_for_codepoint_ranges(coverage.ranges()));
+        Ok(DecodedCmap::from_glyph_mapping(&glyph_mapping))
+    }
+
+    /// Decodes every mapping in this table and serializes it for `CompiledCmap` to load.
+    pub fn compile(&self) -> Result<Vec<u8>, FontError> {
+        let coverage = try!(self.coverage());
+        let glyph_mapping = try!(self.glyph_mapping_for_codepoint_ranges(coverage.ranges()));
+        Ok(glyph_mapping.compile())
+    }
+
+    /// Builds a Bloom filter over the codepoints that this table maps to glyphs.
+    #[inline]
+    pub fn build_bloom(&self) -> Result<CoverageBloom, FontError> {
+        Ok(CoverageBloom::from_coverage(&try!(self.coverage())))
+    }
+
+    /// Returns true if lookups in the given subtable fall back to the private use area.
+    #[inline]
+    fn remaps_symbols(&self, subtable: &SubtableInfo) -> bool {
+        self.symbol_remapping && is_symbol_subtable(subtable)
+    }
+
+    /// Maps each code up to 0xff to its glyph in the symbol subtable that `cmap_reader` points
+    /// to, falling back to the glyph for the same code in the private use area.
+    fn remapped_symbol_mapping(&self, cmap_reader: &[u8], search: SegmentSearch)
+                               -> Result<GlyphMapping, FontError> {
+        let low_codes = [CodepointRange::new(0, u8::MAX as u32)];
+        let private_use_codes = [CodepointRange::new(SYMBOL_PRIVATE_USE_BASE,
+                                                     SYMBOL_PRIVATE_USE_BASE + u8::MAX as u32)];
+        let direct = try!(self.glyph_mapping_for_codes(cmap_reader, &low_codes, search));
+        let private_use = try!(self.glyph_mapping_for_codes(cmap_reader,
+                                                            &private_use_codes,
+                                                            search));
+        Ok(glyph_mapping_for_codepoints(&low_codes, |code| {
+            match direct.glyph_for(code) {
+                Some(MISSING_GLYPH) | None => {
+                    private_use.glyph_for(SYMBOL_PRIVATE_USE_BASE + code).unwrap_or(MISSING_GLYPH)
+                }
+                Some(glyph_id) => glyph_id,
+            }
+        }))
+    }
+
+    /// Finds the mapping table we understand and returns a reader positioned at its format field.
+    fn mapping_table(&self) -> Result<&'a [u8], FontError> {
+        let subtable = try!(self.mapping_subtable());
+        self.subtable_reader(&subtable)
+    }
+
+    /// Returns a reader positioned at the format field of the given subtable.
+    fn subtable_reader(&self, subtable: &SubtableInfo) -> Result<&'a [u8], FontError> {
+        let mut mapping_table = self.table.bytes;
+        try!(mapping_table.jump(subtable.offset as usize).map_err(FontError::eof));
+        Ok(mapping_table)
+    }
+
+    /// Finds the subtable to look codepoints up in.
+    ///
+    /// Unless `with_subtable()` chose one, this is the first Unicode subtable that covers the
+    /// full repertoire, or else the first Unicode subtable limited to the BMP. Fonts commonly
+    /// carry both kinds, and only the former can map codepoints above U+FFFF.
+    ///
+    /// Legacy fonts with no Unicode subtable at all fall back to their Macintosh Roman subtable,
+    /// usually in format 0, whose codes are translated to and from Unicode. Failing that, symbol
+    /// fonts use their Microsoft Symbol subtable, which maps private use codepoints (see
+    /// `with_symbol_remapping()`).
+    fn mapping_subtable(&self) -> Result<SubtableInfo, FontError> {
+        if let Some(subtable) = self.subtable {
+            return Ok(subtable)
+        }
+        default_subtable(&try!(self.subtables())).ok_or(FontError::UnsupportedCmapEncoding)
+    }
+
+    /// Returns the largest codepoint that the format of the mapping table can express.
+    fn max_representable_codepoint(&self) -> Result<u32, FontError> {
+        let subtable = try!(self.mapping_subtable());
+        if is_macintosh_roman_subtable(&subtable) {
+            return Ok(u16::MAX as u32)
+        }
+        let mut cmap_reader = try!(self.subtable_reader(&subtable));
+        match try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof)) {
+            FORMAT_BYTE_ENCODING_TABLE => Ok(u8::MAX as u32),
+            FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE |
+            FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES |
+            FORMAT_TRIMMED_TABLE_MAPPING => {
+                Ok(u16::MAX as u32)
             }
-            FORMAT_SEGMENTED_COVERAGE => {
-                self.glyph_mapping_for_codepoint_ranges_segmented_coverage(cmap_reader,
-                                                                           codepoint_ranges)
+            FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE |
+            FORMAT_TRIMMED_ARRAY |
+            FORMAT_SEGMENTED_COVERAGE |
+            FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+                Ok(u32::MAX)
             }
             _ => Err(FontError::UnsupportedCmapFormat),
         }
     }
 
-    fn glyph_mapping_for_codepoint_ranges_segment_mapping_format(
-            &self,
-            mut cmap_reader: &[u8],
-            codepoint_ranges: &[CodepointRange])
-            -> Result<GlyphMapping, FontError> {
+    fn visit_codepoint_ranges_segment_mapping_format<V>(&self,
+                                                        mut cmap_reader: &[u8],
+                                                        codepoint_ranges: &[CodepointRange],
+                                                        visit: &mut V)
+                                                        -> Result<(), FontError>
+                                                        where V: FnMut(MappedGlyphRange) {
         // Read the mapping table header.
-        let _length = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let _language = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let seg_count = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof)) / 2;
-        let _search_range = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let _entry_selector = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let _range_shift = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let seg_count = try!(read_segment_mapping_header(&mut cmap_reader));
 
         // Set up parallel array pointers.
         //
@@ -126,12 +1055,11 @@ impl<'a> CmapTable<'a> {
         try!(glyph_ids.jump(seg_count as usize * mem::size_of::<u16>()).map_err(FontError::eof));
 
         // Now perform the lookups.
-        let mut glyph_mapping = GlyphMapping::new();
         for codepoint_range in codepoint_ranges {
             let mut codepoint_range = *codepoint_range;
             while codepoint_range.end >= codepoint_range.start {
                 if codepoint_range.start > u16::MAX as u32 {
-                    glyph_mapping.push(MappedGlyphRange {
+                    visit(MappedGlyphRange {
                         codepoint_start: codepoint_range.start,
                         glyphs: GlyphRange {
                             start: MISSING_GLYPH,
@@ -150,9 +1078,13 @@ impl<'a> CmapTable<'a> {
                 let mut segment_index = None;
                 while low < high {
                     let mid = (low + high) / 2;
+                    let segment = match self.segment_order {
+                        Some(ref segment_order) => segment_order[mid as usize],
+                        None => mid,
+                    };
 
                     let mut end_code = end_codes;
-                    try!(end_code.jump(mid as usize * 2).map_err(FontError::eof));
+                    try!(end_code.jump(segment as usize * 2).map_err(FontError::eof));
                     let end_code = try!(end_code.read_u16::<BigEndian>().map_err(FontError::eof));
                     if start_codepoint_range > end_code {
                         low = mid + 1;
@@ -160,21 +1092,21 @@ impl<'a> CmapTable<'a> {
                     }
 
                     let mut start_code = start_codes;
-                    try!(start_code.jump(mid as usize * 2).map_err(FontError::eof));
+                    try!(start_code.jump(segment as usize * 2).map_err(FontError::eof));
                     let start_code = try!(start_code.read_u16::<BigEndian>().map_err(FontError::eof));
                     if start_codepoint_range < start_code {
                         high = mid;
                         continue
                     }
 
-                    segment_index = Some(mid);
+                    segment_index = Some(segment);
                     break
                 }
 
                 let segment_index = match segment_index {
                     Some(segment_index) => segment_index,
                     None => {
-                        glyph_mapping.push(MappedGlyphRange {
+                        visit(MappedGlyphRange {
                             codepoint_start: codepoint_range.start,
                             glyphs: GlyphRange {
                                 start: MISSING_GLYPH,
@@ -213,7 +1145,7 @@ impl<'a> CmapTable<'a> {
                     // Microsoft's documentation is contradictory as to whether the code offset or
                     // the actual code is added to the ID delta here. In reality it seems to be the
                     // latter.
-                    glyph_mapping.push(MappedGlyphRange {
+                    visit(MappedGlyphRange {
                         codepoint_start: start_codepoint_range as u32,
                         glyphs: GlyphRange {
                             start: (start_codepoint_range as i16).wrapping_add(id_delta) as u16,
@@ -230,7 +1162,7 @@ impl<'a> CmapTable<'a> {
                                      id_range_offset as usize).map_err(FontError::eof));
                     let mut glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
                     if glyph_id == 0 {
-                        glyph_mapping.push(MappedGlyphRange {
+                        visit(MappedGlyphRange {
                             codepoint_start: start_code as u32 + code_offset as u32,
                             glyphs: GlyphRange {
                                 start: MISSING_GLYPH,
@@ -239,7 +1171,7 @@ impl<'a> CmapTable<'a> {
                         })
                     } else {
                         glyph_id = (glyph_id as i16).wrapping_add(id_delta) as u16;
-                        glyph_mapping.push(MappedGlyphRange {
+                        visit(MappedGlyphRange {
                             codepoint_start: start_code as u32 + code_offset as u32,
                             glyphs: GlyphRange {
                                 start: glyph_id,
@@ -251,54 +1183,46 @@ impl<'a> CmapTable<'a> {
             }
         }
 
-        Ok(glyph_mapping)
+        Ok(())
     }
 
-    fn glyph_mapping_for_codepoint_ranges_segmented_coverage(&self,
-                                                             mut cmap_reader: &[u8],
-                                                             codepoint_ranges: &[CodepointRange])
-                                                             -> Result<GlyphMapping, FontError> {
+    /// Looks up codepoints in a format 12 subtable, or in a format 13 subtable if `many_to_one`
+    /// is set. The groups of the latter map every codepoint in them to the same glyph.
+    fn visit_codepoint_ranges_segmented_coverage<V>(&self,
+                                                    mut cmap_reader: &[u8],
+                                                    codepoint_ranges: &[CodepointRange],
+                                                    search: SegmentSearch,
+                                                    many_to_one: bool,
+                                                    visit: &mut V)
+                                                    -> Result<(), FontError>
+                                                    where V: FnMut(MappedGlyphRange) {
         let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
         let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
         let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
         let num_groups = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
 
+        // Check the bounds of the group array once up front so that the searches can index it
+        // directly.
+        let groups_length = num_groups as usize * mem::size_of::<[u32; 3]>();
+        if cmap_reader.len() < groups_length {
+            return Err(FontError::UnexpectedEof)
+        }
+        let groups = &cmap_reader[..groups_length];
+
         // Now perform the lookups.
-        let mut glyph_mapping = GlyphMapping::new();
         for codepoint_range in codepoint_ranges {
             let mut codepoint_range = *codepoint_range;
             while codepoint_range.end >= codepoint_range.start {
-                // Binary search to find the segment.
-                let (mut low, mut high) = (0, num_groups);
-                let mut found_segment = None;
-                while low < high {
-                    let mid = (low + high) / 2;
-
-                    let mut reader = cmap_reader;
-                    try!(reader.jump(mid as usize * mem::size_of::<[u32; 3]>())
-                               .map_err(FontError::eof));
-                    let segment = Segment {
-                        start_char_code: try!(reader.read_u32::<BigEndian>()
-                                                    .map_err(FontError::eof)),
-                        end_char_code: try!(reader.read_u32::<BigEndian>()
-                                                  .map_err(FontError::eof)),
-                        start_glyph_id: try!(reader.read_u32::<BigEndian>()
-                                                   .map_err(FontError::eof)),
-                    };
-
-                    if codepoint_range.start < segment.start_char_code {
-                        high = mid
-                    } else if codepoint_range.start > segment.end_char_code {
-                        low = mid + 1
-                    } else {
-                        found_segment = Some(segment);
-                        break
+                let found_segment = match search {
+                    SegmentSearch::Branchy => find_segment_branchy(groups, codepoint_range.start),
+                    SegmentSearch::Branchless => {
+                        find_segment_branchless(groups, codepoint_range.start)
                     }
-                }
+                };
 
                 match found_segment {
                     None => {
-                        glyph_mapping.push(MappedGlyphRange {
+                        visit(MappedGlyphRange {
                             codepoint_start: codepoint_range.start,
                             glyphs: GlyphRange {
                                 start: MISSING_GLYPH,
@@ -307,15 +1231,31 @@ impl<'a> CmapTable<'a> {
                         });
                         codepoint_range.start += 1
                     }
+                    Some(segment) if many_to_one => {
+                        // A mapped range implies consecutive glyphs, so each codepoint needs its
+                        // own.
+                        let end = cmp::min(codepoint_range.end, segment.end_char_code);
+                        for codepoint in codepoint_range.start..(end + 1) {
+                            visit(MappedGlyphRange {
+                                codepoint_start: codepoint,
+                                glyphs: GlyphRange {
+                                    start: segment.start_glyph_id as u16,
+                                    end: segment.start_glyph_id as u16,
+                                },
+                            })
+                        }
+                        codepoint_range.start = end + 1
+                    }
                     Some(segment) => {
                         let end = cmp::min(codepoint_range.end, segment.end_char_code);
-                        glyph_mapping.push(MappedGlyphRange {
+                        let glyph_end = try!(segment.start_glyph_id
+                                                    .checked_add(end - segment.start_char_code)
+                                                    .ok_or(FontError::Failed));
+                        visit(MappedGlyphRange {
                             codepoint_start: codepoint_range.start,
                             glyphs: GlyphRange {
-                                start: (segment.start_glyph_id + codepoint_range.start -
-                                        segment.start_char_code) as u16,
-                                end: (segment.start_glyph_id + end - segment.start_char_code) as
-                                    u16,
+                                start: (glyph_end - (end - codepoint_range.start)) as u16,
+                                end: glyph_end as u16,
                             },
                         });
                         codepoint_range.start = end + 1
@@ -324,7 +1264,869 @@ impl<'a> CmapTable<'a> {
             }
         }
 
-        Ok(glyph_mapping)
+        Ok(())
+    }
+
+    fn coverage_segment_mapping_format(&self,
+                                       mut cmap_reader: &[u8],
+                                       ranges: &mut Vec<CodepointRange>)
+                                       -> Result<(), FontError> {
+        // Read the mapping table header.
+        let seg_count = try!(read_segment_mapping_header(&mut cmap_reader));
+
+        // Set up parallel array pointers.
+        let mut end_codes = cmap_reader;
+        let mut start_codes = cmap_reader;
+        try!(start_codes.jump((seg_count as usize + 1) * mem::size_of::<u16>())
+                        .map_err(FontError::eof));
+        let mut id_deltas = start_codes;
+        try!(id_deltas.jump(seg_count as usize * mem::size_of::<u16>()).map_err(FontError::eof));
+        let mut id_range_offsets = id_deltas;
+        try!(id_range_offsets.jump(seg_count as usize * mem::size_of::<u16>())
+                             .map_err(FontError::eof));
+        let id_range_offsets_start = id_range_offsets;
+
+        for segment_index in 0..seg_count {
+            let end_code = try!(end_codes.read_u16::<BigEndian>().map_err(FontError::eof));
+            let start_code = try!(start_codes.read_u16::<BigEndian>().map_err(FontError::eof));
+            let id_delta = try!(id_deltas.read_i16::<BigEndian>().map_err(FontError::eof));
+            let id_range_offset = try!(id_range_offsets.read_u16::<BigEndian>()
+                                                       .map_err(FontError::eof));
+            if start_code > end_code {
+                continue
+            }
+
+            for code in start_code as u32..(end_code as u32 + 1) {
+                // The final segment maps 0xffff to the missing glyph.
+                if code == u16::MAX as u32 {
+                    break
+                }
+
+                let glyph_id = if id_range_offset == 0 {
+                    (code as i16).wrapping_add(id_delta) as u16
+                } else {
+                    let mut reader = id_range_offsets_start;
+                    try!(reader.jump(segment_index as usize * 2 +
+                                     (code - start_code as u32) as usize * 2 +
+                                     id_range_offset as usize).map_err(FontError::eof));
+                    match try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) {
+                        MISSING_GLYPH => MISSING_GLYPH,
+                        glyph_id => (glyph_id as i16).wrapping_add(id_delta) as u16,
+                    }
+                };
+
+                if glyph_id != MISSING_GLYPH {
+                    push_codepoint(ranges, code)
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn coverage_segmented_coverage(&self,
+                                   mut cmap_reader: &[u8],
+                                   ranges: &mut Vec<CodepointRange>,
+                                   many_to_one: bool)
+                                   -> Result<(), FontError> {
+        let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let num_groups = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        for _ in 0..num_groups {
+            let mut start_char_code = try!(cmap_reader.read_u32::<BigEndian>()
+                                                      .map_err(FontError::eof));
+            let end_char_code = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let start_glyph_id = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+            // A group starting at glyph 0 maps its first codepoint to the missing glyph, or all of
+            // them in format 13.
+            if start_glyph_id == MISSING_GLYPH as u32 {
+                if many_to_one {
+                    continue
+                }
+                start_char_code = match start_char_code.checked_add(1) {
+                    Some(start_char_code) => start_char_code,
+                    None => break,
+                }
+            }
+            // Codes past the end of Unicode can't be characters.
+            let end_char_code = cmp::min(end_char_code, MAX_CODEPOINT);
+            if start_char_code <= end_char_code {
+                ranges.push(CodepointRange::new(start_char_code, end_char_code))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the codepoints that a character map covers, as returned by
+/// `CmapTable::coverage_ranges()`.
+pub struct CoverageRanges<'a> {
+    subtable: CoverageSubtable<'a>,
+    /// The range to be returned next, which may still grow if the subtable continues it.
+    pending: Option<CodepointRange>,
+}
+
+enum CoverageSubtable<'a> {
+    GlyphIdArray {
+        array: GlyphIdArray<'a>,
+        next_index: usize,
+    },
+    HighByteMapping {
+        table: HighByteMappingTable<'a>,
+        /// The first code that hasn't been examined yet.
+        next_code: u32,
+    },
+    MixedCoverage {
+        table: MixedCoverageTable<'a>,
+        next_group_index: usize,
+        /// The codepoints of the last group read that haven't been returned yet.
+        group_ranges: vec::IntoIter<CodepointRange>,
+    },
+    SegmentMapping {
+        /// The parallel segment arrays and the glyph ID array, starting with the end codes.
+        arrays: &'a [u8],
+        seg_count: u16,
+        segment_index: u16,
+        /// The first codepoint of the current segment that hasn't been examined yet.
+        next_code: u32,
+    },
+    /// Coverage already translated from another encoding.
+    Translated {
+        ranges: vec::IntoIter<CodepointRange>,
+    },
+    SegmentedCoverage {
+        groups: &'a [u8],
+        /// Whether this is a format 13 subtable, whose groups map to a single glyph.
+        many_to_one: bool,
+    },
+}
+
+impl<'a> Iterator for CoverageRanges<'a> {
+    type Item = CodepointRange;
+
+    fn next(&mut self) -> Option<CodepointRange> {
+        loop {
+            let range = match self.subtable.next_range() {
+                None => return self.pending.take(),
+                Some(range) => range,
+            };
+            match self.pending {
+                Some(ref mut pending) if range.start <= pending.end.saturating_add(1) => {
+                    pending.end = cmp::max(pending.end, range.end);
+                    continue
+                }
+                _ => {}
+            }
+            if let Some(pending) = mem::replace(&mut self.pending, Some(range)) {
+                return Some(pending)
+            }
+        }
+    }
+}
+
+impl<'a> CoverageSubtable<'a> {
+    /// Returns the next run of covered codepoints, which may adjoin the previous one.
+    fn next_range(&mut self) -> Option<CodepointRange> {
+        match *self {
+            CoverageSubtable::GlyphIdArray { ref array, ref mut next_index } => {
+                while *next_index < array.len() && array.glyph_at(*next_index) == MISSING_GLYPH {
+                    *next_index += 1
+                }
+                if *next_index == array.len() {
+                    return None
+                }
+
+                let start = *next_index;
+                while *next_index < array.len() && array.glyph_at(*next_index) != MISSING_GLYPH {
+                    *next_index += 1
+                }
+                Some(CodepointRange::new(array.first_code + start as u32,
+                                         array.first_code + (*next_index - 1) as u32))
+            }
+            CoverageSubtable::HighByteMapping { ref table, ref mut next_code } => {
+                let start = match table.next_mapped_code(*next_code) {
+                    None => return None,
+                    Some(start) => start,
+                };
+                let mut end = start;
+                while end < u16::MAX as u32 && table.glyph_for(end + 1) != MISSING_GLYPH {
+                    end += 1
+                }
+                *next_code = end + 1;
+                Some(CodepointRange::new(start, end))
+            }
+            CoverageSubtable::MixedCoverage {
+                ref table,
+                ref mut next_group_index,
+                ref mut group_ranges,
+            } => {
+                loop {
+                    if let Some(range) = group_ranges.next() {
+                        return Some(range)
+                    }
+                    if *next_group_index == table.group_count() {
+                        return None
+                    }
+                    let mut ranges = vec![];
+                    table.push_group_ranges(*next_group_index, &mut ranges);
+                    *next_group_index += 1;
+                    *group_ranges = ranges.into_iter()
+                }
+            }
+            CoverageSubtable::SegmentMapping {
+                arrays,
+                seg_count,
+                ref mut segment_index,
+                ref mut next_code,
+            } => {
+                while *segment_index < seg_count {
+                    let segment = FormatFourSegment::read(arrays, seg_count, *segment_index);
+
+                    // The final segment maps 0xffff to the missing glyph.
+                    let end_code = cmp::min(segment.end_code, u16::MAX as u32 - 1);
+                    let mut code = cmp::max(*next_code, segment.start_code);
+                    while code <= end_code && segment.glyph_for(arrays, code) == MISSING_GLYPH {
+                        code += 1
+                    }
+                    if code > end_code {
+                        *segment_index += 1;
+                        *next_code = 0;
+                        continue
+                    }
+
+                    let start = code;
+                    while code < end_code && segment.glyph_for(arrays, code + 1) != MISSING_GLYPH {
+                        code += 1
+                    }
+                    *next_code = code + 1;
+                    return Some(CodepointRange::new(start, code))
+                }
+                None
+            }
+            CoverageSubtable::Translated { ref mut ranges } => ranges.next(),
+            CoverageSubtable::SegmentedCoverage { ref mut groups, many_to_one } => {
+                while !groups.is_empty() {
+                    let segment = Segment::read(groups, 0);
+                    *groups = &groups[mem::size_of::<[u32; 3]>()..];
+
+                    // A group starting at glyph 0 maps its first codepoint to the missing glyph,
+                    // or all of them in format 13.
+                    let mut start_char_code = segment.start_char_code;
+                    if segment.start_glyph_id == MISSING_GLYPH as u32 {
+                        if many_to_one {
+                            continue
+                        }
+                        start_char_code = match start_char_code.checked_add(1) {
+                            Some(start_char_code) => start_char_code,
+                            None => break,
+                        }
+                    }
+                    // Codes past the end of Unicode can't be characters.
+                    let end_char_code = cmp::min(segment.end_char_code, MAX_CODEPOINT);
+                    if start_char_code <= end_char_code {
+                        return Some(CodepointRange::new(start_char_code, end_char_code))
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+/// The glyph ID array of a format 0, 6, or 10 subtable, which map a single run of consecutive
+/// codepoints to glyphs. Format 0 stores 256 one-byte glyph IDs, and formats 6 and 10 store any
+/// number of two-byte glyph IDs starting at an arbitrary codepoint, which is limited to the BMP
+/// in format 6.
+#[derive(Clone, Copy)]
+struct GlyphIdArray<'a> {
+    first_code: u32,
+    glyph_ids: &'a [u8],
+    glyph_id_size: usize,
+}
+
+impl<'a> GlyphIdArray<'a> {
+    /// Reads the subtable header just past the format field and checks that the whole array is
+    /// present.
+    fn read(format: u16, mut cmap_reader: &'a [u8]) -> Result<GlyphIdArray<'a>, FontError> {
+        let (first_code, entry_count, glyph_id_size) = if format == FORMAT_TRIMMED_ARRAY {
+            let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let first_code = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let entry_count = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            (first_code, entry_count as usize, mem::size_of::<u16>())
+        } else {
+            let _length = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let _language = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if format == FORMAT_BYTE_ENCODING_TABLE {
+                (0, u8::MAX as usize + 1, mem::size_of::<u8>())
+            } else {
+                let first_code = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let entry_count = try!(cmap_reader.read_u16::<BigEndian>()
+                                                  .map_err(FontError::eof));
+                (first_code as u32, entry_count as usize, mem::size_of::<u16>())
+            }
+        };
+
+        // The codes must fit in 32 bits.
+        if entry_count as u64 + first_code as u64 > u32::MAX as u64 + 1 {
+            return Err(FontError::CmapMalformed)
+        }
+        let glyph_ids_length = entry_count * glyph_id_size;
+        if cmap_reader.len() < glyph_ids_length {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(GlyphIdArray {
+            first_code: first_code,
+            glyph_ids: &cmap_reader[..glyph_ids_length],
+            glyph_id_size: glyph_id_size,
+        })
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.glyph_ids.len() / self.glyph_id_size
+    }
+
+    #[inline]
+    fn glyph_at(&self, index: usize) -> u16 {
+        if self.glyph_id_size == mem::size_of::<u8>() {
+            self.glyph_ids[index] as u16
+        } else {
+            BigEndian::read_u16(&self.glyph_ids[index * self.glyph_id_size..])
+        }
+    }
+
+    /// Returns the glyph for a codepoint, or the missing glyph if the array doesn't cover it.
+    #[inline]
+    fn glyph_for(&self, codepoint: u32) -> u16 {
+        if codepoint < self.first_code || codepoint - self.first_code >= self.len() as u32 {
+            return MISSING_GLYPH
+        }
+        self.glyph_at((codepoint - self.first_code) as usize)
+    }
+}
+
+/// A format 2 subtable, which maps the mixed one- and two-byte codes of legacy CJK encodings
+/// such as Shift-JIS and Big5.
+///
+/// Each byte value has a key selecting a subheader. Bytes whose key is zero stand alone and are
+/// looked up in the first subheader; the others lead two-byte codes, whose second byte is looked
+/// up in the subheader the first byte selects.
+#[derive(Clone, Copy)]
+struct HighByteMappingTable<'a> {
+    /// The rest of the subtable, starting with the subheader keys.
+    data: &'a [u8],
+}
+
+impl<'a> HighByteMappingTable<'a> {
+    /// Reads the subtable header just past the format field and checks that the subheader keys
+    /// are present. Subheaders and glyph IDs that lie outside the table are treated as missing.
+    fn read(mut cmap_reader: &'a [u8]) -> Result<HighByteMappingTable<'a>, FontError> {
+        let _length = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _language = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if cmap_reader.len() < SUB_HEADER_KEYS_SIZE {
+            return Err(FontError::UnexpectedEof)
+        }
+        Ok(HighByteMappingTable {
+            data: cmap_reader,
+        })
+    }
+
+    /// Returns the key of the given byte, which is the offset of its subheader from the end of
+    /// the keys.
+    #[inline]
+    fn sub_header_key(&self, byte: u32) -> u16 {
+        BigEndian::read_u16(&self.data[byte as usize * mem::size_of::<u16>()..])
+    }
+
+    /// Returns the glyph for a one- or two-byte code, or the missing glyph if it isn't mapped.
+    fn glyph_for(&self, code: u32) -> u16 {
+        let (sub_header_key, low_byte) = if code <= u8::MAX as u32 {
+            match self.sub_header_key(code) {
+                0 => (0, code),
+                _ => return MISSING_GLYPH,
+            }
+        } else if code <= u16::MAX as u32 {
+            match self.sub_header_key(code >> 8) {
+                0 => return MISSING_GLYPH,
+                sub_header_key => (sub_header_key, code & 0xff),
+            }
+        } else {
+            return MISSING_GLYPH
+        };
+
+        let sub_header_position = SUB_HEADER_KEYS_SIZE + sub_header_key as usize;
+        let mut sub_header = match self.data.get(sub_header_position..) {
+            Some(sub_header) if sub_header.len() >= mem::size_of::<[u16; 4]>() => sub_header,
+            _ => return MISSING_GLYPH,
+        };
+        let first_code = sub_header.read_u16::<BigEndian>().unwrap() as u32;
+        let entry_count = sub_header.read_u16::<BigEndian>().unwrap() as u32;
+        let id_delta = sub_header.read_i16::<BigEndian>().unwrap();
+        let id_range_offset = sub_header.read_u16::<BigEndian>().unwrap();
+        if low_byte < first_code || low_byte - first_code >= entry_count {
+            return MISSING_GLYPH
+        }
+
+        // The ID range offset counts from its own position in the subheader.
+        let position = sub_header_position + mem::size_of::<[u16; 3]>() +
+            id_range_offset as usize + (low_byte - first_code) as usize * mem::size_of::<u16>();
+        match self.data.get(position..(position + mem::size_of::<u16>())) {
+            None => MISSING_GLYPH,
+            Some(glyph_id) => {
+                match BigEndian::read_u16(glyph_id) {
+                    MISSING_GLYPH => MISSING_GLYPH,
+                    glyph_id => (glyph_id as i16).wrapping_add(id_delta) as u16,
+                }
+            }
+        }
+    }
+
+    /// Returns the first code at or after `code` that maps to a glyph, skipping the two-byte
+    /// codes whose first byte doesn't lead any.
+    fn next_mapped_code(&self, mut code: u32) -> Option<u32> {
+        while code <= u16::MAX as u32 {
+            if code > u8::MAX as u32 && self.sub_header_key(code >> 8) == 0 {
+                code = ((code >> 8) + 1) << 8;
+                continue
+            }
+            if self.glyph_for(code) != MISSING_GLYPH {
+                return Some(code)
+            }
+            code += 1
+        }
+        None
+    }
+}
+
+/// A format 8 subtable, which maps a mix of 16-bit and 32-bit codes.
+///
+/// The 32-bit codes are UTF-16 surrogate pairs packed into one value, high surrogate first. A
+/// bitmap marks the 16-bit values that begin a 32-bit code, and those can't stand alone. The
+/// groups that follow are laid out as in format 12 but are keyed by these codes.
+#[derive(Clone, Copy)]
+struct MixedCoverageTable<'a> {
+    is32: &'a [u8],
+    groups: &'a [u8],
+}
+
+impl<'a> MixedCoverageTable<'a> {
+    /// Reads the subtable header just past the format field and checks that the bitmap and the
+    /// whole group array are present.
+    fn read(mut cmap_reader: &'a [u8]) -> Result<MixedCoverageTable<'a>, FontError> {
+        let _reserved = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _length = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let _language = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if cmap_reader.len() < IS32_SIZE {
+            return Err(FontError::UnexpectedEof)
+        }
+        let is32 = &cmap_reader[..IS32_SIZE];
+        try!(cmap_reader.jump(IS32_SIZE).map_err(FontError::eof));
+
+        let num_groups = try!(cmap_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let groups_size = num_groups as usize * mem::size_of::<[u32; 3]>();
+        if cmap_reader.len() < groups_size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(MixedCoverageTable {
+            is32: is32,
+            groups: &cmap_reader[..groups_size],
+        })
+    }
+
+    #[inline]
+    fn group_count(&self) -> usize {
+        self.groups.len() / mem::size_of::<[u32; 3]>()
+    }
+
+    /// Returns true if the bitmap marks the given 16-bit value as the start of a 32-bit code.
+    #[inline]
+    fn begins_32_bit_code(&self, value: u32) -> bool {
+        self.is32[value as usize / 8] & (0x80 >> (value % 8)) != 0
+    }
+
+    /// Returns the glyph for a codepoint, or the missing glyph if it isn't mapped.
+    ///
+    /// Fails if the codepoint's group runs past the largest glyph ID.
+    fn glyph_for(&self, codepoint: u32, search: SegmentSearch) -> Result<u16, FontError> {
+        let code = if codepoint <= u16::MAX as u32 {
+            if self.begins_32_bit_code(codepoint) {
+                return Ok(MISSING_GLYPH)
+            }
+            codepoint
+        } else if codepoint <= MAX_CODEPOINT {
+            let offset = codepoint - 0x10000;
+            let high_surrogate = FIRST_SURROGATE + (offset >> 10);
+            if !self.begins_32_bit_code(high_surrogate) {
+                return Ok(MISSING_GLYPH)
+            }
+            (high_surrogate << 16) | (FIRST_LOW_SURROGATE + (offset & 0x3ff))
+        } else {
+            return Ok(MISSING_GLYPH)
+        };
+
+        let found_segment = match search {
+            SegmentSearch::Branchy => find_segment_branchy(self.groups, code),
+            SegmentSearch::Branchless => find_segment_branchless(self.groups, code),
+        };
+        match found_segment {
+            None => Ok(MISSING_GLYPH),
+            Some(segment) => {
+                let glyph_id = try!(segment.start_glyph_id
+                                           .checked_add(code - segment.start_char_code)
+                                           .ok_or(FontError::Failed));
+                Ok(glyph_id as u16)
+            }
+        }
+    }
+
+    /// Appends the codepoints that a group maps to glyphs, in ascending order.
+    fn push_group_ranges(&self, group_index: usize, ranges: &mut Vec<CodepointRange>) {
+        let segment = Segment::read(self.groups, group_index);
+
+        // A group starting at glyph 0 maps its first code to the missing glyph.
+        let mut start = segment.start_char_code;
+        if segment.start_glyph_id == MISSING_GLYPH as u32 {
+            start = match start.checked_add(1) {
+                Some(start) => start,
+                None => return,
+            }
+        }
+
+        for code in start..(cmp::min(segment.end_char_code, u16::MAX as u32) + 1) {
+            if (code < FIRST_SURROGATE || code > LAST_SURROGATE) &&
+                    !self.begins_32_bit_code(code) {
+                push_codepoint(ranges, code)
+            }
+        }
+
+        // Decode the 32-bit codes one high surrogate at a time.
+        let start = cmp::max(start, u16::MAX as u32 + 1);
+        if start > segment.end_char_code {
+            return
+        }
+        for high_surrogate in (start >> 16)..((segment.end_char_code >> 16) + 1) {
+            if high_surrogate < FIRST_SURROGATE || high_surrogate >= FIRST_LOW_SURROGATE ||
+                    !self.begins_32_bit_code(high_surrogate) {
+                continue
+            }
+            let low_start = cmp::max(start, (high_surrogate << 16) | FIRST_LOW_SURROGATE);
+            let low_end = cmp::min(segment.end_char_code, (high_surrogate << 16) | LAST_SURROGATE);
+            if low_start > low_end {
+                continue
+            }
+            let first_codepoint = 0x10000 + ((high_surrogate - FIRST_SURROGATE) << 10);
+            push_range(ranges,
+                       CodepointRange::new(first_codepoint + (low_start & 0xffff) -
+                                           FIRST_LOW_SURROGATE,
+                                           first_codepoint + (low_end & 0xffff) -
+                                           FIRST_LOW_SURROGATE))
+        }
+    }
+}
+
+/// Looks up codepoints one at a time with `glyph_for`, merging runs of codepoints that map to
+/// consecutive glyphs into one range. This suits the formats that store a glyph per codepoint.
+fn glyph_mapping_for_codepoints<F>(codepoint_ranges: &[CodepointRange], glyph_for: F)
+                                   -> GlyphMapping
+                                   where F: FnMut(u32) -> u16 {
+    let mut glyph_mapping = GlyphMapping::new();
+    visit_codepoints(codepoint_ranges, glyph_for, &mut |range| glyph_mapping.push(range));
+    glyph_mapping
+}
+
+/// Like `glyph_mapping_for_codepoints()`, but passes each range to `visit` instead.
+fn visit_codepoints<F, V>(codepoint_ranges: &[CodepointRange], mut glyph_for: F, visit: &mut V)
+                          where F: FnMut(u32) -> u16, V: FnMut(MappedGlyphRange) {
+    for codepoint_range in codepoint_ranges {
+        let mut pending: Option<MappedGlyphRange> = None;
+        for codepoint in codepoint_range.iter() {
+            let glyph_id = glyph_for(codepoint);
+            if let Some(ref mut pending) = pending {
+                if glyph_id != MISSING_GLYPH && pending.glyphs.start != MISSING_GLYPH &&
+                        pending.glyphs.end.checked_add(1) == Some(glyph_id) {
+                    pending.glyphs.end = glyph_id;
+                    continue
+                }
+            }
+
+            if let Some(pending) = pending.take() {
+                visit(pending)
+            }
+            pending = Some(MappedGlyphRange {
+                codepoint_start: codepoint,
+                glyphs: GlyphRange {
+                    start: glyph_id,
+                    end: glyph_id,
+                },
+            })
+        }
+
+        if let Some(pending) = pending {
+            visit(pending)
+        }
+    }
+}
+
+/// A segment of a format 4 mapping table, read from the parallel segment arrays.
+#[derive(Clone, Copy)]
+struct FormatFourSegment {
+    start_code: u32,
+    end_code: u32,
+    id_delta: i16,
+    id_range_offset: u16,
+    /// The offset of this segment's entry in the ID range offset array, in bytes.
+    id_range_offset_position: usize,
+}
+
+impl FormatFourSegment {
+    /// Reads a segment from arrays whose length has already been checked.
+    fn read(arrays: &[u8], seg_count: u16, index: u16) -> FormatFourSegment {
+        // The end codes are followed by a reserved pad before the other three arrays.
+        let position = |array_index: usize| {
+            let pad = if array_index == 0 { 0 } else { 1 };
+            mem::size_of::<u16>() * (array_index * seg_count as usize + pad + index as usize)
+        };
+        FormatFourSegment {
+            end_code: BigEndian::read_u16(&arrays[position(0)..]) as u32,
+            start_code: BigEndian::read_u16(&arrays[position(1)..]) as u32,
+            id_delta: BigEndian::read_i16(&arrays[position(2)..]),
+            id_range_offset: BigEndian::read_u16(&arrays[position(3)..]),
+            id_range_offset_position: position(3),
+        }
+    }
+
+    /// Returns the glyph for a codepoint within this segment, or the missing glyph if the glyph
+    /// ID array doesn't extend that far.
+    fn glyph_for(&self, arrays: &[u8], code: u32) -> u16 {
+        if self.id_range_offset == 0 {
+            return (code as i16).wrapping_add(self.id_delta) as u16
+        }
+
+        let position = self.id_range_offset_position + self.id_range_offset as usize +
+            (code - self.start_code) as usize * mem::size_of::<u16>();
+        match arrays.get(position..(position + mem::size_of::<u16>())) {
+            None => MISSING_GLYPH,
+            Some(glyph_id) => {
+                match BigEndian::read_u16(glyph_id) {
+                    MISSING_GLYPH => MISSING_GLYPH,
+                    glyph_id => (glyph_id as i16).wrapping_add(self.id_delta) as u16,
+                }
+            }
+        }
+    }
+}
+
+/// Chooses the subtable to look codepoints up in by default, as described in
+/// `CmapTable::mapping_subtable()`.
+fn default_subtable(subtables: &[SubtableInfo]) -> Option<SubtableInfo> {
+    // Check platform ID and encoding.
+    // TODO(pcwalton): Handle more.
+    let mut best_subtable: Option<SubtableInfo> = None;
+    let mut symbol_subtable: Option<SubtableInfo> = None;
+    let mut macintosh_roman_subtable: Option<SubtableInfo> = None;
+    for subtable in subtables {
+        if !is_unicode_subtable(subtable) {
+            if is_symbol_subtable(subtable) && symbol_subtable.is_none() {
+                symbol_subtable = Some(*subtable)
+            }
+            if is_macintosh_roman_subtable(subtable) && macintosh_roman_subtable.is_none() {
+                macintosh_roman_subtable = Some(*subtable)
+            }
+            continue
+        }
+        if is_full_repertoire_subtable(subtable) {
+            best_subtable = Some(*subtable);
+            break
+        }
+        if best_subtable.is_none() {
+            best_subtable = Some(*subtable)
+        }
+    }
+    best_subtable.or(macintosh_roman_subtable).or(symbol_subtable)
+}
+
+/// Reads the language field of a subtable, given a reader positioned at its format field. The
+/// field is 16 bits wide in the older formats and 32 bits wide in the newer ones, and format 14
+/// lacks it.
+fn read_subtable_language(mut subtable_reader: &[u8], format: u16) -> Option<u32> {
+    match format {
+        FORMAT_BYTE_ENCODING_TABLE |
+        FORMAT_HIGH_BYTE_MAPPING_THROUGH_TABLE |
+        FORMAT_SEGMENT_MAPPING_TO_DELTA_VALUES |
+        FORMAT_TRIMMED_TABLE_MAPPING => {
+            subtable_reader.jump(mem::size_of::<[u16; 2]>()).ok().and_then(|_| {
+                subtable_reader.read_u16::<BigEndian>().ok().map(|language| language as u32)
+            })
+        }
+        FORMAT_MIXED_16_BIT_AND_32_BIT_COVERAGE |
+        FORMAT_TRIMMED_ARRAY |
+        FORMAT_SEGMENTED_COVERAGE |
+        FORMAT_MANY_TO_ONE_RANGE_MAPPINGS => {
+            subtable_reader.jump(mem::size_of::<[u16; 2]>() + mem::size_of::<u32>()).ok()
+                           .and_then(|_| subtable_reader.read_u32::<BigEndian>().ok())
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the subtable maps Unicode codepoints, as opposed to a legacy encoding or
+/// variation sequences.
+fn is_unicode_subtable(subtable: &SubtableInfo) -> bool {
+    match (subtable.platform_id, subtable.encoding_id) {
+        (PLATFORM_ID_UNICODE, UNICODE_ENCODING_ID_VARIATION_SEQUENCES) => false,
+        (PLATFORM_ID_UNICODE, _) |
+        (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_BMP) |
+        (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_UCS4) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if the subtable is a Unicode subtable that can map codepoints outside the BMP.
+fn is_full_repertoire_subtable(subtable: &SubtableInfo) -> bool {
+    match (subtable.platform_id, subtable.encoding_id) {
+        (PLATFORM_ID_UNICODE, UNICODE_ENCODING_ID_FULL_REPERTOIRE) |
+        (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_UCS4) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if the subtable is a Microsoft Symbol subtable.
+fn is_symbol_subtable(subtable: &SubtableInfo) -> bool {
+    subtable.platform_id == PLATFORM_ID_MICROSOFT &&
+        subtable.encoding_id == MICROSOFT_ENCODING_ID_SYMBOL
+}
+
+/// Returns true if the subtable maps Macintosh Roman codes.
+fn is_macintosh_roman_subtable(subtable: &SubtableInfo) -> bool {
+    subtable.platform_id == PLATFORM_ID_MACINTOSH &&
+        subtable.encoding_id == MACINTOSH_ENCODING_ID_ROMAN
+}
+
+/// Returns the Unicode codepoint for a Macintosh Roman code.
+fn macintosh_roman_byte_to_codepoint(byte: u8) -> u32 {
+    if byte < 0x80 {
+        byte as u32
+    } else {
+        MACINTOSH_ROMAN_HIGH_HALF[(byte - 0x80) as usize] as u32
+    }
+}
+
+/// Returns the Macintosh Roman code for a Unicode codepoint, if it has one.
+fn macintosh_roman_byte_for_codepoint(codepoint: u32) -> Option<u8> {
+    if codepoint < 0x80 {
+        return Some(codepoint as u8)
+    }
+    MACINTOSH_ROMAN_HIGH_HALF.iter().position(|&other| other as u32 == codepoint).map(|index| {
+        (index + 0x80) as u8
+    })
+}
+
+/// Translates ranges of Macintosh Roman codes into unsorted ranges of Unicode codepoints.
+fn macintosh_roman_ranges_to_unicode(ranges: &[CodepointRange]) -> Vec<CodepointRange> {
+    let mut unicode_ranges = vec![];
+    for range in ranges {
+        for code in range.start..(cmp::min(range.end, u8::MAX as u32) + 1) {
+            let codepoint = macintosh_roman_byte_to_codepoint(code as u8);
+            unicode_ranges.push(CodepointRange::new(codepoint, codepoint))
+        }
+    }
+    unicode_ranges
+}
+
+/// The Unicode codepoints of the Macintosh Roman codes 0x80 through 0xff. The lower half matches
+/// ASCII.
+static MACINTOSH_ROMAN_HIGH_HALF: [u16; 128] = [
+    0x00c4, 0x00c5, 0x00c7, 0x00c9, 0x00d1, 0x00d6, 0x00dc, 0x00e1,
+    0x00e0, 0x00e2, 0x00e4, 0x00e3, 0x00e5, 0x00e7, 0x00e9, 0x00e8,
+    0x00ea, 0x00eb, 0x00ed, 0x00ec, 0x00ee, 0x00ef, 0x00f1, 0x00f3,
+    0x00f2, 0x00f4, 0x00f6, 0x00f5, 0x00fa, 0x00f9, 0x00fb, 0x00fc,
+    0x2020, 0x00b0, 0x00a2, 0x00a3, 0x00a7, 0x2022, 0x00b6, 0x00df,
+    0x00ae, 0x00a9, 0x2122, 0x00b4, 0x00a8, 0x2260, 0x00c6, 0x00d8,
+    0x221e, 0x00b1, 0x2264, 0x2265, 0x00a5, 0x00b5, 0x2202, 0x2211,
+    0x220f, 0x03c0, 0x222b, 0x00aa, 0x00ba, 0x03a9, 0x00e6, 0x00f8,
+    0x00bf, 0x00a1, 0x00ac, 0x221a, 0x0192, 0x2248, 0x2206, 0x00ab,
+    0x00bb, 0x2026, 0x00a0, 0x00c0, 0x00c3, 0x00d5, 0x0152, 0x0153,
+    0x2013, 0x2014, 0x201c, 0x201d, 0x2018, 0x2019, 0x00f7, 0x25ca,
+    0x00ff, 0x0178, 0x2044, 0x20ac, 0x2039, 0x203a, 0xfb01, 0xfb02,
+    0x2021, 0x00b7, 0x201a, 0x201e, 0x2030, 0x00c2, 0x00ca, 0x00c1,
+    0x00cb, 0x00c8, 0x00cd, 0x00ce, 0x00cf, 0x00cc, 0x00d3, 0x00d4,
+    0xf8ff, 0x00d2, 0x00da, 0x00db, 0x00d9, 0x0131, 0x02c6, 0x02dc,
+    0x00af, 0x02d8, 0x02d9, 0x02da, 0x00b8, 0x02dd, 0x02db, 0x02c7,
+];
+
+/// Appends a single codepoint to a list of ranges, extending the last range if possible.
+fn push_codepoint(ranges: &mut Vec<CodepointRange>, codepoint: u32) {
+    push_range(ranges, CodepointRange::new(codepoint, codepoint))
+}
+
+/// Appends a range that starts past the end of the last one, extending the last range if the two
+/// adjoin.
+fn push_range(ranges: &mut Vec<CodepointRange>, range: CodepointRange) {
+    if let Some(last) = ranges.last_mut() {
+        if last.end + 1 == range.start {
+            last.end = range.end;
+            return
+        }
+    }
+    ranges.push(range)
+}
+
+/// The binary search used to find the format 12 group containing a codepoint.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SegmentSearch {
+    /// A conventional binary search that stops as soon as it finds the group.
+    Branchy,
+    /// A binary search that always runs for `log2(group count)` iterations but whose loop body
+    /// compiles to a conditional move, avoiding branch mispredictions on large tables.
+    Branchless,
+}
+
+/// Finds the group containing `codepoint` in a bounds-checked array of format 12 groups.
+fn find_segment_branchy(groups: &[u8], codepoint: u32) -> Option<Segment> {
+    let (mut low, mut high) = (0, groups.len() / mem::size_of::<[u32; 3]>());
+    while low < high {
+        let mid = (low + high) / 2;
+        let segment = Segment::read(groups, mid);
+        if codepoint < segment.start_char_code {
+            high = mid
+        } else if codepoint > segment.end_char_code {
+            low = mid + 1
+        } else {
+            return Some(segment)
+        }
+    }
+    None
+}
+
+/// Finds the group containing `codepoint` in a bounds-checked array of format 12 groups.
+fn find_segment_branchless(groups: &[u8], codepoint: u32) -> Option<Segment> {
+    let mut size = groups.len() / mem::size_of::<[u32; 3]>();
+    if size == 0 {
+        return None
+    }
+
+    // Find the last group starting at or before the codepoint.
+    let mut base = 0;
+    while size > 1 {
+        let half = size / 2;
+        let mid = base + half;
+        base = if Segment::read_start_char_code(groups, mid) <= codepoint { mid } else { base };
+        size -= half;
+    }
+
+    let segment = Segment::read(groups, base);
+    if segment.start_char_code <= codepoint && codepoint <= segment.end_char_code {
+        Some(segment)
+    } else {
+        None
     }
 }
 
@@ -335,3 +2137,47 @@ struct Segment {
     start_glyph_id: u32,
 }
 
+impl Segment {
+    #[inline]
+    fn read(groups: &[u8], index: usize) -> Segment {
+        let group = &groups[index * mem::size_of::<[u32; 3]>()..];
+        Segment {
+            start_char_code: BigEndian::read_u32(&group[0..]),
+            end_char_code: BigEndian::read_u32(&group[4..]),
+            start_glyph_id: BigEndian::read_u32(&group[8..]),
+        }
+    }
+
+    #[inline]
+    fn read_start_char_code(groups: &[u8], index: usize) -> u32 {
+        BigEndian::read_u32(&groups[index * mem::size_of::<[u32; 3]>()..])
+    }
+}
+
+/// Reads the header of a format 4 mapping table, just past the format field, and returns the
+/// segment count.
+///
+/// Checks that `segCountX2` is even and that the four parallel segment arrays fit within the
+/// declared subtable length, so that the array pointers derived from it line up.
+fn read_segment_mapping_header(cmap_reader: &mut &[u8]) -> Result<u16, FontError> {
+    let length = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let _language = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let seg_count_x2 = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let _search_range = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let _entry_selector = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let _range_shift = try!(cmap_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    if seg_count_x2 % 2 != 0 {
+        return Err(FontError::CmapMalformed)
+    }
+
+    // The header is 7 `u16`s, including the format. Then come the end codes, a reserved pad, and
+    // the start codes, ID deltas, and ID range offsets. The glyph ID array takes up the rest.
+    let seg_count = seg_count_x2 / 2;
+    let arrays_end = mem::size_of::<u16>() * (7 + 4 * seg_count as usize + 1);
+    if arrays_end > length as usize {
+        return Err(FontError::CmapMalformed)
+    }
+
+    Ok(seg_count)
+}
@@ -0,0 +1,330 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use euclid::{Matrix2D, Point2D};
+use font::FontTable;
+use paint::{ColorLine, ColorStop, Extend, Paint};
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'C' as u32) << 24) |
+                      ((b'O' as u32) << 16) |
+                      ((b'L' as u32) << 8)  |
+                       (b'R' as u32);
+
+const PAINT_FORMAT_COLR_LAYERS: u8 = 1;
+const PAINT_FORMAT_SOLID: u8 = 2;
+const PAINT_FORMAT_LINEAR_GRADIENT: u8 = 4;
+const PAINT_FORMAT_RADIAL_GRADIENT: u8 = 6;
+const PAINT_FORMAT_GLYPH: u8 = 10;
+const PAINT_FORMAT_TRANSFORM: u8 = 12;
+
+const EXTEND_PAD: u8 = 0;
+const EXTEND_REPEAT: u8 = 1;
+const EXTEND_REFLECT: u8 = 2;
+
+/// The size of a `BaseGlyphPaintRecord`: a glyph ID and a 32-bit offset.
+const BASE_GLYPH_PAINT_RECORD_SIZE: usize = 6;
+
+/// The size of a version 0 `BaseGlyphRecord`: a glyph ID, the index of its first layer, and the
+/// number of layers.
+const BASE_GLYPH_RECORD_SIZE: usize = 6;
+
+/// The size of a version 0 `LayerRecord`: a glyph ID and a palette index.
+const LAYER_RECORD_SIZE: usize = 4;
+
+/// How deeply paints may nest. Paint graphs are supposed to be acyclic, but a malicious font
+/// could make one refer back to itself.
+const MAX_PAINT_DEPTH: u32 = 64;
+
+/// How many paints a single paint graph may expand into. Layers can be shared between
+/// `PaintColrLayers` paints, so a small table can describe a graph that's exponentially large
+/// once it's expanded into a tree.
+const MAX_PAINT_COUNT: u32 = 65536;
+
+/// The color table: the flat layers of version 0, and the paint graphs of version 1.
+#[derive(Clone, Copy)]
+pub struct ColrTable<'a> {
+    table: FontTable<'a>,
+    base_glyph_record_count: u16,
+    base_glyph_records_offset: u32,
+    layer_records_offset: u32,
+    layer_record_count: u16,
+    base_glyph_list_offset: u32,
+    layer_list_offset: u32,
+}
+
+impl<'a> ColrTable<'a> {
+    pub fn new(table: FontTable) -> Result<ColrTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let base_glyph_record_count = try!(reader.read_u16::<BigEndian>()
+                                                 .map_err(FontError::eof));
+        let base_glyph_records_offset = try!(reader.read_u32::<BigEndian>()
+                                                   .map_err(FontError::eof));
+        let layer_records_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let layer_record_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // Version 0 has only the flat layer records, which have no paint graph.
+        let (mut base_glyph_list_offset, mut layer_list_offset) = (0, 0);
+        if version >= 1 {
+            base_glyph_list_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            layer_list_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        }
+
+        Ok(ColrTable {
+            table: table,
+            base_glyph_record_count: base_glyph_record_count,
+            base_glyph_records_offset: base_glyph_records_offset,
+            layer_records_offset: layer_records_offset,
+            layer_record_count: layer_record_count,
+            base_glyph_list_offset: base_glyph_list_offset,
+            layer_list_offset: layer_list_offset,
+        })
+    }
+
+    /// Returns the `(glyph ID, palette index)` pairs of the version 0 layers of the given glyph,
+    /// from bottom to top, or an empty list if the glyph has none.
+    pub fn layers(&self, glyph_id: u16) -> Result<Vec<(u16, u16)>, FontError> {
+        let mut base_glyph_records = self.table.bytes;
+        try!(base_glyph_records.jump(self.base_glyph_records_offset as usize)
+                               .map_err(FontError::eof));
+
+        // The records are sorted by glyph ID, so binary search.
+        let (mut low, mut high) = (0, self.base_glyph_record_count as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            let mut record = base_glyph_records;
+            try!(record.jump(mid * BASE_GLYPH_RECORD_SIZE).map_err(FontError::eof));
+            let record_glyph_id = try!(record.read_u16::<BigEndian>().map_err(FontError::eof));
+            if glyph_id < record_glyph_id {
+                high = mid
+            } else if glyph_id > record_glyph_id {
+                low = mid + 1
+            } else {
+                let first_layer_index = try!(record.read_u16::<BigEndian>()
+                                                   .map_err(FontError::eof));
+                let layer_count = try!(record.read_u16::<BigEndian>().map_err(FontError::eof));
+                if first_layer_index as usize + layer_count as usize >
+                        self.layer_record_count as usize {
+                    return Err(FontError::Failed)
+                }
+
+                let mut reader = self.table.bytes;
+                try!(reader.jump(self.layer_records_offset as usize +
+                                 first_layer_index as usize * LAYER_RECORD_SIZE)
+                           .map_err(FontError::eof));
+                let mut layers = Vec::with_capacity(layer_count as usize);
+                for _ in 0..layer_count {
+                    let layer_glyph_id = try!(reader.read_u16::<BigEndian>()
+                                                    .map_err(FontError::eof));
+                    let palette_index = try!(reader.read_u16::<BigEndian>()
+                                                   .map_err(FontError::eof));
+                    layers.push((layer_glyph_id, palette_index))
+                }
+                return Ok(layers)
+            }
+        }
+
+        Ok(vec![])
+    }
+
+    /// Returns the root of the paint graph for the given glyph, or `None` if the glyph has none.
+    pub fn paint_graph(&self, glyph_id: u16) -> Result<Option<Paint>, FontError> {
+        if self.base_glyph_list_offset == 0 {
+            return Ok(None)
+        }
+
+        let mut base_glyph_list = self.table.bytes;
+        try!(base_glyph_list.jump(self.base_glyph_list_offset as usize).map_err(FontError::eof));
+        let mut reader = base_glyph_list;
+        let record_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        // The records are sorted by glyph ID, so binary search.
+        let (mut low, mut high) = (0, record_count as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            let mut record = reader;
+            try!(record.jump(mid * BASE_GLYPH_PAINT_RECORD_SIZE).map_err(FontError::eof));
+            let record_glyph_id = try!(record.read_u16::<BigEndian>().map_err(FontError::eof));
+            if glyph_id < record_glyph_id {
+                high = mid
+            } else if glyph_id > record_glyph_id {
+                low = mid + 1
+            } else {
+                let paint_offset = try!(record.read_u32::<BigEndian>().map_err(FontError::eof));
+                let mut paint = base_glyph_list;
+                try!(paint.jump(paint_offset as usize).map_err(FontError::eof));
+                let mut paint_count = 0;
+                return self.read_paint(paint, 0, &mut paint_count).map(Some)
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads a paint and everything under it, adding the number of paints read to
+    /// `paint_count`.
+    fn read_paint(&self, paint: &[u8], depth: u32, paint_count: &mut u32)
+                  -> Result<Paint, FontError> {
+        *paint_count += 1;
+        if depth > MAX_PAINT_DEPTH || *paint_count > MAX_PAINT_COUNT {
+            return Err(FontError::Failed)
+        }
+
+        let mut reader = paint;
+        let format = try!(reader.read_u8().map_err(FontError::eof));
+        match format {
+            PAINT_FORMAT_COLR_LAYERS => {
+                let layer_count = try!(reader.read_u8().map_err(FontError::eof));
+                let first_layer_index = try!(reader.read_u32::<BigEndian>()
+                                                   .map_err(FontError::eof));
+                let mut layers = Vec::with_capacity(layer_count as usize);
+                for layer_index in 0..layer_count as u32 {
+                    let layer_index = try!(first_layer_index.checked_add(layer_index)
+                                                            .ok_or(FontError::Failed));
+                    let layer = try!(self.layer_paint(layer_index));
+                    layers.push(try!(self.read_paint(layer, depth + 1, paint_count)))
+                }
+                Ok(Paint::Layers(layers))
+            }
+            PAINT_FORMAT_SOLID => {
+                let palette_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let alpha = try!(read_f2dot14(&mut reader));
+                Ok(Paint::Solid {
+                    palette_index: palette_index,
+                    alpha: alpha,
+                })
+            }
+            PAINT_FORMAT_LINEAR_GRADIENT => {
+                let color_line = try!(read_color_line(try!(subtable(paint, &mut reader))));
+                let p0 = try!(read_point(&mut reader));
+                let p1 = try!(read_point(&mut reader));
+                let p2 = try!(read_point(&mut reader));
+                Ok(Paint::LinearGradient {
+                    color_line: color_line,
+                    p0: p0,
+                    p1: p1,
+                    p2: p2,
+                })
+            }
+            PAINT_FORMAT_RADIAL_GRADIENT => {
+                let color_line = try!(read_color_line(try!(subtable(paint, &mut reader))));
+                let c0 = try!(read_point(&mut reader));
+                let r0 = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let c1 = try!(read_point(&mut reader));
+                let r1 = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                Ok(Paint::RadialGradient {
+                    color_line: color_line,
+                    c0: c0,
+                    r0: r0,
+                    c1: c1,
+                    r1: r1,
+                })
+            }
+            PAINT_FORMAT_GLYPH => {
+                let child = try!(subtable(paint, &mut reader));
+                let glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                Ok(Paint::Glyph {
+                    glyph_id: glyph_id,
+                    paint: Box::new(try!(self.read_paint(child, depth + 1, paint_count))),
+                })
+            }
+            PAINT_FORMAT_TRANSFORM => {
+                let child = try!(subtable(paint, &mut reader));
+                let mut affine = try!(subtable(paint, &mut reader));
+                let mut values = [0.0; 6];
+                for value in &mut values {
+                    *value = try!(affine.read_i32::<BigEndian>().map_err(FontError::eof)) as f32 /
+                        65536.0
+                }
+
+                // The `Affine2x3` fields are `xx, yx, xy, yy, dx, dy`, which is the row-major
+                // order that `Matrix2D` uses.
+                let transform = Matrix2D::row_major(values[0], values[1], values[2], values[3],
+                                                    values[4], values[5]);
+                Ok(Paint::Transform {
+                    transform: transform,
+                    paint: Box::new(try!(self.read_paint(child, depth + 1, paint_count))),
+                })
+            }
+            _ => Err(FontError::UnknownFormat),
+        }
+    }
+
+    fn layer_paint(&self, layer_index: u32) -> Result<&'a [u8], FontError> {
+        if self.layer_list_offset == 0 {
+            return Err(FontError::Failed)
+        }
+
+        let mut layer_list = self.table.bytes;
+        try!(layer_list.jump(self.layer_list_offset as usize).map_err(FontError::eof));
+        let mut reader = layer_list;
+        let layer_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if layer_index >= layer_count {
+            return Err(FontError::Failed)
+        }
+
+        try!(reader.jump(layer_index as usize * mem::size_of::<u32>()).map_err(FontError::eof));
+        let paint_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        try!(layer_list.jump(paint_offset as usize).map_err(FontError::eof));
+        Ok(layer_list)
+    }
+}
+
+/// Reads a 24-bit offset from `reader` and returns the part of `paint` that it points to.
+fn subtable<'a>(paint: &'a [u8], reader: &mut &[u8]) -> Result<&'a [u8], FontError> {
+    let offset = try!(reader.read_u24::<BigEndian>().map_err(FontError::eof));
+    let mut subtable = paint;
+    try!(subtable.jump(offset as usize).map_err(FontError::eof));
+    Ok(subtable)
+}
+
+fn read_color_line(mut reader: &[u8]) -> Result<ColorLine, FontError> {
+    let extend = match try!(reader.read_u8().map_err(FontError::eof)) {
+        EXTEND_PAD => Extend::Pad,
+        EXTEND_REPEAT => Extend::Repeat,
+        EXTEND_REFLECT => Extend::Reflect,
+        // The spec says to treat unknown modes as padding.
+        _ => Extend::Pad,
+    };
+
+    let stop_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let mut stops = Vec::with_capacity(stop_count as usize);
+    for _ in 0..stop_count {
+        let offset = try!(read_f2dot14(&mut reader));
+        let palette_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let alpha = try!(read_f2dot14(&mut reader));
+        stops.push(ColorStop {
+            offset: offset,
+            palette_index: palette_index,
+            alpha: alpha,
+        })
+    }
+
+    Ok(ColorLine {
+        extend: extend,
+        stops: stops,
+    })
+}
+
+fn read_f2dot14(reader: &mut &[u8]) -> Result<f32, FontError> {
+    let value = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    Ok(value as f32 / 16384.0)
+}
+
+fn read_point(reader: &mut &[u8]) -> Result<Point2D<i16>, FontError> {
+    let x = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    let y = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    Ok(Point2D::new(x, y))
+}
@@ -0,0 +1,96 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use paint::Color;
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'C' as u32) << 24) |
+                      ((b'P' as u32) << 16) |
+                      ((b'A' as u32) << 8)  |
+                       (b'L' as u32);
+
+/// The size of a `ColorRecord`: blue, green, red, and alpha bytes.
+const COLOR_RECORD_SIZE: usize = 4;
+
+/// The color palette table, which holds the colors that `COLR` layers and paints refer to by
+/// index. Fonts may offer several palettes, such as one for light and one for dark backgrounds.
+#[derive(Clone, Copy)]
+pub struct CpalTable<'a> {
+    table: FontTable<'a>,
+    palette_entry_count: u16,
+    palette_count: u16,
+    color_record_count: u16,
+    color_records_offset: u32,
+}
+
+impl<'a> CpalTable<'a> {
+    pub fn new(table: FontTable) -> Result<CpalTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version > 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let palette_entry_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let palette_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let color_record_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let color_records_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        Ok(CpalTable {
+            table: table,
+            palette_entry_count: palette_entry_count,
+            palette_count: palette_count,
+            color_record_count: color_record_count,
+            color_records_offset: color_records_offset,
+        })
+    }
+
+    #[inline]
+    pub fn palette_count(&self) -> u16 {
+        self.palette_count
+    }
+
+    /// Returns the color at `palette_index` in the given palette, or `None` if either index is
+    /// out of range.
+    pub fn color(&self, palette: u16, palette_index: u16) -> Result<Option<Color>, FontError> {
+        if palette >= self.palette_count || palette_index >= self.palette_entry_count {
+            return Ok(None)
+        }
+
+        // Each palette is a run of consecutive color records starting at the index listed for it
+        // after the header.
+        let mut reader = self.table.bytes;
+        try!(reader.jump(mem::size_of::<u16>() * 4 + mem::size_of::<u32>() +
+                         mem::size_of::<u16>() * palette as usize).map_err(FontError::eof));
+        let first_color_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let color_index = first_color_index as usize + palette_index as usize;
+        if color_index >= self.color_record_count as usize {
+            return Err(FontError::Failed)
+        }
+
+        let mut reader = self.table.bytes;
+        try!(reader.jump(self.color_records_offset as usize + color_index * COLOR_RECORD_SIZE)
+                   .map_err(FontError::eof));
+        let blue = try!(reader.read_u8().map_err(FontError::eof));
+        let green = try!(reader.read_u8().map_err(FontError::eof));
+        let red = try!(reader.read_u8().map_err(FontError::eof));
+        let alpha = try!(reader.read_u8().map_err(FontError::eof));
+        Ok(Some(Color {
+            r: red,
+            g: green,
+            b: blue,
+            a: alpha,
+        }))
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{BitmapFormat, ColorBitmap, FontTable, GrayBitmap, MonoBitmap};
+use tables::eblc::BitmapLocation;
+use util::Jump;
+
+pub const TAG: u32 = ((b'E' as u32) << 24) |
+                      ((b'B' as u32) << 16) |
+                      ((b'D' as u32) << 8)  |
+                       (b'T' as u32);
+
+pub const CBDT_TAG: u32 = ((b'C' as u32) << 24) |
+                           ((b'B' as u32) << 16) |
+                           ((b'D' as u32) << 8)  |
+                            (b'T' as u32);
+
+const IMAGE_FORMAT_SMALL_METRICS_BYTE_ALIGNED: u16 = 1;
+const IMAGE_FORMAT_SMALL_METRICS_BIT_ALIGNED: u16 = 2;
+const IMAGE_FORMAT_BIT_ALIGNED: u16 = 5;
+const IMAGE_FORMAT_BIG_METRICS_BYTE_ALIGNED: u16 = 6;
+const IMAGE_FORMAT_BIG_METRICS_BIT_ALIGNED: u16 = 7;
+const IMAGE_FORMAT_SMALL_METRICS_PNG: u16 = 17;
+const IMAGE_FORMAT_BIG_METRICS_PNG: u16 = 18;
+const IMAGE_FORMAT_PNG: u16 = 19;
+
+/// The dimensions and horizontal metrics of an embedded bitmap, in pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct BigGlyphMetrics {
+    pub height: u8,
+    pub width: u8,
+    /// The distance from the origin to the left edge of the image.
+    pub bearing_x: i8,
+    /// The distance from the baseline to the top edge of the image.
+    pub bearing_y: i8,
+}
+
+impl BigGlyphMetrics {
+    /// Reads a `bigGlyphMetrics` record, leaving the reader just past it.
+    pub fn read(reader: &mut &[u8]) -> Result<BigGlyphMetrics, FontError> {
+        let metrics = try!(BigGlyphMetrics::read_small(reader));
+
+        // Skip the vertical metrics.
+        try!(reader.jump(3).map_err(FontError::eof));
+        Ok(metrics)
+    }
+
+    /// Reads a `smallGlyphMetrics` record, leaving the reader just past it.
+    fn read_small(reader: &mut &[u8]) -> Result<BigGlyphMetrics, FontError> {
+        let height = try!(reader.read_u8().map_err(FontError::eof));
+        let width = try!(reader.read_u8().map_err(FontError::eof));
+        let bearing_x = try!(reader.read_i8().map_err(FontError::eof));
+        let bearing_y = try!(reader.read_i8().map_err(FontError::eof));
+        let _advance = try!(reader.read_u8().map_err(FontError::eof));
+        Ok(BigGlyphMetrics {
+            height: height,
+            width: width,
+            bearing_x: bearing_x,
+            bearing_y: bearing_y,
+        })
+    }
+}
+
+/// The embedded bitmap data table. This also reads the color bitmap data table, `CBDT`.
+#[derive(Clone, Copy)]
+pub struct EbdtTable<'a> {
+    table: FontTable<'a>,
+}
+
+impl<'a> EbdtTable<'a> {
+    #[inline]
+    pub fn new(table: FontTable) -> EbdtTable {
+        EbdtTable {
+            table: table,
+        }
+    }
+
+    /// Returns the PNG image at the given location in a `CBDT` table, from a strike with the
+    /// given size in pixels per em.
+    pub fn color_bitmap(&self, location: &BitmapLocation, ppem: u16)
+                        -> Result<ColorBitmap<'a>, FontError> {
+        let mut reader = try!(self.image_data(location));
+        let metrics = match location.image_format {
+            IMAGE_FORMAT_SMALL_METRICS_PNG => try!(BigGlyphMetrics::read_small(&mut reader)),
+            IMAGE_FORMAT_BIG_METRICS_PNG => try!(BigGlyphMetrics::read(&mut reader)),
+            IMAGE_FORMAT_PNG => try!(location.metrics.ok_or(FontError::Failed)),
+            _ => return Err(FontError::UnknownFormat),
+        };
+
+        let data_length = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+        if reader.len() < data_length {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(ColorBitmap {
+            format: BitmapFormat::Png,
+            ppem: ppem,
+            origin_x: metrics.bearing_x as i16,
+            origin_y: metrics.bearing_y as i16 - metrics.height as i16,
+            data: &reader[..data_length],
+        })
+    }
+
+    /// Returns the bytes of the image at the given location.
+    fn image_data(&self, location: &BitmapLocation) -> Result<&'a [u8], FontError> {
+        let mut reader = self.table.bytes;
+        try!(reader.jump(location.offset as usize).map_err(FontError::eof));
+        if reader.len() < location.length as usize {
+            return Err(FontError::UnexpectedEof)
+        }
+        Ok(&reader[..location.length as usize])
+    }
+
+    /// Decodes the monochrome image at the given location.
+    pub fn mono_bitmap(&self, location: &BitmapLocation) -> Result<MonoBitmap, FontError> {
+        let (metrics, bit_aligned, reader) = try!(self.image(location));
+        let (width, height) = (metrics.width as u32, metrics.height as u32);
+        let stride = ((width + 7) / 8) as usize;
+        let mut data = vec![0; stride * height as usize];
+        if !bit_aligned {
+            let data_length = data.len();
+            if reader.len() < data_length {
+                return Err(FontError::UnexpectedEof)
+            }
+            data.copy_from_slice(&reader[..data_length]);
+        } else {
+            // Rows follow one another without padding, so copy the image a bit at a time.
+            if (reader.len() * 8) < (width * height) as usize {
+                return Err(FontError::UnexpectedEof)
+            }
+            for y in 0..height {
+                for x in 0..width {
+                    let source_bit = (y * width + x) as usize;
+                    if reader[source_bit / 8] & (0x80 >> (source_bit % 8)) != 0 {
+                        data[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8)
+                    }
+                }
+            }
+        }
+
+        Ok(MonoBitmap {
+            width: width,
+            height: height,
+            data: data,
+        })
+    }
+
+    /// Decodes the image at the given location from a strike with the given bit depth (1, 2, 4,
+    /// or 8) into one byte of coverage per pixel.
+    pub fn gray_bitmap(&self, location: &BitmapLocation, bit_depth: u8)
+                       -> Result<GrayBitmap, FontError> {
+        if bit_depth != 1 && bit_depth != 2 && bit_depth != 4 && bit_depth != 8 {
+            return Err(FontError::UnknownFormat)
+        }
+
+        let (metrics, bit_aligned, reader) = try!(self.image(location));
+        let (width, height) = (metrics.width as usize, metrics.height as usize);
+        let bit_depth = bit_depth as usize;
+
+        // Byte-aligned rows are padded to a whole number of bytes; bit-aligned rows aren't.
+        let row_bits = if bit_aligned {
+            width * bit_depth
+        } else {
+            (width * bit_depth + 7) / 8 * 8
+        };
+        if reader.len() * 8 < row_bits * height {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        // Pixels are packed from the most significant bit, so with a bit depth that divides 8 none
+        // straddle a byte boundary.
+        let max_value = (1 << bit_depth) - 1;
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let bit = y * row_bits + x * bit_depth;
+                let shift = 8 - bit_depth - bit % 8;
+                let value = (reader[bit / 8] as usize >> shift) & max_value;
+                data.push((value * 255 / max_value) as u8)
+            }
+        }
+
+        Ok(GrayBitmap {
+            width: width as u32,
+            height: height as u32,
+            data: data,
+        })
+    }
+
+    /// Returns the metrics of the bitmap image at the given location, whether its rows are
+    /// bit-aligned, and its pixel data.
+    fn image(&self, location: &BitmapLocation)
+             -> Result<(BigGlyphMetrics, bool, &'a [u8]), FontError> {
+        let mut reader = try!(self.image_data(location));
+        let (metrics, bit_aligned) = match location.image_format {
+            IMAGE_FORMAT_SMALL_METRICS_BYTE_ALIGNED | IMAGE_FORMAT_SMALL_METRICS_BIT_ALIGNED => {
+                let metrics = try!(BigGlyphMetrics::read_small(&mut reader));
+                (metrics, location.image_format == IMAGE_FORMAT_SMALL_METRICS_BIT_ALIGNED)
+            }
+            IMAGE_FORMAT_BIG_METRICS_BYTE_ALIGNED | IMAGE_FORMAT_BIG_METRICS_BIT_ALIGNED => {
+                let metrics = try!(BigGlyphMetrics::read(&mut reader));
+                (metrics, location.image_format == IMAGE_FORMAT_BIG_METRICS_BIT_ALIGNED)
+            }
+            IMAGE_FORMAT_BIT_ALIGNED => {
+                match location.metrics {
+                    Some(metrics) => (metrics, true),
+                    None => return Err(FontError::Failed),
+                }
+            }
+            _ => return Err(FontError::UnknownFormat),
+        };
+        Ok((metrics, bit_aligned, reader))
+    }
+}
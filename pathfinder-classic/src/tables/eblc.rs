@@ -0,0 +1,256 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use std::mem;
+use tables::ebdt::BigGlyphMetrics;
+use util::Jump;
+
+pub const TAG: u32 = ((b'E' as u32) << 24) |
+                      ((b'B' as u32) << 16) |
+                      ((b'L' as u32) << 8)  |
+                       (b'C' as u32);
+
+pub const CBLC_TAG: u32 = ((b'C' as u32) << 24) |
+                           ((b'B' as u32) << 16) |
+                           ((b'L' as u32) << 8)  |
+                            (b'C' as u32);
+
+const BITMAP_SIZE_RECORD_SIZE: usize = 48;
+const INDEX_SUBTABLE_ARRAY_ENTRY_SIZE: usize = 8;
+
+/// The offset of `startGlyphIndex` within a `BitmapSize` record, past the two line metrics.
+const BITMAP_SIZE_GLYPH_RANGE_OFFSET: usize = 40;
+
+const INDEX_FORMAT_VARIABLE_METRICS_4_BYTE_OFFSETS: u16 = 1;
+const INDEX_FORMAT_CONSTANT_METRICS: u16 = 2;
+const INDEX_FORMAT_VARIABLE_METRICS_2_BYTE_OFFSETS: u16 = 3;
+const INDEX_FORMAT_SPARSE_VARIABLE_METRICS: u16 = 4;
+const INDEX_FORMAT_SPARSE_CONSTANT_METRICS: u16 = 5;
+
+/// The embedded bitmap location table, which says where each glyph's bitmap is for each strike
+/// (size) that the font has bitmaps for.
+///
+/// The color bitmap location table, `CBLC`, is version 3 of the same format.
+#[derive(Clone, Copy)]
+pub struct EblcTable<'a> {
+    table: FontTable<'a>,
+    size_count: u32,
+}
+
+/// Where to find a glyph's image in the `EBDT` or `CBDT` table.
+#[derive(Clone, Copy, Debug)]
+pub struct BitmapLocation {
+    /// The format of the image data.
+    pub image_format: u16,
+    /// The byte offset of the image data from the start of the data table.
+    pub offset: u32,
+    /// The length of the image data in bytes.
+    pub length: u32,
+    /// The metrics shared by every glyph in the subtable, if the image doesn't carry its own.
+    pub metrics: Option<BigGlyphMetrics>,
+}
+
+/// The fields of a `BitmapSize` record that are needed to find images.
+struct BitmapSize {
+    subtable_array_offset: u32,
+    subtable_count: u32,
+    start_glyph_id: u16,
+    end_glyph_id: u16,
+    ppem: u8,
+    bit_depth: u8,
+}
+
+impl<'a> EblcTable<'a> {
+    pub fn new(table: FontTable) -> Result<EblcTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 2 && major_version != 3 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let size_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        Ok(EblcTable {
+            table: table,
+            size_count: size_count,
+        })
+    }
+
+    /// Finds the image of the given glyph in the monochrome strike for the given size in pixels
+    /// per em. Returns `None` if there's no such strike or the strike doesn't have the glyph.
+    pub fn locate(&self, glyph_id: u16, ppem: u16) -> Result<Option<BitmapLocation>, FontError> {
+        for size_index in 0..self.size_count {
+            let size = try!(self.size(size_index));
+            if size.ppem as u16 != ppem || size.bit_depth != 1 || glyph_id < size.start_glyph_id ||
+                    glyph_id > size.end_glyph_id {
+                continue
+            }
+
+            return self.locate_in_strike(glyph_id, size.subtable_array_offset, size.subtable_count)
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the sizes in pixels per em and the bit depths of the strikes, in the order the
+    /// font lists them.
+    pub fn strikes(&self) -> Result<Vec<(u16, u8)>, FontError> {
+        let mut strikes = Vec::with_capacity(self.size_count as usize);
+        for size_index in 0..self.size_count {
+            let size = try!(self.size(size_index));
+            strikes.push((size.ppem as u16, size.bit_depth))
+        }
+        Ok(strikes)
+    }
+
+    /// Finds the image of the given glyph in the strike with the given index. Returns `None` if
+    /// the strike doesn't have the glyph.
+    pub fn locate_in_strike_index(&self, strike_index: u32, glyph_id: u16)
+                                  -> Result<Option<BitmapLocation>, FontError> {
+        if strike_index >= self.size_count {
+            return Err(FontError::Failed)
+        }
+        let size = try!(self.size(strike_index));
+        if glyph_id < size.start_glyph_id || glyph_id > size.end_glyph_id {
+            return Ok(None)
+        }
+        self.locate_in_strike(glyph_id, size.subtable_array_offset, size.subtable_count)
+    }
+
+    fn size(&self, size_index: u32) -> Result<BitmapSize, FontError> {
+        let mut reader = self.table.bytes;
+        try!(reader.jump(mem::size_of::<u32>() * 2 + size_index as usize * BITMAP_SIZE_RECORD_SIZE)
+                   .map_err(FontError::eof));
+        let mut size_record = reader;
+
+        let subtable_array_offset =
+            try!(size_record.read_u32::<BigEndian>().map_err(FontError::eof));
+        let _index_tables_size = try!(size_record.read_u32::<BigEndian>().map_err(FontError::eof));
+        let subtable_count = try!(size_record.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        try!(reader.jump(BITMAP_SIZE_GLYPH_RANGE_OFFSET).map_err(FontError::eof));
+        let start_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let end_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _ppem_x = try!(reader.read_u8().map_err(FontError::eof));
+        let ppem_y = try!(reader.read_u8().map_err(FontError::eof));
+        let bit_depth = try!(reader.read_u8().map_err(FontError::eof));
+
+        Ok(BitmapSize {
+            subtable_array_offset: subtable_array_offset,
+            subtable_count: subtable_count,
+            start_glyph_id: start_glyph_id,
+            end_glyph_id: end_glyph_id,
+            ppem: ppem_y,
+            bit_depth: bit_depth,
+        })
+    }
+
+    fn locate_in_strike(&self, glyph_id: u16, subtable_array_offset: u32, subtable_count: u32)
+                        -> Result<Option<BitmapLocation>, FontError> {
+        let mut subtable_array = self.table.bytes;
+        try!(subtable_array.jump(subtable_array_offset as usize).map_err(FontError::eof));
+
+        for subtable_index in 0..subtable_count as usize {
+            let mut reader = subtable_array;
+            try!(reader.jump(subtable_index * INDEX_SUBTABLE_ARRAY_ENTRY_SIZE)
+                       .map_err(FontError::eof));
+            let first_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let last_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let subtable_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            if glyph_id < first_glyph_id || glyph_id > last_glyph_id {
+                continue
+            }
+
+            let mut subtable = subtable_array;
+            try!(subtable.jump(subtable_offset as usize).map_err(FontError::eof));
+            return read_index_subtable(subtable, glyph_id, first_glyph_id)
+        }
+
+        Ok(None)
+    }
+}
+
+fn read_index_subtable(mut reader: &[u8], glyph_id: u16, first_glyph_id: u16)
+                       -> Result<Option<BitmapLocation>, FontError> {
+    let index_format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let image_format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let image_data_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+    let glyph_index = (glyph_id - first_glyph_id) as usize;
+
+    let location = |start: u32, end: u32, metrics: Option<BigGlyphMetrics>| {
+        if end < start {
+            return Err(FontError::Failed)
+        }
+        let offset = try!(image_data_offset.checked_add(start).ok_or(FontError::Failed));
+        Ok(Some(BitmapLocation {
+            image_format: image_format,
+            offset: offset,
+            length: end - start,
+            metrics: metrics,
+        }))
+    };
+
+    match index_format {
+        INDEX_FORMAT_VARIABLE_METRICS_4_BYTE_OFFSETS => {
+            try!(reader.jump(glyph_index * mem::size_of::<u32>()).map_err(FontError::eof));
+            let start = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let end = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            location(start, end, None)
+        }
+        INDEX_FORMAT_VARIABLE_METRICS_2_BYTE_OFFSETS => {
+            try!(reader.jump(glyph_index * mem::size_of::<u16>()).map_err(FontError::eof));
+            let start = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let end = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            location(start as u32, end as u32, None)
+        }
+        INDEX_FORMAT_CONSTANT_METRICS => {
+            let image_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let metrics = try!(BigGlyphMetrics::read(&mut reader));
+            let start = try!((glyph_index as u32).checked_mul(image_size)
+                                                 .ok_or(FontError::Failed));
+            let end = try!(start.checked_add(image_size).ok_or(FontError::Failed));
+            location(start, end, Some(metrics))
+        }
+        INDEX_FORMAT_SPARSE_VARIABLE_METRICS => {
+            let glyph_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            for _ in 0..glyph_count {
+                let this_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let start = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                if this_glyph_id != glyph_id {
+                    continue
+                }
+
+                // The array has one extra entry so that the last glyph's end can be found.
+                let _next_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let end = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                return location(start as u32, end as u32, None)
+            }
+            Ok(None)
+        }
+        INDEX_FORMAT_SPARSE_CONSTANT_METRICS => {
+            let image_size = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let metrics = try!(BigGlyphMetrics::read(&mut reader));
+            let glyph_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            for sparse_index in 0..glyph_count {
+                if try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) == glyph_id {
+                    let start = try!(sparse_index.checked_mul(image_size)
+                                                 .ok_or(FontError::Failed));
+                    let end = try!(start.checked_add(image_size).ok_or(FontError::Failed));
+                    return location(start, end, Some(metrics))
+                }
+            }
+            Ok(None)
+        }
+        _ => Err(FontError::UnknownFormat),
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{FontTable, NamedInstance};
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'f' as u32) << 24) |
+                      ((b'v' as u32) << 16) |
+                      ((b'a' as u32) << 8)  |
+                       (b'r' as u32);
+
+/// The size of an instance record without its coordinates or PostScript name ID.
+const INSTANCE_RECORD_HEADER_SIZE: usize = 4;
+
+/// The font variations table, which lists the design axes of a variable font and its named
+/// instances.
+#[derive(Clone, Copy)]
+pub struct FvarTable<'a> {
+    instances: &'a [u8],
+    axis_count: u16,
+    instance_count: u16,
+    instance_size: usize,
+}
+
+impl<'a> FvarTable<'a> {
+    pub fn new(table: FontTable) -> Result<FvarTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let axes_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _reserved = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let axis_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let axis_size = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let instance_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let instance_size = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) as usize;
+
+        // Instance records must at least hold their coordinates.
+        let coords_size = axis_count as usize * mem::size_of::<i32>();
+        if instance_size < INSTANCE_RECORD_HEADER_SIZE + coords_size {
+            return Err(FontError::Failed)
+        }
+
+        // The instance records immediately follow the axis records.
+        let mut instances = table.bytes;
+        try!(instances.jump(axes_offset as usize + axis_count as usize * axis_size as usize)
+                      .map_err(FontError::eof));
+        if instances.len() < instance_count as usize * instance_size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(FvarTable {
+            instances: instances,
+            axis_count: axis_count,
+            instance_count: instance_count,
+            instance_size: instance_size,
+        })
+    }
+
+    /// Returns the named instances of the font, in the order they're listed in the table.
+    pub fn named_instances(&self) -> Vec<NamedInstance> {
+        let coords_size = self.axis_count as usize * mem::size_of::<i32>();
+        let has_postscript_name_ids =
+            self.instance_size >= INSTANCE_RECORD_HEADER_SIZE + coords_size + mem::size_of::<u16>();
+
+        (0..self.instance_count as usize).map(|instance_index| {
+            // `new()` checked that all the records fit, so these reads can't fail.
+            let mut reader = &self.instances[instance_index * self.instance_size..];
+            let name_id = reader.read_u16::<BigEndian>().unwrap();
+            let _flags = reader.read_u16::<BigEndian>().unwrap();
+            let coords = (0..self.axis_count).map(|_| {
+                reader.read_i32::<BigEndian>().unwrap() as f32 / 65536.0
+            }).collect();
+
+            // 0xffff means that the instance has no PostScript name.
+            let postscript_name_id = if has_postscript_name_ids {
+                match reader.read_u16::<BigEndian>().unwrap() {
+                    0xffff => None,
+                    postscript_name_id => Some(postscript_name_id),
+                }
+            } else {
+                None
+            };
+
+            NamedInstance {
+                name_id: name_id,
+                coords: coords,
+                postscript_name_id: postscript_name_id,
+            }
+        }).collect()
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'g' as u32) << 24) |
+                      ((b'a' as u32) << 16) |
+                      ((b's' as u32) << 8)  |
+                       (b'p' as u32);
+
+bitflags! {
+    pub flags GaspBehavior: u16 {
+        const GRIDFIT = 1 << 0,
+        const DO_GRAY = 1 << 1,
+        const SYMMETRIC_GRIDFIT = 1 << 2,
+        const SYMMETRIC_SMOOTHING = 1 << 3,
+    }
+}
+
+/// The grid-fitting and scan-conversion procedure table, which tells the rasterizer whether to
+/// hint and antialias glyphs at each range of sizes.
+#[derive(Clone, Copy)]
+pub struct GaspTable<'a> {
+    ranges: &'a [u8],
+    range_count: u16,
+    version: u16,
+}
+
+impl<'a> GaspTable<'a> {
+    pub fn new(table: FontTable) -> Result<GaspTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version > 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let range_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if reader.len() < range_count as usize * mem::size_of::<u16>() * 2 {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(GaspTable {
+            ranges: reader,
+            range_count: range_count,
+            version: version,
+        })
+    }
+
+    /// Returns the behavior for the given size in pixels per em, or `None` if the size is larger
+    /// than the last range.
+    ///
+    /// Each range covers the sizes above the previous range's maximum, up to and including its
+    /// own.
+    pub fn behavior(&self, ppem: u16) -> Option<GaspBehavior> {
+        // `new()` checked that all the ranges fit, so these reads can't fail.
+        let mut reader = self.ranges;
+        for _ in 0..self.range_count {
+            let range_max_ppem = reader.read_u16::<BigEndian>().unwrap();
+            let behavior = reader.read_u16::<BigEndian>().unwrap();
+            if ppem <= range_max_ppem {
+                let mut behavior = GaspBehavior::from_bits_truncate(behavior);
+
+                // The symmetric flags were introduced in version 1. Rasterizers ignore them in
+                // version 0 tables, where they may be garbage.
+                if self.version == 0 {
+                    behavior = behavior & (GRIDFIT | DO_GRAY)
+                }
+                return Some(behavior)
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{FontTable, GlyphClass};
+use std::mem;
+use tables::layout;
+use util::Jump;
+
+pub const TAG: u32 = ((b'G' as u32) << 24) |
+                      ((b'D' as u32) << 16) |
+                      ((b'E' as u32) << 8)  |
+                       (b'F' as u32);
+
+const GLYPH_CLASS_BASE: u16 = 1;
+const GLYPH_CLASS_LIGATURE: u16 = 2;
+const GLYPH_CLASS_MARK: u16 = 3;
+const GLYPH_CLASS_COMPONENT: u16 = 4;
+
+/// The glyph definition table, which classifies glyphs for the layout tables.
+#[derive(Clone, Copy)]
+pub struct GdefTable<'a> {
+    table: FontTable<'a>,
+    glyph_class_def_offset: u16,
+    attach_list_offset: u16,
+    mark_attach_class_def_offset: u16,
+}
+
+impl<'a> GdefTable<'a> {
+    pub fn new(table: FontTable) -> Result<GdefTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let glyph_class_def_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let attach_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _lig_caret_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mark_attach_class_def_offset =
+            try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        Ok(GdefTable {
+            table: table,
+            glyph_class_def_offset: glyph_class_def_offset,
+            attach_list_offset: attach_list_offset,
+            mark_attach_class_def_offset: mark_attach_class_def_offset,
+        })
+    }
+
+    /// Returns the class of the given glyph, or `None` if it has none.
+    pub fn glyph_class(&self, glyph_id: u16) -> Result<Option<GlyphClass>, FontError> {
+        if self.glyph_class_def_offset == 0 {
+            return Ok(None)
+        }
+        let class_def = try!(layout::at(self.table.bytes, self.glyph_class_def_offset as usize));
+        Ok(match try!(layout::glyph_class(class_def, glyph_id)) {
+            GLYPH_CLASS_BASE => Some(GlyphClass::Base),
+            GLYPH_CLASS_LIGATURE => Some(GlyphClass::Ligature),
+            GLYPH_CLASS_MARK => Some(GlyphClass::Mark),
+            GLYPH_CLASS_COMPONENT => Some(GlyphClass::Component),
+            _ => None,
+        })
+    }
+
+    /// Returns the mark attachment class of the given glyph, which lookups can use to skip over
+    /// all marks but those of one class. Glyphs that aren't marks are in class 0.
+    pub fn mark_attachment_class(&self, glyph_id: u16) -> Result<u16, FontError> {
+        if self.mark_attach_class_def_offset == 0 {
+            return Ok(0)
+        }
+        let class_def = try!(layout::at(self.table.bytes,
+                                        self.mark_attach_class_def_offset as usize));
+        layout::glyph_class(class_def, glyph_id)
+    }
+
+    /// Returns the indices of the outline points of the given glyph that marks attach to.
+    pub fn attachment_points(&self, glyph_id: u16) -> Result<Vec<u16>, FontError> {
+        if self.attach_list_offset == 0 {
+            return Ok(vec![])
+        }
+        let attach_list = try!(layout::at(self.table.bytes, self.attach_list_offset as usize));
+        let mut reader = attach_list;
+        let coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let coverage = try!(layout::at(attach_list, coverage_offset as usize));
+        let coverage_index = match try!(layout::coverage_index(coverage, glyph_id)) {
+            Some(coverage_index) => coverage_index,
+            None => return Ok(vec![]),
+        };
+        if coverage_index >= glyph_count {
+            return Err(FontError::UnexpectedEof)
+        }
+        try!(reader.jump(mem::size_of::<u16>() * coverage_index as usize)
+                   .map_err(FontError::eof));
+        let attach_point_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut reader = try!(layout::at(attach_list, attach_point_offset as usize));
+        let point_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut point_indices = Vec::with_capacity(point_count as usize);
+        for _ in 0..point_count {
+            point_indices.push(try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)))
+        }
+        Ok(point_indices)
+    }
+}
@@ -13,7 +13,9 @@ use error::FontError;
 use euclid::Point2D;
 use font::{FontTable, Point, PointKind};
 use outline::GlyphBounds;
+use std::i16;
 use std::mem;
+use tables::gvar::GvarTable;
 use tables::head::HeadTable;
 use tables::loca::LocaTable;
 use util::{F2DOT14_ONE, F2DOT14_ZERO, F2Dot14, Jump};
@@ -85,146 +87,116 @@ impl<'a> GlyfTable<'a> {
         }
     }
 
-    fn for_each_point_in_simple_glyph<F>(&self, mut reader: &[u8], mut callback: F)
-                                         -> Result<(), FontError> where F: FnMut(&Point) {
-        // Determine how many contours we have.
+    /// Like `for_each_point()`, but with the glyph's variations in `gvar` applied at the given
+    /// normalized variation coordinates, one per axis.
+    pub fn for_each_point_at<F>(&self,
+                                head_table: &HeadTable,
+                                loca_table: &LocaTable,
+                                gvar_table: &GvarTable,
+                                glyph_id: u16,
+                                coords: &[f32],
+                                mut callback: F)
+                                -> Result<(), FontError> where F: FnMut(&Point) {
+        let mut reader = self.table.bytes;
+
+        match try!(loca_table.location_of(head_table, glyph_id)) {
+            None => {
+                // No points.
+                return Ok(())
+            }
+            Some(offset) => try!(reader.jump(offset as usize).map_err(FontError::eof)),
+        }
+
+        let glyph_start = reader;
         let number_of_contours = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
-        if number_of_contours == 0 {
-            return Ok(())
+        if number_of_contours >= 0 {
+            return self.for_each_point_in_simple_glyph_at(glyph_start,
+                                                          gvar_table,
+                                                          glyph_id,
+                                                          coords,
+                                                          callback)
         }
 
-        // Skip over the rest of the header.
+        // The points that `gvar` moves in a composite glyph are the offsets of its components.
+        let mut components = vec![];
         try!(reader.jump(mem::size_of::<i16>() * 4).map_err(FontError::eof));
+        loop {
+            let component = try!(Component::read(&mut reader));
+            components.push(component);
+            if !component.flags.contains(MORE_COMPONENTS) {
+                break
+            }
+        }
 
-        // Find out how many points we have.
-        let mut endpoints_reader = reader;
-        try!(reader.jump(mem::size_of::<u16>() as usize * (number_of_contours as usize - 1))
-                   .map_err(FontError::eof));
-        let number_of_points = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) + 1;
-
-        // Skip over hinting instructions.
-        let instruction_length = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        try!(reader.jump(instruction_length as usize).map_err(FontError::eof));
-
-        // Find the offsets of the X and Y coordinates.
-        let flags_reader = reader;
-        let x_coordinate_length = try!(calculate_size_of_x_coordinates(&mut reader,
-                                                                       number_of_points));
-
-        // Set up the streams.
-        let mut flag_parser = try!(FlagParser::new(flags_reader));
-        let mut x_coordinate_reader = reader;
-        try!(reader.jump(x_coordinate_length as usize).map_err(FontError::eof));
-        let mut y_coordinate_reader = reader;
-
-        // Now parse the contours.
-        let (mut position, mut point_index) = (Point2D::new(0, 0), 0);
-        for _ in 0..number_of_contours {
-            let contour_point_count =
-                try!(endpoints_reader.read_u16::<BigEndian>().map_err(FontError::eof)) -
-                point_index + 1;
-
-            let mut first_on_curve_point = None;
-            let mut initial_off_curve_point = None;
-            let mut last_point_was_off_curve = false;
-            let mut point_index_in_contour = 0;
-
-            for _ in 0..contour_point_count {
-                let flags = SimpleFlags::from_bits_truncate(*flag_parser.current);
-                try!(flag_parser.next());
-
-                let mut delta = Point2D::new(0, 0);
-                if flags.contains(X_SHORT_VECTOR) {
-                    delta.x = try!(x_coordinate_reader.read_u8().map_err(FontError::eof)) as i16;
-                    if !flags.contains(THIS_X_IS_SAME) {
-                        delta.x = -delta.x
-                    }
-                } else if !flags.contains(THIS_X_IS_SAME) {
-                    delta.x = try!(x_coordinate_reader.read_i16::<BigEndian>()
-                                                      .map_err(FontError::eof))
-                }
-                if flags.contains(Y_SHORT_VECTOR) {
-                    delta.y = try!(y_coordinate_reader.read_u8().map_err(FontError::eof)) as i16;
-                    if !flags.contains(THIS_Y_IS_SAME) {
-                        delta.y = -delta.y
-                    }
-                } else if !flags.contains(THIS_Y_IS_SAME) {
-                    delta.y = try!(y_coordinate_reader.read_i16::<BigEndian>()
-                                                      .map_err(FontError::eof))
-                }
-
-                if last_point_was_off_curve && !flags.contains(ON_CURVE) {
-                    let position = position + delta / 2;
-
-                    // An important edge case!
-                    if first_on_curve_point.is_none() {
-                        first_on_curve_point = Some(position)
-                    }
-
-                    callback(&Point {
-                        position: position,
-                        index_in_contour: point_index_in_contour,
-                        kind: PointKind::OnCurve,
-                    });
-                    point_index_in_contour += 1
-                }
-
-                position = position + delta;
-
-                if flags.contains(ON_CURVE) && first_on_curve_point.is_none() {
-                    first_on_curve_point = Some(position)
-                }
+        let offsets: Vec<_> = components.iter().map(|component| {
+            Point2D::new(component.transform.m02, component.transform.m12)
+        }).collect();
+        let deltas = try!(gvar_table.glyph_deltas(glyph_id, coords, &offsets, &[]));
 
-                // Sometimes the initial point is an off curve point. In that case, save it so we
-                // can emit it later when closing the path.
-                if !flags.contains(ON_CURVE) && first_on_curve_point.is_none() {
-                    debug_assert!(initial_off_curve_point.is_none());
-                    initial_off_curve_point = Some(position)
-                } else {
-                    callback(&Point {
-                        position: position,
-                        kind: if flags.contains(ON_CURVE) {
-                            PointKind::OnCurve
-                        } else {
-                            PointKind::QuadControl
-                        },
-                        index_in_contour: point_index_in_contour,
-                    });
-                    point_index_in_contour += 1
-                }
+        for (component, delta) in components.iter().zip(deltas) {
+            let mut transform = component.transform;
+            if component.flags.contains(ARGS_ARE_XY_VALUES) {
+                transform.m02 = round_to_i16(transform.m02 as f32 + delta.x);
+                transform.m12 = round_to_i16(transform.m12 as f32 + delta.y);
+            }
 
-                last_point_was_off_curve = !flags.contains(ON_CURVE);
-                point_index += 1;
+            if let Some(offset) = try!(loca_table.location_of(head_table, component.glyph_index)) {
+                let mut reader = self.table.bytes;
+                try!(reader.jump(offset as usize).map_err(FontError::eof));
+                try!(self.for_each_point_in_simple_glyph_at(reader,
+                                                            gvar_table,
+                                                            component.glyph_index,
+                                                            coords,
+                                                            |point| {
+                    callback(&transform.transform(&point))
+                }));
             }
+        }
 
-            // We're about to close the path. Emit the initial off curve point if there was one.
-            if let Some(initial_off_curve_point) = initial_off_curve_point {
-                if last_point_was_off_curve {
-                    // Another important edge case!
-                    let position = position + (initial_off_curve_point - position) / 2;
-                    callback(&Point {
-                        position: position,
-                        index_in_contour: point_index_in_contour,
-                        kind: PointKind::OnCurve,
-                    });
-                    point_index_in_contour += 1
-                }
+        Ok(())
+    }
 
-                callback(&Point {
-                    position: initial_off_curve_point,
-                    kind: PointKind::QuadControl,
-                    index_in_contour: point_index_in_contour,
-                });
-                point_index_in_contour += 1
+    fn for_each_point_in_simple_glyph<F>(&self, reader: &[u8], mut callback: F)
+                                         -> Result<(), FontError> where F: FnMut(&Point) {
+        let mut contour = ContourBuilder::new();
+        for_each_stored_point(reader, |position, on_curve, ends_contour| {
+            contour.push(position, on_curve, &mut callback);
+            if ends_contour {
+                contour.close(&mut callback)
             }
+        })
+    }
 
-            // Close the path.
-            if let Some(first_on_curve_point) = first_on_curve_point {
-                callback(&Point {
-                    position: first_on_curve_point,
-                    kind: PointKind::OnCurve,
-                    index_in_contour: point_index_in_contour,
-                })
+    fn for_each_point_in_simple_glyph_at<F>(&self,
+                                            reader: &[u8],
+                                            gvar_table: &GvarTable,
+                                            glyph_id: u16,
+                                            coords: &[f32],
+                                            mut callback: F)
+                                            -> Result<(), FontError> where F: FnMut(&Point) {
+        let (mut positions, mut on_curve_flags, mut contour_end_points) = (vec![], vec![], vec![]);
+        try!(for_each_stored_point(reader, |position, on_curve, ends_contour| {
+            if ends_contour {
+                contour_end_points.push(positions.len() as u16)
+            }
+            positions.push(position);
+            on_curve_flags.push(on_curve)
+        }));
+
+        let deltas = try!(gvar_table.glyph_deltas(glyph_id,
+                                                  coords,
+                                                  &positions,
+                                                  &contour_end_points));
+
+        let mut contour = ContourBuilder::new();
+        let mut contour_end_points = contour_end_points.iter().peekable();
+        for (point_index, (position, delta)) in positions.iter().zip(deltas.iter()).enumerate() {
+            let position = Point2D::new(round_to_i16(position.x as f32 + delta.x),
+                                        round_to_i16(position.y as f32 + delta.y));
+            contour.push(position, on_curve_flags[point_index], &mut callback);
+            if contour_end_points.peek() == Some(&&(point_index as u16)) {
+                contour_end_points.next();
+                contour.close(&mut callback)
             }
         }
 
@@ -242,46 +214,10 @@ impl<'a> GlyfTable<'a> {
         try!(reader.jump(mem::size_of::<i16>() * 5).map_err(FontError::eof));
 
         loop {
-            let flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let flags = CompositeFlags::from_bits_truncate(flags);
-            let glyph_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-
-            let (arg0, arg1);
-            if flags.contains(ARG_1_AND_2_ARE_WORDS) {
-                arg0 = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
-                arg1 = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
-            } else {
-                arg0 = try!(reader.read_i8().map_err(FontError::eof)) as i16;
-                arg1 = try!(reader.read_i8().map_err(FontError::eof)) as i16;
-            }
-
-            let mut transform = Mat3x2::identity();
-            if flags.contains(ARGS_ARE_XY_VALUES) {
-                transform.m02 = arg0;
-                transform.m12 = arg1;
-            }
-
-            if flags.contains(WE_HAVE_A_SCALE) {
-                let scale = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
-                transform.m00 = scale;
-                transform.m11 = scale;
-            } else if flags.contains(WE_HAVE_AN_X_AND_Y_SCALE) {
-                transform.m00 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-                transform.m11 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-            } else if flags.contains(WE_HAVE_A_TWO_BY_TWO) {
-                transform.m00 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-                transform.m01 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-                transform.m10 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-                transform.m11 = F2Dot14(try!(reader.read_i16::<BigEndian>()
-                                                   .map_err(FontError::eof)));
-            }
+            let component = try!(Component::read(&mut reader));
+            let transform = component.transform;
 
-            if let Some(offset) = try!(loca_table.location_of(head_table, glyph_index)) {
+            if let Some(offset) = try!(loca_table.location_of(head_table, component.glyph_index)) {
                 let mut reader = self.table.bytes;
                 try!(reader.jump(offset as usize).map_err(FontError::eof));
                 try!(self.for_each_point_in_simple_glyph(reader, |point| {
@@ -289,7 +225,7 @@ impl<'a> GlyfTable<'a> {
                 }));
             }
 
-            if !flags.contains(MORE_COMPONENTS) {
+            if !component.flags.contains(MORE_COMPONENTS) {
                 break
             }
         }
@@ -299,18 +235,21 @@ impl<'a> GlyfTable<'a> {
 
     pub fn glyph_bounds(&self, head_table: &HeadTable, loca_table: &LocaTable, glyph_id: u16)
                         -> Result<GlyphBounds, FontError> {
+        self.glyph_header_bounds(head_table, loca_table, glyph_id)
+            .map(|bounds| bounds.unwrap_or_default())
+    }
+
+    /// Returns the bounding box stored in the glyph header, or `None` if the glyph has no
+    /// outlines. Only the header is read; the outline itself isn't decoded.
+    pub fn glyph_header_bounds(&self,
+                               head_table: &HeadTable,
+                               loca_table: &LocaTable,
+                               glyph_id: u16)
+                               -> Result<Option<GlyphBounds>, FontError> {
         let mut reader = self.table.bytes;
 
         match try!(loca_table.location_of(head_table, glyph_id)) {
-            None => {
-                // No outlines.
-                return Ok(GlyphBounds {
-                    left: 0,
-                    bottom: 0,
-                    right: 0,
-                    top: 0,
-                })
-            }
+            None => return Ok(None),
             Some(offset) => try!(reader.jump(offset as usize).map_err(FontError::eof)),
         }
 
@@ -321,15 +260,260 @@ impl<'a> GlyfTable<'a> {
         let y_min = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let x_max = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let y_max = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
-        Ok(GlyphBounds {
+        Ok(Some(GlyphBounds {
             left: x_min as i32,
             bottom: y_min as i32,
             right: x_max as i32,
             top: y_max as i32,
+        }))
+    }
+}
+
+// Decodes the points of the simple glyph that `reader` points to as they're stored, before any
+// implied on-curve points are added. Each point is passed to `callback` along with whether it's
+// on the curve and whether it's the last point of its contour.
+fn for_each_stored_point<F>(mut reader: &[u8], mut callback: F) -> Result<(), FontError>
+                            where F: FnMut(Point2D<i16>, bool, bool) {
+    // Determine how many contours we have.
+    let number_of_contours = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    if number_of_contours == 0 {
+        return Ok(())
+    }
+
+    // Skip over the rest of the header.
+    try!(reader.jump(mem::size_of::<i16>() * 4).map_err(FontError::eof));
+
+    // Find out how many points we have.
+    let mut endpoints_reader = reader;
+    try!(reader.jump(mem::size_of::<u16>() as usize * (number_of_contours as usize - 1))
+               .map_err(FontError::eof));
+    let number_of_points = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) + 1;
+
+    // Skip over hinting instructions.
+    let instruction_length = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    try!(reader.jump(instruction_length as usize).map_err(FontError::eof));
+
+    // Find the offsets of the X and Y coordinates.
+    let flags_reader = reader;
+    let x_coordinate_length = try!(calculate_size_of_x_coordinates(&mut reader,
+                                                                   number_of_points));
+
+    // Set up the streams.
+    let mut flag_parser = try!(FlagParser::new(flags_reader));
+    let mut x_coordinate_reader = reader;
+    try!(reader.jump(x_coordinate_length as usize).map_err(FontError::eof));
+    let mut y_coordinate_reader = reader;
+
+    // Now parse the contours.
+    let (mut position, mut point_index) = (Point2D::new(0, 0), 0);
+    for _ in 0..number_of_contours {
+        let contour_point_count =
+            try!(endpoints_reader.read_u16::<BigEndian>().map_err(FontError::eof)) -
+            point_index + 1;
+
+        for point_index_in_contour in 0..contour_point_count {
+            let flags = SimpleFlags::from_bits_truncate(*flag_parser.current);
+            try!(flag_parser.next());
+
+            let mut delta = Point2D::new(0, 0);
+            if flags.contains(X_SHORT_VECTOR) {
+                delta.x = try!(x_coordinate_reader.read_u8().map_err(FontError::eof)) as i16;
+                if !flags.contains(THIS_X_IS_SAME) {
+                    delta.x = -delta.x
+                }
+            } else if !flags.contains(THIS_X_IS_SAME) {
+                delta.x = try!(x_coordinate_reader.read_i16::<BigEndian>()
+                                                  .map_err(FontError::eof))
+            }
+            if flags.contains(Y_SHORT_VECTOR) {
+                delta.y = try!(y_coordinate_reader.read_u8().map_err(FontError::eof)) as i16;
+                if !flags.contains(THIS_Y_IS_SAME) {
+                    delta.y = -delta.y
+                }
+            } else if !flags.contains(THIS_Y_IS_SAME) {
+                delta.y = try!(y_coordinate_reader.read_i16::<BigEndian>()
+                                                  .map_err(FontError::eof))
+            }
+
+            position = position + delta;
+            callback(position,
+                     flags.contains(ON_CURVE),
+                     point_index_in_contour + 1 == contour_point_count);
+            point_index += 1;
+        }
+    }
+
+    Ok(())
+}
+
+// Turns the points of a contour, as they're stored, into `Point`s: inserts the on-curve points
+// implied between consecutive off-curve points and closes the contour.
+struct ContourBuilder {
+    position: Point2D<i16>,
+    first_on_curve_point: Option<Point2D<i16>>,
+    initial_off_curve_point: Option<Point2D<i16>>,
+    last_point_was_off_curve: bool,
+    point_index_in_contour: u16,
+}
+
+impl ContourBuilder {
+    fn new() -> ContourBuilder {
+        ContourBuilder {
+            position: Point2D::new(0, 0),
+            first_on_curve_point: None,
+            initial_off_curve_point: None,
+            last_point_was_off_curve: false,
+            point_index_in_contour: 0,
+        }
+    }
+
+    fn push<F>(&mut self, position: Point2D<i16>, on_curve: bool, callback: &mut F)
+               where F: FnMut(&Point) {
+        if self.last_point_was_off_curve && !on_curve {
+            let position = midpoint(self.position, position);
+
+            // An important edge case!
+            if self.first_on_curve_point.is_none() {
+                self.first_on_curve_point = Some(position)
+            }
+
+            callback(&Point {
+                position: position,
+                index_in_contour: self.point_index_in_contour,
+                kind: PointKind::OnCurve,
+            });
+            self.point_index_in_contour += 1
+        }
+
+        self.position = position;
+
+        if on_curve && self.first_on_curve_point.is_none() {
+            self.first_on_curve_point = Some(position)
+        }
+
+        // Sometimes the initial point is an off curve point. In that case, save it so we can emit
+        // it later when closing the path.
+        if !on_curve && self.first_on_curve_point.is_none() {
+            debug_assert!(self.initial_off_curve_point.is_none());
+            self.initial_off_curve_point = Some(position)
+        } else {
+            callback(&Point {
+                position: position,
+                kind: if on_curve {
+                    PointKind::OnCurve
+                } else {
+                    PointKind::QuadControl
+                },
+                index_in_contour: self.point_index_in_contour,
+            });
+            self.point_index_in_contour += 1
+        }
+
+        self.last_point_was_off_curve = !on_curve;
+    }
+
+    // Closes the contour, leaving the builder ready to start the next one.
+    fn close<F>(&mut self, callback: &mut F) where F: FnMut(&Point) {
+        // We're about to close the path. Emit the initial off curve point if there was one.
+        if let Some(initial_off_curve_point) = self.initial_off_curve_point {
+            if self.last_point_was_off_curve {
+                // Another important edge case!
+                let position = midpoint(self.position, initial_off_curve_point);
+                callback(&Point {
+                    position: position,
+                    index_in_contour: self.point_index_in_contour,
+                    kind: PointKind::OnCurve,
+                });
+                self.point_index_in_contour += 1
+            }
+
+            callback(&Point {
+                position: initial_off_curve_point,
+                kind: PointKind::QuadControl,
+                index_in_contour: self.point_index_in_contour,
+            });
+            self.point_index_in_contour += 1
+        }
+
+        // Close the path.
+        if let Some(first_on_curve_point) = self.first_on_curve_point {
+            callback(&Point {
+                position: first_on_curve_point,
+                kind: PointKind::OnCurve,
+                index_in_contour: self.point_index_in_contour,
+            })
+        }
+
+        *self = ContourBuilder {
+            position: self.position,
+            ..ContourBuilder::new()
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Component {
+    flags: CompositeFlags,
+    glyph_index: u16,
+    transform: Mat3x2,
+}
+
+impl Component {
+    fn read(reader: &mut &[u8]) -> Result<Component, FontError> {
+        let flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let flags = CompositeFlags::from_bits_truncate(flags);
+        let glyph_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let (arg0, arg1);
+        if flags.contains(ARG_1_AND_2_ARE_WORDS) {
+            arg0 = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+            arg1 = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        } else {
+            arg0 = try!(reader.read_i8().map_err(FontError::eof)) as i16;
+            arg1 = try!(reader.read_i8().map_err(FontError::eof)) as i16;
+        }
+
+        let mut transform = Mat3x2::identity();
+        if flags.contains(ARGS_ARE_XY_VALUES) {
+            transform.m02 = arg0;
+            transform.m12 = arg1;
+        }
+
+        if flags.contains(WE_HAVE_A_SCALE) {
+            let scale = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            transform.m00 = scale;
+            transform.m11 = scale;
+        } else if flags.contains(WE_HAVE_AN_X_AND_Y_SCALE) {
+            transform.m00 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            transform.m11 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+        } else if flags.contains(WE_HAVE_A_TWO_BY_TWO) {
+            transform.m00 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            transform.m01 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            transform.m10 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            transform.m11 = F2Dot14(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+        }
+
+        Ok(Component {
+            flags: flags,
+            glyph_index: glyph_index,
+            transform: transform,
         })
     }
 }
 
+// Returns the point halfway from `from` to `to`, rounding toward `from`.
+#[inline]
+fn midpoint(from: Point2D<i16>, to: Point2D<i16>) -> Point2D<i16> {
+    Point2D::new((from.x as i32 + (to.x as i32 - from.x as i32) / 2) as i16,
+                 (from.y as i32 + (to.y as i32 - from.y as i32) / 2) as i16)
+}
+
+// Rounds a varied coordinate back to font units, saturating at the limits of `i16`.
+#[inline]
+fn round_to_i16(value: f32) -> i16 {
+    value.round().max(i16::MIN as f32).min(i16::MAX as f32) as i16
+}
+
 // Given a reader pointing to the start of the list of flags, returns the size in bytes of the list
 // of X coordinates and positions the reader at the start of that list.
 #[inline]
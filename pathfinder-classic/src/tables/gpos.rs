@@ -0,0 +1,347 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use features::FeatureSettings;
+use font::{FontTable, PairAdjustment, PositionAdjustment};
+use std::cmp::Ordering;
+use std::mem;
+use tables::layout::{self, LayoutTable};
+use util::Jump;
+
+pub const TAG: u32 = ((b'G' as u32) << 24) |
+                      ((b'P' as u32) << 16) |
+                      ((b'O' as u32) << 8)  |
+                       (b'S' as u32);
+
+const LOOKUP_TYPE_PAIR_ADJUSTMENT: u16 = 2;
+const LOOKUP_TYPE_MARK_TO_BASE_ATTACHMENT: u16 = 4;
+const LOOKUP_TYPE_MARK_TO_MARK_ATTACHMENT: u16 = 6;
+const LOOKUP_TYPE_EXTENSION: u16 = 9;
+
+/// The size of a mark record: a class and an anchor offset.
+const MARK_RECORD_SIZE: usize = 4;
+
+// The value format bits for the placement and advance fields of a value record, in order. The
+// remaining four bits are offsets to device tables.
+const VALUE_FORMAT_X_PLACEMENT: u16 = 0x0001;
+const VALUE_FORMAT_Y_PLACEMENT: u16 = 0x0002;
+const VALUE_FORMAT_X_ADVANCE: u16 = 0x0004;
+const VALUE_FORMAT_Y_ADVANCE: u16 = 0x0008;
+
+/// The glyph positioning table.
+#[derive(Clone, Copy)]
+pub struct GposTable<'a> {
+    layout: LayoutTable<'a>,
+}
+
+impl<'a> GposTable<'a> {
+    pub fn new(table: FontTable) -> Result<GposTable, FontError> {
+        Ok(GposTable {
+            layout: try!(LayoutTable::new(table.bytes)),
+        })
+    }
+
+    /// Returns the adjustments that the pair adjustment lookups of the enabled features make to
+    /// the positions of two adjacent glyphs.
+    ///
+    /// Each lookup is applied in turn, and its adjustments are added to those of the previous
+    /// ones. Within a lookup, only the first subtable that covers the pair applies.
+    pub fn pair_adjustment(&self,
+                           first_glyph_id: u16,
+                           second_glyph_id: u16,
+                           features: &FeatureSettings)
+                           -> Result<PairAdjustment, FontError> {
+        let mut adjustment = PairAdjustment::default();
+        for lookup_index in try!(self.layout.lookup_indices(features)) {
+            let lookup = try!(self.layout.lookup(lookup_index, LOOKUP_TYPE_EXTENSION));
+            if lookup.lookup_type != LOOKUP_TYPE_PAIR_ADJUSTMENT {
+                continue
+            }
+
+            for subtable in lookup.subtables {
+                if let Some(subtable_adjustment) = try!(pair_adjustment(subtable,
+                                                                       first_glyph_id,
+                                                                       second_glyph_id)) {
+                    adjustment.first = add(adjustment.first, subtable_adjustment.first);
+                    adjustment.second = add(adjustment.second, subtable_adjustment.second);
+                    break
+                }
+            }
+        }
+        Ok(adjustment)
+    }
+
+    /// Returns the adjustments to the placement of each of the given glyphs that the
+    /// mark-to-base and mark-to-mark attachment lookups of the enabled features make, so that the
+    /// anchor of each combining mark meets the corresponding anchor of the glyph it attaches to.
+    ///
+    /// The adjustments are relative to where the pen puts each glyph when advancing by the given
+    /// advance widths. Glyphs that aren't attached to anything aren't adjusted.
+    pub fn mark_placements(&self,
+                           glyph_ids: &[u16],
+                           advance_widths: &[u16],
+                           features: &FeatureSettings)
+                           -> Result<Vec<PositionAdjustment>, FontError> {
+        // Find what each mark attaches to, and where, relative to the origin of that glyph. Later
+        // lookups override earlier ones.
+        let mut attachments = vec![None; glyph_ids.len()];
+        for lookup_index in try!(self.layout.lookup_indices(features)) {
+            let lookup = try!(self.layout.lookup(lookup_index, LOOKUP_TYPE_EXTENSION));
+            let to_base = match lookup.lookup_type {
+                LOOKUP_TYPE_MARK_TO_BASE_ATTACHMENT => true,
+                LOOKUP_TYPE_MARK_TO_MARK_ATTACHMENT => false,
+                _ => continue,
+            };
+
+            for (mark_index, attachment) in attachments.iter_mut().enumerate() {
+                for subtable in &lookup.subtables {
+                    if let Some(subtable_attachment) =
+                            try!(mark_attachment(subtable, glyph_ids, mark_index, to_base)) {
+                        *attachment = Some(subtable_attachment);
+                        break
+                    }
+                }
+            }
+        }
+
+        // Resolve the attachments in order, so that marks attached to other marks follow them.
+        let mut pen_positions = Vec::with_capacity(glyph_ids.len());
+        let mut pen_position = 0;
+        for glyph_index in 0..glyph_ids.len() {
+            pen_positions.push(pen_position);
+            pen_position += advance_widths.get(glyph_index).cloned().unwrap_or(0) as i32
+        }
+
+        let mut placements = vec![PositionAdjustment::default(); glyph_ids.len()];
+        for (mark_index, attachment) in attachments.into_iter().enumerate() {
+            let (target_index, x_offset, y_offset) = match attachment {
+                None => continue,
+                Some(attachment) => attachment,
+            };
+            let target_placement = placements[target_index];
+            let x_placement = target_placement.x_placement as i32 + pen_positions[target_index] -
+                pen_positions[mark_index] + x_offset;
+            let y_placement = target_placement.y_placement as i32 + y_offset;
+            placements[mark_index].x_placement = x_placement as i16;
+            placements[mark_index].y_placement = y_placement as i16;
+        }
+        Ok(placements)
+    }
+}
+
+/// Looks up a pair in a pair adjustment subtable, returning `None` if the subtable doesn't apply
+/// to it.
+fn pair_adjustment(subtable: &[u8], first_glyph_id: u16, second_glyph_id: u16)
+                   -> Result<Option<PairAdjustment>, FontError> {
+    let mut reader = subtable;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let value_format_1 = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let value_format_2 = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    let coverage = try!(layout::at(subtable, coverage_offset as usize));
+    let coverage_index = match try!(layout::coverage_index(coverage, first_glyph_id)) {
+        Some(coverage_index) => coverage_index,
+        None => return Ok(None),
+    };
+
+    let values_size = value_record_size(value_format_1) + value_record_size(value_format_2);
+    match format {
+        1 => {
+            // A set of pairs for each covered first glyph, sorted by the second glyph.
+            let pair_set_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if coverage_index >= pair_set_count {
+                return Err(FontError::UnexpectedEof)
+            }
+            try!(reader.jump(mem::size_of::<u16>() * coverage_index as usize)
+                       .map_err(FontError::eof));
+            let pair_set_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+            let mut pair_set = try!(layout::at(subtable, pair_set_offset as usize));
+            let pair_value_count = try!(pair_set.read_u16::<BigEndian>().map_err(FontError::eof));
+            let pair_value_record_size = mem::size_of::<u16>() + values_size;
+
+            let (mut low, mut high) = (0, pair_value_count);
+            while low < high {
+                let mid = (low + high) / 2;
+                let mut reader = pair_set;
+                try!(reader.jump(pair_value_record_size * mid as usize).map_err(FontError::eof));
+                let second_glyph = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                match second_glyph_id.cmp(&second_glyph) {
+                    Ordering::Less => high = mid,
+                    Ordering::Greater => low = mid + 1,
+                    Ordering::Equal => {
+                        return Ok(Some(PairAdjustment {
+                            first: try!(read_value_record(&mut reader, value_format_1)),
+                            second: try!(read_value_record(&mut reader, value_format_2)),
+                        }))
+                    }
+                }
+            }
+            Ok(None)
+        }
+        2 => {
+            // A two-dimensional array of values indexed by the classes of the two glyphs.
+            let class_def_1_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let class_def_2_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let class_1_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let class_2_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+            let class_def_1 = try!(layout::at(subtable, class_def_1_offset as usize));
+            let class_def_2 = try!(layout::at(subtable, class_def_2_offset as usize));
+            let class_1 = try!(layout::glyph_class(class_def_1, first_glyph_id));
+            let class_2 = try!(layout::glyph_class(class_def_2, second_glyph_id));
+            if class_1 >= class_1_count || class_2 >= class_2_count {
+                return Err(FontError::UnexpectedEof)
+            }
+
+            let record_index = class_1 as usize * class_2_count as usize + class_2 as usize;
+            try!(reader.jump(values_size * record_index).map_err(FontError::eof));
+            Ok(Some(PairAdjustment {
+                first: try!(read_value_record(&mut reader, value_format_1)),
+                second: try!(read_value_record(&mut reader, value_format_2)),
+            }))
+        }
+        _ => Err(FontError::UnknownFormat),
+    }
+}
+
+/// Attaches the mark at the given index to a preceding glyph with a mark-to-base or mark-to-mark
+/// attachment subtable, which share the same layout.
+///
+/// Returns the index of the glyph that the mark attaches to and the offset from the origin of
+/// that glyph to the origin of the mark, or `None` if the subtable doesn't apply. A mark attaches
+/// to the closest preceding glyph that isn't a mark of the subtable if `to_base` is true, and
+/// to the glyph right before it otherwise.
+fn mark_attachment(subtable: &[u8], glyph_ids: &[u16], mark_index: usize, to_base: bool)
+                   -> Result<Option<(usize, i32, i32)>, FontError> {
+    let mut reader = subtable;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if format != 1 {
+        return Err(FontError::UnknownFormat)
+    }
+    let mark_coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let base_coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let mark_class_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let mark_array_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let base_array_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    let mark_coverage = try!(layout::at(subtable, mark_coverage_offset as usize));
+    let mark_coverage_index =
+        match try!(layout::coverage_index(mark_coverage, glyph_ids[mark_index])) {
+            Some(mark_coverage_index) => mark_coverage_index,
+            None => return Ok(None),
+        };
+
+    // Find the glyph to attach to.
+    let mut target_index = mark_index;
+    loop {
+        if target_index == 0 {
+            return Ok(None)
+        }
+        target_index -= 1;
+        if !to_base ||
+                try!(layout::coverage_index(mark_coverage, glyph_ids[target_index])).is_none() {
+            break
+        }
+    }
+    let base_coverage = try!(layout::at(subtable, base_coverage_offset as usize));
+    let base_coverage_index =
+        match try!(layout::coverage_index(base_coverage, glyph_ids[target_index])) {
+            Some(base_coverage_index) => base_coverage_index,
+            None => return Ok(None),
+        };
+
+    // Look up the class and anchor of the mark.
+    let mark_array = try!(layout::at(subtable, mark_array_offset as usize));
+    let mut reader = mark_array;
+    let mark_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if mark_coverage_index >= mark_count {
+        return Err(FontError::UnexpectedEof)
+    }
+    try!(reader.jump(MARK_RECORD_SIZE * mark_coverage_index as usize).map_err(FontError::eof));
+    let mark_class = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let mark_anchor_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if mark_class >= mark_class_count {
+        return Err(FontError::UnexpectedEof)
+    }
+    let (mark_x, mark_y) = try!(read_anchor(try!(layout::at(mark_array,
+                                                            mark_anchor_offset as usize))));
+
+    // Look up the anchor of the glyph to attach to for that class. A null offset means that
+    // marks of that class don't attach to it.
+    let base_array = try!(layout::at(subtable, base_array_offset as usize));
+    let mut reader = base_array;
+    let base_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if base_coverage_index >= base_count {
+        return Err(FontError::UnexpectedEof)
+    }
+    let anchor_index = base_coverage_index as usize * mark_class_count as usize +
+        mark_class as usize;
+    try!(reader.jump(mem::size_of::<u16>() * anchor_index).map_err(FontError::eof));
+    let base_anchor_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if base_anchor_offset == 0 {
+        return Ok(None)
+    }
+    let (base_x, base_y) = try!(read_anchor(try!(layout::at(base_array,
+                                                            base_anchor_offset as usize))));
+
+    Ok(Some((target_index, base_x as i32 - mark_x as i32, base_y as i32 - mark_y as i32)))
+}
+
+/// Reads the coordinates of an anchor table. Contour points and device table adjustments, which
+/// only matter when hinting, are ignored.
+fn read_anchor(mut reader: &[u8]) -> Result<(i16, i16), FontError> {
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if format == 0 || format > 3 {
+        return Err(FontError::UnknownFormat)
+    }
+    let x = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    let y = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    Ok((x, y))
+}
+
+/// Returns the size in bytes of a value record with the given format: one 16-bit field for each
+/// bit set.
+fn value_record_size(value_format: u16) -> usize {
+    mem::size_of::<u16>() * (value_format & 0xff).count_ones() as usize
+}
+
+/// Reads a value record with the given format. Device table adjustments are ignored.
+fn read_value_record(reader: &mut &[u8], value_format: u16)
+                     -> Result<PositionAdjustment, FontError> {
+    let mut adjustment = PositionAdjustment::default();
+    for bit in 0..8 {
+        let flag = 1 << bit;
+        if value_format & flag == 0 {
+            continue
+        }
+        let value = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        match flag {
+            VALUE_FORMAT_X_PLACEMENT => adjustment.x_placement = value,
+            VALUE_FORMAT_Y_PLACEMENT => adjustment.y_placement = value,
+            VALUE_FORMAT_X_ADVANCE => adjustment.x_advance = value,
+            VALUE_FORMAT_Y_ADVANCE => adjustment.y_advance = value,
+            _ => {}
+        }
+    }
+    Ok(adjustment)
+}
+
+fn add(a: PositionAdjustment, b: PositionAdjustment) -> PositionAdjustment {
+    PositionAdjustment {
+        x_placement: a.x_placement.wrapping_add(b.x_placement),
+        y_placement: a.y_placement.wrapping_add(b.y_placement),
+        x_advance: a.x_advance.wrapping_add(b.x_advance),
+        y_advance: a.y_advance.wrapping_add(b.y_advance),
+    }
+}
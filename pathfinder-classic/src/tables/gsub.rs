@@ -0,0 +1,166 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use features::FeatureSettings;
+use font::FontTable;
+use std::mem;
+use tables::layout::{self, LayoutTable};
+use util::Jump;
+
+pub const TAG: u32 = ((b'G' as u32) << 24) |
+                      ((b'S' as u32) << 16) |
+                      ((b'U' as u32) << 8)  |
+                       (b'B' as u32);
+
+const LOOKUP_TYPE_SINGLE: u16 = 1;
+const LOOKUP_TYPE_LIGATURE: u16 = 4;
+const LOOKUP_TYPE_EXTENSION: u16 = 7;
+
+/// The glyph substitution table. Only single and ligature substitutions are supported.
+#[derive(Clone, Copy)]
+pub struct GsubTable<'a> {
+    layout: LayoutTable<'a>,
+}
+
+impl<'a> GsubTable<'a> {
+    pub fn new(table: FontTable) -> Result<GsubTable, FontError> {
+        Ok(GsubTable {
+            layout: try!(LayoutTable::new(table.bytes)),
+        })
+    }
+
+    /// Applies the single and ligature substitution lookups of the enabled features to the glyphs,
+    /// in place, and returns the cluster map: for each resulting glyph, the index of the first of
+    /// the original glyphs it replaces.
+    ///
+    /// Each lookup is applied to the whole run in turn. At each position, only the first
+    /// subtable that applies is used.
+    pub fn substitute(&self, glyph_ids: &mut Vec<u16>, features: &FeatureSettings)
+                      -> Result<Vec<usize>, FontError> {
+        let mut clusters: Vec<usize> = (0..glyph_ids.len()).collect();
+        for lookup_index in try!(self.layout.lookup_indices(features)) {
+            let lookup = try!(self.layout.lookup(lookup_index, LOOKUP_TYPE_EXTENSION));
+            match lookup.lookup_type {
+                LOOKUP_TYPE_SINGLE | LOOKUP_TYPE_LIGATURE => {}
+                _ => continue,
+            }
+
+            let mut glyph_index = 0;
+            while glyph_index < glyph_ids.len() {
+                for subtable in &lookup.subtables {
+                    let applied = if lookup.lookup_type == LOOKUP_TYPE_SINGLE {
+                        try!(substitute_single(subtable, &mut glyph_ids[glyph_index]))
+                    } else {
+                        match try!(ligature(subtable, &glyph_ids[glyph_index..])) {
+                            None => false,
+                            Some((ligature_glyph_id, component_count)) => {
+                                glyph_ids[glyph_index] = ligature_glyph_id;
+                                let components = (glyph_index + 1)..(glyph_index + component_count);
+                                glyph_ids.drain(components.clone());
+                                clusters.drain(components);
+                                true
+                            }
+                        }
+                    };
+                    if applied {
+                        break
+                    }
+                }
+                glyph_index += 1
+            }
+        }
+        Ok(clusters)
+    }
+}
+
+/// Replaces the given glyph using a single substitution subtable. Returns true if the subtable
+/// covers the glyph.
+fn substitute_single(subtable: &[u8], glyph_id: &mut u16) -> Result<bool, FontError> {
+    let mut reader = subtable;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let coverage = try!(layout::at(subtable, coverage_offset as usize));
+    let coverage_index = match try!(layout::coverage_index(coverage, *glyph_id)) {
+        Some(coverage_index) => coverage_index,
+        None => return Ok(false),
+    };
+
+    match format {
+        1 => {
+            // The same delta is added to every covered glyph.
+            let delta_glyph_id = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+            *glyph_id = glyph_id.wrapping_add(delta_glyph_id as u16)
+        }
+        2 => {
+            // Each covered glyph has its own substitute.
+            let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if coverage_index >= glyph_count {
+                return Err(FontError::UnexpectedEof)
+            }
+            try!(reader.jump(mem::size_of::<u16>() * coverage_index as usize)
+                       .map_err(FontError::eof));
+            *glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof))
+        }
+        _ => return Err(FontError::UnknownFormat),
+    }
+    Ok(true)
+}
+
+/// Finds the ligature that a ligature substitution subtable forms out of the glyphs at the start
+/// of the given run, returning its glyph ID and how many glyphs it replaces.
+///
+/// The ligatures starting with the same glyph are listed in order of preference, longest first.
+fn ligature(subtable: &[u8], glyph_ids: &[u16]) -> Result<Option<(u16, usize)>, FontError> {
+    let mut reader = subtable;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if format != 1 {
+        return Err(FontError::UnknownFormat)
+    }
+    let coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let ligature_set_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    let coverage = try!(layout::at(subtable, coverage_offset as usize));
+    let coverage_index = match try!(layout::coverage_index(coverage, glyph_ids[0])) {
+        Some(coverage_index) => coverage_index,
+        None => return Ok(None),
+    };
+    if coverage_index >= ligature_set_count {
+        return Err(FontError::UnexpectedEof)
+    }
+    try!(reader.jump(mem::size_of::<u16>() * coverage_index as usize).map_err(FontError::eof));
+    let ligature_set_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    let ligature_set = try!(layout::at(subtable, ligature_set_offset as usize));
+    let mut reader = ligature_set;
+    let ligature_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    'ligatures: for _ in 0..ligature_count {
+        let ligature_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut ligature_reader = try!(layout::at(ligature_set, ligature_offset as usize));
+        let ligature_glyph_id =
+            try!(ligature_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let component_count =
+            try!(ligature_reader.read_u16::<BigEndian>().map_err(FontError::eof)) as usize;
+        if component_count == 0 || component_count > glyph_ids.len() {
+            continue
+        }
+
+        // The first component is the covered glyph.
+        for &glyph_id in &glyph_ids[1..component_count] {
+            let component = try!(ligature_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if component != glyph_id {
+                continue 'ligatures
+            }
+        }
+        return Ok(Some((ligature_glyph_id, component_count)))
+    }
+    Ok(None)
+}
@@ -0,0 +1,398 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use euclid::Point2D;
+use font::FontTable;
+use std::cmp;
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'g' as u32) << 24) |
+                      ((b'v' as u32) << 16) |
+                      ((b'a' as u32) << 8)  |
+                       (b'r' as u32);
+
+/// Every glyph has four phantom points after its own, which vary its metrics.
+const PHANTOM_POINT_COUNT: usize = 4;
+
+const LONG_OFFSETS: u16 = 1 << 0;
+
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const TUPLE_COUNT_MASK: u16 = 0x0fff;
+
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+const TUPLE_INDEX_MASK: u16 = 0x0fff;
+
+const POINTS_ARE_WORDS: u8 = 0x80;
+const POINT_RUN_COUNT_MASK: u8 = 0x7f;
+
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+const DELTA_RUN_COUNT_MASK: u8 = 0x3f;
+
+/// The glyph variations table, which describes how the outlines in `glyf` move across the design
+/// space of a variable font.
+#[derive(Clone, Copy)]
+pub struct GvarTable<'a> {
+    table: FontTable<'a>,
+    axis_count: u16,
+    shared_tuples: &'a [u8],
+    shared_tuple_count: u16,
+    glyph_count: u16,
+    long_offsets: bool,
+    offsets: &'a [u8],
+    data_offset: u32,
+}
+
+impl<'a> GvarTable<'a> {
+    pub fn new(table: FontTable) -> Result<GvarTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let axis_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let shared_tuple_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let shared_tuples_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let data_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        let long_offsets = (flags & LONG_OFFSETS) != 0;
+        let offset_size = if long_offsets { mem::size_of::<u32>() } else { mem::size_of::<u16>() };
+        if reader.len() < (glyph_count as usize + 1) * offset_size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        let mut shared_tuples = table.bytes;
+        try!(shared_tuples.jump(shared_tuples_offset as usize).map_err(FontError::eof));
+        let shared_tuples_size = shared_tuple_count as usize * axis_count as usize *
+            mem::size_of::<i16>();
+        if shared_tuples.len() < shared_tuples_size {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(GvarTable {
+            table: table,
+            axis_count: axis_count,
+            shared_tuples: &shared_tuples[..shared_tuples_size],
+            shared_tuple_count: shared_tuple_count,
+            glyph_count: glyph_count,
+            long_offsets: long_offsets,
+            offsets: reader,
+            data_offset: data_offset,
+        })
+    }
+
+    /// Returns how far each of the given points of a glyph moves at the given normalized
+    /// variation coordinates, one per axis. Missing coordinates are taken to be zero.
+    ///
+    /// `points` are the glyph's points in `glyf`, and `contour_end_points` holds the index of the
+    /// last point of each contour. Points that a variation leaves untouched are moved by
+    /// interpolating between their neighbors in the same contour, so composite glyphs, whose
+    /// points are their components' offsets, should pass no contours.
+    pub fn glyph_deltas(&self,
+                        glyph_id: u16,
+                        coords: &[f32],
+                        points: &[Point2D<i16>],
+                        contour_end_points: &[u16])
+                        -> Result<Vec<Point2D<f32>>, FontError> {
+        let mut deltas = vec![Point2D::zero(); points.len()];
+        if glyph_id >= self.glyph_count {
+            return Ok(deltas)
+        }
+
+        // `new()` checked that all the offsets fit, so these reads can't fail.
+        let mut offsets = self.offsets;
+        let (start, end) = if self.long_offsets {
+            try!(offsets.jump(glyph_id as usize * mem::size_of::<u32>()).map_err(FontError::eof));
+            (offsets.read_u32::<BigEndian>().unwrap(), offsets.read_u32::<BigEndian>().unwrap())
+        } else {
+            try!(offsets.jump(glyph_id as usize * mem::size_of::<u16>()).map_err(FontError::eof));
+            (offsets.read_u16::<BigEndian>().unwrap() as u32 * 2,
+             offsets.read_u16::<BigEndian>().unwrap() as u32 * 2)
+        };
+        if end <= start {
+            return Ok(deltas)
+        }
+
+        let start = try!(self.data_offset.checked_add(start).ok_or(FontError::Failed)) as usize;
+        let end = try!(self.data_offset.checked_add(end).ok_or(FontError::Failed)) as usize;
+        if end > self.table.bytes.len() {
+            return Err(FontError::UnexpectedEof)
+        }
+        let glyph_data = &self.table.bytes[start..end];
+
+        let mut headers = glyph_data;
+        let tuple_count = try!(headers.read_u16::<BigEndian>().map_err(FontError::eof));
+        let serialized_data_offset = try!(headers.read_u16::<BigEndian>()
+                                                 .map_err(FontError::eof));
+        let mut serialized_data = glyph_data;
+        try!(serialized_data.jump(serialized_data_offset as usize).map_err(FontError::eof));
+
+        // Phantom points can be referenced, but we don't report how they move.
+        let point_count = points.len() + PHANTOM_POINT_COUNT;
+        let shared_point_numbers = if (tuple_count & SHARED_POINT_NUMBERS) != 0 {
+            try!(read_packed_point_numbers(&mut serialized_data, point_count))
+        } else {
+            None
+        };
+
+        let mut peak = vec![0.0; self.axis_count as usize];
+        let mut intermediate_start = vec![0.0; self.axis_count as usize];
+        let mut intermediate_end = vec![0.0; self.axis_count as usize];
+        for _ in 0..(tuple_count & TUPLE_COUNT_MASK) {
+            let variation_data_size = try!(headers.read_u16::<BigEndian>()
+                                                  .map_err(FontError::eof));
+            let tuple_index = try!(headers.read_u16::<BigEndian>().map_err(FontError::eof));
+
+            if (tuple_index & EMBEDDED_PEAK_TUPLE) != 0 {
+                try!(read_tuple(&mut headers, &mut peak));
+            } else {
+                let shared_tuple_index = tuple_index & TUPLE_INDEX_MASK;
+                if shared_tuple_index >= self.shared_tuple_count {
+                    return Err(FontError::Failed)
+                }
+                let mut shared_tuple = self.shared_tuples;
+                try!(shared_tuple.jump(shared_tuple_index as usize * self.axis_count as usize *
+                                       mem::size_of::<i16>()).map_err(FontError::eof));
+                try!(read_tuple(&mut shared_tuple, &mut peak));
+            }
+            let intermediate = (tuple_index & INTERMEDIATE_REGION) != 0;
+            if intermediate {
+                try!(read_tuple(&mut headers, &mut intermediate_start));
+                try!(read_tuple(&mut headers, &mut intermediate_end));
+            }
+
+            if serialized_data.len() < variation_data_size as usize {
+                return Err(FontError::UnexpectedEof)
+            }
+            let (mut data, rest) = serialized_data.split_at(variation_data_size as usize);
+            serialized_data = rest;
+
+            let region = if intermediate {
+                Some((&intermediate_start[..], &intermediate_end[..]))
+            } else {
+                None
+            };
+            let scalar = tuple_scalar(coords, &peak, region);
+            if scalar == 0.0 {
+                continue
+            }
+
+            let private_point_numbers = if (tuple_index & PRIVATE_POINT_NUMBERS) != 0 {
+                try!(read_packed_point_numbers(&mut data, point_count))
+            } else {
+                None
+            };
+            let point_numbers = if (tuple_index & PRIVATE_POINT_NUMBERS) != 0 {
+                private_point_numbers.as_ref()
+            } else {
+                shared_point_numbers.as_ref()
+            };
+
+            // With no point numbers, the tuple covers every point.
+            let delta_count = match point_numbers {
+                Some(point_numbers) => point_numbers.len(),
+                None => point_count,
+            };
+            let x_deltas = try!(read_packed_deltas(&mut data, delta_count));
+            let y_deltas = try!(read_packed_deltas(&mut data, delta_count));
+
+            let mut tuple_deltas = vec![None; points.len()];
+            for delta_index in 0..delta_count {
+                let point_index = match point_numbers {
+                    Some(point_numbers) => point_numbers[delta_index] as usize,
+                    None => delta_index,
+                };
+                if point_index < points.len() {
+                    tuple_deltas[point_index] = Some(Point2D::new(x_deltas[delta_index] as f32,
+                                                                  y_deltas[delta_index] as f32))
+                }
+            }
+            if point_numbers.is_some() {
+                interpolate_untouched_points(points, contour_end_points, &mut tuple_deltas)
+            }
+
+            for (delta, tuple_delta) in deltas.iter_mut().zip(tuple_deltas.iter()) {
+                if let Some(tuple_delta) = *tuple_delta {
+                    *delta = *delta + tuple_delta * scalar
+                }
+            }
+        }
+
+        Ok(deltas)
+    }
+}
+
+fn read_tuple(reader: &mut &[u8], tuple: &mut [f32]) -> Result<(), FontError> {
+    for coord in tuple {
+        *coord = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)) as f32 / 16384.0
+    }
+    Ok(())
+}
+
+/// Returns how much of a tuple's deltas apply at the given coordinates.
+///
+/// Each axis contributes a factor that falls off linearly from 1 at the peak to 0 at the edges of
+/// the region: the intermediate region if there is one, or else the span between the default and
+/// the peak.
+fn tuple_scalar(coords: &[f32], peak: &[f32], intermediate: Option<(&[f32], &[f32])>) -> f32 {
+    let mut scalar = 1.0;
+    for (axis_index, &peak) in peak.iter().enumerate() {
+        let coord = coords.get(axis_index).map_or(0.0, |&coord| coord.max(-1.0).min(1.0));
+        if peak == 0.0 || coord == peak {
+            continue
+        }
+
+        let (start, end) = match intermediate {
+            Some((start, end)) => (start[axis_index], end[axis_index]),
+            None => (peak.min(0.0), peak.max(0.0)),
+        };
+
+        // Malformed regions are ignored, as they are by other implementations.
+        if start > peak || peak > end || (start < 0.0 && end > 0.0) {
+            continue
+        }
+
+        if coord < start || coord > end {
+            return 0.0
+        }
+        scalar *= if coord < peak {
+            (coord - start) / (peak - start)
+        } else {
+            (end - coord) / (end - peak)
+        }
+    }
+    scalar
+}
+
+/// Reads a list of point numbers, or returns `None` if the list covers every point.
+fn read_packed_point_numbers(reader: &mut &[u8], point_count: usize)
+                             -> Result<Option<Vec<u16>>, FontError> {
+    let first_byte = try!(reader.read_u8().map_err(FontError::eof));
+    let count = if first_byte == 0 {
+        return Ok(None)
+    } else if (first_byte & POINTS_ARE_WORDS) == 0 {
+        first_byte as usize
+    } else {
+        let second_byte = try!(reader.read_u8().map_err(FontError::eof));
+        (((first_byte & POINT_RUN_COUNT_MASK) as usize) << 8) | second_byte as usize
+    };
+
+    // No point may be listed twice, so there can't be more numbers than points.
+    if count > point_count {
+        return Err(FontError::Failed)
+    }
+
+    let mut point_numbers = Vec::with_capacity(count);
+    let mut point_number = 0u16;
+    while point_numbers.len() < count {
+        let control = try!(reader.read_u8().map_err(FontError::eof));
+        let run_count = (control & POINT_RUN_COUNT_MASK) as usize + 1;
+        for _ in 0..cmp::min(run_count, count - point_numbers.len()) {
+            let delta = if (control & POINTS_ARE_WORDS) != 0 {
+                try!(reader.read_u16::<BigEndian>().map_err(FontError::eof))
+            } else {
+                try!(reader.read_u8().map_err(FontError::eof)) as u16
+            };
+            point_number = try!(point_number.checked_add(delta).ok_or(FontError::Failed));
+            point_numbers.push(point_number)
+        }
+    }
+
+    Ok(Some(point_numbers))
+}
+
+fn read_packed_deltas(reader: &mut &[u8], count: usize) -> Result<Vec<i16>, FontError> {
+    let mut deltas = Vec::with_capacity(count);
+    while deltas.len() < count {
+        let control = try!(reader.read_u8().map_err(FontError::eof));
+        let run_count = (control & DELTA_RUN_COUNT_MASK) as usize + 1;
+        for _ in 0..cmp::min(run_count, count - deltas.len()) {
+            let delta = if (control & DELTAS_ARE_ZERO) != 0 {
+                0
+            } else if (control & DELTAS_ARE_WORDS) != 0 {
+                try!(reader.read_i16::<BigEndian>().map_err(FontError::eof))
+            } else {
+                try!(reader.read_i8().map_err(FontError::eof)) as i16
+            };
+            deltas.push(delta)
+        }
+    }
+    Ok(deltas)
+}
+
+/// Fills in the deltas of the points in each contour that a tuple doesn't mention, by
+/// interpolating between the nearest points before and after them that it does.
+///
+/// A contour with one touched point moves rigidly with it; one with none doesn't move.
+fn interpolate_untouched_points(points: &[Point2D<i16>],
+                                contour_end_points: &[u16],
+                                deltas: &mut [Option<Point2D<f32>>]) {
+    let mut contour_start = 0;
+    for &contour_end in contour_end_points {
+        let contour_end = contour_end as usize;
+        if contour_end < contour_start || contour_end >= points.len() {
+            break
+        }
+        let contour = contour_start..(contour_end + 1);
+        contour_start = contour_end + 1;
+
+        let touched: Vec<usize> = contour.clone().filter(|&index| deltas[index].is_some())
+                                                 .collect();
+        if touched.is_empty() || touched.len() == contour.len() {
+            continue
+        }
+
+        for (touched_index, &prev) in touched.iter().enumerate() {
+            let next = touched[(touched_index + 1) % touched.len()];
+            let (prev_delta, next_delta) = (deltas[prev].unwrap(), deltas[next].unwrap());
+
+            // Walk forward from `prev` to `next`, wrapping around the contour.
+            let mut index = prev;
+            loop {
+                index = if index == contour.end - 1 { contour.start } else { index + 1 };
+                if index == next {
+                    break
+                }
+                deltas[index] = Some(Point2D::new(
+                    interpolate_delta(points[index].x, points[prev].x, points[next].x,
+                                      prev_delta.x, next_delta.x),
+                    interpolate_delta(points[index].y, points[prev].y, points[next].y,
+                                      prev_delta.y, next_delta.y)))
+            }
+        }
+    }
+}
+
+/// Interpolates the delta of a coordinate from those of two reference coordinates. Coordinates
+/// outside the references take the delta of the nearer one.
+fn interpolate_delta(coord: i16, prev: i16, next: i16, prev_delta: f32, next_delta: f32) -> f32 {
+    let (low, high, low_delta, high_delta) = if prev <= next {
+        (prev, next, prev_delta, next_delta)
+    } else {
+        (next, prev, next_delta, prev_delta)
+    };
+    if coord <= low {
+        low_delta
+    } else if coord >= high {
+        high_delta
+    } else {
+        let t = (coord as f32 - low as f32) / (high as f32 - low as f32);
+        low_delta + (high_delta - low_delta) * t
+    }
+}
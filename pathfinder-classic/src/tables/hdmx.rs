@@ -0,0 +1,77 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use util::Jump;
+
+pub const TAG: u32 = ((b'h' as u32) << 24) |
+                      ((b'd' as u32) << 16) |
+                      ((b'm' as u32) << 8)  |
+                       (b'x' as u32);
+
+/// The size of the `pixelSize` and `maxWidth` fields that precede the widths in each record.
+const DEVICE_RECORD_HEADER_SIZE: usize = 2;
+
+/// The horizontal device metrics table, which stores grid-fitted advance widths in whole pixels
+/// for a handful of pixel sizes.
+#[derive(Clone, Copy)]
+pub struct HdmxTable<'a> {
+    records: &'a [u8],
+    record_count: u16,
+    record_size: usize,
+}
+
+impl<'a> HdmxTable<'a> {
+    pub fn new(table: FontTable) -> Result<HdmxTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version != 0 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let record_count = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let record_size = try!(reader.read_i32::<BigEndian>().map_err(FontError::eof));
+        if record_count < 0 || (record_size as usize) < DEVICE_RECORD_HEADER_SIZE {
+            return Err(FontError::Failed)
+        }
+
+        Ok(HdmxTable {
+            records: reader,
+            record_count: record_count as u16,
+            record_size: record_size as usize,
+        })
+    }
+
+    /// Returns the advance width of the given glyph in pixels at the given size in pixels per em,
+    /// or `None` if the table has no record for that size or the glyph is out of range.
+    pub fn advance(&self, glyph_id: u16, ppem: u8) -> Option<u8> {
+        // Records are padded to a multiple of four bytes, so this doesn't catch every glyph ID
+        // that's out of range.
+        if glyph_id as usize >= self.record_size - DEVICE_RECORD_HEADER_SIZE {
+            return None
+        }
+
+        for record_index in 0..self.record_count as usize {
+            let mut reader = self.records;
+            if reader.jump(record_index * self.record_size).is_err() {
+                return None
+            }
+            if reader.len() < self.record_size {
+                return None
+            }
+            if reader[0] == ppem {
+                return Some(reader[DEVICE_RECORD_HEADER_SIZE + glyph_id as usize])
+            }
+        }
+        None
+    }
+}
@@ -10,7 +10,7 @@
 
 use byteorder::{BigEndian, ReadBytesExt};
 use error::FontError;
-use font::FontTable;
+use font::{FontTable, MacStyle};
 use outline::GlyphBounds;
 use std::mem;
 use util::Jump;
@@ -22,11 +22,18 @@ pub const TAG: u32 = ((b'h' as u32) << 24) |
 
 const MAGIC_NUMBER: u32 = 0x5f0f3cf5;
 
+/// The number of seconds between 1904-01-01, the epoch of `LONGDATETIME`, and 1970-01-01.
+const UNIX_EPOCH_IN_LONG_DATE_TIME: i64 = 2082844800;
+
 #[derive(Clone, Debug)]
 pub struct HeadTable {
     pub units_per_em: u16,
     pub index_to_loc_format: i16,
     pub max_glyph_bounds: GlyphBounds,
+    pub mac_style: MacStyle,
+    pub lowest_rec_ppem: u16,
+    created: i64,
+    modified: i64,
 }
 
 impl HeadTable {
@@ -51,8 +58,11 @@ impl HeadTable {
         try!(reader.jump(mem::size_of::<u16>()).map_err(FontError::eof));
         let units_per_em = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
 
+        // Read the creation and modification dates.
+        let created = try!(reader.read_i64::<BigEndian>().map_err(FontError::eof));
+        let modified = try!(reader.read_i64::<BigEndian>().map_err(FontError::eof));
+
         // Read the maximum bounds.
-        try!(reader.jump(mem::size_of::<i64>() * 2).map_err(FontError::eof));
         let x_min = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let y_min = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let x_max = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
@@ -64,9 +74,11 @@ impl HeadTable {
             top: y_max as i32,
         };
 
-        // Read the index-to-location format.
-        try!(reader.jump(mem::size_of::<u16>() * 2 + mem::size_of::<i16>())
-                   .map_err(FontError::eof));
+        let mac_style = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let lowest_rec_ppem = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // Skip the deprecated font direction hint, and read the index-to-location format.
+        try!(reader.jump(mem::size_of::<i16>()).map_err(FontError::eof));
         let index_to_loc_format = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
 
         // Check the glyph data format.
@@ -79,6 +91,24 @@ impl HeadTable {
             units_per_em: units_per_em,
             index_to_loc_format: index_to_loc_format,
             max_glyph_bounds: max_glyph_bounds,
+            mac_style: MacStyle::from_bits_truncate(mac_style),
+            lowest_rec_ppem: lowest_rec_ppem,
+            created: created,
+            modified: modified,
         })
     }
+
+    /// The creation date of the font, in seconds since the Unix epoch, or `None` if it's out of
+    /// range.
+    #[inline]
+    pub fn created(&self) -> Option<i64> {
+        self.created.checked_sub(UNIX_EPOCH_IN_LONG_DATE_TIME)
+    }
+
+    /// The date the font was last modified, in seconds since the Unix epoch, or `None` if it's out
+    /// of range.
+    #[inline]
+    pub fn modified(&self) -> Option<i64> {
+        self.modified.checked_sub(UNIX_EPOCH_IN_LONG_DATE_TIME)
+    }
 }
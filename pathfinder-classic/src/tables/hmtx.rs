@@ -11,6 +11,7 @@
 use byteorder::{BigEndian, ReadBytesExt};
 use error::FontError;
 use font::FontTable;
+use std::cmp;
 use std::mem;
 use tables::hhea::HheaTable;
 use util::Jump;
@@ -20,6 +21,9 @@ pub const TAG: u32 = ((b'h' as u32) << 24) |
                       ((b't' as u32) << 8)  |
                        (b'x' as u32);
 
+/// The size of an advance width and left side bearing pair.
+const LONG_HOR_METRIC_SIZE: usize = 4;
+
 #[derive(Clone, Copy)]
 pub struct HmtxTable<'a> {
     table: FontTable<'a>,
@@ -34,27 +38,44 @@ impl<'a> HmtxTable<'a> {
 
     pub fn metrics_for_glyph(&self, hhea_table: &HheaTable, glyph_id: u16)
                              -> Result<HorizontalMetrics, FontError> {
-        let mut reader = self.table.bytes;
+        Ok(HorizontalMetrics {
+            advance_width: try!(self.advance_width(hhea_table, glyph_id)),
+            lsb: try!(self.left_side_bearing(hhea_table, glyph_id)),
+        })
+    }
 
-        // Read the advance width.
-        let advance_width;
-        if glyph_id < hhea_table.number_of_h_metrics {
-            try!(reader.jump(mem::size_of::<u16>() * 2 * glyph_id as usize).map_err(FontError::eof));
-            advance_width = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof))
-        } else {
-            try!(reader.jump(mem::size_of::<u16>() * 2 *
-                             (hhea_table.number_of_h_metrics - 1) as usize).map_err(FontError::eof));
-            advance_width = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            try!(reader.jump(mem::size_of::<i16>() * glyph_id as usize).map_err(FontError::eof));
+    /// Returns the advance width of the given glyph.
+    ///
+    /// Only the first `numberOfHMetrics` glyphs have an advance width of their own. The glyphs
+    /// after them, typically a monospaced run at the end of the font, all share the last one.
+    pub fn advance_width(&self, hhea_table: &HheaTable, glyph_id: u16) -> Result<u16, FontError> {
+        if hhea_table.number_of_h_metrics == 0 {
+            return Err(FontError::UnexpectedEof)
         }
+        let metric_index = cmp::min(glyph_id, hhea_table.number_of_h_metrics - 1);
 
-        // Read the left-side bearing.
-        let lsb = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let mut reader = self.table.bytes;
+        try!(reader.jump(LONG_HOR_METRIC_SIZE * metric_index as usize).map_err(FontError::eof));
+        reader.read_u16::<BigEndian>().map_err(FontError::eof)
+    }
 
-        Ok(HorizontalMetrics {
-            advance_width: advance_width,
-            lsb: lsb,
-        })
+    /// Returns the left side bearing of the given glyph.
+    ///
+    /// The glyphs past the first `numberOfHMetrics` store their left side bearings in an array
+    /// of their own after the last advance width.
+    pub fn left_side_bearing(&self, hhea_table: &HheaTable, glyph_id: u16)
+                             -> Result<i16, FontError> {
+        let number_of_h_metrics = hhea_table.number_of_h_metrics as usize;
+        let offset = if (glyph_id as usize) < number_of_h_metrics {
+            LONG_HOR_METRIC_SIZE * glyph_id as usize + mem::size_of::<u16>()
+        } else {
+            LONG_HOR_METRIC_SIZE * number_of_h_metrics +
+                mem::size_of::<i16>() * (glyph_id as usize - number_of_h_metrics)
+        };
+
+        let mut reader = self.table.bytes;
+        try!(reader.jump(offset).map_err(FontError::eof));
+        reader.read_i16::<BigEndian>().map_err(FontError::eof)
     }
 }
 
@@ -28,9 +28,16 @@ bitflags! {
     }
 }
 
+/// The size of a subtable header: its version, length, and coverage.
+const SUBTABLE_HEADER_SIZE: usize = 6;
+
+/// The size of a kerning pair in a format 0 subtable.
+const KERNING_PAIR_SIZE: usize = 6;
+
 #[derive(Clone, Copy)]
 pub struct KernTable<'a> {
-    horizontal_table: &'a [u8],
+    n_tables: u16,
+    subtables: &'a [u8],
 }
 
 impl<'a> KernTable<'a> {
@@ -42,60 +49,195 @@ impl<'a> KernTable<'a> {
         }
 
         let n_tables = try!(kern_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        let mut horizontal_table = None;
-        for _ in 0..n_tables {
-            let mut table_reader = kern_reader;
-            let _version = try!(table_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let length = try!(table_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let coverage = try!(table_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let coverage_flags = Coverage::from_bits_truncate(coverage);
-
-            if coverage_flags.contains(HORIZONTAL) && !coverage_flags.contains(MINIMUM) &&
-                    !coverage_flags.contains(CROSS_STREAM) && (coverage >> 8) == 0 {
-                let length = length as usize - mem::size_of::<u16>() * 3;
-                horizontal_table = Some(&table_reader[0..length]);
-                break
-            }
+        let kern_table = KernTable {
+            n_tables: n_tables,
+            subtables: kern_reader,
+        };
 
-            try!(kern_reader.jump(length as usize).map_err(FontError::eof));
+        // Make sure that there's at least one subtable we know how to apply.
+        let mut horizontal_subtable_found = false;
+        for subtable in kern_table.subtables() {
+            let (coverage, _) = try!(subtable);
+            horizontal_subtable_found = horizontal_subtable_found ||
+                (is_applicable(coverage) && (coverage >> 8 == 0 || coverage >> 8 == 2))
+        }
+        if !horizontal_subtable_found {
+            return Err(FontError::UnknownFormat)
         }
 
-        match horizontal_table {
-            Some(horizontal_table) => {
-                Ok(KernTable {
-                    horizontal_table: horizontal_table,
-                })
+        Ok(kern_table)
+    }
+
+    /// Returns the horizontal kerning between the given two glyph IDs in font units.
+    ///
+    /// The values of every horizontal subtable listing the pair are added up in order, except
+    /// that a subtable with the override flag set replaces the value accumulated so far.
+    /// Cross-stream subtables and subtables of minimum values don't contribute, and neither do
+    /// malformed subtables or subtables in formats we don't support.
+    pub fn kerning(&self, left_glyph_id: u16, right_glyph_id: u16) -> i16 {
+        let mut kerning = 0i16;
+        for subtable in self.subtables() {
+            let (coverage, subtable) = match subtable {
+                Ok(subtable) if is_applicable(subtable.0) => subtable,
+                Ok(_) => continue,
+                Err(_) => break,
+            };
+            let value = match coverage >> 8 {
+                0 => format_0_kerning(subtable, left_glyph_id, right_glyph_id),
+                2 => format_2_kerning(subtable, left_glyph_id, right_glyph_id),
+                _ => continue,
+            };
+            kerning = match value {
+                Ok(Some(value)) if Coverage::from_bits_truncate(coverage).contains(OVERRIDE) => {
+                    value
+                }
+                Ok(Some(value)) => kerning.wrapping_add(value),
+                Ok(None) | Err(_) => kerning,
             }
-            None => Err(FontError::UnknownFormat),
         }
+        kerning
     }
 
-    pub fn kerning_for_glyph_pair(&self, left_glyph_id: u16, right_glyph_id: u16)
-                                  -> Result<i16, FontError> {
-        let mut table_reader = self.horizontal_table;
-        let n_pairs = try!(table_reader.read_u16::<BigEndian>().map_err(FontError::eof));
-        try!(table_reader.jump(mem::size_of::<[u16; 3]>()).map_err(FontError::eof));
-
-        let (mut low, mut high) = (0, n_pairs as u32);
-        while low < high {
-            let mut reader = table_reader;
-            let mid = (low + high) / 2;
-
-            try!(reader.jump(mid as usize * mem::size_of::<[u16; 3]>()).map_err(FontError::eof));
-            let left = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let right = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
-            let value = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
-
-            if left_glyph_id < left || (left_glyph_id == left && right_glyph_id < right) {
-                high = mid
-            } else if left_glyph_id > left || (left_glyph_id == left && right_glyph_id > right) {
-                low = mid + 1
-            } else {
-                return Ok(value)
+    /// Returns the raw coverage field and the contents after the header of each subtable.
+    fn subtables(&self) -> Subtables<'a> {
+        Subtables {
+            reader: self.subtables,
+            remaining: self.n_tables,
+        }
+    }
+}
+
+struct Subtables<'a> {
+    reader: &'a [u8],
+    remaining: u16,
+}
+
+impl<'a> Iterator for Subtables<'a> {
+    type Item = Result<(u16, &'a [u8]), FontError>;
+
+    fn next(&mut self) -> Option<Result<(u16, &'a [u8]), FontError>> {
+        if self.remaining == 0 {
+            return None
+        }
+        self.remaining -= 1;
+
+        let mut table_reader = self.reader;
+        let subtable = table_reader.read_u16::<BigEndian>().and_then(|_version| {
+            let length = try!(table_reader.read_u16::<BigEndian>()) as usize;
+            let coverage = try!(table_reader.read_u16::<BigEndian>());
+            Ok((length, coverage))
+        });
+        let (length, coverage) = match subtable {
+            Ok(subtable) => subtable,
+            Err(error) => {
+                self.remaining = 0;
+                return Some(Err(FontError::eof(error)))
             }
+        };
+
+        // Large format 0 subtables overflow their 16-bit length, so the last subtable is allowed
+        // to extend to the end of the table.
+        let length = if self.remaining == 0 {
+            self.reader.len()
+        } else {
+            length
+        };
+        if length < SUBTABLE_HEADER_SIZE || length > self.reader.len() {
+            self.remaining = 0;
+            return Some(Err(FontError::UnexpectedEof))
         }
 
-        Ok(0)
+        let contents = &self.reader[SUBTABLE_HEADER_SIZE..length];
+        self.reader = &self.reader[length..];
+        Some(Ok((coverage, contents)))
     }
 }
 
+/// Returns true if a subtable with the given coverage holds horizontal kerning values.
+fn is_applicable(coverage: u16) -> bool {
+    let coverage = Coverage::from_bits_truncate(coverage);
+    coverage.contains(HORIZONTAL) && !coverage.contains(MINIMUM) &&
+        !coverage.contains(CROSS_STREAM)
+}
+
+/// Looks up a pair in a format 0 subtable, an ordered list of pairs searched by binary search.
+fn format_0_kerning(mut table_reader: &[u8], left_glyph_id: u16, right_glyph_id: u16)
+                    -> Result<Option<i16>, FontError> {
+    let n_pairs = try!(table_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    try!(table_reader.jump(mem::size_of::<[u16; 3]>()).map_err(FontError::eof));
+
+    let (mut low, mut high) = (0, n_pairs as u32);
+    while low < high {
+        let mut reader = table_reader;
+        let mid = (low + high) / 2;
+
+        try!(reader.jump(mid as usize * KERNING_PAIR_SIZE).map_err(FontError::eof));
+        let left = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let right = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let value = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+
+        if left_glyph_id < left || (left_glyph_id == left && right_glyph_id < right) {
+            high = mid
+        } else if left_glyph_id > left || (left_glyph_id == left && right_glyph_id > right) {
+            low = mid + 1
+        } else {
+            return Ok(Some(value))
+        }
+    }
+
+    Ok(None)
+}
+
+/// Looks up a pair in a format 2 subtable, a two-dimensional array of values indexed by the
+/// classes of the left and right glyphs.
+///
+/// The class tables store byte offsets rather than class numbers: left classes are offsets from
+/// the start of the subtable to a row of the array, and right classes are offsets within a row.
+/// Glyphs that no class table covers aren't kerned.
+fn format_2_kerning(subtable: &[u8], left_glyph_id: u16, right_glyph_id: u16)
+                    -> Result<Option<i16>, FontError> {
+    let mut reader = subtable;
+    let _row_width = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let left_class_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let right_class_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let array_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+    let left_offset = match try!(format_2_class(subtable, left_class_offset, left_glyph_id)) {
+        Some(left_offset) if left_offset >= array_offset => left_offset,
+        Some(_) | None => return Ok(None),
+    };
+    let right_offset = match try!(format_2_class(subtable, right_class_offset, right_glyph_id)) {
+        Some(right_offset) => right_offset,
+        None => return Ok(None),
+    };
+
+    let mut reader = subtable;
+    let value_offset = left_offset as usize + right_offset as usize;
+    if value_offset < SUBTABLE_HEADER_SIZE {
+        return Err(FontError::UnexpectedEof)
+    }
+    try!(reader.jump(value_offset - SUBTABLE_HEADER_SIZE).map_err(FontError::eof));
+    reader.read_i16::<BigEndian>().map_err(FontError::eof).map(Some)
+}
+
+/// Returns the value that a format 2 class table at the given offset from the start of the
+/// subtable assigns to a glyph, or `None` if the glyph is outside its range.
+fn format_2_class(subtable: &[u8], class_table_offset: u16, glyph_id: u16)
+                  -> Result<Option<u16>, FontError> {
+    let class_table_offset = class_table_offset as usize;
+    if class_table_offset < SUBTABLE_HEADER_SIZE {
+        return Err(FontError::UnexpectedEof)
+    }
+
+    let mut reader = subtable;
+    try!(reader.jump(class_table_offset - SUBTABLE_HEADER_SIZE).map_err(FontError::eof));
+    let first_glyph = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let n_glyphs = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if glyph_id < first_glyph || glyph_id - first_glyph >= n_glyphs {
+        return Ok(None)
+    }
+
+    try!(reader.jump(mem::size_of::<u16>() * (glyph_id - first_glyph) as usize)
+               .map_err(FontError::eof));
+    reader.read_u16::<BigEndian>().map_err(FontError::eof).map(Some)
+}
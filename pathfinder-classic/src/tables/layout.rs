@@ -0,0 +1,299 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The structures shared by the OpenType layout tables, `GPOS` and `GSUB`: the script, feature,
+//! and lookup lists, coverage tables, and class definitions.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use features::FeatureSettings;
+use std::cmp::Ordering;
+use std::mem;
+use util::Jump;
+
+/// The size of a script, language system, or feature record: a tag and a 16-bit offset.
+const TAG_RECORD_SIZE: usize = 6;
+
+/// The size of a range record in a coverage table or class definition.
+const RANGE_RECORD_SIZE: usize = 6;
+
+/// The feature index that means a language system has no required feature.
+const NO_REQUIRED_FEATURE: u16 = 0xffff;
+
+/// The header of a `GPOS` or `GSUB` table.
+#[derive(Clone, Copy)]
+pub struct LayoutTable<'a> {
+    table: &'a [u8],
+    script_list_offset: u16,
+    feature_list_offset: u16,
+    lookup_list_offset: u16,
+}
+
+/// A lookup, with its extension subtables, if any, resolved.
+pub struct Lookup<'a> {
+    pub lookup_type: u16,
+    pub lookup_flag: u16,
+    /// Each subtable, starting at its format and extending to the end of the table.
+    pub subtables: Vec<&'a [u8]>,
+}
+
+impl<'a> LayoutTable<'a> {
+    pub fn new(table: &[u8]) -> Result<LayoutTable, FontError> {
+        let mut reader = table;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let script_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let feature_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let lookup_list_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        Ok(LayoutTable {
+            table: table,
+            script_list_offset: script_list_offset,
+            feature_list_offset: feature_list_offset,
+            lookup_list_offset: lookup_list_offset,
+        })
+    }
+
+    /// Returns the indices of the lookups of the enabled features and of the required features,
+    /// sorted and without duplicates, which is the order in which they must be applied.
+    ///
+    /// Only the features that the default language system of some script enables are considered,
+    /// so the lookups of every script are returned. They rarely overlap, since each script covers
+    /// its own glyphs.
+    pub fn lookup_indices(&self, features: &FeatureSettings) -> Result<Vec<u16>, FontError> {
+        let feature_list = try!(at(self.table, self.feature_list_offset as usize));
+        let mut feature_records = feature_list;
+        let feature_count = try!(feature_records.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut lookup_indices = vec![];
+        for (feature_index, required) in try!(self.default_feature_indices()) {
+            if feature_index >= feature_count {
+                return Err(FontError::UnexpectedEof)
+            }
+
+            let mut reader = feature_records;
+            try!(reader.jump(TAG_RECORD_SIZE * feature_index as usize).map_err(FontError::eof));
+            let feature_tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            if !required && !features.is_enabled(feature_tag) {
+                continue
+            }
+
+            let feature_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let mut reader = try!(at(feature_list, feature_offset as usize));
+            let _feature_params_offset =
+                try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let lookup_index_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            for _ in 0..lookup_index_count {
+                lookup_indices.push(try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)))
+            }
+        }
+
+        lookup_indices.sort();
+        lookup_indices.dedup();
+        Ok(lookup_indices)
+    }
+
+    /// Returns the indices of the features enabled by the default language system of each script,
+    /// and whether each is required.
+    fn default_feature_indices(&self) -> Result<Vec<(u16, bool)>, FontError> {
+        let script_list = try!(at(self.table, self.script_list_offset as usize));
+        let mut reader = script_list;
+        let script_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut feature_indices = vec![];
+        for _ in 0..script_count {
+            let _script_tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let script_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+            let script = try!(at(script_list, script_offset as usize));
+            let default_lang_sys_offset =
+                try!((&mut &script[..]).read_u16::<BigEndian>().map_err(FontError::eof));
+            if default_lang_sys_offset == 0 {
+                continue
+            }
+
+            let mut lang_sys_reader = try!(at(script, default_lang_sys_offset as usize));
+            let _lookup_order_offset =
+                try!(lang_sys_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let required_feature_index =
+                try!(lang_sys_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if required_feature_index != NO_REQUIRED_FEATURE {
+                feature_indices.push((required_feature_index, true))
+            }
+            let feature_index_count =
+                try!(lang_sys_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            for _ in 0..feature_index_count {
+                let feature_index =
+                    try!(lang_sys_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                feature_indices.push((feature_index, false))
+            }
+        }
+
+        feature_indices.sort();
+        feature_indices.dedup();
+        Ok(feature_indices)
+    }
+
+    /// Returns the lookup with the given index.
+    ///
+    /// Subtables of lookups of `extension_lookup_type` are replaced with the subtables that they
+    /// point to, and the lookup takes on their type.
+    pub fn lookup(&self, lookup_index: u16, extension_lookup_type: u16)
+                  -> Result<Lookup<'a>, FontError> {
+        let lookup_list = try!(at(self.table, self.lookup_list_offset as usize));
+        let mut reader = lookup_list;
+        let lookup_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if lookup_index >= lookup_count {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        try!(reader.jump(mem::size_of::<u16>() * lookup_index as usize).map_err(FontError::eof));
+        let lookup_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let lookup = try!(at(lookup_list, lookup_offset as usize));
+
+        let mut reader = lookup;
+        let mut lookup_type = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let lookup_flag = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let subtable_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut subtables = Vec::with_capacity(subtable_count as usize);
+        for _ in 0..subtable_count {
+            let subtable_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let mut subtable = try!(at(lookup, subtable_offset as usize));
+
+            if lookup_type == extension_lookup_type {
+                let mut extension_reader = subtable;
+                let _format =
+                    try!(extension_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let extension_type =
+                    try!(extension_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let extension_offset =
+                    try!(extension_reader.read_u32::<BigEndian>().map_err(FontError::eof));
+                subtable = try!(at(subtable, extension_offset as usize));
+
+                // All the subtables of an extension lookup must be of the same type.
+                if subtables.is_empty() {
+                    lookup_type = extension_type
+                } else if extension_type != lookup_type {
+                    return Err(FontError::Failed)
+                }
+            }
+
+            subtables.push(subtable)
+        }
+
+        Ok(Lookup {
+            lookup_type: lookup_type,
+            lookup_flag: lookup_flag,
+            subtables: subtables,
+        })
+    }
+}
+
+/// Returns the bytes of a table or subtable starting at the given offset from its start.
+pub fn at(table: &[u8], offset: usize) -> Result<&[u8], FontError> {
+    if offset > table.len() {
+        return Err(FontError::UnexpectedEof)
+    }
+    Ok(&table[offset..])
+}
+
+/// Returns the index of the given glyph in a coverage table, or `None` if it isn't covered.
+pub fn coverage_index(coverage: &[u8], glyph_id: u16) -> Result<Option<u16>, FontError> {
+    let mut reader = coverage;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    match format {
+        1 => {
+            // A sorted list of glyph IDs.
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let mid = (low + high) / 2;
+                let mut glyph_reader = reader;
+                try!(glyph_reader.jump(mem::size_of::<u16>() * mid as usize)
+                                 .map_err(FontError::eof));
+                let glyph = try!(glyph_reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                match glyph_id.cmp(&glyph) {
+                    Ordering::Less => high = mid,
+                    Ordering::Greater => low = mid + 1,
+                    Ordering::Equal => return Ok(Some(mid)),
+                }
+            }
+            Ok(None)
+        }
+        2 => {
+            // A sorted list of ranges of glyph IDs, each with the coverage index of its start.
+            match try!(find_range(reader, count, glyph_id)) {
+                Some((start_glyph_id, start_coverage_index)) => {
+                    Ok(Some(start_coverage_index.wrapping_add(glyph_id - start_glyph_id)))
+                }
+                None => Ok(None),
+            }
+        }
+        _ => Err(FontError::UnknownFormat),
+    }
+}
+
+/// Returns the class that a class definition table assigns to the given glyph.
+///
+/// Glyphs that aren't listed are in class 0.
+pub fn glyph_class(class_def: &[u8], glyph_id: u16) -> Result<u16, FontError> {
+    let mut reader = class_def;
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    match format {
+        1 => {
+            // An array of classes for a contiguous run of glyph IDs.
+            let start_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            if glyph_id < start_glyph_id || glyph_id - start_glyph_id >= glyph_count {
+                return Ok(0)
+            }
+            try!(reader.jump(mem::size_of::<u16>() * (glyph_id - start_glyph_id) as usize)
+                       .map_err(FontError::eof));
+            reader.read_u16::<BigEndian>().map_err(FontError::eof)
+        }
+        2 => {
+            // A sorted list of ranges of glyph IDs, each with a class.
+            let class_range_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            match try!(find_range(reader, class_range_count, glyph_id)) {
+                Some((_, class)) => Ok(class),
+                None => Ok(0),
+            }
+        }
+        _ => Err(FontError::UnknownFormat),
+    }
+}
+
+/// Searches a sorted list of `(start, end, value)` range records for the one containing the
+/// given glyph and returns its start and value.
+fn find_range(records: &[u8], count: u16, glyph_id: u16)
+              -> Result<Option<(u16, u16)>, FontError> {
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let mid = (low + high) / 2;
+        let mut reader = records;
+        try!(reader.jump(RANGE_RECORD_SIZE * mid as usize).map_err(FontError::eof));
+        let start_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let end_glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if glyph_id < start_glyph_id {
+            high = mid
+        } else if glyph_id > end_glyph_id {
+            low = mid + 1
+        } else {
+            let value = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            return Ok(Some((start_glyph_id, value)))
+        }
+    }
+    Ok(None)
+}
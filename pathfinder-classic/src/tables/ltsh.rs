@@ -0,0 +1,53 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+
+pub const TAG: u32 = ((b'L' as u32) << 24) |
+                      ((b'T' as u32) << 16) |
+                      ((b'S' as u32) << 8)  |
+                       (b'H' as u32);
+
+/// The linear threshold table, which records for each glyph the size at which its advance width
+/// starts scaling linearly with the size.
+#[derive(Clone, Copy)]
+pub struct LtshTable<'a> {
+    thresholds: &'a [u8],
+}
+
+impl<'a> LtshTable<'a> {
+    pub fn new(table: FontTable) -> Result<LtshTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version != 0 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if reader.len() < glyph_count as usize {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(LtshTable {
+            thresholds: &reader[..glyph_count as usize],
+        })
+    }
+
+    /// Returns the size in pixels per em at and above which the advance width of the given glyph
+    /// scales linearly, or `None` if the glyph is out of range.
+    ///
+    /// A threshold of 1 means that the advance always scales linearly.
+    #[inline]
+    pub fn linear_threshold(&self, glyph_id: u16) -> Option<u8> {
+        self.thresholds.get(glyph_id as usize).cloned()
+    }
+}
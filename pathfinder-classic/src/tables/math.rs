@@ -0,0 +1,267 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{FontTable, GlyphAssembly, GlyphPart, MathConstant, MathDirection, MathGlyphVariant};
+use std::mem;
+use tables::layout;
+use util::Jump;
+
+pub const TAG: u32 = ((b'M' as u32) << 24) |
+                      ((b'A' as u32) << 16) |
+                      ((b'T' as u32) << 8)  |
+                       (b'H' as u32);
+
+/// The number of plain 16-bit integers at the start of the `MathConstants` table, before the
+/// value records.
+const LEADING_INTEGER_CONSTANT_COUNT: usize = 4;
+
+/// The size of a `MathValueRecord`: a value and a device table offset, which we ignore.
+const MATH_VALUE_RECORD_SIZE: usize = 4;
+
+/// The size of a `MathGlyphVariantRecord`.
+const GLYPH_VARIANT_RECORD_SIZE: usize = 4;
+
+/// Set in a glyph part that can be repeated to make an assembly longer.
+const PART_FLAG_EXTENDER: u16 = 0x0001;
+
+/// The mathematical typesetting table, which holds the constants, per-glyph metrics, and
+/// stretchy glyph constructions needed to lay out formulas.
+#[derive(Clone, Copy)]
+pub struct MathTable<'a> {
+    table: FontTable<'a>,
+    constants_offset: u16,
+    glyph_info_offset: u16,
+    variants_offset: u16,
+}
+
+impl<'a> MathTable<'a> {
+    pub fn new(table: FontTable) -> Result<MathTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let constants_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let glyph_info_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let variants_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        Ok(MathTable {
+            table: table,
+            constants_offset: constants_offset,
+            glyph_info_offset: glyph_info_offset,
+            variants_offset: variants_offset,
+        })
+    }
+
+    /// Returns the value of the given constant, or `None` if the table has no constants.
+    ///
+    /// The constants are a few plain integers followed by value records and then one more
+    /// integer, so the position of each follows from its place in the list.
+    pub fn constant(&self, constant: MathConstant) -> Result<Option<i16>, FontError> {
+        if self.constants_offset == 0 {
+            return Ok(None)
+        }
+        let mut reader = try!(layout::at(self.table.bytes, self.constants_offset as usize));
+        let index = constant as usize;
+        let offset = if index < LEADING_INTEGER_CONSTANT_COUNT {
+            mem::size_of::<i16>() * index
+        } else {
+            mem::size_of::<i16>() * LEADING_INTEGER_CONSTANT_COUNT +
+                MATH_VALUE_RECORD_SIZE * (index - LEADING_INTEGER_CONSTANT_COUNT)
+        };
+        try!(reader.jump(offset).map_err(FontError::eof));
+        Ok(Some(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof))))
+    }
+
+    /// Returns the italics correction of the given glyph, or `None` if it has none.
+    pub fn italics_correction(&self, glyph_id: u16) -> Result<Option<i16>, FontError> {
+        self.glyph_info_value(0, glyph_id)
+    }
+
+    /// Returns the horizontal position at which accents attach to the top of the given glyph, or
+    /// `None` if the glyph doesn't say, in which case accents are centered.
+    pub fn top_accent_attachment(&self, glyph_id: u16) -> Result<Option<i16>, FontError> {
+        self.glyph_info_value(1, glyph_id)
+    }
+
+    /// Returns true if the given glyph is an extended shape, such as a tall parenthesis, for
+    /// which superscripts and subscripts are placed relative to the whole glyph.
+    pub fn is_extended_shape(&self, glyph_id: u16) -> Result<bool, FontError> {
+        let coverage_offset = try!(self.glyph_info_subtable_offset(2));
+        if coverage_offset == 0 {
+            return Ok(false)
+        }
+        let glyph_info = try!(layout::at(self.table.bytes, self.glyph_info_offset as usize));
+        let coverage = try!(layout::at(glyph_info, coverage_offset as usize));
+        Ok(try!(layout::coverage_index(coverage, glyph_id)).is_some())
+    }
+
+    /// Returns the amount by which adjacent parts of a glyph assembly must overlap at least.
+    pub fn min_connector_overlap(&self) -> Result<u16, FontError> {
+        if self.variants_offset == 0 {
+            return Ok(0)
+        }
+        let mut reader = try!(layout::at(self.table.bytes, self.variants_offset as usize));
+        reader.read_u16::<BigEndian>().map_err(FontError::eof)
+    }
+
+    /// Returns the successively larger versions of the given glyph in the given direction,
+    /// starting with the glyph itself if the font lists it.
+    pub fn glyph_variants(&self, glyph_id: u16, direction: MathDirection)
+                          -> Result<Vec<MathGlyphVariant>, FontError> {
+        let mut reader = match try!(self.glyph_construction(glyph_id, direction)) {
+            None => return Ok(vec![]),
+            Some(construction) => construction,
+        };
+        let _glyph_assembly_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let variant_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if reader.len() < variant_count as usize * GLYPH_VARIANT_RECORD_SIZE {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        let mut variants = Vec::with_capacity(variant_count as usize);
+        for _ in 0..variant_count {
+            let glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let advance = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            variants.push(MathGlyphVariant {
+                glyph_id: glyph_id,
+                advance: advance,
+            })
+        }
+        Ok(variants)
+    }
+
+    /// Returns the parts from which a version of the given glyph of arbitrary size can be built
+    /// in the given direction, or `None` if it can't be.
+    pub fn glyph_assembly(&self, glyph_id: u16, direction: MathDirection)
+                          -> Result<Option<GlyphAssembly>, FontError> {
+        let construction = match try!(self.glyph_construction(glyph_id, direction)) {
+            None => return Ok(None),
+            Some(construction) => construction,
+        };
+        let glyph_assembly_offset =
+            try!((&mut &construction[..]).read_u16::<BigEndian>().map_err(FontError::eof));
+        if glyph_assembly_offset == 0 {
+            return Ok(None)
+        }
+
+        let mut reader = try!(layout::at(construction, glyph_assembly_offset as usize));
+        let italics_correction = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let _device_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let part_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut parts = Vec::with_capacity(part_count as usize);
+        for _ in 0..part_count {
+            let glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let start_connector_length =
+                try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let end_connector_length = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let full_advance = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let part_flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            parts.push(GlyphPart {
+                glyph_id: glyph_id,
+                start_connector_length: start_connector_length,
+                end_connector_length: end_connector_length,
+                full_advance: full_advance,
+                is_extender: part_flags & PART_FLAG_EXTENDER != 0,
+            })
+        }
+
+        Ok(Some(GlyphAssembly {
+            italics_correction: italics_correction,
+            parts: parts,
+        }))
+    }
+
+    /// Returns the offset, from the start of the `MathGlyphInfo` table, of the subtable with the
+    /// given index: italics corrections, top accent attachments, extended shapes, or kerning.
+    fn glyph_info_subtable_offset(&self, subtable_index: usize) -> Result<u16, FontError> {
+        if self.glyph_info_offset == 0 {
+            return Ok(0)
+        }
+        let mut reader = try!(layout::at(self.table.bytes, self.glyph_info_offset as usize));
+        try!(reader.jump(mem::size_of::<u16>() * subtable_index).map_err(FontError::eof));
+        reader.read_u16::<BigEndian>().map_err(FontError::eof)
+    }
+
+    /// Looks the given glyph up in a subtable made of a coverage table and one value record per
+    /// covered glyph, which is the layout of both the italics correction and the top accent
+    /// attachment subtables.
+    fn glyph_info_value(&self, subtable_index: usize, glyph_id: u16)
+                        -> Result<Option<i16>, FontError> {
+        let subtable_offset = try!(self.glyph_info_subtable_offset(subtable_index));
+        if subtable_offset == 0 {
+            return Ok(None)
+        }
+        let glyph_info = try!(layout::at(self.table.bytes, self.glyph_info_offset as usize));
+        let subtable = try!(layout::at(glyph_info, subtable_offset as usize));
+
+        let mut reader = subtable;
+        let coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let value_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let coverage = try!(layout::at(subtable, coverage_offset as usize));
+        let coverage_index = match try!(layout::coverage_index(coverage, glyph_id)) {
+            None => return Ok(None),
+            Some(coverage_index) => coverage_index,
+        };
+        if coverage_index >= value_count {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        try!(reader.jump(MATH_VALUE_RECORD_SIZE * coverage_index as usize)
+                   .map_err(FontError::eof));
+        Ok(Some(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof))))
+    }
+
+    /// Returns the `MathGlyphConstruction` table of the given glyph in the given direction, or
+    /// `None` if the glyph has no variants in that direction.
+    fn glyph_construction(&self, glyph_id: u16, direction: MathDirection)
+                          -> Result<Option<&'a [u8]>, FontError> {
+        if self.variants_offset == 0 {
+            return Ok(None)
+        }
+        let variants = try!(layout::at(self.table.bytes, self.variants_offset as usize));
+        let mut reader = variants;
+        let _min_connector_overlap = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let vert_coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let horiz_coverage_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let vert_glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let horiz_glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // The vertical construction offsets come first, followed by the horizontal ones.
+        let (coverage_offset, glyph_count, first_construction) = match direction {
+            MathDirection::Vertical => (vert_coverage_offset, vert_glyph_count, 0),
+            MathDirection::Horizontal => {
+                (horiz_coverage_offset, horiz_glyph_count, vert_glyph_count)
+            }
+        };
+        if coverage_offset == 0 {
+            return Ok(None)
+        }
+
+        let coverage = try!(layout::at(variants, coverage_offset as usize));
+        let coverage_index = match try!(layout::coverage_index(coverage, glyph_id)) {
+            None => return Ok(None),
+            Some(coverage_index) => coverage_index,
+        };
+        if coverage_index >= glyph_count {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        let construction_index = first_construction as usize + coverage_index as usize;
+        try!(reader.jump(mem::size_of::<u16>() * construction_index).map_err(FontError::eof));
+        let construction_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        layout::at(variants, construction_offset as usize).map(Some)
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{FontTable, GlyphLimits};
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'm' as u32) << 24) |
+                      ((b'a' as u32) << 16) |
+                      ((b'x' as u32) << 8)  |
+                       (b'p' as u32);
+
+/// Version 0.5, used by fonts with CFF outlines, has only the number of glyphs.
+const VERSION_0_5: u32 = 0x00005000;
+const VERSION_1_0: u32 = 0x00010000;
+
+/// The maximum profile table.
+#[derive(Clone, Debug)]
+pub struct MaxpTable {
+    pub num_glyphs: u16,
+    /// Only present in version 1.0, which fonts with TrueType outlines use.
+    pub limits: Option<GlyphLimits>,
+}
+
+impl MaxpTable {
+    pub fn new(table: FontTable) -> Result<MaxpTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let num_glyphs = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let limits = match version {
+            VERSION_0_5 => None,
+            VERSION_1_0 => {
+                let max_points = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let max_contours = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let max_composite_points =
+                    try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let max_composite_contours =
+                    try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+                // Skip the limits that only the hinting interpreter cares about.
+                try!(reader.jump(mem::size_of::<u16>() * 7).map_err(FontError::eof));
+                let max_component_elements =
+                    try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let max_component_depth =
+                    try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+                Some(GlyphLimits {
+                    max_points: max_points,
+                    max_contours: max_contours,
+                    max_composite_points: max_composite_points,
+                    max_composite_contours: max_composite_contours,
+                    max_component_elements: max_component_elements,
+                    max_component_depth: max_component_depth,
+                })
+            }
+            _ => return Err(FontError::UnsupportedMaxpVersion),
+        };
+
+        Ok(MaxpTable {
+            num_glyphs: num_glyphs,
+            limits: limits,
+        })
+    }
+}
@@ -18,6 +18,13 @@ pub mod cvt {
                            (b' ' as u32);
 }
 
+pub mod dsig {
+    pub const TAG: u32 = ((b'D' as u32) << 24) |
+                          ((b'S' as u32) << 16) |
+                          ((b'I' as u32) << 8)  |
+                           (b'G' as u32);
+}
+
 pub mod fpgm {
     pub const TAG: u32 = ((b'f' as u32) << 24) |
                           ((b'p' as u32) << 16) |
@@ -32,13 +39,37 @@ pub mod prep {
                            (b'p' as u32);
 }
 
+pub mod base;
 pub mod cff;
 pub mod cmap;
+pub mod colr;
+pub mod cpal;
+pub mod ebdt;
+pub mod eblc;
+pub mod fvar;
+pub mod gasp;
+pub mod gdef;
 pub mod glyf;
+pub mod gpos;
+pub mod gsub;
+pub mod gvar;
+pub mod hdmx;
 pub mod head;
 pub mod hhea;
 pub mod hmtx;
 pub mod kern;
+pub mod layout;
 pub mod loca;
+pub mod ltsh;
+pub mod math;
+pub mod maxp;
+pub mod name;
 pub mod os_2;
+pub mod post;
+pub mod sbix;
+pub mod stat;
+pub mod svg;
+pub mod vhea;
+pub mod vmtx;
+pub mod vorg;
 
@@ -0,0 +1,194 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use encoding;
+use error::FontError;
+use font::FontTable;
+use std::char;
+use util::Jump;
+
+pub const TAG: u32 = ((b'n' as u32) << 24) |
+                      ((b'a' as u32) << 16) |
+                      ((b'm' as u32) << 8)  |
+                       (b'e' as u32);
+
+pub const NAME_ID_FAMILY: u16 = 1;
+pub const NAME_ID_SUBFAMILY: u16 = 2;
+pub const NAME_ID_FULL_NAME: u16 = 4;
+pub const NAME_ID_POSTSCRIPT_NAME: u16 = 6;
+pub const NAME_ID_TYPOGRAPHIC_FAMILY: u16 = 16;
+pub const NAME_ID_TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+
+const PLATFORM_ID_UNICODE: u16 = 0;
+const PLATFORM_ID_MACINTOSH: u16 = 1;
+const PLATFORM_ID_MICROSOFT: u16 = 3;
+
+const MACINTOSH_ENCODING_ID_ROMAN: u16 = 0;
+
+const MICROSOFT_ENCODING_ID_SYMBOL: u16 = 0;
+const MICROSOFT_ENCODING_ID_UNICODE_BMP: u16 = 1;
+const MICROSOFT_ENCODING_ID_UNICODE_UCS4: u16 = 10;
+
+const MACINTOSH_LANGUAGE_ID_ENGLISH: u16 = 0;
+const MICROSOFT_LANGUAGE_ID_ENGLISH_US: u16 = 0x0409;
+
+/// The order in which platforms are consulted when several records match.
+static PLATFORM_PREFERENCE: [u16; 3] = [
+    PLATFORM_ID_MICROSOFT,
+    PLATFORM_ID_UNICODE,
+    PLATFORM_ID_MACINTOSH,
+];
+
+#[derive(Clone, Copy)]
+pub struct NameTable<'a> {
+    records: &'a [u8],
+    record_count: u16,
+    storage: &'a [u8],
+}
+
+#[derive(Clone, Copy, Debug)]
+struct NameRecord {
+    platform_id: u16,
+    encoding_id: u16,
+    language_id: u16,
+    name_id: u16,
+    length: u16,
+    offset: u16,
+}
+
+impl<'a> NameTable<'a> {
+    pub fn new(table: FontTable) -> Result<NameTable, FontError> {
+        let mut reader = table.bytes;
+
+        // Formats 0 and 1 share the same record layout; format 1 merely appends language tags,
+        // which we don't need.
+        let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if format > 1 {
+            return Err(FontError::UnknownFormat)
+        }
+
+        let record_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let string_offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        let mut storage = table.bytes;
+        try!(storage.jump(string_offset as usize).map_err(FontError::eof));
+
+        Ok(NameTable {
+            records: reader,
+            record_count: record_count,
+            storage: storage,
+        })
+    }
+
+    /// Returns the name with the given ID in the given language.
+    ///
+    /// If no record exists for `language_id`, this falls back to English: language 0x409 on the
+    /// Microsoft platform, then language 0 on the Macintosh platform. When several platforms have
+    /// a record in the same language, Microsoft records are preferred, then Unicode, then
+    /// Macintosh.
+    ///
+    /// Records in encodings we can't decode (legacy CJK encodings, for example) are skipped
+    /// rather than being decoded lossily, so this returns `None` if no decodable record exists.
+    pub fn get_localized(&self, name_id: u16, language_id: u16) -> Option<String> {
+        let requested = self.find(name_id, |record| record.language_id == language_id);
+        if requested.is_some() {
+            return requested
+        }
+
+        self.find(name_id, |record| {
+            (record.platform_id == PLATFORM_ID_MICROSOFT &&
+             record.language_id == MICROSOFT_LANGUAGE_ID_ENGLISH_US) ||
+            (record.platform_id == PLATFORM_ID_MACINTOSH &&
+             record.language_id == MACINTOSH_LANGUAGE_ID_ENGLISH)
+        })
+    }
+
+    fn find<F>(&self, name_id: u16, mut predicate: F) -> Option<String>
+               where F: FnMut(&NameRecord) -> bool {
+        for &platform_id in &PLATFORM_PREFERENCE {
+            for record_index in 0..self.record_count {
+                let record = match self.record(record_index) {
+                    Ok(record) => record,
+                    Err(_) => return None,
+                };
+                if record.name_id != name_id || record.platform_id != platform_id ||
+                        !predicate(&record) {
+                    continue
+                }
+                if let Some(string) = self.decode(&record) {
+                    return Some(string)
+                }
+            }
+        }
+        None
+    }
+
+    fn record(&self, index: u16) -> Result<NameRecord, FontError> {
+        let mut reader = self.records;
+        try!(reader.jump(index as usize * 12).map_err(FontError::eof));
+        Ok(NameRecord {
+            platform_id: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+            encoding_id: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+            language_id: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+            name_id: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+            length: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+            offset: try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)),
+        })
+    }
+
+    fn decode(&self, record: &NameRecord) -> Option<String> {
+        let start = record.offset as usize;
+        let end = start + record.length as usize;
+        if end > self.storage.len() {
+            return None
+        }
+        let bytes = &self.storage[start..end];
+
+        let string = match (record.platform_id, record.encoding_id) {
+            (PLATFORM_ID_UNICODE, _) |
+            (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_SYMBOL) |
+            (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_BMP) |
+            (PLATFORM_ID_MICROSOFT, MICROSOFT_ENCODING_ID_UNICODE_UCS4) => {
+                match decode_utf16_be(bytes) {
+                    Some(string) => string,
+                    None => return None,
+                }
+            }
+            (PLATFORM_ID_MACINTOSH, MACINTOSH_ENCODING_ID_ROMAN) => decode_mac_roman(bytes),
+            _ => return None,
+        };
+
+        Some(normalize_newlines(&string))
+    }
+}
+
+fn decode_utf16_be(bytes: &[u8]) -> Option<String> {
+    if bytes.len() % 2 != 0 {
+        return None
+    }
+
+    let units: Vec<u16> = bytes.chunks(2)
+                               .map(|unit| ((unit[0] as u16) << 8) | (unit[1] as u16))
+                               .collect();
+    String::from_utf16(&units).ok()
+}
+
+fn decode_mac_roman(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| {
+        char::from_u32(encoding::mac_roman_to_unicode(byte)).unwrap_or('\u{fffd}')
+    }).collect()
+}
+
+/// Converts Macintosh (`\r`) and Windows (`\r\n`) line endings, which appear in long strings
+/// like license text, to `\n`.
+fn normalize_newlines(string: &str) -> String {
+    string.replace("\r\n", "\n").replace('\r', "\n")
+}
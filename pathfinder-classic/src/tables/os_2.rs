@@ -19,11 +19,34 @@ pub const TAG: u32 = ((b'O' as u32) << 24) |
                       ((b'/' as u32) << 8)  |
                        (b'2' as u32);
 
+bitflags! {
+    pub flags FsSelection: u16 {
+        const ITALIC = 1 << 0,
+        const BOLD = 1 << 5,
+        const REGULAR = 1 << 6,
+        const USE_TYPO_METRICS = 1 << 7,
+        const OBLIQUE = 1 << 9,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Os2Table {
+    pub weight_class: u16,
+    pub width_class: u16,
+    pub strikeout_size: i16,
+    pub strikeout_position: i16,
+    /// The Unicode blocks the font covers, as a 128-bit field. Zero in version 0 tables.
+    pub unicode_ranges: [u32; 4],
+    pub fs_selection: FsSelection,
     pub typo_ascender: i16,
     pub typo_descender: i16,
     pub typo_line_gap: i16,
+    /// The code pages the font covers, as a 64-bit field. Only present since version 1.
+    pub code_page_ranges: Option<[u32; 2]>,
+    /// Only present since version 2.
+    pub x_height: Option<i16>,
+    /// Only present since version 2.
+    pub cap_height: Option<i16>,
 }
 
 impl Os2Table {
@@ -34,27 +57,72 @@ impl Os2Table {
         // Postel's law and hope for the best.
         let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
 
-        // Skip to the line gap.
-        try!(reader.jump(mem::size_of::<u16>() * 15).map_err(FontError::eof));
+        // Skip the average character width.
+        try!(reader.jump(mem::size_of::<i16>()).map_err(FontError::eof));
+        let weight_class = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let width_class = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // Skip the embedding permissions and the subscript and superscript metrics.
+        try!(reader.jump(mem::size_of::<u16>() * 9).map_err(FontError::eof));
+        let strikeout_size = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let strikeout_position = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+
+        // Skip the family class and PANOSE classification.
+        try!(reader.jump(mem::size_of::<u16>()).map_err(FontError::eof));
         try!(reader.jump(10).map_err(FontError::eof));
+
+        let mut unicode_ranges = [0; 4];
         if version == 0 {
             try!(reader.jump(mem::size_of::<u32>() * 2).map_err(FontError::eof));
         } else {
-            try!(reader.jump(mem::size_of::<u32>() * 5).map_err(FontError::eof));
+            for range in &mut unicode_ranges {
+                *range = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof))
+            }
+
+            // Skip the vendor ID.
+            try!(reader.jump(mem::size_of::<u32>()).map_err(FontError::eof));
         }
-        try!(reader.jump(mem::size_of::<u16>() * 3).map_err(FontError::eof));
+
+        let fs_selection = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let fs_selection = FsSelection::from_bits_truncate(fs_selection);
+
+        // Skip the first and last character indices.
+        try!(reader.jump(mem::size_of::<u16>() * 2).map_err(FontError::eof));
 
         // Read the line spacing information.
         let typo_ascender = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let typo_descender = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
         let typo_line_gap = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
 
+        // Skip the Windows ascent and descent.
+        try!(reader.jump(mem::size_of::<u16>() * 2).map_err(FontError::eof));
+
+        let mut code_page_ranges = None;
+        if version >= 1 {
+            let low = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let high = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            code_page_ranges = Some([low, high])
+        }
+
+        let (mut x_height, mut cap_height) = (None, None);
+        if version >= 2 {
+            x_height = Some(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+            cap_height = Some(try!(reader.read_i16::<BigEndian>().map_err(FontError::eof)));
+        }
+
         Ok(Os2Table {
+            weight_class: weight_class,
+            width_class: width_class,
+            strikeout_size: strikeout_size,
+            strikeout_position: strikeout_position,
+            unicode_ranges: unicode_ranges,
+            fs_selection: fs_selection,
             typo_ascender: typo_ascender,
             typo_descender: typo_descender,
             typo_line_gap: typo_line_gap,
+            code_page_ranges: code_page_ranges,
+            x_height: x_height,
+            cap_height: cap_height,
         })
     }
 }
-
-
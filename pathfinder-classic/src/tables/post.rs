@@ -0,0 +1,206 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b'p' as u32) << 24) |
+                      ((b'o' as u32) << 16) |
+                      ((b's' as u32) << 8)  |
+                       (b't' as u32);
+
+const VERSION_1_0: u32 = 0x00010000;
+const VERSION_2_0: u32 = 0x00020000;
+
+/// The PostScript table: the header, plus the glyph names that versions 1.0 and 2.0 provide.
+#[derive(Clone, Debug)]
+pub struct PostTable<'a> {
+    /// The angle of the stems of upright letters, in degrees counterclockwise from the vertical.
+    pub italic_angle: f32,
+    pub underline_position: i16,
+    pub underline_thickness: i16,
+    pub is_fixed_pitch: bool,
+    version: u32,
+    /// The data following the header, which is where version 2.0 stores its glyph names.
+    glyph_name_data: &'a [u8],
+}
+
+impl<'a> PostTable<'a> {
+    pub fn new(table: FontTable) -> Result<PostTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        let italic_angle = try!(reader.read_i32::<BigEndian>().map_err(FontError::eof));
+        let underline_position = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let underline_thickness = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let is_fixed_pitch = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+
+        // Skip the memory usage hints.
+        try!(reader.jump(mem::size_of::<u32>() * 4).map_err(FontError::eof));
+
+        Ok(PostTable {
+            italic_angle: italic_angle as f32 / 65536.0,
+            underline_position: underline_position,
+            underline_thickness: underline_thickness,
+            is_fixed_pitch: is_fixed_pitch != 0,
+            version: version,
+            glyph_name_data: reader,
+        })
+    }
+
+    /// Returns the ID of the first glyph with the given PostScript name, or `None` if no glyph
+    /// has that name or this version of the table doesn't name glyphs.
+    ///
+    /// Version 1.0 fonts contain exactly the glyphs of the standard Macintosh character set, in
+    /// order. Version 2.0 fonts name each glyph either by index into that set or by index into a
+    /// list of Pascal strings following the glyph name indices.
+    pub fn glyph_for_name(&self, name: &str) -> Result<Option<u16>, FontError> {
+        let standard_index = STANDARD_MAC_GLYPH_NAMES.iter().position(|&standard_name| {
+            standard_name == name
+        });
+
+        match self.version {
+            VERSION_1_0 => Ok(standard_index.map(|index| index as u16)),
+            VERSION_2_0 => {
+                let mut reader = self.glyph_name_data;
+                let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                let mut strings = reader;
+                try!(strings.jump(mem::size_of::<u16>() * glyph_count as usize)
+                            .map_err(FontError::eof));
+
+                // Fonts use the standard index for standard names, so only look for other names
+                // among the strings.
+                let name_index = match standard_index {
+                    Some(standard_index) => standard_index,
+                    None => {
+                        match try!(find_pascal_string(strings, name.as_bytes())) {
+                            Some(string_index) => STANDARD_MAC_GLYPH_NAMES.len() + string_index,
+                            None => return Ok(None),
+                        }
+                    }
+                };
+
+                for glyph_id in 0..glyph_count {
+                    let glyph_name_index =
+                        try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                    if glyph_name_index as usize == name_index {
+                        return Ok(Some(glyph_id))
+                    }
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the PostScript name of the given glyph, or `None` if the glyph is out of range or
+    /// this version of the table doesn't name glyphs.
+    pub fn glyph_name(&self, glyph_id: u16) -> Result<Option<String>, FontError> {
+        match self.version {
+            VERSION_1_0 => {
+                Ok(STANDARD_MAC_GLYPH_NAMES.get(glyph_id as usize).map(|&name| name.to_owned()))
+            }
+            VERSION_2_0 => {
+                let mut reader = self.glyph_name_data;
+                let glyph_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+                if glyph_id >= glyph_count {
+                    return Ok(None)
+                }
+
+                let mut strings = reader;
+                try!(strings.jump(mem::size_of::<u16>() * glyph_count as usize)
+                            .map_err(FontError::eof));
+                try!(reader.jump(mem::size_of::<u16>() * glyph_id as usize)
+                           .map_err(FontError::eof));
+                let name_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+                if let Some(&name) = STANDARD_MAC_GLYPH_NAMES.get(name_index as usize) {
+                    return Ok(Some(name.to_owned()))
+                }
+                let string_index = name_index as usize - STANDARD_MAC_GLYPH_NAMES.len();
+                let string = try!(nth_pascal_string(strings, string_index));
+                Ok(string.map(|string| String::from_utf8_lossy(string).into_owned()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns the Pascal string at `index` among the consecutive Pascal strings in `reader`.
+fn nth_pascal_string(mut reader: &[u8], index: usize) -> Result<Option<&[u8]>, FontError> {
+    for string_index in 0.. {
+        if reader.is_empty() {
+            break
+        }
+        let length = try!(reader.read_u8().map_err(FontError::eof)) as usize;
+        if reader.len() < length {
+            return Err(FontError::UnexpectedEof)
+        }
+        if string_index == index {
+            return Ok(Some(&reader[..length]))
+        }
+        reader = &reader[length..]
+    }
+    Ok(None)
+}
+
+/// Returns the index of `string` among the consecutive Pascal strings in `reader`.
+fn find_pascal_string(mut reader: &[u8], string: &[u8]) -> Result<Option<usize>, FontError> {
+    let mut index = 0;
+    while !reader.is_empty() {
+        let length = try!(reader.read_u8().map_err(FontError::eof)) as usize;
+        if reader.len() < length {
+            return Err(FontError::UnexpectedEof)
+        }
+        if &reader[..length] == string {
+            return Ok(Some(index))
+        }
+        reader = &reader[length..];
+        index += 1
+    }
+    Ok(None)
+}
+
+/// The names of the glyphs in the standard Macintosh character set, in order.
+static STANDARD_MAC_GLYPH_NAMES: [&'static str; 258] = [
+    ".notdef", ".null", "nonmarkingreturn", "space", "exclam", "quotedbl", "numbersign",
+    "dollar", "percent", "ampersand", "quotesingle", "parenleft", "parenright", "asterisk",
+    "plus", "comma", "hyphen", "period", "slash", "zero", "one", "two", "three", "four", "five",
+    "six", "seven", "eight", "nine", "colon", "semicolon", "less", "equal", "greater",
+    "question", "at", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O",
+    "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "bracketleft", "backslash",
+    "bracketright", "asciicircum", "underscore", "grave", "a", "b", "c", "d", "e", "f", "g", "h",
+    "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+    "braceleft", "bar", "braceright", "asciitilde", "Adieresis", "Aring", "Ccedilla", "Eacute",
+    "Ntilde", "Odieresis", "Udieresis", "aacute", "agrave", "acircumflex", "adieresis", "atilde",
+    "aring", "ccedilla", "eacute", "egrave", "ecircumflex", "edieresis", "iacute", "igrave",
+    "icircumflex", "idieresis", "ntilde", "oacute", "ograve", "ocircumflex", "odieresis",
+    "otilde", "uacute", "ugrave", "ucircumflex", "udieresis", "dagger", "degree", "cent",
+    "sterling", "section", "bullet", "paragraph", "germandbls", "registered", "copyright",
+    "trademark", "acute", "dieresis", "notequal", "AE", "Oslash", "infinity", "plusminus",
+    "lessequal", "greaterequal", "yen", "mu", "partialdiff", "summation", "product", "pi",
+    "integral", "ordfeminine", "ordmasculine", "Omega", "ae", "oslash", "questiondown",
+    "exclamdown", "logicalnot", "radical", "florin", "approxequal", "Delta", "guillemotleft",
+    "guillemotright", "ellipsis", "nonbreakingspace", "Agrave", "Atilde", "Otilde", "OE", "oe",
+    "endash", "emdash", "quotedblleft", "quotedblright", "quoteleft", "quoteright", "divide",
+    "lozenge", "ydieresis", "Ydieresis", "fraction", "currency", "guilsinglleft",
+    "guilsinglright", "fi", "fl", "daggerdbl", "periodcentered", "quotesinglbase",
+    "quotedblbase", "perthousand", "Acircumflex", "Ecircumflex", "Aacute", "Edieresis", "Egrave",
+    "Iacute", "Icircumflex", "Idieresis", "Igrave", "Oacute", "Ocircumflex", "apple", "Ograve",
+    "Uacute", "Ucircumflex", "Ugrave", "dotlessi", "circumflex", "tilde", "macron", "breve",
+    "dotaccent", "ring", "cedilla", "hungarumlaut", "ogonek", "caron", "Lslash", "lslash",
+    "Scaron", "scaron", "Zcaron", "zcaron", "brokenbar", "Eth", "eth", "Yacute", "yacute",
+    "Thorn", "thorn", "minus", "multiply", "onesuperior", "twosuperior", "threesuperior",
+    "onehalf", "onequarter", "threequarters", "franc", "Gbreve", "gbreve", "Idotaccent",
+    "Scedilla", "scedilla", "Cacute", "cacute", "Ccaron", "ccaron", "dcroat",
+];
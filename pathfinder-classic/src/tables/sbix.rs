@@ -0,0 +1,166 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{BitmapFormat, BitmapStrike, ColorBitmap, FontTable};
+use std::mem;
+use util::Jump;
+
+pub const TAG: u32 = ((b's' as u32) << 24) |
+                      ((b'b' as u32) << 16) |
+                      ((b'i' as u32) << 8)  |
+                       (b'x' as u32);
+
+const GRAPHIC_TYPE_DUPE: u32 = ((b'd' as u32) << 24) | ((b'u' as u32) << 16) |
+                               ((b'p' as u32) << 8) | (b'e' as u32);
+const GRAPHIC_TYPE_JPG: u32 = ((b'j' as u32) << 24) | ((b'p' as u32) << 16) |
+                              ((b'g' as u32) << 8) | (b' ' as u32);
+const GRAPHIC_TYPE_PNG: u32 = ((b'p' as u32) << 24) | ((b'n' as u32) << 16) |
+                              ((b'g' as u32) << 8) | (b' ' as u32);
+const GRAPHIC_TYPE_TIFF: u32 = ((b't' as u32) << 24) | ((b'i' as u32) << 16) |
+                               ((b'f' as u32) << 8) | (b'f' as u32);
+
+/// The size of the header of each strike: the pixels per em and the pixels per inch.
+const STRIKE_HEADER_SIZE: usize = 4;
+
+/// The size of the header of each glyph's data: the origin and the graphic type.
+const GLYPH_DATA_HEADER_SIZE: usize = 8;
+
+/// The horizontal and vertical origin, graphic type, and data of a glyph's image.
+type GlyphData<'a> = (i16, i16, u32, &'a [u8]);
+
+/// The standard bitmap graphics table, which stores color glyphs as images (usually PNGs) at a
+/// handful of sizes.
+#[derive(Clone, Copy)]
+pub struct SbixTable<'a> {
+    table: FontTable<'a>,
+    strike_count: u32,
+}
+
+impl<'a> SbixTable<'a> {
+    pub fn new(table: FontTable) -> Result<SbixTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        try!(reader.jump(mem::size_of::<u16>()).map_err(FontError::eof));
+        let strike_count = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if reader.len() < strike_count as usize * mem::size_of::<u32>() {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(SbixTable {
+            table: table,
+            strike_count: strike_count,
+        })
+    }
+
+    /// Returns the sizes of the strikes in the order the font lists them.
+    pub fn strikes(&self) -> Result<Vec<BitmapStrike>, FontError> {
+        let mut strikes = Vec::with_capacity(self.strike_count as usize);
+        for strike_index in 0..self.strike_count {
+            let mut reader = try!(self.strike(strike_index));
+            let ppem = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let ppi = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            strikes.push(BitmapStrike {
+                ppem: ppem,
+                ppi: ppi,
+            })
+        }
+        Ok(strikes)
+    }
+
+    /// Returns the image of the given glyph in the strike with the given index, or `None` if the
+    /// strike has no image for the glyph. `dupe` glyphs are resolved to the glyph they copy.
+    pub fn glyph_bitmap(&self, strike_index: u32, glyph_id: u16, glyph_count: u16)
+                        -> Result<Option<ColorBitmap<'a>>, FontError> {
+        let strike = try!(self.strike(strike_index));
+        let (origin_x, origin_y, graphic_type, data) =
+            match try!(glyph_data(strike, glyph_id, glyph_count)) {
+                None => return Ok(None),
+                Some(glyph_data) => glyph_data,
+            };
+
+        let (origin_x, origin_y, graphic_type, data) = if graphic_type == GRAPHIC_TYPE_DUPE {
+            let mut reader = data;
+            let glyph_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            match try!(glyph_data(strike, glyph_id, glyph_count)) {
+                None => return Ok(None),
+                // Don't follow chains of duplicates, which could loop.
+                Some((_, _, GRAPHIC_TYPE_DUPE, _)) => return Err(FontError::Failed),
+                Some(glyph_data) => glyph_data,
+            }
+        } else {
+            (origin_x, origin_y, graphic_type, data)
+        };
+
+        let format = match graphic_type {
+            GRAPHIC_TYPE_PNG => BitmapFormat::Png,
+            GRAPHIC_TYPE_JPG => BitmapFormat::Jpeg,
+            GRAPHIC_TYPE_TIFF => BitmapFormat::Tiff,
+            _ => return Err(FontError::UnsupportedGlyphFormat),
+        };
+
+        let mut reader = strike;
+        let ppem = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        Ok(Some(ColorBitmap {
+            format: format,
+            ppem: ppem,
+            origin_x: origin_x,
+            origin_y: origin_y,
+            data: data,
+        }))
+    }
+
+    fn strike(&self, strike_index: u32) -> Result<&'a [u8], FontError> {
+        if strike_index >= self.strike_count {
+            return Err(FontError::Failed)
+        }
+
+        let mut reader = self.table.bytes;
+        try!(reader.jump(mem::size_of::<u16>() * 2 + mem::size_of::<u32>() +
+                         mem::size_of::<u32>() * strike_index as usize).map_err(FontError::eof));
+        let strike_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let mut strike = self.table.bytes;
+        try!(strike.jump(strike_offset as usize).map_err(FontError::eof));
+        Ok(strike)
+    }
+}
+
+/// Returns the origin, graphic type, and data of the given glyph in `strike`, or `None` if the
+/// strike has no image for it.
+fn glyph_data(strike: &[u8], glyph_id: u16, glyph_count: u16)
+              -> Result<Option<GlyphData>, FontError> {
+    if glyph_id >= glyph_count {
+        return Ok(None)
+    }
+
+    // Each glyph's data runs up to the next glyph's offset, so empty data means no image.
+    let mut reader = strike;
+    try!(reader.jump(STRIKE_HEADER_SIZE + mem::size_of::<u32>() * glyph_id as usize)
+               .map_err(FontError::eof));
+    let offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+    let next_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+    if next_offset <= offset {
+        return Ok(None)
+    }
+    if next_offset - offset < GLYPH_DATA_HEADER_SIZE || next_offset > strike.len() {
+        return Err(FontError::UnexpectedEof)
+    }
+
+    let mut reader = &strike[offset..next_offset];
+    let origin_x = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    let origin_y = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+    let graphic_type = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+    Ok(Some((origin_x, origin_y, graphic_type, reader)))
+}
@@ -0,0 +1,168 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::{AxisValue, AxisValueFlags, DesignAxis, FontTable};
+use std::mem;
+use tables::layout;
+use util::Jump;
+
+pub const TAG: u32 = ((b'S' as u32) << 24) |
+                      ((b'T' as u32) << 16) |
+                      ((b'A' as u32) << 8)  |
+                       (b'T' as u32);
+
+/// The size of the fields of a design axis record that we know about: a tag, a name ID, and an
+/// ordering. Records may be larger.
+const DESIGN_AXIS_RECORD_MIN_SIZE: usize = 8;
+
+const AXIS_VALUE_FORMAT_SINGLE: u16 = 1;
+const AXIS_VALUE_FORMAT_RANGE: u16 = 2;
+const AXIS_VALUE_FORMAT_LINKED: u16 = 3;
+const AXIS_VALUE_FORMAT_MULTIPLE_AXES: u16 = 4;
+
+/// The style attributes table, which describes the design axes of a font family and names
+/// positions along them, such as "Bold" on the weight axis.
+#[derive(Clone, Copy)]
+pub struct StatTable<'a> {
+    table: FontTable<'a>,
+    design_axis_size: u16,
+    design_axis_count: u16,
+    design_axes_offset: u32,
+    axis_value_count: u16,
+    axis_value_offsets_offset: u32,
+    elided_fallback_name_id: Option<u16>,
+}
+
+impl<'a> StatTable<'a> {
+    pub fn new(table: FontTable) -> Result<StatTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let design_axis_size = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let design_axis_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let design_axes_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let axis_value_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let axis_value_offsets_offset =
+            try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        if design_axis_count > 0 && (design_axis_size as usize) < DESIGN_AXIS_RECORD_MIN_SIZE {
+            return Err(FontError::Failed)
+        }
+
+        // Version 1.0 has no elided fallback name.
+        let elided_fallback_name_id = if minor_version >= 1 {
+            Some(try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)))
+        } else {
+            None
+        };
+
+        Ok(StatTable {
+            table: table,
+            design_axis_size: design_axis_size,
+            design_axis_count: design_axis_count,
+            design_axes_offset: design_axes_offset,
+            axis_value_count: axis_value_count,
+            axis_value_offsets_offset: axis_value_offsets_offset,
+            elided_fallback_name_id: elided_fallback_name_id,
+        })
+    }
+
+    /// Returns the design axes, in the order that axis values refer to them by.
+    pub fn design_axes(&self) -> Result<Vec<DesignAxis>, FontError> {
+        let mut axes = Vec::with_capacity(self.design_axis_count as usize);
+        for axis_index in 0..self.design_axis_count as usize {
+            let mut reader = try!(layout::at(self.table.bytes, self.design_axes_offset as usize));
+            try!(reader.jump(axis_index * self.design_axis_size as usize)
+                       .map_err(FontError::eof));
+            let tag = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+            let name_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            let ordering = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            axes.push(DesignAxis {
+                tag: tag,
+                name_id: name_id,
+                ordering: ordering,
+            })
+        }
+        Ok(axes)
+    }
+
+    /// Returns the axis values, in the order they're listed in the table.
+    pub fn axis_values(&self) -> Result<Vec<AxisValue>, FontError> {
+        let offsets = try!(layout::at(self.table.bytes, self.axis_value_offsets_offset as usize));
+        let mut values = Vec::with_capacity(self.axis_value_count as usize);
+        for value_index in 0..self.axis_value_count as usize {
+            let mut reader = offsets;
+            try!(reader.jump(value_index * mem::size_of::<u16>()).map_err(FontError::eof));
+            let offset = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            values.push(try!(read_axis_value(try!(layout::at(offsets, offset as usize)))))
+        }
+        Ok(values)
+    }
+
+    /// Returns the ID of the name to use when every axis value of a style is elided, such as
+    /// "Regular", if the table has one.
+    #[inline]
+    pub fn elided_fallback_name_id(&self) -> Option<u16> {
+        self.elided_fallback_name_id
+    }
+}
+
+fn read_axis_value(mut reader: &[u8]) -> Result<AxisValue, FontError> {
+    let format = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    if format == AXIS_VALUE_FORMAT_MULTIPLE_AXES {
+        let axis_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let name_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let mut locations = Vec::with_capacity(axis_count as usize);
+        for _ in 0..axis_count {
+            let axis_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+            locations.push((axis_index, try!(read_fixed(&mut reader))))
+        }
+        return Ok(AxisValue {
+            name_id: name_id,
+            flags: AxisValueFlags::from_bits_truncate(flags),
+            locations: locations,
+            range: None,
+            linked_value: None,
+        })
+    }
+
+    let axis_index = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let flags = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let name_id = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+    let value = try!(read_fixed(&mut reader));
+    let (range, linked_value) = match format {
+        AXIS_VALUE_FORMAT_SINGLE => (None, None),
+        AXIS_VALUE_FORMAT_RANGE => {
+            let min = try!(read_fixed(&mut reader));
+            let max = try!(read_fixed(&mut reader));
+            (Some((min, max)), None)
+        }
+        AXIS_VALUE_FORMAT_LINKED => (None, Some(try!(read_fixed(&mut reader)))),
+        _ => return Err(FontError::UnknownFormat),
+    };
+
+    Ok(AxisValue {
+        name_id: name_id,
+        flags: AxisValueFlags::from_bits_truncate(flags),
+        locations: vec![(axis_index, value)],
+        range: range,
+        linked_value: linked_value,
+    })
+}
+
+fn read_fixed(reader: &mut &[u8]) -> Result<f32, FontError> {
+    Ok(try!(reader.read_i32::<BigEndian>().map_err(FontError::eof)) as f32 / 65536.0)
+}
@@ -0,0 +1,82 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use paint::SvgDocument;
+use util::Jump;
+
+pub const TAG: u32 = ((b'S' as u32) << 24) |
+                      ((b'V' as u32) << 16) |
+                      ((b'G' as u32) << 8)  |
+                       (b' ' as u32);
+
+/// The size of an `SVGDocumentRecord`: the first and last glyph IDs, and the offset and length of
+/// the document.
+const SVG_DOCUMENT_RECORD_SIZE: usize = 12;
+
+/// The table of SVG documents that describe color glyphs.
+#[derive(Clone, Copy)]
+pub struct SvgTable<'a> {
+    document_list: &'a [u8],
+}
+
+impl<'a> SvgTable<'a> {
+    pub fn new(table: FontTable) -> Result<SvgTable, FontError> {
+        let mut reader = table.bytes;
+        let version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if version != 0 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let document_list_offset = try!(reader.read_u32::<BigEndian>().map_err(FontError::eof));
+        let mut document_list = table.bytes;
+        try!(document_list.jump(document_list_offset as usize).map_err(FontError::eof));
+
+        Ok(SvgTable {
+            document_list: document_list,
+        })
+    }
+
+    /// Returns the document containing the given glyph, or `None` if no document does.
+    pub fn document(&self, glyph_id: u16) -> Result<Option<SvgDocument<'a>>, FontError> {
+        let mut reader = self.document_list;
+        let record_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+
+        // The records are sorted by glyph ID and don't overlap, so binary search.
+        let (mut low, mut high) = (0, record_count as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            let mut record = reader;
+            try!(record.jump(mid * SVG_DOCUMENT_RECORD_SIZE).map_err(FontError::eof));
+            let start_glyph_id = try!(record.read_u16::<BigEndian>().map_err(FontError::eof));
+            let end_glyph_id = try!(record.read_u16::<BigEndian>().map_err(FontError::eof));
+            if glyph_id < start_glyph_id {
+                high = mid
+            } else if glyph_id > end_glyph_id {
+                low = mid + 1
+            } else {
+                let offset = try!(record.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+                let length = try!(record.read_u32::<BigEndian>().map_err(FontError::eof)) as usize;
+                if offset + length > self.document_list.len() {
+                    return Err(FontError::UnexpectedEof)
+                }
+                return Ok(Some(SvgDocument {
+                    bytes: &self.document_list[offset..offset + length],
+                    start_glyph_id: start_glyph_id,
+                    end_glyph_id: end_glyph_id,
+                }))
+            }
+        }
+
+        Ok(None)
+    }
+}
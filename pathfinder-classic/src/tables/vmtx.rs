@@ -0,0 +1,84 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use std::cmp;
+use std::mem;
+use tables::vhea::VheaTable;
+use util::Jump;
+
+pub const TAG: u32 = ((b'v' as u32) << 24) |
+                      ((b'm' as u32) << 16) |
+                      ((b't' as u32) << 8)  |
+                       (b'x' as u32);
+
+/// The size of an advance height and top side bearing pair.
+const LONG_VER_METRIC_SIZE: usize = 4;
+
+#[derive(Clone, Copy)]
+pub struct VmtxTable<'a> {
+    table: FontTable<'a>,
+}
+
+impl<'a> VmtxTable<'a> {
+    pub fn new(table: FontTable) -> VmtxTable {
+        VmtxTable {
+            table: table,
+        }
+    }
+
+    pub fn metrics_for_glyph(&self, vhea_table: &VheaTable, glyph_id: u16)
+                             -> Result<VerticalMetrics, FontError> {
+        Ok(VerticalMetrics {
+            advance_height: try!(self.advance_height(vhea_table, glyph_id)),
+            tsb: try!(self.top_side_bearing(vhea_table, glyph_id)),
+        })
+    }
+
+    /// Returns the advance height of the given glyph.
+    ///
+    /// As in `hmtx`, the glyphs after the first `numOfLongVerMetrics` share the last advance.
+    pub fn advance_height(&self, vhea_table: &VheaTable, glyph_id: u16)
+                          -> Result<u16, FontError> {
+        if vhea_table.number_of_long_ver_metrics == 0 {
+            return Err(FontError::UnexpectedEof)
+        }
+        let metric_index = cmp::min(glyph_id, vhea_table.number_of_long_ver_metrics - 1);
+
+        let mut reader = self.table.bytes;
+        try!(reader.jump(LONG_VER_METRIC_SIZE * metric_index as usize).map_err(FontError::eof));
+        reader.read_u16::<BigEndian>().map_err(FontError::eof)
+    }
+
+    /// Returns the top side bearing of the given glyph: the distance from the top of the
+    /// vertical line to the top edge of its bounding box.
+    pub fn top_side_bearing(&self, vhea_table: &VheaTable, glyph_id: u16)
+                            -> Result<i16, FontError> {
+        let number_of_long_ver_metrics = vhea_table.number_of_long_ver_metrics as usize;
+        let offset = if (glyph_id as usize) < number_of_long_ver_metrics {
+            LONG_VER_METRIC_SIZE * glyph_id as usize + mem::size_of::<u16>()
+        } else {
+            LONG_VER_METRIC_SIZE * number_of_long_ver_metrics +
+                mem::size_of::<i16>() * (glyph_id as usize - number_of_long_ver_metrics)
+        };
+
+        let mut reader = self.table.bytes;
+        try!(reader.jump(offset).map_err(FontError::eof));
+        reader.read_i16::<BigEndian>().map_err(FontError::eof)
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct VerticalMetrics {
+    pub advance_height: u16,
+    pub tsb: i16,
+}
@@ -0,0 +1,70 @@
+// Copyright 2017 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
+use error::FontError;
+use font::FontTable;
+use std::cmp::Ordering;
+
+pub const TAG: u32 = ((b'V' as u32) << 24) |
+                      ((b'O' as u32) << 16) |
+                      ((b'R' as u32) << 8)  |
+                       (b'G' as u32);
+
+/// The size of a glyph ID and vertical origin pair.
+const VERT_ORIGIN_Y_METRICS_SIZE: usize = 4;
+
+/// The vertical origin table, which CFF-flavored fonts use to record the Y coordinate of each
+/// glyph's origin in vertical layout.
+#[derive(Clone, Copy)]
+pub struct VorgTable<'a> {
+    default_vert_origin_y: i16,
+    metrics: &'a [u8],
+}
+
+impl<'a> VorgTable<'a> {
+    pub fn new(table: FontTable) -> Result<VorgTable, FontError> {
+        let mut reader = table.bytes;
+        let major_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        let _minor_version = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof));
+        if major_version != 1 {
+            return Err(FontError::UnsupportedVersion)
+        }
+
+        let default_vert_origin_y = try!(reader.read_i16::<BigEndian>().map_err(FontError::eof));
+        let metrics_count = try!(reader.read_u16::<BigEndian>().map_err(FontError::eof)) as usize;
+        if reader.len() < metrics_count * VERT_ORIGIN_Y_METRICS_SIZE {
+            return Err(FontError::UnexpectedEof)
+        }
+
+        Ok(VorgTable {
+            default_vert_origin_y: default_vert_origin_y,
+            metrics: &reader[..metrics_count * VERT_ORIGIN_Y_METRICS_SIZE],
+        })
+    }
+
+    /// Returns the Y coordinate of the given glyph's vertical origin.
+    ///
+    /// Only the glyphs whose origin differs from the default are listed, sorted by glyph ID, so
+    /// every other glyph uses the default.
+    pub fn vertical_origin_y(&self, glyph_id: u16) -> i16 {
+        let (mut low, mut high) = (0, self.metrics.len() / VERT_ORIGIN_Y_METRICS_SIZE);
+        while low < high {
+            let mid = (low + high) / 2;
+            let record = &self.metrics[mid * VERT_ORIGIN_Y_METRICS_SIZE..];
+            match BigEndian::read_u16(record).cmp(&glyph_id) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return BigEndian::read_i16(&record[2..]),
+            }
+        }
+        self.default_vert_origin_y
+    }
+}
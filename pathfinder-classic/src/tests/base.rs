@@ -0,0 +1,170 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{BaselineAxis, BaselineExtents, Font, ScriptBaselines};
+use tables::base;
+use tests::sfnt;
+
+fn font_bytes(base_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(base_table) = base_table {
+        tables.push((base::TAG, base_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+fn base_coord(coordinate: i16) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_i16::<BigEndian>(coordinate).unwrap();
+    table
+}
+
+fn base_values(default_baseline_index: u16, coordinates: &[i16]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(default_baseline_index).unwrap();
+    table.write_u16::<BigEndian>(coordinates.len() as u16).unwrap();
+    for index in 0..coordinates.len() {
+        table.write_u16::<BigEndian>((4 + 2 * coordinates.len() + 4 * index) as u16).unwrap();
+    }
+    for &coordinate in coordinates {
+        table.extend_from_slice(&base_coord(coordinate));
+    }
+    table
+}
+
+fn min_max(min: Option<i16>, max: Option<i16>) -> Vec<u8> {
+    let mut table = vec![];
+    let mut coords = vec![];
+    for &coordinate in &[min, max] {
+        match coordinate {
+            None => table.write_u16::<BigEndian>(0).unwrap(),
+            Some(coordinate) => {
+                table.write_u16::<BigEndian>((6 + coords.len()) as u16).unwrap();
+                coords.extend_from_slice(&base_coord(coordinate));
+            }
+        }
+    }
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.extend_from_slice(&coords);
+    table
+}
+
+fn base_script(values: Option<Vec<u8>>, default_min_max: Option<Vec<u8>>,
+               lang_sys: &[(u32, Vec<u8>)])
+               -> Vec<u8> {
+    let mut body = vec![];
+    let mut offset = |table: Option<&Vec<u8>>, body: &mut Vec<u8>| {
+        match table {
+            None => 0,
+            Some(table) => {
+                let offset = 6 + 6 * lang_sys.len() + body.len();
+                body.extend_from_slice(table);
+                offset as u16
+            }
+        }
+    };
+
+    let mut table = vec![];
+    let values_offset = offset(values.as_ref(), &mut body);
+    let default_min_max_offset = offset(default_min_max.as_ref(), &mut body);
+    table.write_u16::<BigEndian>(values_offset).unwrap();
+    table.write_u16::<BigEndian>(default_min_max_offset).unwrap();
+    table.write_u16::<BigEndian>(lang_sys.len() as u16).unwrap();
+    for &(tag, ref min_max) in lang_sys {
+        table.write_u32::<BigEndian>(tag).unwrap();
+        table.write_u16::<BigEndian>(offset(Some(min_max), &mut body)).unwrap();
+    }
+    table.extend_from_slice(&body);
+    table
+}
+
+/// A table with horizontal baselines for the default script and for Latin, and extents for
+/// Latin and for Turkish.
+fn base_table() -> Vec<u8> {
+    let scripts = [
+        (sfnt::tag(b"DFLT"), base_script(Some(base_values(2, &[1500, -120, 0])), None, &[])),
+        (sfnt::tag(b"latn"), base_script(Some(base_values(2, &[1400, -100, 0])),
+                                         Some(min_max(Some(-300), Some(1800))),
+                                         &[(sfnt::tag(b"TRK "), min_max(Some(-350), None))])),
+    ];
+
+    let mut base_tag_list = vec![];
+    base_tag_list.write_u16::<BigEndian>(3).unwrap();
+    for name in &[b"hang", b"ideo", b"romn"] {
+        base_tag_list.write_u32::<BigEndian>(sfnt::tag(name)).unwrap();
+    }
+
+    let mut base_script_list = vec![];
+    let mut script_tables = vec![];
+    base_script_list.write_u16::<BigEndian>(scripts.len() as u16).unwrap();
+    for &(tag, ref script) in &scripts {
+        let offset = 2 + 6 * scripts.len() + script_tables.len();
+        base_script_list.write_u32::<BigEndian>(tag).unwrap();
+        base_script_list.write_u16::<BigEndian>(offset as u16).unwrap();
+        script_tables.extend_from_slice(script);
+    }
+    base_script_list.extend_from_slice(&script_tables);
+
+    let mut table = vec![];
+    for &value in &[1, 0, 8, 0, 4, 4 + base_tag_list.len() as u16] {
+        table.write_u16::<BigEndian>(value).unwrap();
+    }
+    table.extend_from_slice(&base_tag_list);
+    table.extend_from_slice(&base_script_list);
+    table
+}
+
+#[test]
+fn script_baselines() {
+    let bytes = font_bytes(Some(base_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let (hang, ideo, romn) = (sfnt::tag(b"hang"), sfnt::tag(b"ideo"), sfnt::tag(b"romn"));
+
+    assert_eq!(font.script_baselines(BaselineAxis::Horizontal, sfnt::tag(b"latn")),
+               Ok(Some(ScriptBaselines {
+                   default_baseline: romn,
+                   baselines: vec![(hang, 1400), (ideo, -100), (romn, 0)],
+               })));
+    assert_eq!(font.baseline(BaselineAxis::Horizontal, sfnt::tag(b"latn"), ideo),
+               Ok(Some(-100)));
+    assert_eq!(font.baseline(BaselineAxis::Horizontal, sfnt::tag(b"latn"), sfnt::tag(b"math")),
+               Ok(None));
+
+    // Scripts that aren't listed get the baselines of the default script.
+    assert_eq!(font.baseline(BaselineAxis::Horizontal, sfnt::tag(b"cyrl"), hang), Ok(Some(1500)));
+
+    assert_eq!(font.script_baselines(BaselineAxis::Vertical, sfnt::tag(b"latn")), Ok(None));
+}
+
+#[test]
+fn baseline_extents_fall_back_to_the_script() {
+    let bytes = font_bytes(Some(base_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let latn = sfnt::tag(b"latn");
+
+    assert_eq!(font.baseline_extents(BaselineAxis::Horizontal, latn, sfnt::tag(b"TRK ")),
+               Ok(Some(BaselineExtents { min: Some(-350), max: None })));
+    assert_eq!(font.baseline_extents(BaselineAxis::Horizontal, latn, sfnt::tag(b"ENG ")),
+               Ok(Some(BaselineExtents { min: Some(-300), max: Some(1800) })));
+    assert_eq!(font.baseline_extents(BaselineAxis::Horizontal, sfnt::tag(b"cyrl"),
+                                     sfnt::tag(b"RUS ")),
+               Ok(None));
+}
+
+#[test]
+fn fonts_without_base_have_no_baselines() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let latn = sfnt::tag(b"latn");
+    assert_eq!(font.script_baselines(BaselineAxis::Horizontal, latn), Ok(None));
+    assert_eq!(font.baseline(BaselineAxis::Horizontal, latn, sfnt::tag(b"romn")), Ok(None));
+    assert_eq!(font.baseline_extents(BaselineAxis::Horizontal, latn, sfnt::tag(b"ENG ")),
+               Ok(None));
+}
@@ -0,0 +1,95 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use charmap::CodepointRange;
+use font::FontTable;
+use tables::cmap::{CmapTable, SegmentSearch};
+use test::Bencher;
+use tests::sfnt;
+
+/// Builds a character map with 10,000 format 12 groups of three codepoints each, separated by
+/// gaps of two unmapped codepoints.
+fn large_format_12_cmap() -> Vec<u8> {
+    let groups: Vec<_> = (0..10000).map(|group_index| {
+        let start = 0x100 + group_index * 5;
+        (start, start + 2, 1 + group_index * 3)
+    }).collect();
+    sfnt::cmap_table(&[(3, 10, sfnt::cmap_format_12(&groups))])
+}
+
+fn whole_range() -> [CodepointRange; 1] {
+    [CodepointRange::new(0, 0x100 + 10000 * 5 + 10)]
+}
+
+#[test]
+fn branchless_search_agrees_with_branchy_search() {
+    let bytes = large_format_12_cmap();
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let branchy = cmap.glyph_mapping_for_codepoint_ranges_using(&whole_range(),
+                                                                SegmentSearch::Branchy).unwrap();
+    let branchless =
+        cmap.glyph_mapping_for_codepoint_ranges_using(&whole_range(),
+                                                      SegmentSearch::Branchless).unwrap();
+    assert!(branchy.iter().eq(branchless.iter()));
+    assert_eq!(branchless.glyph_for(0x100), Some(1));
+    assert_eq!(branchless.glyph_for(0x103), Some(0));
+    assert_eq!(branchless.glyph_for(0x105), Some(4));
+}
+
+#[test]
+fn coverage_bloom_has_no_false_negatives_and_few_false_positives() {
+    let bytes = large_format_12_cmap();
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    let coverage = cmap.coverage().unwrap();
+    let bloom = cmap.build_bloom().unwrap();
+
+    for range in coverage.ranges() {
+        assert!(range.iter().all(|codepoint| bloom.maybe_contains(codepoint)));
+    }
+
+    // Sample uncovered codepoints with a xorshift generator so that the test is deterministic.
+    let (mut state, mut sample_count, mut false_positive_count) = (0x2545f491u32, 0, 0);
+    while sample_count < 100000 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let codepoint = state % 0x110000;
+        if coverage.contains(codepoint) {
+            continue
+        }
+        sample_count += 1;
+        if bloom.maybe_contains(codepoint) {
+            false_positive_count += 1
+        }
+    }
+    assert!(false_positive_count < sample_count / 50);
+}
+
+#[bench]
+fn bench_branchy_search(bencher: &mut Bencher) {
+    let bytes = large_format_12_cmap();
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    bencher.iter(|| {
+        cmap.glyph_mapping_for_codepoint_ranges_using(&whole_range(), SegmentSearch::Branchy)
+            .unwrap()
+    })
+}
+
+#[bench]
+fn bench_branchless_search(bencher: &mut Bencher) {
+    let bytes = large_format_12_cmap();
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    bencher.iter(|| {
+        cmap.glyph_mapping_for_codepoint_ranges_using(&whole_range(), SegmentSearch::Branchless)
+            .unwrap()
+    })
+}
@@ -0,0 +1,358 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use charmap::{CodepointMismatch, CodepointRange, GlyphMapping};
+use error::FontError;
+use font::FontTable;
+use tables::cmap::CmapTable;
+use tests::sfnt;
+
+#[test]
+fn repaired_segments_find_codepoints_in_out_of_order_segments() {
+    // The lowercase segment comes before the uppercase one, so a binary search for 'b' passes
+    // over it.
+    let bytes = sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4_with_glyph_ids(&[
+            (0x61, 0x63, -0x5e, None),
+            (0x41, 0x43, -0x40, None),
+            (0xffff, 0xffff, 1, None),
+        ], &[])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.glyphs_for_str("Bb").unwrap(), [2, 0]);
+
+    let repaired = cmap.with_repaired_segments().unwrap();
+    assert_eq!(repaired.glyphs_for_str("Bb").unwrap(), [2, 4]);
+    assert_eq!(repaired.glyph_for_char('a'), Ok(Some(3)));
+    assert_eq!(repaired.glyph_for_char('d'), Ok(None));
+}
+
+#[test]
+fn compare_subtables_reports_the_disagreeing_codepoint() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x5a, -0x40)])),
+        (3, 10, sfnt::cmap_format_12(&[
+            (0x41, 0x46, 1),
+            (0x47, 0x47, 40),
+            (0x48, 0x5a, 8),
+            (0x1f600, 0x1f600, 50),
+        ])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let subtables = cmap.subtables().unwrap();
+    assert_eq!(subtables.len(), 2);
+    assert_eq!((subtables[1].platform_id, subtables[1].encoding_id), (3, 10));
+
+    let comparison = cmap.compare_subtables(&subtables[0], &subtables[1]).unwrap();
+    assert!(!comparison.is_identical());
+    assert_eq!(comparison.mismatches, [
+        CodepointMismatch {
+            codepoint: 0x47,
+            glyph_a: 7,
+            glyph_b: 40,
+        },
+    ]);
+}
+
+#[test]
+fn compare_subtables_with_itself_is_identical() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x30, 0x39, -0x2f)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let subtables = cmap.subtables().unwrap();
+    assert!(cmap.compare_subtables(&subtables[0], &subtables[0]).unwrap().is_identical());
+}
+
+#[test]
+fn format_2_maps_one_and_two_byte_codes() {
+    // A Shift-JIS subtable, where 0x82 leads two-byte codes and so can't stand alone.
+    let bytes = sfnt::cmap_table(&[
+        (3, 0, sfnt::cmap_format_4(&[(0xf041, 0xf041, 0x0fc8)])),
+        (3, 2, sfnt::cmap_format_2((0x41, &[1, 2, 0]), &[(0x82, 0x9f, &[10, 11, 12])])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    let subtables = cmap.subtables().unwrap();
+    let cmap = cmap.with_subtable(&subtables[1]);
+
+    let codes = [0x40, 0x41, 0x42, 0x43, 0x82, 0x829e, 0x829f, 0x82a1, 0x82a2, 0x839f, 0x10000];
+    let codepoint_ranges: Vec<_> = codes.iter().map(|&code| {
+        CodepointRange::new(code, code)
+    }).collect();
+    let glyph_mapping = cmap.glyph_mapping_for_codepoint_ranges(&codepoint_ranges).unwrap();
+    assert_eq!(codes.iter().map(|&code| glyph_mapping.glyph_for(code).unwrap()).collect::<Vec<_>>(),
+               [0, 1, 2, 0, 0, 0, 10, 12, 0, 0, 0]);
+
+    let coverage: Vec<_> = cmap.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, cmap.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x41, 0x42), CodepointRange::new(0x829f, 0x82a1)]);
+}
+
+#[test]
+fn preferred_subtables_override_the_default_choice() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 0, sfnt::cmap_format_4(&[(0xf041, 0xf041, 0x0fdd)])),
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x41, -0x41 + 10)])),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x41, 20)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    let subtables = cmap.subtables().unwrap();
+    assert_eq!(subtables.iter().map(|subtable| subtable.format).collect::<Vec<_>>(),
+               [Some(4), Some(4), Some(12)]);
+    assert_eq!(cmap.glyph_for_char('A'), Ok(Some(20)));
+
+    let bmp = cmap.with_preferred_subtable(&[(0, 3), (3, 1), (3, 10)]).unwrap();
+    assert_eq!(bmp.glyph_for_char('A'), Ok(Some(10)));
+    let symbol = cmap.with_preferred_subtable(&[(3, 0)]).unwrap();
+    assert_eq!(symbol.glyph_for_codepoint_resilient(0xf041), Ok(30));
+    assert_eq!(symbol.glyphs_for_str("\u{f041}A").unwrap(), [30, 0]);
+    let unmatched = cmap.with_preferred_subtable(&[(1, 0)]).unwrap();
+    assert_eq!(unmatched.glyph_for_char('A'), Ok(Some(20)));
+}
+
+#[test]
+fn preferred_languages_pick_localized_subtables() {
+    // Two Macintosh Roman subtables, for English (language ID 0) and French (language ID 1),
+    // which disagree about 'A'.
+    let macintosh_roman = |glyph_id, language| {
+        let mut glyph_ids = vec![0; 0x41];
+        glyph_ids.push(glyph_id);
+        let mut subtable = sfnt::cmap_format_0(&glyph_ids);
+        subtable[5] = language;
+        subtable
+    };
+    let bytes = sfnt::cmap_table(&[
+        (1, 0, macintosh_roman(1, 1)),
+        (1, 0, macintosh_roman(2, 2)),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x41, 3)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.subtables().unwrap().iter().map(|subtable| {
+        subtable.language
+    }).collect::<Vec<_>>(), [Some(1), Some(2), Some(0)]);
+
+    assert_eq!(cmap.with_preferred_language(2).unwrap().glyph_for_char('A'), Ok(Some(2)));
+    assert_eq!(cmap.with_preferred_language(1).unwrap().glyph_for_char('A'), Ok(Some(1)));
+    assert_eq!(cmap.with_preferred_language(0).unwrap().glyph_for_char('A'), Ok(Some(3)));
+    assert_eq!(cmap.with_preferred_language(5).unwrap().glyph_for_char('A'), Ok(Some(3)));
+}
+
+#[test]
+fn lookups_reject_overflowing_glyph_ids() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 0xffffffff)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.glyph_for_char('B'), Err(FontError::Failed));
+    assert_eq!(cmap.glyph_for_char('a'), Ok(None));
+    assert_eq!(cmap.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0x41, 0x42)]).err(),
+               Some(FontError::Failed));
+}
+
+#[test]
+fn single_lookups_agree_with_glyph_mappings() {
+    let bytes = sfnt::cmap_table(&[
+        (0, 3, sfnt::cmap_format_4_with_glyph_ids(&[
+            (0x20, 0x3f, -0x1f, None),
+            (0x41, 0x43, 0, Some(0)),
+            (0xd7f0, 0xe010, 0x100, None),
+            (0xffff, 0xffff, 1, None),
+        ], &[7, 0, 9])),
+        (1, 0, sfnt::cmap_format_6(0x80, &[20, 21, 22])),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 1), (0x1f600, 0x1f64f, 100)])),
+        (3, 1, sfnt::cmap_format_0(&[3; 256])),
+        (0, 6, sfnt::cmap_format_13(&[(0x30, 0x39, 5), (0x2000, 0x2fff, 6)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let codepoints = [
+        CodepointRange::new(0, 0x300),
+        CodepointRange::new(0x2000, 0x2010),
+        CodepointRange::new(0xd7f0, 0xe010),
+        CodepointRange::new(0x1f5ff, 0x1f650),
+    ];
+    for subtable in &cmap.subtables().unwrap() {
+        let cmap = cmap.with_subtable(subtable);
+        let mapping = cmap.glyph_mapping_for_codepoint_ranges(&codepoints).unwrap();
+        for (codepoint, glyph_id) in mapping.iter() {
+            let character = match ::std::char::from_u32(codepoint) {
+                Some(character) => character,
+                None => continue,
+            };
+            let expected = if glyph_id == 0 { None } else { Some(glyph_id) };
+            assert_eq!(cmap.glyph_for_char(character), Ok(expected),
+                       "U+{:04X} in subtable {:?}", codepoint, subtable);
+        }
+    }
+}
+
+#[test]
+fn mapping_reports_list_unmapped_codepoints() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x43, -0x40), (0x61, 0x61, -0x5c)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let codepoints = [CodepointRange::new(0x61, 0x62), CodepointRange::new(0x40, 0x44)];
+    let (mapping, report) = cmap.glyph_mapping_with_report(&codepoints).unwrap();
+    assert_eq!(mapping.glyph_for(0x42), Some(2));
+    assert_eq!((report.subtable.platform_id, report.subtable.encoding_id), (3, 1));
+    assert_eq!(report.subtable.format, Some(4));
+    assert!(!report.is_complete());
+    assert_eq!(report.unmapped, [
+        CodepointRange::new(0x40, 0x40),
+        CodepointRange::new(0x44, 0x44),
+        CodepointRange::new(0x62, 0x62),
+    ]);
+
+    let (_, report) = cmap.glyph_mapping_with_report(&[CodepointRange::new(0x41, 0x43)]).unwrap();
+    assert!(report.is_complete());
+}
+
+#[test]
+fn visited_ranges_match_the_glyph_mapping() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4_with_glyph_ids(&[
+            (0x20, 0x3f, -0x1f, None),
+            (0x41, 0x43, 0, Some(0)),
+            (0xffff, 0xffff, 1, None),
+        ], &[7, 0, 9])),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 1), (0x1f600, 0x1f64f, 100)])),
+        (1, 0, sfnt::cmap_format_6(0x80, &[20, 21, 22])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let codepoints = [CodepointRange::new(0x1f5ff, 0x1f601), CodepointRange::new(0x30, 0x50)];
+    for subtable in &cmap.subtables().unwrap() {
+        let cmap = cmap.with_subtable(subtable);
+        let mut visited = GlyphMapping::new();
+        cmap.for_each_mapped_range(&codepoints, |range| visited.push(range)).unwrap();
+        let expected = cmap.glyph_mapping_for_codepoint_ranges(&codepoints).unwrap();
+        assert_eq!(visited.iter().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+    }
+
+    // Surrogates are visited in place rather than sorted to the end.
+    let codepoints = [CodepointRange::new(0xd7ff, 0xd800), CodepointRange::new(0x41, 0x41)];
+    let mut visited = vec![];
+    cmap.for_each_mapped_range(&codepoints, |range| {
+        visited.push((range.codepoint_start, range.glyphs.start))
+    }).unwrap();
+    assert_eq!(visited, [(0xd7ff, 0), (0xd800, 0), (0x41, 1)]);
+}
+
+#[test]
+fn glyph_mappings_keep_the_order_of_the_requested_codepoints() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 1), (0xe000, 0xe001, 50)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+
+    let codepoints = [
+        CodepointRange::new(0xdfff, 0xe000),
+        CodepointRange::new(0x42, 0x42),
+        CodepointRange::new(0xd7ff, 0xd800),
+        CodepointRange::new(0x41, 0x41),
+    ];
+    let mapping = cmap.glyph_mapping_for_codepoint_ranges(&codepoints).unwrap();
+    assert_eq!(mapping.iter().collect::<Vec<_>>(), [
+        (0xdfff, 0),
+        (0xe000, 50),
+        (0x42, 2),
+        (0xd7ff, 0),
+        (0xd800, 0),
+        (0x41, 1),
+    ]);
+}
+
+#[test]
+fn symbol_subtables_remap_ascii_into_the_private_use_area() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 0, sfnt::cmap_format_4(&[(0x20, 0x20, -0x1f), (0xf041, 0xf043, 0x0fc4)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.glyphs_for_str(" A\u{f041}").unwrap(), [1, 0, 5]);
+
+    let remapped = cmap.with_symbol_remapping();
+    assert_eq!(remapped.glyphs_for_str(" ABCD\u{f041}\u{141}").unwrap(), [1, 5, 6, 7, 0, 5, 0]);
+    assert_eq!(remapped.glyph_for_char('B'), Ok(Some(6)));
+    assert_eq!(remapped.glyph_for_char('\u{f042}'), Ok(Some(6)));
+    assert_eq!(remapped.coverage().unwrap().ranges(), [
+        CodepointRange::new(0x20, 0x20),
+        CodepointRange::new(0x41, 0x43),
+        CodepointRange::new(0xf041, 0xf043),
+    ]);
+    assert_eq!(remapped.coverage_ranges().unwrap().collect::<Vec<_>>(),
+               remapped.coverage().unwrap().ranges());
+
+    let mut visited = GlyphMapping::new();
+    let codepoints = [CodepointRange::new(0x40, 0x42), CodepointRange::new(0xf042, 0xf042)];
+    remapped.for_each_mapped_range(&codepoints, |range| visited.push(range)).unwrap();
+    assert_eq!(visited.iter().collect::<Vec<_>>(), [(0x40, 0), (0x41, 5), (0x42, 6), (0xf042, 6)]);
+}
+
+#[test]
+fn coverage_ranges_stop_at_a_missing_glyph_group_at_the_last_code() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x42, 1), (0xffff_ffff, 0xffff_ffff, 0)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.coverage_ranges().unwrap().collect::<Vec<_>>(),
+               [CodepointRange::new(0x41, 0x42)]);
+}
+
+#[test]
+fn coverage_stops_at_a_missing_glyph_group_at_the_last_code() {
+    let bytes = sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x42, 1), (0xffff_ffff, 0xffff_ffff, 0)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    assert_eq!(cmap.coverage().unwrap().ranges(), [CodepointRange::new(0x41, 0x42)]);
+}
+
+#[test]
+fn segmented_coverage_ends_at_the_last_codepoint() {
+    // A malformed group running almost to the end of the 32-bit code space.
+    let bytes = sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x41, 1), (0x10fff0, 0xffff_fff0, 2)])),
+    ]);
+    let cmap = CmapTable::new(FontTable {
+        bytes: &bytes,
+    });
+    let coverage = [CodepointRange::new(0x41, 0x41), CodepointRange::new(0x10fff0, 0x10ffff)];
+    assert_eq!(cmap.coverage().unwrap().ranges(), coverage);
+    assert_eq!(cmap.coverage_ranges().unwrap().collect::<Vec<_>>(), coverage);
+
+    let bloom = cmap.build_bloom().unwrap();
+    assert!(bloom.maybe_contains(0x41));
+    assert!(bloom.maybe_contains(0x10ffff));
+}
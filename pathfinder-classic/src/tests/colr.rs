@@ -0,0 +1,279 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use error::FontError;
+use euclid::{Matrix2D, Point2D};
+use font::Font;
+use paint::{Color, ColorLayer, ColorLine, ColorStop, Extend, Paint};
+use tables::{colr, cpal};
+use tests::sfnt;
+
+// Glyph 3 fills its own outline with a two-stop linear gradient. Glyph 7 has two layers from the
+// layer list: a solid fill, and a solid fill under a transform.
+fn colr_table() -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(34).unwrap();
+    table.write_u32::<BigEndian>(93).unwrap();
+    for _ in 0..3 {
+        table.write_u32::<BigEndian>(0).unwrap();
+    }
+
+    // BaseGlyphList.
+    table.write_u32::<BigEndian>(2).unwrap();
+    table.write_u16::<BigEndian>(3).unwrap();
+    table.write_u32::<BigEndian>(16).unwrap();
+    table.write_u16::<BigEndian>(7).unwrap();
+    table.write_u32::<BigEndian>(53).unwrap();
+
+    // PaintGlyph, then its PaintLinearGradient and that paint's ColorLine.
+    table.write_u8(10).unwrap();
+    table.write_u24::<BigEndian>(6).unwrap();
+    table.write_u16::<BigEndian>(3).unwrap();
+    table.write_u8(4).unwrap();
+    table.write_u24::<BigEndian>(16).unwrap();
+    for &coordinate in &[0, 0, 1000, 0, 0, 1000] {
+        table.write_i16::<BigEndian>(coordinate).unwrap();
+    }
+    table.write_u8(1).unwrap();
+    table.write_u16::<BigEndian>(2).unwrap();
+    for &(offset, palette_index, alpha) in &[(0, 0, 0x4000), (0x4000, 1, 0x2000)] {
+        table.write_i16::<BigEndian>(offset).unwrap();
+        table.write_u16::<BigEndian>(palette_index).unwrap();
+        table.write_i16::<BigEndian>(alpha).unwrap();
+    }
+
+    // PaintColrLayers.
+    table.write_u8(1).unwrap();
+    table.write_u8(2).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+
+    // LayerList, with a PaintSolid and a PaintTransform of another PaintSolid.
+    table.write_u32::<BigEndian>(2).unwrap();
+    table.write_u32::<BigEndian>(12).unwrap();
+    table.write_u32::<BigEndian>(17).unwrap();
+    table.write_u8(2).unwrap();
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.write_i16::<BigEndian>(0x4000).unwrap();
+    table.write_u8(12).unwrap();
+    table.write_u24::<BigEndian>(7).unwrap();
+    table.write_u24::<BigEndian>(12).unwrap();
+    table.write_u8(2).unwrap();
+    table.write_u16::<BigEndian>(0xffff).unwrap();
+    table.write_i16::<BigEndian>(0x4000).unwrap();
+    for &value in &[0x20000, 0, 0, 0x20000, 100 << 16, -50 << 16] {
+        table.write_i32::<BigEndian>(value).unwrap();
+    }
+
+    table
+}
+
+fn colr_font_bytes() -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((colr::TAG, colr_table()));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn linear_gradient_clipped_to_glyph() {
+    let bytes = colr_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.paint_graph(3).unwrap(), Some(Paint::Glyph {
+        glyph_id: 3,
+        paint: Box::new(Paint::LinearGradient {
+            color_line: ColorLine {
+                extend: Extend::Repeat,
+                stops: vec![
+                    ColorStop {
+                        offset: 0.0,
+                        palette_index: 0,
+                        alpha: 1.0,
+                    },
+                    ColorStop {
+                        offset: 1.0,
+                        palette_index: 1,
+                        alpha: 0.5,
+                    },
+                ],
+            },
+            p0: Point2D::new(0, 0),
+            p1: Point2D::new(1000, 0),
+            p2: Point2D::new(0, 1000),
+        }),
+    }));
+}
+
+#[test]
+fn layers_and_transforms() {
+    let bytes = colr_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.paint_graph(7).unwrap(), Some(Paint::Layers(vec![
+        Paint::Solid {
+            palette_index: 2,
+            alpha: 1.0,
+        },
+        Paint::Transform {
+            transform: Matrix2D::row_major(2.0, 0.0, 0.0, 2.0, 100.0, -50.0),
+            paint: Box::new(Paint::Solid {
+                palette_index: 0xffff,
+                alpha: 1.0,
+            }),
+        },
+    ])));
+}
+
+#[test]
+fn no_paint_graph_for_other_glyphs() {
+    let bytes = colr_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.paint_graph(4).unwrap(), None);
+
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.paint_graph(3).unwrap(), None);
+}
+
+// Glyph 1 is a PaintColrLayers of `layer_count` layers starting at `first_layer_index`. The layer
+// list has three tiers of 255 layers. Each layer in the first two tiers is a PaintColrLayers of the
+// whole next tier, and each layer in the last is a PaintSolid, so glyph 1 expands into millions of
+// paints although the table is small.
+fn shared_layers_colr_table(layer_count: u8, first_layer_index: u32) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(34).unwrap();
+    table.write_u32::<BigEndian>(50).unwrap();
+    for _ in 0..3 {
+        table.write_u32::<BigEndian>(0).unwrap();
+    }
+
+    // BaseGlyphList, then glyph 1's PaintColrLayers.
+    table.write_u32::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u32::<BigEndian>(10).unwrap();
+    table.write_u8(1).unwrap();
+    table.write_u8(layer_count).unwrap();
+    table.write_u32::<BigEndian>(first_layer_index).unwrap();
+
+    // LayerList, then the two PaintColrLayers and the PaintSolid that its tiers point to.
+    let tier_size = 255;
+    let paints_offset = 4 + 4 * 3 * tier_size;
+    table.write_u32::<BigEndian>(3 * tier_size).unwrap();
+    for &paint_offset in &[paints_offset, paints_offset + 6, paints_offset + 12] {
+        for _ in 0..tier_size {
+            table.write_u32::<BigEndian>(paint_offset).unwrap();
+        }
+    }
+    for tier in 1..3 {
+        table.write_u8(1).unwrap();
+        table.write_u8(tier_size as u8).unwrap();
+        table.write_u32::<BigEndian>(tier * tier_size).unwrap();
+    }
+    table.write_u8(2).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_i16::<BigEndian>(0x4000).unwrap();
+
+    table
+}
+
+fn shared_layers_font_bytes(layer_count: u8, first_layer_index: u32) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((colr::TAG, shared_layers_colr_table(layer_count, first_layer_index)));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn shared_layers_are_not_expanded_without_bound() {
+    let bytes = shared_layers_font_bytes(255, 0);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.paint_graph(1).err(), Some(FontError::Failed));
+}
+
+#[test]
+fn layer_indices_must_not_overflow() {
+    let bytes = shared_layers_font_bytes(2, 0xffffffff);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.paint_graph(1).err(), Some(FontError::Failed));
+}
+
+// Glyph 5 has a layer in palette entry 0 under a layer in the text color. Glyph 9 has one layer in
+// palette entry 1.
+fn colr_version_0_table() -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.write_u32::<BigEndian>(14).unwrap();
+    table.write_u32::<BigEndian>(26).unwrap();
+    table.write_u16::<BigEndian>(3).unwrap();
+    for &(glyph_id, first_layer_index, layer_count) in &[(5, 0, 2), (9, 2, 1)] {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+        table.write_u16::<BigEndian>(first_layer_index).unwrap();
+        table.write_u16::<BigEndian>(layer_count).unwrap();
+    }
+    for &(glyph_id, palette_index) in &[(10, 0), (11, 0xffff), (12, 1)] {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+        table.write_u16::<BigEndian>(palette_index).unwrap();
+    }
+    table
+}
+
+// Two palettes of two entries each. Colors are stored as blue, green, red, alpha.
+fn cpal_table() -> Vec<u8> {
+    let mut table = vec![];
+    for &value in &[0, 2, 2, 4] {
+        table.write_u16::<BigEndian>(value).unwrap();
+    }
+    table.write_u32::<BigEndian>(16).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.extend_from_slice(&[0, 0, 255, 255, 255, 0, 0, 128, 255, 255, 255, 255, 0, 0, 0, 255]);
+    table
+}
+
+#[test]
+fn version_0_layers_are_colored_from_the_palette() {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((colr::TAG, colr_version_0_table()));
+    tables.push((cpal::TAG, cpal_table()));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let red = Color { r: 255, g: 0, b: 0, a: 255 };
+    let white = Color { r: 255, g: 255, b: 255, a: 255 };
+    let translucent_blue = Color { r: 0, g: 0, b: 255, a: 128 };
+    assert_eq!(font.palette_count(), 2);
+    assert_eq!(font.color_layers(5, 0), Ok(vec![
+        ColorLayer { glyph_id: 10, palette_index: 0, color: Some(red) },
+        ColorLayer { glyph_id: 11, palette_index: 0xffff, color: None },
+    ]));
+    assert_eq!(font.color_layers(5, 1).unwrap()[0].color, Some(white));
+    assert_eq!(font.color_layers(9, 0), Ok(vec![
+        ColorLayer { glyph_id: 12, palette_index: 1, color: Some(translucent_blue) },
+    ]));
+    assert_eq!(font.color_layers(7, 0), Ok(vec![]));
+    assert_eq!(font.palette_color(2, 0), Ok(None));
+}
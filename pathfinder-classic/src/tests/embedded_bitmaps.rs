@@ -0,0 +1,301 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use error::FontError;
+use font::{BitmapFormat, BitmapStrike, ColorBitmap, EmbeddedStrike, Font, GrayBitmap};
+use font::MonoBitmap;
+use tables::{ebdt, eblc};
+use tests::sfnt;
+
+// One 12 ppem strike: glyph 1 has its own small metrics and byte-aligned rows (index format 1,
+// image format 1), while glyphs 2 and 3 share 10x3 metrics and are bit-aligned (index format 2,
+// image format 5).
+fn bitmap_tables() -> (Vec<u8>, Vec<u8>) {
+    let mut ebdt = vec![];
+    ebdt.write_u32::<BigEndian>(0x00020000).unwrap();
+    ebdt.extend_from_slice(&[2, 9, 0, 2, 10]);
+    ebdt.extend_from_slice(&[0xff, 0x80, 0x80, 0x00]);
+    ebdt.extend_from_slice(&[0xff, 0xff, 0xff, 0xfc]);
+    ebdt.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+
+    let mut eblc = vec![];
+    eblc.write_u32::<BigEndian>(0x00020000).unwrap();
+    eblc.write_u32::<BigEndian>(1).unwrap();
+
+    // BitmapSize record.
+    eblc.write_u32::<BigEndian>(56).unwrap();
+    eblc.write_u32::<BigEndian>(52).unwrap();
+    eblc.write_u32::<BigEndian>(2).unwrap();
+    eblc.write_u32::<BigEndian>(0).unwrap();
+    eblc.extend_from_slice(&[0; 24]);
+    eblc.write_u16::<BigEndian>(1).unwrap();
+    eblc.write_u16::<BigEndian>(3).unwrap();
+    eblc.extend_from_slice(&[12, 12, 1, 1]);
+
+    // IndexSubTableArray.
+    eblc.write_u16::<BigEndian>(1).unwrap();
+    eblc.write_u16::<BigEndian>(1).unwrap();
+    eblc.write_u32::<BigEndian>(16).unwrap();
+    eblc.write_u16::<BigEndian>(2).unwrap();
+    eblc.write_u16::<BigEndian>(3).unwrap();
+    eblc.write_u32::<BigEndian>(32).unwrap();
+
+    // Index format 1.
+    eblc.write_u16::<BigEndian>(1).unwrap();
+    eblc.write_u16::<BigEndian>(1).unwrap();
+    eblc.write_u32::<BigEndian>(4).unwrap();
+    eblc.write_u32::<BigEndian>(0).unwrap();
+    eblc.write_u32::<BigEndian>(9).unwrap();
+
+    // Index format 2.
+    eblc.write_u16::<BigEndian>(2).unwrap();
+    eblc.write_u16::<BigEndian>(5).unwrap();
+    eblc.write_u32::<BigEndian>(13).unwrap();
+    eblc.write_u32::<BigEndian>(4).unwrap();
+    eblc.extend_from_slice(&[3, 10, 0, 3, 11, 0, 0, 0]);
+
+    (eblc, ebdt)
+}
+
+fn bitmap_font_bytes() -> Vec<u8> {
+    let (eblc, ebdt) = bitmap_tables();
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((eblc::TAG, eblc));
+    tables.push((ebdt::TAG, ebdt));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn byte_aligned_bitmap() {
+    let bytes = bitmap_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_mono_bitmap(1, 12).unwrap(), Some(MonoBitmap {
+        width: 9,
+        height: 2,
+        data: vec![0xff, 0x80, 0x80, 0x00],
+    }));
+}
+
+#[test]
+fn bit_aligned_bitmap_with_shared_metrics() {
+    let bytes = bitmap_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let bitmap = font.glyph_mono_bitmap(2, 12).unwrap().unwrap();
+    assert_eq!((bitmap.width, bitmap.height), (10, 3));
+    assert_eq!(bitmap.data, vec![0xff, 0xc0, 0xff, 0xc0, 0xff, 0xc0]);
+
+    let bitmap = font.glyph_mono_bitmap(3, 12).unwrap().unwrap();
+    assert_eq!((bitmap.width, bitmap.height), (10, 3));
+    assert_eq!(bitmap.data, vec![0; 6]);
+}
+
+#[test]
+fn no_bitmap_outside_strikes() {
+    let bytes = bitmap_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_mono_bitmap(1, 13).unwrap(), None);
+    assert_eq!(font.glyph_mono_bitmap(4, 12).unwrap(), None);
+
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_mono_bitmap(1, 12).unwrap(), None);
+}
+
+#[test]
+fn bitmap_locations_must_not_overflow() {
+    let (eblc, ebdt) = bitmap_tables();
+    let mut buffer = vec![];
+    for &(offset, value) in &[(92, 0xffffffff), (96, 0x80000000)] {
+        let mut eblc = eblc.clone();
+        (&mut eblc[offset..(offset + 4)]).write_u32::<BigEndian>(value).unwrap();
+        let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+            (3, 1, sfnt::cmap_format_4(&[])),
+        ]));
+        tables.push((eblc::TAG, eblc));
+        tables.push((ebdt::TAG, ebdt.clone()));
+        let bytes = sfnt::sfnt(&tables);
+        let font = Font::new(&bytes, &mut buffer).unwrap();
+        assert_eq!(font.glyph_mono_bitmap(3, 12), Err(FontError::Failed));
+    }
+}
+
+fn bitmap_size_record(subtable_array_offset: u32, glyph_range: (u16, u16), ppem: u8, bit_depth: u8)
+                      -> Vec<u8> {
+    let mut record = vec![];
+    record.write_u32::<BigEndian>(subtable_array_offset).unwrap();
+    record.write_u32::<BigEndian>(24).unwrap();
+    record.write_u32::<BigEndian>(1).unwrap();
+    record.write_u32::<BigEndian>(0).unwrap();
+    record.extend_from_slice(&[0; 24]);
+    record.write_u16::<BigEndian>(glyph_range.0).unwrap();
+    record.write_u16::<BigEndian>(glyph_range.1).unwrap();
+    record.extend_from_slice(&[ppem, ppem, bit_depth, 1]);
+    record
+}
+
+// A 20 ppem strike where glyph 1 carries its own small metrics (image format 17), and a 40 ppem
+// strike where glyphs 1 and 2 share big metrics (index format 2, image format 19).
+fn color_bitmap_tables() -> (Vec<u8>, Vec<u8>) {
+    let mut cbdt = vec![];
+    cbdt.write_u32::<BigEndian>(0x00030000).unwrap();
+    cbdt.extend_from_slice(&[2, 3, 1, 5, 4]);
+    cbdt.write_u32::<BigEndian>(4).unwrap();
+    cbdt.extend_from_slice(b"PNG1");
+    for data in &[b"PNG2", b"PNG3"] {
+        cbdt.write_u32::<BigEndian>(4).unwrap();
+        cbdt.extend_from_slice(*data);
+    }
+
+    let mut cblc = vec![];
+    cblc.write_u32::<BigEndian>(0x00030000).unwrap();
+    cblc.write_u32::<BigEndian>(2).unwrap();
+    cblc.extend_from_slice(&bitmap_size_record(104, (1, 1), 20, 32));
+    cblc.extend_from_slice(&bitmap_size_record(128, (1, 2), 40, 32));
+
+    // The first strike's IndexSubTableArray and index format 1 subtable.
+    cblc.write_u16::<BigEndian>(1).unwrap();
+    cblc.write_u16::<BigEndian>(1).unwrap();
+    cblc.write_u32::<BigEndian>(8).unwrap();
+    cblc.write_u16::<BigEndian>(1).unwrap();
+    cblc.write_u16::<BigEndian>(17).unwrap();
+    cblc.write_u32::<BigEndian>(4).unwrap();
+    cblc.write_u32::<BigEndian>(0).unwrap();
+    cblc.write_u32::<BigEndian>(13).unwrap();
+
+    // The second strike's IndexSubTableArray and index format 2 subtable.
+    cblc.write_u16::<BigEndian>(1).unwrap();
+    cblc.write_u16::<BigEndian>(2).unwrap();
+    cblc.write_u32::<BigEndian>(8).unwrap();
+    cblc.write_u16::<BigEndian>(2).unwrap();
+    cblc.write_u16::<BigEndian>(19).unwrap();
+    cblc.write_u32::<BigEndian>(17).unwrap();
+    cblc.write_u32::<BigEndian>(8).unwrap();
+    cblc.extend_from_slice(&[4, 6, 0xff, 3, 7, 0, 0, 0]);
+
+    (cblc, cbdt)
+}
+
+#[test]
+fn color_bitmaps_from_cbdt() {
+    let (cblc, cbdt) = color_bitmap_tables();
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((eblc::CBLC_TAG, cblc));
+    tables.push((ebdt::CBDT_TAG, cbdt));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.color_bitmap_strikes(), Ok(vec![
+        BitmapStrike { ppem: 20, ppi: 72 },
+        BitmapStrike { ppem: 40, ppi: 72 },
+    ]));
+    assert_eq!(font.glyph_color_bitmap(1, 16), Ok(Some(ColorBitmap {
+        format: BitmapFormat::Png,
+        ppem: 20,
+        origin_x: 1,
+        origin_y: 3,
+        data: b"PNG1",
+    })));
+    assert_eq!(font.glyph_color_bitmap(2, 16), Ok(None));
+    assert_eq!(font.glyph_color_bitmap(2, 30), Ok(Some(ColorBitmap {
+        format: BitmapFormat::Png,
+        ppem: 40,
+        origin_x: -1,
+        origin_y: -1,
+        data: b"PNG3",
+    })));
+    assert_eq!(font.glyph_color_bitmap_in_strike(1, 1).unwrap().unwrap().data, b"PNG2");
+
+    // Color strikes aren't mistaken for monochrome ones.
+    assert_eq!(font.glyph_mono_bitmap(1, 20), Ok(None));
+}
+
+// Glyph 1 at 12 ppem in a monochrome strike and a bit-aligned 2-bit strike, and at 16 ppem in a
+// byte-aligned 4-bit strike.
+fn gray_bitmap_font_bytes() -> Vec<u8> {
+    let images: [(u8, u8, &[u8]); 3] = [
+        (12, 1, &[1, 2, 0, 1, 2, 0x80]),
+        (12, 2, &[2, 3, 0, 2, 3, 0x1b, 0xc0]),
+        (16, 4, &[1, 3, 0, 1, 3, 0xf8, 0x00]),
+    ];
+
+    let mut ebdt = vec![];
+    ebdt.write_u32::<BigEndian>(0x00020000).unwrap();
+    let mut eblc = vec![];
+    eblc.write_u32::<BigEndian>(0x00020000).unwrap();
+    eblc.write_u32::<BigEndian>(images.len() as u32).unwrap();
+    for (strike_index, &(ppem, bit_depth, _)) in images.iter().enumerate() {
+        let subtable_array_offset = 8 + 48 * images.len() + 24 * strike_index;
+        eblc.extend_from_slice(&bitmap_size_record(subtable_array_offset as u32, (1, 1), ppem,
+                                                   bit_depth));
+    }
+    for &(_, bit_depth, image) in &images {
+        // Index format 1, with image format 2 (bit-aligned) for the 2-bit strike and 1
+        // (byte-aligned) otherwise.
+        eblc.write_u16::<BigEndian>(1).unwrap();
+        eblc.write_u16::<BigEndian>(1).unwrap();
+        eblc.write_u32::<BigEndian>(8).unwrap();
+        eblc.write_u16::<BigEndian>(1).unwrap();
+        eblc.write_u16::<BigEndian>(if bit_depth == 2 { 2 } else { 1 }).unwrap();
+        eblc.write_u32::<BigEndian>(ebdt.len() as u32).unwrap();
+        eblc.write_u32::<BigEndian>(0).unwrap();
+        eblc.write_u32::<BigEndian>(image.len() as u32).unwrap();
+        ebdt.extend_from_slice(image);
+    }
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((eblc::TAG, eblc));
+    tables.push((ebdt::TAG, ebdt));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn best_strike_prefers_grayscale_at_the_exact_size() {
+    let bytes = gray_bitmap_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.embedded_bitmap_strikes(), Ok(vec![
+        EmbeddedStrike { ppem: 12, bit_depth: 1 },
+        EmbeddedStrike { ppem: 12, bit_depth: 2 },
+        EmbeddedStrike { ppem: 16, bit_depth: 4 },
+    ]));
+    assert_eq!(font.best_strike_for_size(12), Ok(Some(1)));
+    assert_eq!(font.best_strike_for_size(16), Ok(Some(2)));
+    assert_eq!(font.best_strike_for_size(14), Ok(None));
+}
+
+#[test]
+fn gray_bitmaps_are_expanded_to_bytes() {
+    let bytes = gray_bitmap_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_gray_bitmap(1, 0), Ok(Some(GrayBitmap {
+        width: 2,
+        height: 1,
+        data: vec![255, 0],
+    })));
+    assert_eq!(font.glyph_gray_bitmap(1, 1), Ok(Some(GrayBitmap {
+        width: 3,
+        height: 2,
+        data: vec![0, 85, 170, 255, 255, 0],
+    })));
+    assert_eq!(font.glyph_gray_bitmap(1, 2), Ok(Some(GrayBitmap {
+        width: 3,
+        height: 1,
+        data: vec![255, 136, 0],
+    })));
+    assert_eq!(font.glyph_gray_bitmap(2, 2), Ok(None));
+}
@@ -0,0 +1,91 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use error::FontError;
+use font::{Font, FontCollection};
+use tests::sfnt;
+
+#[test]
+fn combined_coverage_unions_every_face() {
+    let latin = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x5a, -0x40)])),
+    ]));
+    let greek = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x391, 0x3a9, -0x390)])),
+    ]));
+    let bytes = sfnt::ttc(&[latin, greek]);
+
+    let collection = FontCollection::new(&bytes).unwrap();
+    assert_eq!(collection.len(), 2);
+    assert!(!collection.is_empty());
+    assert!(!collection.font(0).unwrap().coverage().unwrap().contains(0x391));
+    assert!(!collection.font(1).unwrap().coverage().unwrap().contains(0x41));
+
+    let coverage = collection.combined_coverage().unwrap();
+    assert!(coverage.contains(0x41));
+    assert!(coverage.contains(0x5a));
+    assert!(coverage.contains(0x391));
+    assert!(coverage.contains(0x3a9));
+    assert!(!coverage.contains(0x40));
+    assert!(!coverage.contains(0x3aa));
+    assert_eq!(coverage.ranges().len(), 2);
+}
+
+#[test]
+fn single_font_is_a_collection_of_one() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x30, 0x39, -0x2f)])),
+    ])));
+
+    let collection = FontCollection::new(&bytes).unwrap();
+    assert_eq!(collection.len(), 1);
+    assert!(!collection.is_empty());
+    assert!(collection.font(1).is_err());
+    assert!(collection.combined_coverage().unwrap().contains(0x35));
+}
+
+#[test]
+fn collections_can_be_empty() {
+    let bytes = sfnt::ttc(&[]);
+    let collection = FontCollection::new(&bytes).unwrap();
+    assert_eq!(collection.len(), 0);
+    assert!(collection.is_empty());
+    assert!(collection.font(0).is_err());
+}
+
+#[test]
+fn faces_are_loaded_by_index() {
+    let latin = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x5a, -0x40)])),
+    ]));
+    let greek = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x391, 0x3a9, -0x390)])),
+    ]));
+    let bytes = sfnt::ttc(&[latin, greek]);
+    assert_eq!(Font::collection_size(&bytes), Ok(2));
+
+    let mut buffer = vec![];
+    let font = Font::from_collection_index(&bytes, 1, &mut buffer).unwrap();
+    assert_eq!(font.glyph_for_char('\u{391}'), Ok(1));
+    assert_eq!(font.glyph_for_char('A'), Ok(0));
+
+    let mut buffer = vec![];
+    assert_eq!(Font::from_collection_index(&bytes, 2, &mut buffer).err(),
+               Some(FontError::FontIndexOutOfBounds));
+}
+
+#[test]
+fn single_fonts_have_one_face() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    assert_eq!(Font::collection_size(&bytes), Ok(1));
+
+    let mut buffer = vec![];
+    assert!(Font::from_collection_index(&bytes, 0, &mut buffer).is_ok());
+    let mut buffer = vec![];
+    assert_eq!(Font::from_collection_index(&bytes, 1, &mut buffer).err(),
+               Some(FontError::FontIndexOutOfBounds));
+
+    assert_eq!(Font::collection_size(b"\x00\x00\x00\x02"), Err(FontError::UnknownFormat));
+}
@@ -0,0 +1,49 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use font::{Font, NamedInstance};
+use tables::{fvar, name};
+use tests::sfnt;
+
+const WGHT: u32 = ((b'w' as u32) << 24) | ((b'g' as u32) << 16) | ((b'h' as u32) << 8) |
+    (b't' as u32);
+const WDTH: u32 = ((b'w' as u32) << 24) | ((b'd' as u32) << 16) | ((b't' as u32) << 8) |
+    (b'h' as u32);
+
+#[test]
+fn lists_named_instances_in_order() {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((fvar::TAG, sfnt::fvar_table(&[WGHT, WDTH], &[
+        (257, vec![400.0, 100.0], 260),
+        (258, vec![700.0, 100.0], 0xffff),
+        (259, vec![300.0, 75.5], 261),
+    ])));
+    tables.push((name::TAG, sfnt::name_table(&[
+        (3, 1, 0x409, 257, sfnt::utf16_be("Regular")),
+        (3, 1, 0x409, 258, sfnt::utf16_be("Bold")),
+        (3, 1, 0x409, 259, sfnt::utf16_be("Condensed Light")),
+    ])));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.named_instances(), [
+        NamedInstance { name_id: 257, coords: vec![400.0, 100.0], postscript_name_id: Some(260) },
+        NamedInstance { name_id: 258, coords: vec![700.0, 100.0], postscript_name_id: None },
+        NamedInstance { name_id: 259, coords: vec![300.0, 75.5], postscript_name_id: Some(261) },
+    ]);
+    assert_eq!(font.instance_names(), ["Regular", "Bold", "Condensed Light"]);
+}
+
+#[test]
+fn static_fonts_have_no_named_instances() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(font.named_instances().is_empty());
+    assert!(font.instance_names().is_empty());
+}
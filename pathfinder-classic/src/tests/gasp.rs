@@ -0,0 +1,73 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{AntialiasMode, Font, RenderingHint};
+use tables::gasp;
+use tests::sfnt;
+
+fn font_bytes(gasp_ranges: Option<&[(u16, u16)]>) -> Vec<u8> {
+    font_bytes_with_version(1, gasp_ranges)
+}
+
+fn font_bytes_with_version(version: u16, gasp_ranges: Option<&[(u16, u16)]>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(gasp_ranges) = gasp_ranges {
+        let mut table = vec![];
+        table.write_u16::<BigEndian>(version).unwrap();
+        table.write_u16::<BigEndian>(gasp_ranges.len() as u16).unwrap();
+        for &(range_max_ppem, behavior) in gasp_ranges {
+            table.write_u16::<BigEndian>(range_max_ppem).unwrap();
+            table.write_u16::<BigEndian>(behavior).unwrap();
+        }
+        tables.push((gasp::TAG, table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+fn hint(gridfit: bool, antialias: AntialiasMode) -> RenderingHint {
+    RenderingHint {
+        gridfit: gridfit,
+        antialias: antialias,
+    }
+}
+
+#[test]
+fn recommendation_changes_at_range_boundaries() {
+    // Typical ranges: aliased and hinted when tiny, then hinted with grayscale, then smoothed.
+    let bytes = font_bytes(Some(&[(8, 0x1), (16, 0x3), (0xffff, 0xa)]));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.recommended_rendering(6), hint(true, AntialiasMode::None));
+    assert_eq!(font.recommended_rendering(8), hint(true, AntialiasMode::None));
+    assert_eq!(font.recommended_rendering(9), hint(true, AntialiasMode::Grayscale));
+    assert_eq!(font.recommended_rendering(16), hint(true, AntialiasMode::Grayscale));
+    assert_eq!(font.recommended_rendering(17), hint(false, AntialiasMode::Subpixel));
+    assert_eq!(font.recommended_rendering(200), hint(false, AntialiasMode::Subpixel));
+}
+
+#[test]
+fn fonts_without_gasp_get_grayscale_without_gridfit() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.recommended_rendering(12), hint(false, AntialiasMode::Grayscale));
+
+    // Sizes past the last range are treated the same way.
+    let bytes = font_bytes(Some(&[(20, 0xf)]));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.recommended_rendering(20), hint(true, AntialiasMode::Subpixel));
+    assert_eq!(font.recommended_rendering(21), hint(false, AntialiasMode::Grayscale));
+}
+
+#[test]
+fn symmetric_flags_are_ignored_in_version_0() {
+    let bytes = font_bytes_with_version(0, Some(&[(12, 0xa), (0xffff, 0xf)]));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.recommended_rendering(10), hint(false, AntialiasMode::Grayscale));
+    assert_eq!(font.recommended_rendering(20), hint(true, AntialiasMode::Grayscale));
+}
@@ -0,0 +1,91 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{Font, GlyphClass};
+use tables::gdef;
+use tests::sfnt;
+
+fn font_bytes(gdef_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(gdef_table) = gdef_table {
+        tables.push((gdef::TAG, gdef_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn glyph_classes_and_attachment_points() {
+    let glyph_class_def = sfnt::class_def_format_2(&[
+        (1, 9, 1),
+        (10, 10, 2),
+        (20, 25, 3),
+        (30, 30, 4),
+    ]);
+    let mark_attach_class_def = sfnt::class_def_format_2(&[(20, 22, 1), (23, 25, 2)]);
+
+    // Glyph 5 has attachment points 3 and 17; glyph 7 has none listed.
+    let mut attach_list = vec![];
+    attach_list.write_u16::<BigEndian>(8).unwrap();
+    attach_list.write_u16::<BigEndian>(2).unwrap();
+    attach_list.write_u16::<BigEndian>(16).unwrap();
+    attach_list.write_u16::<BigEndian>(22).unwrap();
+    attach_list.extend_from_slice(&sfnt::coverage_format_1(&[5, 7]));
+    for &value in &[2, 3, 17, 0] {
+        attach_list.write_u16::<BigEndian>(value).unwrap();
+    }
+
+    let glyph_class_def_offset = 12;
+    let attach_list_offset = glyph_class_def_offset + glyph_class_def.len();
+    let mark_attach_class_def_offset = attach_list_offset + attach_list.len();
+    let mut table = vec![];
+    for &value in &[
+        1,
+        0,
+        glyph_class_def_offset as u16,
+        attach_list_offset as u16,
+        0,
+        mark_attach_class_def_offset as u16,
+    ] {
+        table.write_u16::<BigEndian>(value).unwrap();
+    }
+    table.extend_from_slice(&glyph_class_def);
+    table.extend_from_slice(&attach_list);
+    table.extend_from_slice(&mark_attach_class_def);
+
+    let bytes = font_bytes(Some(table));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let classes: Vec<_> = [0, 1, 10, 21, 30, 31].iter().map(|&glyph_id| {
+        font.glyph_class(glyph_id).unwrap()
+    }).collect();
+    assert_eq!(classes, [
+        None,
+        Some(GlyphClass::Base),
+        Some(GlyphClass::Ligature),
+        Some(GlyphClass::Mark),
+        Some(GlyphClass::Component),
+        None,
+    ]);
+
+    assert_eq!(font.mark_attachment_class(20), Ok(1));
+    assert_eq!(font.mark_attachment_class(25), Ok(2));
+    assert_eq!(font.mark_attachment_class(5), Ok(0));
+
+    assert_eq!(font.attachment_points(5), Ok(vec![3, 17]));
+    assert_eq!(font.attachment_points(7), Ok(vec![]));
+    assert_eq!(font.attachment_points(6), Ok(vec![]));
+}
+
+#[test]
+fn fonts_without_gdef_have_no_glyph_classes() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_class(1), Ok(None));
+    assert_eq!(font.mark_attachment_class(1), Ok(0));
+    assert_eq!(font.attachment_points(1), Ok(vec![]));
+}
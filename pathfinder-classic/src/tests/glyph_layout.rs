@@ -0,0 +1,47 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::{glyf, hhea, hmtx, loca};
+use tests::sfnt;
+
+#[test]
+fn layout_info_reads_bounds_from_the_glyph_header() {
+    // This glyph claims one contour but ends after its header, so its outline can't be decoded.
+    let mut truncated = vec![];
+    truncated.write_i16::<BigEndian>(1).unwrap();
+    for &coordinate in &[-20, -150, 480, 700] {
+        truncated.write_i16::<BigEndian>(coordinate).unwrap();
+    }
+    let square = sfnt::glyf_simple_glyph(&[&[(100, 0), (600, 0), (600, 500), (100, 500)]]);
+    let (glyf_table, loca_table) = sfnt::glyf_and_loca_tables(&[vec![], square, truncated]);
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, hhea::TAG, sfnt::hhea_table(3));
+    sfnt::replace_table(&mut tables,
+                        hmtx::TAG,
+                        sfnt::hmtx_table(&[(250, 0), (700, 100), (520, -20)]));
+    tables.push((glyf::TAG, glyf_table));
+    tables.push((loca::TAG, loca_table));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let space = font.glyph_layout_info(0).unwrap();
+    assert_eq!((space.advance, space.lsb), (250, 0));
+    assert!(space.bounds.is_none());
+
+    let square = font.glyph_layout_info(1).unwrap();
+    assert_eq!((square.advance, square.lsb), (700, 100));
+    let bounds = square.bounds.unwrap();
+    assert_eq!((bounds.left, bounds.bottom, bounds.right, bounds.top), (100, 0, 600, 500));
+
+    assert!(font.for_each_point(2, |_| {}).is_err());
+    let truncated = font.glyph_layout_info(2).unwrap();
+    assert_eq!((truncated.advance, truncated.lsb), (520, -20));
+    let bounds = truncated.bounds.unwrap();
+    assert_eq!((bounds.left, bounds.bottom, bounds.right, bounds.top), (-20, -150, 480, 700));
+}
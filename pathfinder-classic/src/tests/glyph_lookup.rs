@@ -0,0 +1,572 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use charmap::{CodepointRange, CodepointRangeSet, CompiledCmap, CoverageSet, DecodedCmap};
+use charmap::{GlyphMapping, GlyphRange, MappedGlyphRange};
+use error::FontError;
+use font::Font;
+use tests::sfnt;
+
+#[test]
+fn glyph_for_char_and_str() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 1), (0x1f600, 0x1f600, 27)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('A'), Ok(1));
+    assert_eq!(font.glyph_for_char('Z'), Ok(26));
+    assert_eq!(font.glyph_for_char('\u{1f600}'), Ok(27));
+    assert_eq!(font.glyph_for_char('a'), Ok(0));
+
+    assert_eq!(font.glyphs_for_str("BAB!\u{1f600}C").unwrap(), [2, 1, 2, 0, 27, 3]);
+    assert_eq!(font.glyphs_for_str("").unwrap(), []);
+}
+
+#[test]
+fn resilient_lookup_falls_back_to_other_subtables() {
+    // The full-Unicode subtable comes first and so is preferred, but it lacks U+0042.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (1, 0, sfnt::cmap_format_4(&[(0x43, 0x43, 50)])),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x41, 1), (0x43, 0x43, 3)])),
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x43, -0x40)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('B'), Ok(0));
+    assert_eq!(font.glyph_for_codepoint_resilient(0x42), Ok(2));
+    assert_eq!(font.glyph_for_codepoint_resilient(0x43), Ok(3));
+    assert_eq!(font.glyph_for_codepoint_resilient(0x44), Ok(0));
+}
+
+fn font_bytes_with_seg_count_x2(seg_count_x2: u16) -> Vec<u8> {
+    let mut subtable = sfnt::cmap_format_4(&[(0x41, 0x43, -0x40)]);
+    subtable[6] = (seg_count_x2 >> 8) as u8;
+    subtable[7] = seg_count_x2 as u8;
+    sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[(3, 1, subtable)])))
+}
+
+#[test]
+fn format_4_rejects_odd_segment_count() {
+    let bytes = font_bytes_with_seg_count_x2(3);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('A'), Err(FontError::CmapMalformed));
+    assert_eq!(font.coverage().err(), Some(FontError::CmapMalformed));
+}
+
+#[test]
+fn format_4_rejects_segment_count_exceeding_length() {
+    let bytes = font_bytes_with_seg_count_x2(0x1000);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('A'), Err(FontError::CmapMalformed));
+    assert_eq!(font.coverage().err(), Some(FontError::CmapMalformed));
+
+    // The unmodified segment count is fine.
+    let bytes = font_bytes_with_seg_count_x2(4);
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_for_char('A'), Ok(1));
+}
+
+#[test]
+fn format_4_reads_last_entry_of_glyph_id_array() {
+    // The first segment's `idRangeOffset` points at the very start of the glyph ID array, so its
+    // last codepoint maps through the final entry of the array, which ends the subtable.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4_with_glyph_ids(&[
+            (0x41, 0x43, 0, Some(0)),
+            (0xffff, 0xffff, 1, None),
+        ], &[5, 6, 7])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('A'), Ok(5));
+    assert_eq!(font.glyph_for_char('C'), Ok(7));
+    assert_eq!(font.glyphs_for_str("CBA").unwrap(), [7, 6, 5]);
+    assert_eq!(font.glyph_for_char('D'), Ok(0));
+
+    let coverage = font.coverage().unwrap();
+    assert!(coverage.contains(0x43));
+    assert!(!coverage.contains(0x44));
+}
+
+#[test]
+fn coverage_ranges_reproduce_coverage() {
+    // The first two segments adjoin, the second maps its middle codepoint to the missing glyph
+    // through the glyph ID array, and the third does so through its delta.
+    let format_4 = sfnt::cmap_format_4_with_glyph_ids(&[
+        (0x41, 0x43, 0, Some(0)),
+        (0x44, 0x46, 0, Some(3)),
+        (0x60, 0x62, -0x61, None),
+        (0xffff, 0xffff, 1, None),
+    ], &[5, 6, 7, 8, 0, 9]);
+    // The first two groups adjoin, and the third maps its first codepoint to the missing glyph.
+    let format_12 = sfnt::cmap_format_12(&[(0x41, 0x43, 1), (0x44, 0x46, 4), (0x5f, 0x62, 0)]);
+
+    for &(encoding_id, ref subtable) in &[(1, format_4), (10, format_12)] {
+        let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+            (3, encoding_id, subtable.clone()),
+        ])));
+        let mut buffer = vec![];
+        let font = Font::new(&bytes, &mut buffer).unwrap();
+
+        let ranges: Vec<_> = font.coverage_ranges().unwrap().collect();
+        assert_eq!(ranges, font.coverage().unwrap().ranges());
+        for pair in ranges.windows(2) {
+            assert!(pair[0].end + 1 < pair[1].start);
+        }
+        let codepoints: Vec<_> = ranges.iter().flat_map(|range| range.iter()).collect();
+        if encoding_id == 1 {
+            assert_eq!(codepoints, [0x41, 0x42, 0x43, 0x44, 0x46, 0x60, 0x62]);
+        } else {
+            assert_eq!(codepoints, [0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x60, 0x61, 0x62]);
+        }
+    }
+}
+
+#[test]
+fn reverse_glyph_mapping_inverts_the_character_map() {
+    // Both cases of each letter map to the same glyph.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x43, 1), (0x61, 0x63, 1), (0x1f600, 0x1f600, 2)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let reverse = font.reverse_glyph_mapping().unwrap();
+    assert_eq!(reverse.codepoints_for_glyph(1), [0x41, 0x61]);
+    assert_eq!(reverse.codepoints_for_glyph(2), [0x42, 0x62, 0x1f600]);
+    assert_eq!(reverse.codepoints_for_glyph(3), [0x43, 0x63]);
+    assert!(reverse.codepoints_for_glyph(4).is_empty());
+}
+
+#[test]
+fn coverage_answers_block_fallback_queries() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[
+            (0x20, 0x7e, 1),
+            (0x4e00, 0x4fff, 100),
+            (0x5100, 0x9fff, 1000),
+        ])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let coverage = font.coverage().unwrap();
+
+    let cjk = CodepointRange::new(0x4e00, 0x9fff);
+    assert_eq!(coverage.count_in_range(&cjk), 0x9fff - 0x4e00 + 1 - 0x100);
+    assert!(!coverage.contains_range(&cjk));
+    assert!(coverage.contains_range(&CodepointRange::new(0x41, 0x5a)));
+    assert!(coverage.contains_range(&CodepointRange::new(0x5100, 0x5100)));
+    assert_eq!(coverage.count_in_range(&CodepointRange::new(0x0, 0x1f)), 0);
+    assert_eq!(coverage.count_in_range(&CodepointRange::new(0x70, 0x4e01)), 0x7e - 0x70 + 1 + 2);
+}
+
+#[test]
+fn codepoint_range_sets_intersect_and_subtract() {
+    let set = |ranges: &[(u32, u32)]| {
+        CodepointRangeSet::from_ranges(ranges.iter().map(|&(start, end)| {
+            CodepointRange::new(start, end)
+        }).collect())
+    };
+    let run = set(&[(0x20, 0x7e), (0x3041, 0x3096), (0x4e00, 0x4e10)]);
+    let face = set(&[(0x0, 0x40), (0x61, 0x7a), (0x3000, 0x3050), (0x4e05, 0x4e05)]);
+
+    assert_eq!(run.intersection(&face), set(&[
+        (0x20, 0x40),
+        (0x61, 0x7a),
+        (0x3041, 0x3050),
+        (0x4e05, 0x4e05),
+    ]));
+    assert_eq!(run.difference(&face), set(&[
+        (0x41, 0x60),
+        (0x7b, 0x7e),
+        (0x3051, 0x3096),
+        (0x4e00, 0x4e04),
+        (0x4e06, 0x4e10),
+    ]));
+    assert_eq!(run.difference(&face).union(&run.intersection(&face)), run);
+    assert!(run.difference(&run).is_empty());
+    assert!(run.intersection(&CodepointRangeSet::new()).is_empty());
+    assert_eq!(run.difference(&CodepointRangeSet::new()), run);
+}
+
+#[test]
+fn codepoint_range_sets_coalesce_their_union() {
+    let set = |ranges: &[(u32, u32)]| {
+        CodepointRangeSet::from_ranges(ranges.iter().map(|&(start, end)| {
+            CodepointRange::new(start, end)
+        }).collect())
+    };
+    let latin = set(&[(0x61, 0x7a), (0x41, 0x5a), (0x50, 0x60), (0x7e, 0x7d)]);
+    assert_eq!(latin.ranges(), [CodepointRange::new(0x41, 0x7a)]);
+    assert!(latin.contains(0x41) && latin.contains(0x7a));
+    assert!(!latin.contains(0x40) && !latin.contains(0x7b));
+
+    let union = latin.union(&set(&[(0x7b, 0x7b), (0x391, 0x3a9)]));
+    assert_eq!(union, set(&[(0x41, 0x7b), (0x391, 0x3a9)]));
+    assert_eq!(union.union(&CodepointRangeSet::new()), union);
+    assert!(CodepointRangeSet::new().union(&CodepointRangeSet::new()).is_empty());
+
+    // A font's coverage converts into a set to combine with the codepoints of a run.
+    let coverage = CoverageSet::from_ranges(vec![CodepointRange::new(0x41, 0x5a)]);
+    assert_eq!(union.difference(coverage.codepoints()), set(&[(0x5b, 0x7b), (0x391, 0x3a9)]));
+    assert_eq!(CodepointRangeSet::from(coverage), set(&[(0x41, 0x5a)]));
+}
+
+#[test]
+fn format_0_maps_single_byte_codepoints() {
+    let mut glyph_ids = vec![0; 0x43];
+    glyph_ids.extend_from_slice(&[3, 4, 0, 9]);
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (0, 3, sfnt::cmap_format_0(&glyph_ids)),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("ABCDEF\u{100}").unwrap(), [0, 0, 3, 4, 0, 9, 0]);
+    let glyph_mapping =
+        font.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0x42, 0x101)]).unwrap();
+    assert_eq!(glyph_mapping.iter().count(), 0x101 - 0x42 + 1);
+    assert_eq!(glyph_mapping.glyph_for(0x46), Some(9));
+    assert_eq!(glyph_mapping.glyph_for(0x101), Some(0));
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x43, 0x44), CodepointRange::new(0x46, 0x46)]);
+}
+
+#[test]
+fn format_0_macintosh_roman_subtable_is_used_without_a_unicode_subtable() {
+    let mut glyph_ids = vec![0; 0x41];
+    glyph_ids.extend_from_slice(&[7, 8]);
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 0, sfnt::cmap_format_4(&[(0xf041, 0xf041, 0x0fc8)])),
+        (1, 0, sfnt::cmap_format_0(&glyph_ids)),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("ABC").unwrap(), [7, 8, 0]);
+    assert_eq!(font.coverage_ranges().unwrap().collect::<Vec<_>>(),
+               [CodepointRange::new(0x41, 0x42)]);
+}
+
+#[test]
+fn macintosh_roman_codes_are_translated_to_unicode() {
+    // Codes 0x8e and 0xdb are 'é' and '€' in Macintosh Roman.
+    let mut glyph_ids = vec![0; 0x100];
+    glyph_ids[0x41] = 1;
+    glyph_ids[0x8e] = 2;
+    glyph_ids[0xdb] = 3;
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (1, 0, sfnt::cmap_format_0(&glyph_ids)),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("Aé€\u{8e}\u{db}").unwrap(), [1, 2, 3, 0, 0]);
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [
+        CodepointRange::new(0x41, 0x41),
+        CodepointRange::new(0xe9, 0xe9),
+        CodepointRange::new(0x20ac, 0x20ac),
+    ]);
+}
+
+#[test]
+fn format_6_maps_a_trimmed_range() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_6(0x3b1, &[20, 21, 22, 0, 30])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("\u{3b0}\u{3b1}\u{3b3}\u{3b4}\u{3b5}\u{3b6}").unwrap(),
+               [0, 20, 22, 0, 30, 0]);
+    let glyph_mapping =
+        font.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0x3a0, 0x3c0)]).unwrap();
+    assert_eq!(glyph_mapping.iter().count(), 0x3c0 - 0x3a0 + 1);
+    assert_eq!(glyph_mapping.glyph_for(0x3b2), Some(21));
+    assert_eq!(glyph_mapping.glyph_for(0x3b6), Some(0));
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x3b1, 0x3b3), CodepointRange::new(0x3b5, 0x3b5)]);
+}
+
+#[test]
+fn format_8_maps_16_bit_and_32_bit_codes() {
+    // U+1F600 is encoded as the surrogate pair D83D DE00. The last group lies under a high
+    // surrogate that the bitmap doesn't mark, so it maps nothing.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_8(&[0xd83d], &[
+            (0x41, 0x43, 1),
+            (0xd83dde00, 0xd83dde02, 10),
+            (0xd83edc00, 0xd83edc00, 20),
+        ])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("AC\u{1f600}\u{1f602}\u{1f603}\u{1f800}D").unwrap(),
+               [1, 3, 10, 12, 0, 0, 0]);
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x41, 0x43), CodepointRange::new(0x1f600, 0x1f602)]);
+}
+
+#[test]
+fn format_8_rejects_overflowing_glyph_ids() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_8(&[], &[
+            (0x41, 0x5a, 0xffffffff),
+            (0xffffffff, 0xffffffff, 0),
+        ])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('B'), Err(FontError::Failed));
+    assert_eq!(font.glyph_for_char('a'), Ok(0));
+    assert_eq!(font.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0x41, 0x42)]).err(),
+               Some(FontError::Failed));
+    assert_eq!(font.coverage().unwrap().ranges(), [CodepointRange::new(0x41, 0x5a)]);
+}
+
+#[test]
+fn format_10_maps_a_trimmed_range_beyond_the_bmp() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_10(0x1f600, &[5, 0, 7, 8])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("A\u{1f5ff}\u{1f600}\u{1f601}\u{1f603}\u{1f604}").unwrap(),
+               [0, 0, 5, 0, 8, 0]);
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x1f600, 0x1f600),
+                          CodepointRange::new(0x1f602, 0x1f603)]);
+}
+
+#[test]
+fn format_13_maps_whole_groups_to_one_glyph() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_13(&[(0x0, 0x40, 0), (0x41, 0x5a, 3), (0x400, 0x4ff, 4)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyphs_for_str("!AMZ[\u{416}").unwrap(), [0, 3, 3, 3, 0, 4]);
+    let glyph_mapping =
+        font.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0x3ff, 0x401)]).unwrap();
+    assert_eq!(glyph_mapping.iter().collect::<Vec<_>>(), [(0x3ff, 0), (0x400, 4), (0x401, 4)]);
+
+    let coverage: Vec<_> = font.coverage_ranges().unwrap().collect();
+    assert_eq!(coverage, font.coverage().unwrap().ranges());
+    assert_eq!(coverage, [CodepointRange::new(0x41, 0x5a), CodepointRange::new(0x400, 0x4ff)]);
+}
+
+#[test]
+fn full_repertoire_subtables_are_preferred_over_bmp_subtables() {
+    // The BMP subtable comes first and disagrees about 'A', so it's clear which one is used.
+    let bmp_subtable = sfnt::cmap_format_4(&[(0x41, 0x5a, -0x3f)]);
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, bmp_subtable.clone()),
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x5a, 10), (0x1f600, 0x1f600, 40)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyphs_for_str("A\u{1f600}").unwrap(), [10, 40]);
+
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[(3, 1, bmp_subtable)])));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyphs_for_str("A\u{1f600}").unwrap(), [2, 0]);
+}
+
+#[test]
+fn variation_sequences_fall_back_to_the_base_glyph() {
+    // The variation sequence subtable comes first, but it can't serve as the character map.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (0, 5, sfnt::cmap_format_14(&[
+            (0xfe0e, &[(0x2764, 20)]),
+            (0xfe0f, &[(0x263a, 30), (0x2764, 21), (0x2b50, 31)]),
+        ])),
+        (3, 1, sfnt::cmap_format_4(&[(0x2764, 0x2764, -0x2764 + 10)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.glyph_for_char('\u{2764}'), Ok(10));
+    assert_eq!(font.glyph_for_variation_sequence(0x2764, 0xfe0e), Ok(20));
+    assert_eq!(font.glyph_for_variation_sequence(0x2764, 0xfe0f), Ok(21));
+    assert_eq!(font.glyph_for_variation_sequence(0x2b50, 0xfe0f), Ok(31));
+    assert_eq!(font.glyph_for_variation_sequence(0x2764, 0xfe00), Ok(10));
+    assert_eq!(font.glyph_for_variation_sequence(0x2765, 0xfe0f), Ok(0));
+
+    // Without the subtable, every sequence uses the base glyph.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x2764, 0x2764, -0x2764 + 10)])),
+    ])));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_for_variation_sequence(0x2764, 0xfe0f), Ok(10));
+}
+
+#[test]
+fn surrogates_map_to_the_missing_glyph() {
+    // This malformed group maps the surrogates along with their neighbors.
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0xd7f0, 0xe00f, 1)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let glyph_mapping =
+        font.glyph_mapping_for_codepoint_ranges(&[CodepointRange::new(0xd700, 0xe0ff)]).unwrap();
+    assert_eq!(glyph_mapping.glyph_for(0xd7ff), Some(16));
+    assert!((0xd800..0xe000).all(|codepoint| glyph_mapping.glyph_for(codepoint) == Some(0)));
+    assert_eq!(glyph_mapping.glyph_for(0xe000), Some(0xe000 - 0xd7f0 + 1));
+
+    assert_eq!(font.glyph_for_codepoint_resilient(0xdc00), Ok(0));
+}
+
+#[test]
+fn compiled_cmaps_agree_with_the_font() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[
+            (0x20, 0x7e, 1),
+            (0x7f, 0x7f, 0),
+            (0xa0, 0xff, 96),
+            (0x1f600, 0x1f64f, 200),
+        ])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let compiled = font.compile_cmap().unwrap();
+    let cmap = CompiledCmap::from_bytes(&compiled).unwrap();
+    assert_eq!(cmap.len(), 3);
+
+    for &codepoint in &[0, 0x1f, 0x20, 0x41, 0x7e, 0x7f, 0x9f, 0xa0, 0xff, 0x100, 0x1f600,
+                        0x1f64f, 0x1f650, 0x10ffff] {
+        let character = ::std::char::from_u32(codepoint).unwrap();
+        assert_eq!(cmap.glyph_for(codepoint), font.glyph_for_char(character).unwrap(),
+                   "U+{:04X}", codepoint);
+    }
+    assert_eq!(cmap.to_glyph_mapping().glyph_for(0x1f601), Some(201));
+
+    assert_eq!(CompiledCmap::from_bytes(&compiled[..compiled.len() - 1]).unwrap_err(),
+               FontError::UnexpectedEof);
+    assert_eq!(CompiledCmap::from_bytes(&bytes).unwrap_err(), FontError::UnknownFormat);
+    let mut newer = compiled.clone();
+    newer[7] += 1;
+    assert_eq!(CompiledCmap::from_bytes(&newer).unwrap_err(), FontError::UnsupportedVersion);
+}
+
+#[test]
+fn compiled_cmaps_end_at_the_last_codepoint() {
+    let mut mapping = GlyphMapping::new();
+    for &(codepoint_start, start, end) in &[(0x41, 1, 1), (0x10fffe, 5, 8), (0x200000, 9, 9)] {
+        mapping.push(MappedGlyphRange {
+            codepoint_start: codepoint_start,
+            glyphs: GlyphRange {
+                start: start,
+                end: end,
+            },
+        })
+    }
+    let compiled = mapping.compile();
+    let cmap = CompiledCmap::from_bytes(&compiled).unwrap();
+    assert_eq!(cmap.len(), 2);
+    assert_eq!(cmap.glyph_for(0x10ffff), 6);
+    assert_eq!(cmap.glyph_for(0x110000), 0);
+    assert_eq!(cmap.glyph_for(0x200000), 0);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn normalized_lookups_match_either_form() {
+    // One font has only the precomposed 'é'; the other has only 'e' and the combining acute.
+    let precomposed = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[
+            (0x65, 0x66, -0x64),
+            (0x69, 0x69, -0x66),
+            (0xe9, 0xe9, -0xe5),
+        ])),
+    ])));
+    let decomposed = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x65, 0x65, -0x64), (0x301, 0x301, -0x2ff)])),
+    ])));
+    let (mut precomposed_buffer, mut decomposed_buffer) = (vec![], vec![]);
+    let precomposed = Font::new(&precomposed, &mut precomposed_buffer).unwrap();
+    let decomposed = Font::new(&decomposed, &mut decomposed_buffer).unwrap();
+
+    for string in &["\u{e9}", "e\u{301}"] {
+        assert_eq!(precomposed.glyphs_for_normalized_str(string, false).unwrap(), [4]);
+        assert_eq!(decomposed.glyphs_for_normalized_str(string, false).unwrap(), [1, 2]);
+    }
+
+    // The ligature only maps with compatibility decompositions.
+    assert_eq!(precomposed.glyphs_for_normalized_str("\u{fb01}", false).unwrap(), [0]);
+    assert_eq!(precomposed.glyphs_for_normalized_str("\u{fb01}", true).unwrap(), [2, 3]);
+}
+
+#[test]
+fn decoded_cmaps_agree_with_the_font() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[
+            (0x20, 0x7e, 1),
+            (0xa0, 0x1ff, 96),
+            (0xfff0, 0x1000f, 500),
+            (0x1f600, 0x1f64f, 200),
+        ])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let cmap = font.decode_cmap().unwrap();
+
+    for &codepoint in &[0, 0x1f, 0x20, 0x41, 0x7e, 0x7f, 0xa0, 0xff, 0x100, 0x1ff, 0x200, 0xffef,
+                        0xfff0, 0xffff, 0x10000, 0x1000f, 0x10010, 0x1f600, 0x1f64f, 0x10ffff] {
+        let character = ::std::char::from_u32(codepoint).unwrap();
+        assert_eq!(cmap.glyph_for_codepoint(codepoint), font.glyph_for_char(character).unwrap(),
+                   "U+{:04X}", codepoint);
+    }
+    assert_eq!(cmap.glyph_for_codepoint(0xd800), 0);
+    assert_eq!(cmap.glyph_for_codepoint(0x110000), 0);
+}
+
+#[test]
+fn decoded_cmaps_end_at_the_last_codepoint() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 10, sfnt::cmap_format_12(&[(0x41, 0x41, 1), (0x10fff0, 0xffff_fff0, 2)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let cmap = font.decode_cmap().unwrap();
+    assert_eq!(cmap.glyph_for_codepoint(0x41), 1);
+    assert_eq!(cmap.glyph_for_codepoint(0x10ffff), 17);
+    assert_eq!(cmap.glyph_for_codepoint(0x110000), 0);
+
+    // Mappings built by hand are clamped too.
+    let mut mapping = GlyphMapping::new();
+    mapping.push(MappedGlyphRange {
+        codepoint_start: 0x10fffe,
+        glyphs: GlyphRange {
+            start: 5,
+            end: 8,
+        },
+    });
+    let cmap = DecodedCmap::from_glyph_mapping(&mapping);
+    assert_eq!(cmap.glyph_for_codepoint(0x10ffff), 6);
+    assert_eq!(cmap.glyph_for_codepoint(0x110000), 0);
+}
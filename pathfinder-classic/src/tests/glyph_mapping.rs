@@ -0,0 +1,245 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use charmap::{CodepointRange, CodepointRanges, GlyphMapping, GlyphRange, MISSING_GLYPH};
+use charmap::MappedGlyphRange;
+use std::collections::{BTreeMap, HashMap};
+
+fn mapping(ranges: &[(u32, u16, u16)]) -> GlyphMapping {
+    let mut mapping = GlyphMapping::new();
+    for &(codepoint_start, start, end) in ranges {
+        mapping.push(MappedGlyphRange {
+            codepoint_start: codepoint_start,
+            glyphs: GlyphRange {
+                start: start,
+                end: end,
+            },
+        })
+    }
+    mapping
+}
+
+#[test]
+fn glyph_id_set_coalesces_overlapping_ranges() {
+    let mapping = mapping(&[
+        (0x41, 10, 20),
+        (0x60, 0, 0),
+        (0x61, 15, 25),
+        (0x80, 26, 26),
+        (0x100, 40, 45),
+        (0x200, 2, 4),
+    ]);
+
+    assert_eq!(mapping.glyph_id_set(), [
+        GlyphRange {
+            start: 2,
+            end: 4,
+        },
+        GlyphRange {
+            start: 10,
+            end: 26,
+        },
+        GlyphRange {
+            start: 40,
+            end: 45,
+        },
+    ]);
+}
+
+#[test]
+fn glyph_id_set_of_unmapped_codepoints_is_empty() {
+    assert!(mapping(&[(0x41, 0, 0), (0x42, 0, 0)]).glyph_id_set().is_empty());
+}
+
+#[test]
+fn to_dense_fills_gaps_with_the_missing_glyph() {
+    let mapping = mapping(&[(0x20, 3, 5), (0x41, 36, 61), (0xe9, 100, 100)]);
+
+    let dense = mapping.to_dense(0x40, 0xc0);
+    assert_eq!(dense.len(), 0xc0);
+    assert_eq!(dense[0x00], MISSING_GLYPH);
+    assert_eq!(dense[0x01], 36);
+    assert_eq!(dense[0x1a], 61);
+    assert_eq!(dense[0x1b], MISSING_GLYPH);
+    assert_eq!(dense[0xa9], 100);
+    assert_eq!(dense.iter().filter(|&&glyph| glyph != MISSING_GLYPH).count(), 27);
+
+    // Ranges straddling either end of the block are clipped to it.
+    assert_eq!(mapping.to_dense(0x21, 4), [4, 5, MISSING_GLYPH, MISSING_GLYPH]);
+    assert_eq!(mapping.to_dense(0xffff_fffe, 4), [MISSING_GLYPH; 4]);
+}
+
+#[test]
+fn merge_resolves_overlaps_in_favor_of_earlier_mappings() {
+    let latin = mapping(&[(0x41, 1, 5), (0x50, 0, 0), (0x60, 30, 31)]);
+    let greek = mapping(&[(0x43, 103, 110), (0x50, 200, 200), (0x62, 32, 33), (0x391, 300, 301)]);
+
+    let merged = GlyphMapping::merge(&[latin.clone(), greek.clone()]);
+    assert_eq!(merged.iter().collect::<Vec<_>>(), [
+        (0x41, 1), (0x42, 2), (0x43, 3), (0x44, 4), (0x45, 5),
+        (0x46, 106), (0x47, 107), (0x48, 108), (0x49, 109), (0x4a, 110),
+        (0x50, 200),
+        (0x60, 30), (0x61, 31), (0x62, 32), (0x63, 33),
+        (0x391, 300), (0x392, 301),
+    ]);
+
+    // Contiguous runs are coalesced.
+    assert_eq!(merged.glyph_for(0x62), Some(32));
+    assert_eq!(GlyphMapping::merge(&[mapping(&[(0x60, 30, 31)]), mapping(&[(0x62, 32, 33)])])
+                   .glyph_id_set(),
+               [GlyphRange { start: 30, end: 33 }]);
+
+    // Swapping the order changes only the overlapping codepoints.
+    let merged = GlyphMapping::merge(&[greek, latin]);
+    assert_eq!(merged.glyph_for(0x42), Some(2));
+    assert_eq!(merged.glyph_for(0x43), Some(103));
+    assert_eq!(merged.glyph_for(0x45), Some(105));
+    assert_eq!(merged.glyph_for(0x50), Some(200));
+}
+
+#[test]
+fn merge_splits_ranges_around_nested_overlaps() {
+    // The preferred range lies inside a later one, which resumes after it.
+    let merged = GlyphMapping::merge(&[mapping(&[(0x43, 50, 51)]), mapping(&[(0x41, 1, 6)])]);
+    assert_eq!(merged.iter().collect::<Vec<_>>(), [
+        (0x41, 1), (0x42, 2), (0x43, 50), (0x44, 51), (0x45, 5), (0x46, 6),
+    ]);
+
+    let codepoints = mapping(&(0..0x10000).map(|codepoint| (codepoint, 1, 1)).collect::<Vec<_>>());
+    let glyphs = mapping(&[(0, 1, 0xffff)]);
+    let merged = GlyphMapping::merge(&[codepoints, glyphs]);
+    assert_eq!(merged.iter().count(), 0x10000);
+    assert_eq!(merged.glyph_for(0), Some(1));
+    assert_eq!(merged.glyph_for(1), Some(1));
+    assert_eq!(merged.glyph_for(0xffff), Some(1));
+}
+
+#[test]
+fn merging_nothing_is_empty() {
+    assert_eq!(GlyphMapping::merge(&[]).iter().count(), 0);
+    assert_eq!(GlyphMapping::merge(&[GlyphMapping::new()]).iter().count(), 0);
+}
+
+#[test]
+fn reverse_groups_codepoints_by_glyph() {
+    let mapping = mapping(&[(0x41, 3, 5), (0x50, 0, 0), (0x61, 3, 4), (0x100, 0xffff, 0xffff)]);
+    let reverse = mapping.reverse();
+    assert_eq!(reverse.codepoints_for_glyph(3), [0x41, 0x61]);
+    assert_eq!(reverse.codepoints_for_glyph(5), [0x43]);
+    assert_eq!(reverse.codepoints_for_glyph(0xffff), [0x100]);
+    assert!(reverse.codepoints_for_glyph(MISSING_GLYPH).is_empty());
+    assert!(reverse.codepoints_for_glyph(6).is_empty());
+}
+
+#[test]
+fn reverse_ends_at_the_last_codepoint() {
+    let reverse = mapping(&[(0x10fffe, 1, 4), (0x7fff_ffff, 5, 0xffff)]).reverse();
+    assert_eq!(reverse.codepoints_for_glyph(2), [0x10ffff]);
+    assert!(reverse.codepoints_for_glyph(3).is_empty());
+    assert!(reverse.codepoints_for_glyph(5).is_empty());
+}
+
+#[test]
+fn glyph_for_finds_codepoints_pushed_out_of_order() {
+    let mapping = mapping(&[(0x61, 10, 12), (0x41, 1, 3), (0x100, 20, 20), (0x50, 0, 0)]);
+    assert_eq!(mapping.glyph_for(0x62), Some(11));
+    assert_eq!(mapping.glyph_for(0x43), Some(3));
+    assert_eq!(mapping.glyph_for(0x100), Some(20));
+    assert_eq!(mapping.glyph_for(0x50), Some(MISSING_GLYPH));
+    assert_eq!(mapping.glyph_for(0x44), None);
+    assert_eq!(mapping.glyph_for(0x101), None);
+
+    // Iteration still follows the order of the lookups.
+    assert_eq!(mapping.iter().map(|(codepoint, _)| codepoint).collect::<Vec<_>>(),
+               [0x61, 0x62, 0x63, 0x41, 0x42, 0x43, 0x100, 0x50]);
+}
+
+#[test]
+fn glyph_for_codepoint_binary_searches_the_ranges() {
+    let mapping = mapping(&[(0x61, 10, 12), (0x41, 1, 3), (0x7f, 0, 0), (0x10000, 20, 21)]);
+    assert_eq!(mapping.glyph_for_codepoint(0x41), Some(1));
+    assert_eq!(mapping.glyph_for_codepoint(0x63), Some(12));
+    assert_eq!(mapping.glyph_for_codepoint(0x10001), Some(21));
+    assert_eq!(mapping.glyph_for_codepoint(0x7f), Some(MISSING_GLYPH));
+    assert_eq!(mapping.glyph_for_codepoint(0x40), None);
+    assert_eq!(mapping.glyph_for_codepoint(0x64), None);
+    assert_eq!(mapping.glyph_for_codepoint(0x10002), None);
+    assert_eq!(GlyphMapping::new().glyph_for_codepoint(0), None);
+
+    for codepoint in 0..0x10010 {
+        assert_eq!(mapping.glyph_for_codepoint(codepoint), mapping.glyph_for(codepoint));
+    }
+}
+
+#[test]
+fn normalize_sorts_deduplicates_and_coalesces() {
+    let mut mapping = mapping(&[
+        (0x44, 4, 5),
+        (0x41, 1, 3),
+        (0x42, 2, 2),
+        (0x60, 0, 0),
+        (0x43, 3, 4),
+        (0x46, 9, 9),
+    ]);
+    mapping.normalize();
+    assert_eq!(mapping.iter().collect::<Vec<_>>(), [
+        (0x41, 1),
+        (0x42, 2),
+        (0x43, 3),
+        (0x44, 4),
+        (0x45, 5),
+        (0x46, 9),
+        (0x60, MISSING_GLYPH),
+    ]);
+    assert_eq!(mapping.glyph_for(0x45), Some(5));
+    assert_eq!(mapping.glyph_id_set(), [
+        GlyphRange { start: 1, end: 5 },
+        GlyphRange { start: 9, end: 9 },
+    ]);
+}
+
+#[test]
+fn mappings_convert_into_standard_maps() {
+    let mapping = mapping(&[(0x61, 5, 6), (0x41, 1, 2), (0x62, 9, 9), (0x7f, 0, 0)]);
+    let pairs: Vec<(u32, u16)> = (&mapping).into_iter().collect();
+    assert_eq!(pairs, [(0x61, 5), (0x62, 6), (0x41, 1), (0x42, 2), (0x62, 9), (0x7f, 0)]);
+
+    let hash_map = HashMap::from(mapping.clone());
+    assert_eq!(hash_map.len(), 5);
+    assert_eq!(hash_map[&0x62], 6);
+    assert_eq!(hash_map[&0x7f], MISSING_GLYPH);
+    assert!(!hash_map.contains_key(&0x63));
+
+    let btree_map = BTreeMap::from(mapping);
+    assert_eq!(btree_map.into_iter().collect::<Vec<_>>(),
+               [(0x41, 1), (0x42, 2), (0x61, 5), (0x62, 6), (0x7f, 0)]);
+}
+
+#[test]
+fn iter_pairs_skips_missing_glyphs() {
+    assert_eq!(mapping(&[(0x41, 0, 0)]).iter_pairs().count(), 0);
+    assert_eq!(GlyphMapping::new().iter_pairs().count(), 0);
+
+    let mapping = mapping(&[(0x61, 5, 6), (0x63, 0, 0), (0x64, 0, 0), (0x41, 1, 2), (0x7f, 0, 0)]);
+    assert_eq!(mapping.iter().count(), 7);
+    assert_eq!(mapping.iter_pairs().collect::<Vec<_>>(),
+               [(0x61, 5), (0x62, 6), (0x41, 1), (0x42, 2)]);
+}
+
+#[test]
+fn codepoint_ranges_collect_text_into_minimal_sorted_ranges() {
+    let ranges = CodepointRange::ranges_from_str("dcba! xyzzy, abcd");
+    assert_eq!(ranges.ranges, [
+        CodepointRange::new(0x20, 0x21),
+        CodepointRange::new(0x2c, 0x2c),
+        CodepointRange::new(0x61, 0x64),
+        CodepointRange::new(0x78, 0x7a),
+    ]);
+
+    let ranges: CodepointRanges = "\u{1f600}a\u{1f601}".chars().collect();
+    assert_eq!(ranges.ranges, [
+        CodepointRange::new(0x61, 0x61),
+        CodepointRange::new(0x1f600, 0x1f601),
+    ]);
+    assert!(CodepointRange::ranges_from_str("").ranges.is_empty());
+}
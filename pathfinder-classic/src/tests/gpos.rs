@@ -0,0 +1,249 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use features::FeatureSettings;
+use font::{Font, PairAdjustment, PositionAdjustment};
+use tables::{gpos, hhea, hmtx};
+use tests::sfnt;
+
+const X_PLACEMENT: u16 = 0x0001;
+const X_ADVANCE: u16 = 0x0004;
+
+type Anchor = (i16, i16);
+
+/// Builds a format 1 pair adjustment subtable adjusting the advance of the first glyph, out of
+/// the pairs for each first glyph, sorted by glyph ID.
+fn pair_pos_format_1(pair_sets: &[(u16, &[(u16, i16)])]) -> Vec<u8> {
+    let mut subtable = vec![];
+    let mut offset = 10 + 2 * pair_sets.len();
+    let coverage_offset = offset + pair_sets.iter().map(|&(_, pairs)| 2 + 4 * pairs.len())
+                                                   .sum::<usize>();
+    for &value in &[1, coverage_offset as u16, X_ADVANCE, 0, pair_sets.len() as u16] {
+        subtable.write_u16::<BigEndian>(value).unwrap();
+    }
+    for &(_, pairs) in pair_sets {
+        subtable.write_u16::<BigEndian>(offset as u16).unwrap();
+        offset += 2 + 4 * pairs.len();
+    }
+    for &(_, pairs) in pair_sets {
+        subtable.write_u16::<BigEndian>(pairs.len() as u16).unwrap();
+        for &(second_glyph, x_advance) in pairs {
+            subtable.write_u16::<BigEndian>(second_glyph).unwrap();
+            subtable.write_i16::<BigEndian>(x_advance).unwrap();
+        }
+    }
+    let first_glyphs: Vec<_> = pair_sets.iter().map(|&(first_glyph, _)| first_glyph).collect();
+    subtable.extend_from_slice(&sfnt::coverage_format_1(&first_glyphs));
+    subtable
+}
+
+/// Builds a format 2 pair adjustment subtable adjusting the advance of the first glyph and the
+/// placement of the second, with a row of `(x advance, x placement)` values per first class.
+fn pair_pos_format_2(coverage: &[u16],
+                     classes_1: &[(u16, u16, u16)],
+                     classes_2: &[(u16, u16, u16)],
+                     values: &[&[(i16, i16)]])
+                     -> Vec<u8> {
+    let class_2_count = values[0].len();
+    let coverage_offset = 16 + 4 * values.len() * class_2_count;
+    let coverage = sfnt::coverage_format_1(coverage);
+    let class_def_1 = sfnt::class_def_format_2(classes_1);
+    let class_def_2 = sfnt::class_def_format_2(classes_2);
+    let class_def_1_offset = coverage_offset + coverage.len();
+    let class_def_2_offset = class_def_1_offset + class_def_1.len();
+
+    let mut subtable = vec![];
+    for &value in &[
+        2,
+        coverage_offset as u16,
+        X_ADVANCE,
+        X_PLACEMENT,
+        class_def_1_offset as u16,
+        class_def_2_offset as u16,
+        values.len() as u16,
+        class_2_count as u16,
+    ] {
+        subtable.write_u16::<BigEndian>(value).unwrap();
+    }
+    for row in values {
+        for &(x_advance, x_placement) in row.iter() {
+            subtable.write_i16::<BigEndian>(x_advance).unwrap();
+            subtable.write_i16::<BigEndian>(x_placement).unwrap();
+        }
+    }
+    subtable.extend_from_slice(&coverage);
+    subtable.extend_from_slice(&class_def_1);
+    subtable.extend_from_slice(&class_def_2);
+    subtable
+}
+
+/// Builds a mark-to-base or mark-to-mark attachment subtable out of the sorted marks, as
+/// `(glyph ID, class, anchor)`, and the sorted glyphs they attach to, with an optional anchor
+/// for each class.
+fn mark_attachment(marks: &[(u16, u16, Anchor)], bases: &[(u16, &[Option<Anchor>])]) -> Vec<u8> {
+    fn anchor(table: &mut Vec<u8>, (x, y): Anchor) {
+        table.write_u16::<BigEndian>(1).unwrap();
+        table.write_i16::<BigEndian>(x).unwrap();
+        table.write_i16::<BigEndian>(y).unwrap();
+    }
+
+    let mark_class_count = bases[0].1.len();
+    let mut mark_array = vec![];
+    mark_array.write_u16::<BigEndian>(marks.len() as u16).unwrap();
+    for (mark_index, &(_, class, _)) in marks.iter().enumerate() {
+        mark_array.write_u16::<BigEndian>(class).unwrap();
+        mark_array.write_u16::<BigEndian>((2 + 4 * marks.len() + 6 * mark_index) as u16).unwrap();
+    }
+    for &(_, _, mark_anchor) in marks {
+        anchor(&mut mark_array, mark_anchor);
+    }
+
+    let mut base_array = vec![];
+    base_array.write_u16::<BigEndian>(bases.len() as u16).unwrap();
+    let mut offset = 2 + 2 * mark_class_count * bases.len();
+    for &(_, anchors) in bases {
+        for base_anchor in anchors {
+            let anchor_offset = if base_anchor.is_some() { offset } else { 0 };
+            base_array.write_u16::<BigEndian>(anchor_offset as u16).unwrap();
+            offset += if base_anchor.is_some() { 6 } else { 0 };
+        }
+    }
+    for &(_, anchors) in bases {
+        for base_anchor in anchors.iter().filter_map(|&base_anchor| base_anchor) {
+            anchor(&mut base_array, base_anchor);
+        }
+    }
+
+    let mark_glyphs: Vec<_> = marks.iter().map(|&(glyph_id, _, _)| glyph_id).collect();
+    let base_glyphs: Vec<_> = bases.iter().map(|&(glyph_id, _)| glyph_id).collect();
+    let mark_coverage = sfnt::coverage_format_1(&mark_glyphs);
+    let base_coverage = sfnt::coverage_format_1(&base_glyphs);
+    let mark_coverage_offset = 12;
+    let base_coverage_offset = mark_coverage_offset + mark_coverage.len();
+    let mark_array_offset = base_coverage_offset + base_coverage.len();
+    let base_array_offset = mark_array_offset + mark_array.len();
+
+    let mut subtable = vec![];
+    for &value in &[
+        1,
+        mark_coverage_offset as u16,
+        base_coverage_offset as u16,
+        mark_class_count as u16,
+        mark_array_offset as u16,
+        base_array_offset as u16,
+    ] {
+        subtable.write_u16::<BigEndian>(value).unwrap();
+    }
+    subtable.extend_from_slice(&mark_coverage);
+    subtable.extend_from_slice(&base_coverage);
+    subtable.extend_from_slice(&mark_array);
+    subtable.extend_from_slice(&base_array);
+    subtable
+}
+
+fn font_bytes(gpos_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(gpos_table) = gpos_table {
+        tables.push((gpos::TAG, gpos_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+fn adjustment(first_x_advance: i16, second_x_placement: i16) -> PairAdjustment {
+    PairAdjustment {
+        first: PositionAdjustment {
+            x_advance: first_x_advance,
+            ..PositionAdjustment::default()
+        },
+        second: PositionAdjustment {
+            x_placement: second_x_placement,
+            ..PositionAdjustment::default()
+        },
+    }
+}
+
+#[test]
+fn pair_adjustments_accumulate_over_the_lookups_of_the_features() {
+    let kern = sfnt::tag(b"kern");
+    let dist = sfnt::tag(b"dist");
+    let class_pairs = pair_pos_format_2(&[5, 10, 11], &[(10, 11, 1)], &[(6, 6, 1), (20, 25, 2)], &[
+        &[(0, 0), (-5, 0), (0, 0)],
+        &[(0, 0), (-50, 12), (-20, 0)],
+    ]);
+    let bytes = font_bytes(Some(sfnt::layout_table(&[(kern, &[0, 1]), (dist, &[2])], &[
+        (2, vec![
+            pair_pos_format_1(&[(5, &[(6, -30), (9, -10)]), (7, &[(6, -15)])]),
+            pair_pos_format_1(&[(5, &[(6, -1000)]), (8, &[(6, -8)])]),
+        ]),
+        (9, vec![sfnt::layout_extension_subtable(2, &class_pairs)]),
+        (2, vec![pair_pos_format_1(&[(5, &[(6, -100)])])]),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let kerning = sfnt::feature_settings(&[kern]);
+    assert_eq!(font.pair_adjustment(5, 6, &kerning), Ok(adjustment(-35, 0)));
+    assert_eq!(font.pair_adjustment(5, 9, &kerning), Ok(adjustment(-10, 0)));
+    assert_eq!(font.pair_adjustment(7, 6, &kerning), Ok(adjustment(-15, 0)));
+    assert_eq!(font.pair_adjustment(8, 6, &kerning), Ok(adjustment(-8, 0)));
+    assert_eq!(font.pair_adjustment(10, 6, &kerning), Ok(adjustment(-50, 12)));
+    assert_eq!(font.pair_adjustment(11, 22, &kerning), Ok(adjustment(-20, 0)));
+    assert_eq!(font.pair_adjustment(10, 7, &kerning), Ok(adjustment(0, 0)));
+    assert_eq!(font.pair_adjustment(6, 5, &kerning), Ok(adjustment(0, 0)));
+
+    let mut features = sfnt::feature_settings(&[kern, dist]);
+    assert_eq!(font.pair_adjustment(5, 6, &features), Ok(adjustment(-135, 0)));
+    features.set(kern, 0);
+    assert_eq!(font.pair_adjustment(5, 6, &features), Ok(adjustment(-100, 0)));
+    assert_eq!(font.pair_adjustment(5, 6, &FeatureSettings::new()), Ok(adjustment(0, 0)));
+}
+
+#[test]
+fn fonts_without_gpos_have_no_pair_adjustments() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.pair_adjustment(5, 6, &FeatureSettings::standard()),
+               Ok(PairAdjustment::default()));
+}
+
+#[test]
+fn marks_attach_to_bases_and_to_other_marks() {
+    let (mark, mkmk) = (sfnt::tag(b"mark"), sfnt::tag(b"mkmk"));
+    let (marks, marks_on_marks) = (sfnt::feature_settings(&[mark]), FeatureSettings::standard());
+    let gpos_table = sfnt::layout_table(&[(mark, &[0]), (mkmk, &[1])], &[
+        (4, vec![mark_attachment(&[(2, 0, (50, 0)), (3, 0, (40, 10)), (5, 1, (0, 0))], &[
+            (1, &[Some((250, 700)), None]),
+            (4, &[Some((300, 650)), Some((0, -100))]),
+        ])]),
+        (6, vec![mark_attachment(&[(3, 0, (40, 10))], &[(2, &[Some((50, 300))])])]),
+    ]);
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, hhea::TAG, sfnt::hhea_table(6));
+    sfnt::replace_table(&mut tables, hmtx::TAG, sfnt::hmtx_table(&[
+        (0, 0), (500, 0), (0, 0), (0, 0), (600, 0), (0, 0),
+    ]));
+    tables.push((gpos::TAG, gpos_table));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let placements = font.mark_placements(&[1, 2, 3, 4, 2, 5], &marks_on_marks).unwrap();
+    let placements: Vec<_> = placements.iter().map(|placement| {
+        (placement.x_placement, placement.y_placement)
+    }).collect();
+    assert_eq!(placements, [(0, 0), (-300, 700), (-290, 990), (0, 0), (-350, 650), (-600, -100)]);
+
+    // Without `mkmk`, the second mark attaches to the base, skipping over the first.
+    let placements = font.mark_placements(&[1, 2, 3], &marks).unwrap();
+    assert_eq!((placements[2].x_placement, placements[2].y_placement), (-290, 690));
+
+    // Marks of classes that the base has no anchor for, and marks without a base, stay put.
+    let placements = font.mark_placements(&[2, 1, 5], &marks_on_marks).unwrap();
+    assert_eq!(placements, [PositionAdjustment::default(); 3]);
+}
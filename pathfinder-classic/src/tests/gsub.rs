@@ -0,0 +1,156 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use features::{self, FeatureSettings};
+use font::Font;
+use tables::gsub;
+use tests::sfnt;
+
+/// A ligature glyph and the components after the first.
+type Ligature<'a> = (u16, &'a [u16]);
+
+fn single_substitution_format_1(glyph_ids: &[u16], delta_glyph_id: i16) -> Vec<u8> {
+    let mut subtable = vec![];
+    subtable.write_u16::<BigEndian>(1).unwrap();
+    subtable.write_u16::<BigEndian>(6).unwrap();
+    subtable.write_i16::<BigEndian>(delta_glyph_id).unwrap();
+    subtable.extend_from_slice(&sfnt::coverage_format_1(glyph_ids));
+    subtable
+}
+
+/// Builds a format 2 single substitution subtable out of sorted `(glyph, substitute)` pairs.
+fn single_substitution_format_2(substitutions: &[(u16, u16)]) -> Vec<u8> {
+    let mut subtable = vec![];
+    subtable.write_u16::<BigEndian>(2).unwrap();
+    subtable.write_u16::<BigEndian>(6 + 2 * substitutions.len() as u16).unwrap();
+    subtable.write_u16::<BigEndian>(substitutions.len() as u16).unwrap();
+    for &(_, substitute) in substitutions {
+        subtable.write_u16::<BigEndian>(substitute).unwrap();
+    }
+    let glyph_ids: Vec<_> = substitutions.iter().map(|&(glyph_id, _)| glyph_id).collect();
+    subtable.extend_from_slice(&sfnt::coverage_format_1(&glyph_ids));
+    subtable
+}
+
+/// Builds a ligature substitution subtable out of the ligatures for each sorted first glyph.
+fn ligature_substitution(ligature_sets: &[(u16, &[Ligature])]) -> Vec<u8> {
+    let mut sets = vec![];
+    for &(_, ligatures) in ligature_sets {
+        let mut set = vec![];
+        set.write_u16::<BigEndian>(ligatures.len() as u16).unwrap();
+        let mut offset = 2 + 2 * ligatures.len();
+        for &(_, components) in ligatures {
+            set.write_u16::<BigEndian>(offset as u16).unwrap();
+            offset += 4 + 2 * components.len();
+        }
+        for &(ligature_glyph, components) in ligatures {
+            set.write_u16::<BigEndian>(ligature_glyph).unwrap();
+            set.write_u16::<BigEndian>(components.len() as u16 + 1).unwrap();
+            for &component in components {
+                set.write_u16::<BigEndian>(component).unwrap();
+            }
+        }
+        sets.push(set);
+    }
+
+    let mut subtable = vec![];
+    let mut offset = 6 + 2 * ligature_sets.len();
+    let coverage_offset = offset + sets.iter().map(|set| set.len()).sum::<usize>();
+    subtable.write_u16::<BigEndian>(1).unwrap();
+    subtable.write_u16::<BigEndian>(coverage_offset as u16).unwrap();
+    subtable.write_u16::<BigEndian>(ligature_sets.len() as u16).unwrap();
+    for set in &sets {
+        subtable.write_u16::<BigEndian>(offset as u16).unwrap();
+        offset += set.len();
+    }
+    for set in &sets {
+        subtable.extend_from_slice(set);
+    }
+    let first_glyphs: Vec<_> = ligature_sets.iter().map(|&(glyph_id, _)| glyph_id).collect();
+    subtable.extend_from_slice(&sfnt::coverage_format_1(&first_glyphs));
+    subtable
+}
+
+fn font_bytes(gsub_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(gsub_table) = gsub_table {
+        tables.push((gsub::TAG, gsub_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn ligatures_replace_their_components_and_merge_clusters() {
+    // Glyphs 10, 11, and 12 are "f", "i", and "l"; 20, 21, 22, and 23 are "ff", "fi", "fl", and
+    // "ffi"; 30 is a small-caps "i".
+    let liga = sfnt::tag(b"liga");
+    let smcp = sfnt::tag(b"smcp");
+    let bytes = font_bytes(Some(sfnt::layout_table(&[(liga, &[0]), (smcp, &[1, 2])], &[
+        (4, vec![ligature_substitution(&[
+            (10, &[(23, &[10, 11]), (20, &[10]), (21, &[11]), (22, &[12])]),
+        ])]),
+        (7, vec![sfnt::layout_extension_subtable(1, &single_substitution_format_1(&[11], 19))]),
+        (1, vec![single_substitution_format_2(&[(11, 99), (21, 31)])]),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let ligatures = sfnt::feature_settings(&[liga]);
+    let small_caps = sfnt::feature_settings(&[smcp]);
+    let mut glyph_ids = vec![10, 10, 11, 5, 10, 12, 10, 11, 11, 10];
+    let clusters = font.substitute_glyphs(&mut glyph_ids, &ligatures).unwrap();
+    assert_eq!(glyph_ids, [23, 5, 22, 21, 11, 10]);
+    assert_eq!(clusters, [0, 3, 4, 6, 8, 9]);
+
+    // Single substitutions apply in lookup order, one subtable per glyph.
+    let mut glyph_ids = vec![10, 11, 21];
+    let clusters = font.substitute_glyphs(&mut glyph_ids, &small_caps).unwrap();
+    assert_eq!(glyph_ids, [10, 30, 31]);
+    assert_eq!(clusters, [0, 1, 2]);
+
+    let mut glyph_ids = vec![10, 11];
+    let features = FeatureSettings::standard().parse_css("\"smcp\" on").unwrap();
+    font.substitute_glyphs(&mut glyph_ids, &features).unwrap();
+    assert_eq!(glyph_ids, [31]);
+
+    // Disabling a standard feature turns off its lookups.
+    let mut glyph_ids = vec![10, 11];
+    let features = FeatureSettings::standard().parse_css("'liga' 0").unwrap();
+    font.substitute_glyphs(&mut glyph_ids, &features).unwrap();
+    assert_eq!(glyph_ids, [10, 11]);
+}
+
+#[test]
+fn fonts_without_gsub_leave_glyphs_untouched() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    let mut glyph_ids = vec![10, 11];
+    let clusters = font.substitute_glyphs(&mut glyph_ids, &FeatureSettings::standard()).unwrap();
+    assert_eq!(clusters, [0, 1]);
+    assert_eq!(glyph_ids, [10, 11]);
+}
+
+#[test]
+fn feature_settings_parse_css() {
+    let liga = features::tag(b"liga");
+    let salt = features::tag(b"salt");
+    let smcp = features::tag(b"smcp");
+
+    let settings = FeatureSettings::standard().parse_css(r#""liga" 0, 'smcp',"salt" 3"#).unwrap();
+    assert!(!settings.is_enabled(liga));
+    assert!(settings.is_enabled(smcp));
+    assert_eq!(settings.value(salt), 3);
+    assert!(settings.is_enabled(features::tag(b"kern")));
+    assert!(!settings.is_enabled(features::tag(b"tnum")));
+
+    assert_eq!(FeatureSettings::standard().parse_css("normal"), Some(FeatureSettings::standard()));
+    let settings = FeatureSettings::new().parse_css(r#""liga" off, "liga" on"#).unwrap();
+    assert_eq!(settings.value(liga), 1);
+    for malformed in &[r#""lig" 1"#, "liga", r#""liga" -1"#, r#""liga" 1,"#, r#""liga "#] {
+        assert_eq!(FeatureSettings::new().parse_css(malformed), None);
+    }
+}
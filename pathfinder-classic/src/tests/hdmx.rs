@@ -0,0 +1,73 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::{hdmx, maxp};
+use tests::sfnt;
+
+/// Builds an `hdmx` table out of `(ppem, widths)` device records.
+fn hdmx_table(records: &[(u8, &[u8])]) -> Vec<u8> {
+    let record_size = (2 + records[0].1.len() + 3) & !3;
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_i16::<BigEndian>(records.len() as i16).unwrap();
+    table.write_i32::<BigEndian>(record_size as i32).unwrap();
+    for &(ppem, widths) in records {
+        table.push(ppem);
+        table.push(*widths.iter().max().unwrap());
+        table.extend_from_slice(widths);
+        while table.len() % 4 != 0 {
+            table.push(0)
+        }
+    }
+    table
+}
+
+fn font_with_device_metrics() -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, maxp::TAG, sfnt::maxp_table(3));
+    tables.push((hdmx::TAG, hdmx_table(&[(9, &[5, 3, 7]), (12, &[6, 4, 9])])));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn device_advance_widths() {
+    let bytes = font_with_device_metrics();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.device_advance_width(0, 9), Some(5));
+    assert_eq!(font.device_advance_width(2, 9), Some(7));
+    assert_eq!(font.device_advance_width(1, 12), Some(4));
+    assert_eq!(font.device_advance_width(2, 12), Some(9));
+    assert_eq!(font.device_advance_width(1, 10), None);
+    assert_eq!(font.device_advance_width(100, 12), None);
+
+    // The padding after the last glyph isn't mistaken for another glyph.
+    assert_eq!(font.device_advance_width(3, 12), None);
+}
+
+#[test]
+fn pixel_advance_widths_fall_back_to_scaled_advances() {
+    // Every glyph is 500 units wide in a 1000-unit em.
+    let bytes = font_with_device_metrics();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.pixel_advance_width(1, 9), Ok(3));
+    assert_eq!(font.pixel_advance_width(2, 12), Ok(9));
+    assert_eq!(font.pixel_advance_width(1, 10), Ok(5));
+    assert_eq!(font.pixel_advance_width(1, 13), Ok(7));
+}
+
+#[test]
+fn no_device_advance_widths_without_hdmx() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.device_advance_width(0, 12), None);
+}
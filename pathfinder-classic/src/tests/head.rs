@@ -0,0 +1,70 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{Font, MAC_STYLE_BOLD, MAC_STYLE_ITALIC};
+use outline::GlyphBounds;
+use tables::head;
+use tests::sfnt;
+
+#[test]
+fn dates_are_converted_from_the_1904_epoch() {
+    // 2018-01-28T16:00:00Z and 1970-01-01T00:00:00Z, as seconds since 1904-01-01.
+    let mut dates = vec![];
+    dates.write_i64::<BigEndian>(3600000000).unwrap();
+    dates.write_i64::<BigEndian>(2082844800).unwrap();
+    let mut head_table = sfnt::head_table();
+    head_table[20..36].copy_from_slice(&dates);
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, head::TAG, head_table);
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.created(), Some(1517155200));
+    assert_eq!(font.modified(), Some(0));
+}
+
+#[test]
+fn dates_before_the_representable_range_are_none() {
+    let mut dates = vec![];
+    dates.write_i64::<BigEndian>(i64::min_value()).unwrap();
+    dates.write_i64::<BigEndian>(0).unwrap();
+    let mut head_table = sfnt::head_table();
+    head_table[20..36].copy_from_slice(&dates);
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, head::TAG, head_table);
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.created(), None);
+    assert_eq!(font.modified(), Some(-2082844800));
+}
+
+#[test]
+fn head_metrics_and_style_are_exposed() {
+    let mut head_table = sfnt::head_table();
+    (&mut head_table[44..46]).write_u16::<BigEndian>(0x0003).unwrap();
+    (&mut head_table[50..52]).write_i16::<BigEndian>(1).unwrap();
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, head::TAG, head_table);
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.units_per_em(), 1000);
+    assert_eq!(font.font_bounds(), GlyphBounds { left: 0, bottom: -200, right: 1000, top: 800 });
+    assert_eq!(font.mac_style(), MAC_STYLE_BOLD | MAC_STYLE_ITALIC);
+    assert_eq!(font.lowest_recommended_ppem(), 8);
+    assert!(font.has_long_glyph_offsets());
+}
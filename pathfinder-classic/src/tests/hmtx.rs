@@ -0,0 +1,45 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use error::FontError;
+use font::Font;
+use tables::{hhea, hmtx};
+use tests::sfnt;
+
+fn font_tables(number_of_h_metrics: u16, hmtx_table: Vec<u8>) -> Vec<(u32, Vec<u8>)> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, hhea::TAG, sfnt::hhea_table(number_of_h_metrics));
+    sfnt::replace_table(&mut tables, hmtx::TAG, hmtx_table);
+    tables
+}
+
+#[test]
+fn trailing_glyphs_share_the_last_advance_width() {
+    let mut hmtx_table = sfnt::hmtx_table(&[(250, 0), (700, 100), (600, 30)]);
+    for &lsb in &[40, -10] {
+        hmtx_table.write_i16::<BigEndian>(lsb).unwrap();
+    }
+    let bytes = sfnt::sfnt(&font_tables(3, hmtx_table));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let advance_widths: Vec<_> = (0..5).map(|glyph_id| font.advance_width(glyph_id)).collect();
+    assert_eq!(advance_widths, [Ok(250), Ok(700), Ok(600), Ok(600), Ok(600)]);
+    let lsbs: Vec<_> = (0..5).map(|glyph_id| font.left_side_bearing(glyph_id)).collect();
+    assert_eq!(lsbs, [Ok(0), Ok(100), Ok(30), Ok(40), Ok(-10)]);
+
+    let metrics = font.metrics_for_glyph(4).unwrap();
+    assert_eq!((metrics.advance_width, metrics.lsb), (600, -10));
+    assert_eq!(font.left_side_bearing(5), Err(FontError::UnexpectedEof));
+}
+
+#[test]
+fn fonts_without_horizontal_metrics_report_an_error() {
+    let bytes = sfnt::sfnt(&font_tables(0, vec![]));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.advance_width(0), Err(FontError::UnexpectedEof));
+}
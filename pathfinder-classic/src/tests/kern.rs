@@ -0,0 +1,154 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::kern;
+use tests::sfnt;
+
+const HORIZONTAL: u16 = 1 << 0;
+const MINIMUM: u16 = 1 << 1;
+const CROSS_STREAM: u16 = 1 << 2;
+const OVERRIDE: u16 = 1 << 3;
+
+fn kern_table(subtables: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(subtables.len() as u16).unwrap();
+    for &(coverage, ref subtable) in subtables {
+        table.write_u16::<BigEndian>(0).unwrap();
+        table.write_u16::<BigEndian>(6 + subtable.len() as u16).unwrap();
+        table.write_u16::<BigEndian>(coverage).unwrap();
+        table.extend_from_slice(subtable);
+    }
+    table
+}
+
+fn kern_format_0(pairs: &[(u16, u16, i16)]) -> Vec<u8> {
+    let mut subtable = vec![];
+    subtable.write_u16::<BigEndian>(pairs.len() as u16).unwrap();
+    for _ in 0..3 {
+        subtable.write_u16::<BigEndian>(0).unwrap();
+    }
+    for &(left, right, value) in pairs {
+        subtable.write_u16::<BigEndian>(left).unwrap();
+        subtable.write_u16::<BigEndian>(right).unwrap();
+        subtable.write_i16::<BigEndian>(value).unwrap();
+    }
+    subtable
+}
+
+/// Builds a format 2 subtable from the classes of the left glyphs starting at glyph 0, the
+/// classes of the right glyphs starting at `first_right_glyph`, and a row of values per left class.
+fn kern_format_2(left_classes: &[u16], first_right_glyph: u16, right_classes: &[u16],
+                 values: &[&[i16]])
+                 -> Vec<u8> {
+    let row_width = 2 * values[0].len() as u16;
+    let left_class_offset = 6 + 8;
+    let right_class_offset = left_class_offset + 4 + 2 * left_classes.len() as u16;
+    let array_offset = right_class_offset + 4 + 2 * right_classes.len() as u16;
+
+    let mut subtable = vec![];
+    for &value in &[row_width, left_class_offset, right_class_offset, array_offset] {
+        subtable.write_u16::<BigEndian>(value).unwrap();
+    }
+    subtable.write_u16::<BigEndian>(0).unwrap();
+    subtable.write_u16::<BigEndian>(left_classes.len() as u16).unwrap();
+    for &class in left_classes {
+        subtable.write_u16::<BigEndian>(array_offset + class * row_width).unwrap();
+    }
+    subtable.write_u16::<BigEndian>(first_right_glyph).unwrap();
+    subtable.write_u16::<BigEndian>(right_classes.len() as u16).unwrap();
+    for &class in right_classes {
+        subtable.write_u16::<BigEndian>(class * 2).unwrap();
+    }
+    for row in values {
+        for &value in row.iter() {
+            subtable.write_i16::<BigEndian>(value).unwrap();
+        }
+    }
+    subtable
+}
+
+fn font_bytes(kern_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(kern_table) = kern_table {
+        tables.push((kern::TAG, kern_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn format_0_pairs_are_found_by_binary_search() {
+    let pairs = [(1, 2, -50), (1, 7, -20), (3, 1, 15), (3, 4, -80), (9, 9, 5)];
+    let bytes = font_bytes(Some(kern_table(&[(HORIZONTAL, kern_format_0(&pairs))])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    for &(left, right, value) in &pairs {
+        assert_eq!(font.kerning_for_glyph_pair(left, right), value);
+    }
+    assert_eq!(font.kerning_for_glyph_pair(2, 1), 0);
+    assert_eq!(font.kerning_for_glyph_pair(3, 2), 0);
+    assert_eq!(font.kerning_for_glyph_pair(10, 0), 0);
+}
+
+#[test]
+fn horizontal_subtables_accumulate_unless_overridden() {
+    let bytes = font_bytes(Some(kern_table(&[
+        (HORIZONTAL, kern_format_0(&[(1, 2, -50), (3, 4, -10)])),
+        (HORIZONTAL | CROSS_STREAM, kern_format_0(&[(1, 2, 100)])),
+        (HORIZONTAL | MINIMUM, kern_format_0(&[(1, 2, 200)])),
+        (0, kern_format_0(&[(1, 2, 400)])),
+        (HORIZONTAL, kern_format_0(&[(1, 2, -5), (5, 6, 30)])),
+        (HORIZONTAL | OVERRIDE, kern_format_0(&[(3, 4, -25)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.kerning_for_glyph_pair(1, 2), -55);
+    assert_eq!(font.kerning_for_glyph_pair(3, 4), -25);
+    assert_eq!(font.kerning_for_glyph_pair(5, 6), 30);
+}
+
+#[test]
+fn fonts_without_horizontal_kerning_have_none() {
+    let bytes = font_bytes(Some(kern_table(&[
+        (CROSS_STREAM | HORIZONTAL, kern_format_0(&[(1, 2, -50)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.kerning_for_glyph_pair(1, 2), 0);
+
+    let bytes = font_bytes(None);
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.kerning_for_glyph_pair(1, 2), 0);
+}
+
+#[test]
+fn format_2_pairs_are_kerned_by_class() {
+    let bytes = font_bytes(Some(kern_table(&[
+        (HORIZONTAL | (2 << 8), kern_format_2(&[0, 1, 2, 1], 2, &[1, 0, 1], &[
+            &[0, 0],
+            &[0, -40],
+            &[10, 25],
+        ])),
+        (HORIZONTAL, kern_format_0(&[(1, 4, -5)])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.kerning_for_glyph_pair(1, 2), -40);
+    assert_eq!(font.kerning_for_glyph_pair(3, 4), -40);
+    assert_eq!(font.kerning_for_glyph_pair(1, 4), -45);
+    assert_eq!(font.kerning_for_glyph_pair(2, 3), 10);
+    assert_eq!(font.kerning_for_glyph_pair(2, 4), 25);
+    assert_eq!(font.kerning_for_glyph_pair(0, 2), 0);
+
+    // Glyphs outside either class table aren't kerned.
+    assert_eq!(font.kerning_for_glyph_pair(4, 2), 0);
+    assert_eq!(font.kerning_for_glyph_pair(2, 1), 0);
+    assert_eq!(font.kerning_for_glyph_pair(2, 5), 0);
+}
@@ -0,0 +1,38 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::ltsh;
+use tests::sfnt;
+
+#[test]
+fn linear_thresholds() {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(3).unwrap();
+    table.extend_from_slice(&[1, 24, 255]);
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((ltsh::TAG, table));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.linear_threshold(0), Some(1));
+    assert_eq!(font.linear_threshold(1), Some(24));
+    assert_eq!(font.linear_threshold(2), Some(255));
+    assert_eq!(font.linear_threshold(3), None);
+}
+
+#[test]
+fn no_linear_thresholds_without_ltsh() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.linear_threshold(0), None);
+}
@@ -0,0 +1,204 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{Font, GlyphAssembly, GlyphPart, MathConstant, MathDirection, MathGlyphVariant};
+use tables::math;
+use tests::sfnt;
+
+fn font_bytes(math_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(math_table) = math_table {
+        tables.push((math::TAG, math_table));
+    }
+    sfnt::sfnt(&tables)
+}
+
+fn write_u16s(table: &mut Vec<u8>, values: &[u16]) {
+    for &value in values {
+        table.write_u16::<BigEndian>(value).unwrap();
+    }
+}
+
+/// A subtable of one value record per glyph, followed by the coverage of those glyphs.
+fn glyph_value_subtable(values: &[(u16, i16)]) -> Vec<u8> {
+    let glyph_ids: Vec<_> = values.iter().map(|&(glyph_id, _)| glyph_id).collect();
+    let mut subtable = vec![];
+    write_u16s(&mut subtable, &[4 + 4 * values.len() as u16, values.len() as u16]);
+    for &(_, value) in values {
+        subtable.write_i16::<BigEndian>(value).unwrap();
+        subtable.write_u16::<BigEndian>(0).unwrap();
+    }
+    subtable.extend_from_slice(&sfnt::coverage_format_1(&glyph_ids));
+    subtable
+}
+
+fn math_table() -> Vec<u8> {
+    // Every constant is its index times 10, except for a few that we check by name.
+    let mut constants = vec![];
+    for index in 0..56 {
+        let value = match index {
+            0 => 70,
+            5 => 250,
+            38 => 40,
+            55 => 60,
+            index => index * 10,
+        };
+        constants.write_i16::<BigEndian>(value).unwrap();
+        if index >= 4 && index < 55 {
+            constants.write_u16::<BigEndian>(0).unwrap();
+        }
+    }
+    assert_eq!(constants.len(), 214);
+
+    // Glyphs 5 and 7 have italics corrections, glyph 5 has a top accent attachment, and glyph 20
+    // is an extended shape.
+    let italics_corrections = glyph_value_subtable(&[(5, 30), (7, -10)]);
+    let top_accent_attachments = glyph_value_subtable(&[(5, 250)]);
+    let mut glyph_info = vec![];
+    write_u16s(&mut glyph_info, &[
+        8,
+        8 + italics_corrections.len() as u16,
+        8 + (italics_corrections.len() + top_accent_attachments.len()) as u16,
+        0,
+    ]);
+    glyph_info.extend_from_slice(&italics_corrections);
+    glyph_info.extend_from_slice(&top_accent_attachments);
+    glyph_info.extend_from_slice(&sfnt::coverage_format_1(&[20]));
+
+    // Glyph 20 grows vertically into glyph 21 or an assembly of glyphs 22 to 24; glyph 30 grows
+    // horizontally into glyph 31.
+    let mut vertical_construction = vec![];
+    write_u16s(&mut vertical_construction, &[12, 2, 20, 1000, 21, 1500]);
+    write_u16s(&mut vertical_construction, &[5, 0, 3]);
+    write_u16s(&mut vertical_construction, &[22, 0, 100, 500, 0]);
+    write_u16s(&mut vertical_construction, &[23, 100, 100, 300, 1]);
+    write_u16s(&mut vertical_construction, &[24, 100, 0, 500, 0]);
+    let mut horizontal_construction = vec![];
+    write_u16s(&mut horizontal_construction, &[0, 1, 31, 2000]);
+
+    let vertical_construction_offset = 14;
+    let horizontal_construction_offset = vertical_construction_offset +
+        vertical_construction.len() as u16;
+    let vertical_coverage_offset = horizontal_construction_offset +
+        horizontal_construction.len() as u16;
+    let horizontal_coverage_offset = vertical_coverage_offset + 6;
+    let mut variants = vec![];
+    write_u16s(&mut variants, &[
+        20,
+        vertical_coverage_offset,
+        horizontal_coverage_offset,
+        1,
+        1,
+        vertical_construction_offset,
+        horizontal_construction_offset,
+    ]);
+    variants.extend_from_slice(&vertical_construction);
+    variants.extend_from_slice(&horizontal_construction);
+    variants.extend_from_slice(&sfnt::coverage_format_1(&[20]));
+    variants.extend_from_slice(&sfnt::coverage_format_1(&[30]));
+
+    let constants_offset = 10;
+    let glyph_info_offset = constants_offset + constants.len();
+    let variants_offset = glyph_info_offset + glyph_info.len();
+    let mut table = vec![];
+    write_u16s(&mut table, &[
+        1,
+        0,
+        constants_offset as u16,
+        glyph_info_offset as u16,
+        variants_offset as u16,
+    ]);
+    table.extend_from_slice(&constants);
+    table.extend_from_slice(&glyph_info);
+    table.extend_from_slice(&variants);
+    table
+}
+
+#[test]
+fn math_constants() {
+    let bytes = font_bytes(Some(math_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.math_constant(MathConstant::ScriptPercentScaleDown), Ok(Some(70)));
+    assert_eq!(font.math_constant(MathConstant::DisplayOperatorMinHeight), Ok(Some(30)));
+    assert_eq!(font.math_constant(MathConstant::MathLeading), Ok(Some(40)));
+    assert_eq!(font.math_constant(MathConstant::AxisHeight), Ok(Some(250)));
+    assert_eq!(font.math_constant(MathConstant::FractionRuleThickness), Ok(Some(40)));
+    assert_eq!(font.math_constant(MathConstant::RadicalKernAfterDegree), Ok(Some(540)));
+    assert_eq!(font.math_constant(MathConstant::RadicalDegreeBottomRaisePercent), Ok(Some(60)));
+}
+
+#[test]
+fn math_glyph_info() {
+    let bytes = font_bytes(Some(math_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.math_italics_correction(5), Ok(Some(30)));
+    assert_eq!(font.math_italics_correction(7), Ok(Some(-10)));
+    assert_eq!(font.math_italics_correction(6), Ok(None));
+    assert_eq!(font.math_top_accent_attachment(5), Ok(Some(250)));
+    assert_eq!(font.math_top_accent_attachment(7), Ok(None));
+    assert_eq!(font.is_math_extended_shape(20), Ok(true));
+    assert_eq!(font.is_math_extended_shape(5), Ok(false));
+}
+
+#[test]
+fn math_glyph_variants_and_assemblies() {
+    let bytes = font_bytes(Some(math_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.math_min_connector_overlap(), Ok(20));
+
+    assert_eq!(font.math_glyph_variants(20, MathDirection::Vertical), Ok(vec![
+        MathGlyphVariant { glyph_id: 20, advance: 1000 },
+        MathGlyphVariant { glyph_id: 21, advance: 1500 },
+    ]));
+    assert_eq!(font.math_glyph_assembly(20, MathDirection::Vertical), Ok(Some(GlyphAssembly {
+        italics_correction: 5,
+        parts: vec![
+            GlyphPart {
+                glyph_id: 22,
+                start_connector_length: 0,
+                end_connector_length: 100,
+                full_advance: 500,
+                is_extender: false,
+            },
+            GlyphPart {
+                glyph_id: 23,
+                start_connector_length: 100,
+                end_connector_length: 100,
+                full_advance: 300,
+                is_extender: true,
+            },
+            GlyphPart {
+                glyph_id: 24,
+                start_connector_length: 100,
+                end_connector_length: 0,
+                full_advance: 500,
+                is_extender: false,
+            },
+        ],
+    })));
+    assert_eq!(font.math_glyph_variants(20, MathDirection::Horizontal), Ok(vec![]));
+
+    assert_eq!(font.math_glyph_variants(30, MathDirection::Horizontal), Ok(vec![
+        MathGlyphVariant { glyph_id: 31, advance: 2000 },
+    ]));
+    assert_eq!(font.math_glyph_assembly(30, MathDirection::Horizontal), Ok(None));
+    assert_eq!(font.math_glyph_assembly(30, MathDirection::Vertical), Ok(None));
+}
+
+#[test]
+fn fonts_without_math_have_no_math_data() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.math_constant(MathConstant::AxisHeight), Ok(None));
+    assert_eq!(font.math_italics_correction(5), Ok(None));
+    assert_eq!(font.is_math_extended_shape(20), Ok(false));
+    assert_eq!(font.math_glyph_variants(20, MathDirection::Vertical), Ok(vec![]));
+    assert_eq!(font.math_glyph_assembly(20, MathDirection::Vertical), Ok(None));
+}
@@ -0,0 +1,62 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{Font, GlyphLimits};
+use tables::maxp;
+use tests::sfnt;
+
+fn font_with_maxp_table(maxp_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    match maxp_table {
+        Some(maxp_table) => sfnt::replace_table(&mut tables, maxp::TAG, maxp_table),
+        None => tables.retain(|&(tag, _)| tag != maxp::TAG),
+    }
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn glyph_count_and_limits_come_from_maxp() {
+    let bytes = font_with_maxp_table(Some(sfnt::maxp_table(300)));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_count(), Some(300));
+    assert_eq!(font.glyph_limits(), Some(GlyphLimits {
+        max_points: 64,
+        max_contours: 4,
+        max_composite_points: 128,
+        max_composite_contours: 8,
+        max_component_elements: 2,
+        max_component_depth: 1,
+    }));
+}
+
+#[test]
+fn version_0_5_maxp_has_only_the_glyph_count() {
+    let mut maxp_table = vec![];
+    maxp_table.write_u32::<BigEndian>(0x00005000).unwrap();
+    maxp_table.write_u16::<BigEndian>(42).unwrap();
+    let bytes = font_with_maxp_table(Some(maxp_table));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_count(), Some(42));
+    assert_eq!(font.glyph_limits(), None);
+}
+
+#[test]
+fn missing_and_unsupported_maxp_tables_are_ignored() {
+    let bytes = font_with_maxp_table(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_count(), None);
+    assert_eq!(font.glyph_limits(), None);
+    assert_eq!(font.glyph_for_char('A'), Ok(0));
+
+    let mut maxp_table = sfnt::maxp_table(1);
+    maxp_table[1] = 2;
+    let bytes = font_with_maxp_table(Some(maxp_table));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_count(), None);
+}
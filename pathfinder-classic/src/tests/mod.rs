@@ -8,6 +8,41 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod base;
 mod buffers;
+mod cmap_search;
+mod cmap_subtables;
+mod colr;
+mod embedded_bitmaps;
+mod font_collection;
+mod fvar;
+mod gasp;
+mod gdef;
+mod gpos;
+mod gsub;
+mod glyph_layout;
+mod glyph_lookup;
+mod glyph_mapping;
+mod hdmx;
+mod head;
+mod hmtx;
+mod kern;
+mod ltsh;
+mod math;
+mod maxp;
+mod name;
+mod os_2;
+mod post;
 mod rect_packer;
+mod sbix;
+mod sfnt;
+mod signature;
+mod stat;
+mod stroke;
+mod svg;
+mod variations;
+mod vmtx;
+mod woff;
+#[cfg(feature = "brotli-decompressor")]
+mod woff2;
 
@@ -0,0 +1,106 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use font::{Font, FontTable};
+use tables::name::{self, NameTable};
+use tests::sfnt;
+
+const FAMILY_NAME: u16 = 1;
+const LICENSE: u16 = 13;
+
+#[test]
+fn decodes_non_ascii_utf16_be_family_name() {
+    let bytes = sfnt::name_table(&[
+        (3, 1, 0x409, FAMILY_NAME, sfnt::utf16_be("Zürich Ñandú 𝔉")),
+    ]);
+    let table = NameTable::new(FontTable { bytes: &bytes }).unwrap();
+    assert_eq!(table.get_localized(FAMILY_NAME, 0x409), Some("Zürich Ñandú 𝔉".to_owned()));
+}
+
+#[test]
+fn falls_back_to_english() {
+    let bytes = sfnt::name_table(&[
+        (1, 0, 0, FAMILY_NAME, b"Caf\x8e".to_vec()),
+        (3, 1, 0x407, FAMILY_NAME, sfnt::utf16_be("Schrift")),
+        (3, 1, 0x409, FAMILY_NAME, sfnt::utf16_be("Typeface")),
+    ]);
+    let table = NameTable::new(FontTable { bytes: &bytes }).unwrap();
+    assert_eq!(table.get_localized(FAMILY_NAME, 0x407), Some("Schrift".to_owned()));
+    assert_eq!(table.get_localized(FAMILY_NAME, 0x40c), Some("Typeface".to_owned()));
+
+    let bytes = sfnt::name_table(&[(1, 0, 0, FAMILY_NAME, b"Caf\x8e".to_vec())]);
+    let table = NameTable::new(FontTable { bytes: &bytes }).unwrap();
+    assert_eq!(table.get_localized(FAMILY_NAME, 0x40c), Some("Café".to_owned()));
+}
+
+#[test]
+fn unknown_encodings_and_malformed_strings_are_not_decoded() {
+    let bytes = sfnt::name_table(&[
+        (3, 2, 0x411, FAMILY_NAME, vec![0x82, 0xa0]),
+        (3, 1, 0x409, LICENSE, vec![0xd8, 0x00]),
+    ]);
+    let table = NameTable::new(FontTable { bytes: &bytes }).unwrap();
+    assert_eq!(table.get_localized(FAMILY_NAME, 0x411), None);
+    assert_eq!(table.get_localized(LICENSE, 0x409), None);
+}
+
+#[test]
+fn newlines_are_normalized() {
+    let bytes = sfnt::name_table(&[
+        (1, 0, 0, LICENSE, b"one\rtwo".to_vec()),
+        (3, 1, 0x409, LICENSE, sfnt::utf16_be("one\r\ntwo")),
+    ]);
+    let table = NameTable::new(FontTable { bytes: &bytes }).unwrap();
+    assert_eq!(table.get_localized(LICENSE, 0), Some("one\ntwo".to_owned()));
+    assert_eq!(table.get_localized(LICENSE, 0x409), Some("one\ntwo".to_owned()));
+}
+
+#[test]
+fn font_exposes_localized_names() {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((name::TAG, sfnt::name_table(&[
+        (3, 1, 0x409, FAMILY_NAME, sfnt::utf16_be("Test Sans")),
+    ])));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.localized_name(FAMILY_NAME, 0x409), Some("Test Sans".to_owned()));
+    assert_eq!(font.localized_name(LICENSE, 0x409), None);
+}
+
+#[test]
+fn font_picker_names_prefer_typographic_names() {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((name::TAG, sfnt::name_table(&[
+        (1, 0, 0, 4, b"Test Sans Light Italic".to_vec()),
+        (1, 0, 0, 6, b"TestSans-LightItalic".to_vec()),
+        (3, 1, 0x409, FAMILY_NAME, sfnt::utf16_be("Test Sans Light")),
+        (3, 1, 0x409, 2, sfnt::utf16_be("Italic")),
+        (3, 1, 0x409, 16, sfnt::utf16_be("Test Sans")),
+        (3, 1, 0x409, 17, sfnt::utf16_be("Light Italic")),
+    ])));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.family_name(), Some("Test Sans".to_owned()));
+    assert_eq!(font.subfamily_name(), Some("Light Italic".to_owned()));
+    assert_eq!(font.full_name(), Some("Test Sans Light Italic".to_owned()));
+    assert_eq!(font.postscript_name(), Some("TestSans-LightItalic".to_owned()));
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((name::TAG, sfnt::name_table(&[
+        (1, 0, 0, 2, b"Regular".to_vec()),
+        (3, 1, 0x409, FAMILY_NAME, sfnt::utf16_be("Test Serif")),
+    ])));
+    let bytes = sfnt::sfnt(&tables);
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.family_name(), Some("Test Serif".to_owned()));
+    assert_eq!(font.subfamily_name(), Some("Regular".to_owned()));
+    assert_eq!(font.postscript_name(), None);
+}
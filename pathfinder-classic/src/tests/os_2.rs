@@ -0,0 +1,72 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::os_2;
+use tests::sfnt;
+
+fn font_with_os_2_table(os_2_table: Vec<u8>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, os_2::TAG, os_2_table);
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn os_2_metrics_and_style_are_exposed() {
+    let mut os_2_table = sfnt::os_2_table();
+    (&mut os_2_table[4..8]).write_u16::<BigEndian>(300).unwrap();
+    (&mut os_2_table[6..8]).write_u16::<BigEndian>(3).unwrap();
+    (&mut os_2_table[26..28]).write_i16::<BigEndian>(50).unwrap();
+    (&mut os_2_table[28..30]).write_i16::<BigEndian>(250).unwrap();
+    (&mut os_2_table[42..46]).write_u32::<BigEndian>(0x0000_0007).unwrap();
+    (&mut os_2_table[54..58]).write_u32::<BigEndian>(0x8000_0000).unwrap();
+    (&mut os_2_table[62..64]).write_u16::<BigEndian>(0x0081).unwrap();
+    (&mut os_2_table[78..82]).write_u32::<BigEndian>(0x0000_0001).unwrap();
+    (&mut os_2_table[86..88]).write_i16::<BigEndian>(480).unwrap();
+    (&mut os_2_table[88..90]).write_i16::<BigEndian>(700).unwrap();
+    let bytes = font_with_os_2_table(os_2_table);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.weight_class(), 300);
+    assert_eq!(font.width_class(), 3);
+    assert_eq!(font.strikeout_size(), 50);
+    assert_eq!(font.strikeout_position(), 250);
+    assert_eq!(font.unicode_ranges(), [7, 0, 0, 0x8000_0000]);
+    assert!(font.is_italic());
+    assert!(font.use_typo_metrics());
+    assert!(!font.is_bold());
+    assert!(!font.is_oblique());
+    assert_eq!(font.ascender(), 800);
+    assert_eq!(font.descender(), -200);
+    assert_eq!(font.line_gap(), 90);
+    assert_eq!(font.code_page_ranges(), Some([1, 0]));
+    assert_eq!(font.x_height(), Some(480));
+    assert_eq!(font.cap_height(), Some(700));
+}
+
+#[test]
+fn version_0_os_2_tables_lack_later_fields() {
+    // Version 0 tables have a shorter Unicode range field and end after the Windows metrics.
+    let mut os_2_table = sfnt::os_2_table();
+    (&mut os_2_table[0..2]).write_u16::<BigEndian>(0).unwrap();
+    (&mut os_2_table[4..6]).write_u16::<BigEndian>(700).unwrap();
+    (&mut os_2_table[62..64]).write_u16::<BigEndian>(0x0020).unwrap();
+    os_2_table.drain(50..62);
+    os_2_table.truncate(66);
+    let bytes = font_with_os_2_table(os_2_table);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.weight_class(), 700);
+    assert!(font.is_bold());
+    assert_eq!(font.unicode_ranges(), [0; 4]);
+    assert_eq!(font.ascender(), 800);
+    assert_eq!(font.line_gap(), 90);
+    assert_eq!(font.code_page_ranges(), None);
+    assert_eq!(font.x_height(), None);
+    assert_eq!(font.cap_height(), None);
+}
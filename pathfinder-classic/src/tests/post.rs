@@ -0,0 +1,146 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::{hhea, hmtx, post};
+use tests::sfnt;
+
+fn post_table(is_fixed_pitch: bool) -> Vec<u8> {
+    post_table_header(0x00030000, is_fixed_pitch)
+}
+
+fn post_table_header(version: u32, is_fixed_pitch: bool) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u32::<BigEndian>(version).unwrap();
+    table.write_i32::<BigEndian>(0).unwrap();
+    table.write_i16::<BigEndian>(-100).unwrap();
+    table.write_i16::<BigEndian>(50).unwrap();
+    table.write_u32::<BigEndian>(is_fixed_pitch as u32).unwrap();
+    for _ in 0..4 {
+        table.write_u32::<BigEndian>(0).unwrap();
+    }
+    table
+}
+
+fn font_tables(advance_widths: &[u16]) -> Vec<(u32, Vec<u8>)> {
+    let metrics: Vec<_> = advance_widths.iter().map(|&advance_width| (advance_width, 0)).collect();
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, hhea::TAG, sfnt::hhea_table(metrics.len() as u16));
+    sfnt::replace_table(&mut tables, hmtx::TAG, sfnt::hmtx_table(&metrics));
+    tables
+}
+
+#[test]
+fn monospaced_according_to_post() {
+    let mut tables = font_tables(&[600, 600, 600]);
+    tables.push((post::TAG, post_table(true)));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.is_monospaced(), Ok(true));
+
+    let mut tables = font_tables(&[250, 600, 480]);
+    tables.push((post::TAG, post_table(false)));
+    let bytes = sfnt::sfnt(&tables);
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.is_monospaced(), Ok(false));
+}
+
+#[test]
+fn monospaced_according_to_advances_without_post() {
+    // The zero advance belongs to a combining mark and doesn't count.
+    let bytes = sfnt::sfnt(&font_tables(&[600, 0, 600]));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.is_monospaced(), Ok(true));
+
+    let bytes = sfnt::sfnt(&font_tables(&[600, 0, 480]));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.is_monospaced(), Ok(false));
+}
+
+fn font_with_post(post_table: Vec<u8>) -> Vec<u8> {
+    let mut tables = font_tables(&[500, 250, 600, 600]);
+    tables.push((post::TAG, post_table));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn glyph_names_resolve_with_version_2_0() {
+    // Glyphs 1 and 3 use standard names, and glyphs 2 and 0 use the first two custom names.
+    let mut table = post_table_header(0x00020000, false);
+    table.write_u16::<BigEndian>(4).unwrap();
+    for &glyph_name_index in &[259, 3, 258, 36] {
+        table.write_u16::<BigEndian>(glyph_name_index).unwrap();
+    }
+    for name in &["A.alt", "uni0041.ss01"] {
+        table.push(name.len() as u8);
+        table.extend_from_slice(name.as_bytes());
+    }
+
+    let bytes = font_with_post(table);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyphs_for_names(&["A", "space", "A.alt", "uni0041.ss01", "B", "bogus"]),
+               Ok(vec![Some(3), Some(1), Some(2), Some(0), None, None]));
+}
+
+#[test]
+fn glyph_names_resolve_with_version_1_0() {
+    let bytes = font_with_post(post_table_header(0x00010000, false));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyphs_for_names(&["A", "space", "dcroat", "bogus"]),
+               Ok(vec![Some(36), Some(3), Some(257), None]));
+
+    // Version 3.0 has no glyph names at all.
+    let bytes = font_with_post(post_table(false));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyphs_for_names(&["A", "space"]), Ok(vec![None, None]));
+}
+
+#[test]
+fn glyph_names_are_looked_up_by_glyph_id() {
+    let mut table = post_table_header(0x00020000, false);
+    table.write_u16::<BigEndian>(3).unwrap();
+    for &glyph_name_index in &[0, 259, 36] {
+        table.write_u16::<BigEndian>(glyph_name_index).unwrap();
+    }
+    for name in &["A.alt", "uni0041.ss01"] {
+        table.push(name.len() as u8);
+        table.extend_from_slice(name.as_bytes());
+    }
+
+    let bytes = font_with_post(table);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_name(0), Ok(Some(".notdef".to_owned())));
+    assert_eq!(font.glyph_name(1), Ok(Some("uni0041.ss01".to_owned())));
+    assert_eq!(font.glyph_name(2), Ok(Some("A".to_owned())));
+    assert_eq!(font.glyph_name(3), Ok(None));
+    assert_eq!(font.glyph_for_name("uni0041.ss01"), Ok(Some(1)));
+
+    let bytes = font_with_post(post_table_header(0x00010000, false));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_name(36), Ok(Some("A".to_owned())));
+}
+
+#[test]
+fn italic_angle_and_underline_come_from_post() {
+    let mut table = post_table(false);
+    (&mut table[4..8]).write_i32::<BigEndian>(-12 * 65536 - 32768).unwrap();
+    let bytes = font_with_post(table);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.italic_angle(), -12.5);
+    assert_eq!(font.underline_position(), Some(-100));
+    assert_eq!(font.underline_thickness(), Some(50));
+
+    let bytes = sfnt::sfnt(&font_tables(&[500]));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.italic_angle(), 0.0);
+    assert_eq!(font.underline_position(), None);
+}
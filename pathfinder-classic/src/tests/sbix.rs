@@ -0,0 +1,108 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{BitmapFormat, BitmapStrike, ColorBitmap, Font};
+use tables::{maxp, sbix};
+use tests::sfnt;
+
+static PNG: &[u8] = b"\x89PNG fake";
+static JPEG: &[u8] = b"\xff\xd8 fake";
+
+/// The origin, graphic type, and data of a glyph's image.
+type Image<'a> = (i16, &'a [u8; 4], &'a [u8]);
+
+/// Builds a strike out of the images of each glyph.
+fn strike(ppem: u16, images: &[Option<Image>]) -> Vec<u8> {
+    let mut data = vec![];
+    let mut offsets = vec![];
+    let header_size = 4 + 4 * (images.len() + 1);
+    for image in images {
+        offsets.push(header_size + data.len());
+        if let Some((origin, graphic_type, image_data)) = *image {
+            data.write_i16::<BigEndian>(origin).unwrap();
+            data.write_i16::<BigEndian>(-origin).unwrap();
+            data.extend_from_slice(graphic_type);
+            data.extend_from_slice(image_data);
+        }
+    }
+    offsets.push(header_size + data.len());
+
+    let mut strike = vec![];
+    strike.write_u16::<BigEndian>(ppem).unwrap();
+    strike.write_u16::<BigEndian>(72).unwrap();
+    for offset in offsets {
+        strike.write_u32::<BigEndian>(offset as u32).unwrap();
+    }
+    strike.extend_from_slice(&data);
+    strike
+}
+
+// Glyph 1 is a PNG at 20 pixels per em and a JPEG at 40. Glyph 2 duplicates glyph 1.
+fn sbix_font_bytes() -> Vec<u8> {
+    let strikes = vec![
+        strike(20, &[None, Some((1, b"png ", PNG)), Some((0, b"dupe", &[0, 1]))]),
+        strike(40, &[None, Some((2, b"jpg ", JPEG)), None]),
+    ];
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u32::<BigEndian>(strikes.len() as u32).unwrap();
+    let mut offset = 8 + 4 * strikes.len();
+    for strike in &strikes {
+        table.write_u32::<BigEndian>(offset as u32).unwrap();
+        offset += strike.len()
+    }
+    for strike in &strikes {
+        table.extend_from_slice(strike)
+    }
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    sfnt::replace_table(&mut tables, maxp::TAG, sfnt::maxp_table(3));
+    tables.push((sbix::TAG, table));
+    sfnt::sfnt(&tables)
+}
+
+fn bitmap(format: BitmapFormat, ppem: u16, origin: i16, data: &[u8]) -> ColorBitmap {
+    ColorBitmap {
+        format: format,
+        ppem: ppem,
+        origin_x: origin,
+        origin_y: -origin,
+        data: data,
+    }
+}
+
+#[test]
+fn strikes_are_enumerated() {
+    let bytes = sbix_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.color_bitmap_strikes(), Ok(vec![
+        BitmapStrike { ppem: 20, ppi: 72 },
+        BitmapStrike { ppem: 40, ppi: 72 },
+    ]));
+}
+
+#[test]
+fn color_bitmaps_come_from_the_nearest_larger_strike() {
+    let bytes = sbix_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let png = Some(bitmap(BitmapFormat::Png, 20, 1, PNG));
+    let jpeg = Some(bitmap(BitmapFormat::Jpeg, 40, 2, JPEG));
+    assert_eq!(font.glyph_color_bitmap(1, 12), Ok(png));
+    assert_eq!(font.glyph_color_bitmap(1, 20), Ok(png));
+    assert_eq!(font.glyph_color_bitmap(1, 21), Ok(jpeg));
+    assert_eq!(font.glyph_color_bitmap(1, 200), Ok(jpeg));
+
+    // Duplicates take the image and origin of the glyph they copy.
+    assert_eq!(font.glyph_color_bitmap(2, 16), Ok(png));
+    assert_eq!(font.glyph_color_bitmap(2, 32), Ok(None));
+
+    assert_eq!(font.glyph_color_bitmap(0, 16), Ok(None));
+    assert_eq!(font.glyph_color_bitmap(3, 16), Ok(None));
+}
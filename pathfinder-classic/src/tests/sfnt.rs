@@ -0,0 +1,658 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+//! Helpers to build minimal synthetic fonts in memory.
+
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use containers::ttc;
+use containers::woff;
+use features::FeatureSettings;
+use tables::{cmap, glyf, head, hhea, hmtx, loca, maxp, os_2};
+
+pub fn tag(name: &[u8; 4]) -> u32 {
+    ((name[0] as u32) << 24) | ((name[1] as u32) << 16) | ((name[2] as u32) << 8) |
+        (name[3] as u32)
+}
+
+/// Builds a `cmap` table out of `(platform ID, encoding ID, subtable)` triples.
+pub fn cmap_table(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(subtables.len() as u16).unwrap();
+
+    let mut offset = 4 + 8 * subtables.len();
+    for &(platform_id, encoding_id, ref subtable) in subtables {
+        table.write_u16::<BigEndian>(platform_id).unwrap();
+        table.write_u16::<BigEndian>(encoding_id).unwrap();
+        table.write_u32::<BigEndian>(offset as u32).unwrap();
+        offset += subtable.len();
+    }
+    for &(_, _, ref subtable) in subtables {
+        table.extend_from_slice(subtable)
+    }
+    table
+}
+
+/// Builds a format 2 subtable. The first subheader maps the single-byte codes starting at its
+/// first code, and the others map the two-byte codes with their lead byte, as
+/// `(lead byte, first code, glyph IDs)`.
+pub fn cmap_format_2(single_bytes: (u8, &[u16]), two_bytes: &[(u8, u8, &[u16])]) -> Vec<u8> {
+    let mut sub_headers = vec![(single_bytes.0, single_bytes.1)];
+    sub_headers.extend(two_bytes.iter().map(|&(_, first_code, glyph_ids)| (first_code, glyph_ids)));
+    let glyph_count: usize = sub_headers.iter().map(|&(_, glyph_ids)| glyph_ids.len()).sum();
+
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.write_u16::<BigEndian>((518 + 8 * sub_headers.len() + 2 * glyph_count) as u16).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    for byte in 0..256 {
+        let sub_header_index = two_bytes.iter().position(|&(lead_byte, _, _)| {
+            lead_byte as usize == byte
+        }).map_or(0, |index| index + 1);
+        table.write_u16::<BigEndian>(sub_header_index as u16 * 8).unwrap();
+    }
+    let mut glyph_offset = 0;
+    for (sub_header_index, &(first_code, glyph_ids)) in sub_headers.iter().enumerate() {
+        let id_range_offset = (sub_headers.len() - sub_header_index) * 8 - 6 + glyph_offset;
+        table.write_u16::<BigEndian>(first_code as u16).unwrap();
+        table.write_u16::<BigEndian>(glyph_ids.len() as u16).unwrap();
+        table.write_i16::<BigEndian>(0).unwrap();
+        table.write_u16::<BigEndian>(id_range_offset as u16).unwrap();
+        glyph_offset += 2 * glyph_ids.len();
+    }
+    for &(_, glyph_ids) in &sub_headers {
+        for &glyph_id in glyph_ids {
+            table.write_u16::<BigEndian>(glyph_id).unwrap();
+        }
+    }
+    table
+}
+
+/// Builds a format 4 subtable out of `(start code, end code, ID delta)` segments, which must be
+/// sorted. The final `0xffff` segment is appended automatically.
+pub fn cmap_format_4(segments: &[(u16, u16, i16)]) -> Vec<u8> {
+    let mut segments = segments.to_vec();
+    segments.push((0xffff, 0xffff, 1));
+    cmap_format_4_with_glyph_ids(&segments.iter().map(|&(start, end, delta)| {
+        (start, end, delta, None)
+    }).collect::<Vec<_>>(), &[])
+}
+
+/// Builds a format 4 subtable out of `(start code, end code, ID delta, glyph ID array index)`
+/// segments, used verbatim. Segments with a glyph ID array index look their glyphs up in
+/// `glyph_ids` starting at that index.
+pub fn cmap_format_4_with_glyph_ids(segments: &[(u16, u16, i16, Option<u16>)], glyph_ids: &[u16])
+                                    -> Vec<u8> {
+    let seg_count = segments.len() as u16;
+    let length = 16 + 8 * seg_count + 2 * glyph_ids.len() as u16;
+
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(4).unwrap();
+    table.write_u16::<BigEndian>(length).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(seg_count * 2).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    for &(_, end, _, _) in segments {
+        table.write_u16::<BigEndian>(end).unwrap();
+    }
+    table.write_u16::<BigEndian>(0).unwrap();
+    for &(start, _, _, _) in segments {
+        table.write_u16::<BigEndian>(start).unwrap();
+    }
+    for &(_, _, delta, _) in segments {
+        table.write_i16::<BigEndian>(delta).unwrap();
+    }
+    for (segment_index, &(_, _, _, glyph_id_index)) in segments.iter().enumerate() {
+        let id_range_offset = match glyph_id_index {
+            None => 0,
+            Some(glyph_id_index) => (seg_count - segment_index as u16 + glyph_id_index) * 2,
+        };
+        table.write_u16::<BigEndian>(id_range_offset).unwrap();
+    }
+    for &glyph_id in glyph_ids {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+/// Builds a format 0 subtable mapping each codepoint below 256 to the corresponding entry of
+/// `glyph_ids`, or to glyph 0 past its end.
+pub fn cmap_format_0(glyph_ids: &[u8]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(262).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    for index in 0..256 {
+        table.push(glyph_ids.get(index).cloned().unwrap_or(0))
+    }
+    table
+}
+
+pub fn cmap_format_6(first_code: u16, glyph_ids: &[u16]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(6).unwrap();
+    table.write_u16::<BigEndian>(10 + 2 * glyph_ids.len() as u16).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(first_code).unwrap();
+    table.write_u16::<BigEndian>(glyph_ids.len() as u16).unwrap();
+    for &glyph_id in glyph_ids {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+/// Builds a format 8 subtable whose bitmap marks the given 16-bit values as beginning 32-bit
+/// codes.
+pub fn cmap_format_8(is32: &[u16], groups: &[(u32, u32, u32)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(8).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(8208 + 12 * groups.len() as u32).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    let mut bitmap = vec![0; 8192];
+    for &value in is32 {
+        bitmap[value as usize / 8] |= 0x80 >> (value % 8)
+    }
+    table.extend_from_slice(&bitmap);
+    table.write_u32::<BigEndian>(groups.len() as u32).unwrap();
+    for &(start, end, glyph_id) in groups {
+        table.write_u32::<BigEndian>(start).unwrap();
+        table.write_u32::<BigEndian>(end).unwrap();
+        table.write_u32::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+pub fn cmap_format_10(first_code: u32, glyph_ids: &[u16]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(10).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(20 + 2 * glyph_ids.len() as u32).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(first_code).unwrap();
+    table.write_u32::<BigEndian>(glyph_ids.len() as u32).unwrap();
+    for &glyph_id in glyph_ids {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+/// Builds a format 12 subtable out of `(start code, end code, start glyph ID)` groups.
+pub fn cmap_format_12(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+    cmap_segmented_format(12, groups)
+}
+
+pub fn cmap_format_13(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+    cmap_segmented_format(13, groups)
+}
+
+fn cmap_segmented_format(format: u16, groups: &[(u32, u32, u32)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(format).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(16 + 12 * groups.len() as u32).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(groups.len() as u32).unwrap();
+    for &(start, end, glyph_id) in groups {
+        table.write_u32::<BigEndian>(start).unwrap();
+        table.write_u32::<BigEndian>(end).unwrap();
+        table.write_u32::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+/// Builds a format 14 subtable out of `(variation selector, [(base codepoint, glyph ID)])`
+/// records, with no default UVS tables.
+pub fn cmap_format_14(records: &[(u32, &[(u32, u16)])]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(14).unwrap();
+    let length_position = table.len();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(records.len() as u32).unwrap();
+    let mut non_default_uvs_offset = 10 + 11 * records.len();
+    for &(var_selector, mappings) in records {
+        table.write_u24::<BigEndian>(var_selector).unwrap();
+        table.write_u32::<BigEndian>(0).unwrap();
+        table.write_u32::<BigEndian>(non_default_uvs_offset as u32).unwrap();
+        non_default_uvs_offset += 4 + 5 * mappings.len();
+    }
+    for &(_, mappings) in records {
+        table.write_u32::<BigEndian>(mappings.len() as u32).unwrap();
+        for &(unicode_value, glyph_id) in mappings {
+            table.write_u24::<BigEndian>(unicode_value).unwrap();
+            table.write_u16::<BigEndian>(glyph_id).unwrap();
+        }
+    }
+    let length = table.len() as u32;
+    BigEndian::write_u32(&mut table[length_position..], length);
+    table
+}
+
+pub fn head_table() -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0x10000).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(0x5f0f3cf5).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(1000).unwrap();
+    table.write_i64::<BigEndian>(0).unwrap();
+    table.write_i64::<BigEndian>(0).unwrap();
+    for &bound in &[0, -200, 1000, 800] {
+        table.write_i16::<BigEndian>(bound).unwrap();
+    }
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(8).unwrap();
+    table.write_i16::<BigEndian>(2).unwrap();
+    table.write_i16::<BigEndian>(0).unwrap();
+    table.write_i16::<BigEndian>(0).unwrap();
+    table
+}
+
+pub fn hhea_table(number_of_h_metrics: u16) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_i16::<BigEndian>(800).unwrap();
+    table.write_i16::<BigEndian>(-200).unwrap();
+    table.write_i16::<BigEndian>(90).unwrap();
+    for _ in 0..12 {
+        table.write_u16::<BigEndian>(0).unwrap();
+    }
+    table.write_u16::<BigEndian>(number_of_h_metrics).unwrap();
+    table
+}
+
+pub fn hmtx_table(metrics: &[(u16, i16)]) -> Vec<u8> {
+    let mut table = vec![];
+    for &(advance_width, lsb) in metrics {
+        table.write_u16::<BigEndian>(advance_width).unwrap();
+        table.write_i16::<BigEndian>(lsb).unwrap();
+    }
+    table
+}
+
+pub fn vhea_table(number_of_long_ver_metrics: u16) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u32::<BigEndian>(0x0001_1000).unwrap();
+    table.write_i16::<BigEndian>(500).unwrap();
+    table.write_i16::<BigEndian>(-500).unwrap();
+    table.write_i16::<BigEndian>(0).unwrap();
+    for _ in 0..12 {
+        table.write_u16::<BigEndian>(0).unwrap();
+    }
+    table.write_u16::<BigEndian>(number_of_long_ver_metrics).unwrap();
+    table
+}
+
+/// Returns a version 1.0 `maxp` table declaring `num_glyphs` glyphs.
+pub fn maxp_table(num_glyphs: u16) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u32::<BigEndian>(0x00010000).unwrap();
+    table.write_u16::<BigEndian>(num_glyphs).unwrap();
+    for &limit in &[64, 4, 128, 8, 1, 0, 0, 0, 0, 256, 0, 2, 1] {
+        table.write_u16::<BigEndian>(limit).unwrap();
+    }
+    table
+}
+
+pub fn os_2_table() -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(4).unwrap();
+    for _ in 0..15 {
+        table.write_u16::<BigEndian>(0).unwrap();
+    }
+    table.extend_from_slice(&[0; 10]);
+    table.extend_from_slice(&[0; 20]);
+    for _ in 0..3 {
+        table.write_u16::<BigEndian>(0).unwrap();
+    }
+    table.write_i16::<BigEndian>(800).unwrap();
+    table.write_i16::<BigEndian>(-200).unwrap();
+    table.write_i16::<BigEndian>(90).unwrap();
+    table.extend_from_slice(&[0; 22]);
+    table
+}
+
+/// Returns the tables every font must have, using the given character map.
+pub fn required_tables(cmap: Vec<u8>) -> Vec<(u32, Vec<u8>)> {
+    vec![
+        (cmap::TAG, cmap),
+        (head::TAG, head_table()),
+        (hhea::TAG, hhea_table(1)),
+        (hmtx::TAG, hmtx_table(&[(500, 0)])),
+        (maxp::TAG, maxp_table(1)),
+        (os_2::TAG, os_2_table()),
+    ]
+}
+
+/// Replaces the table with the given tag in `tables`, such as the ones from `required_tables()`,
+/// or adds it if there isn't one.
+pub fn replace_table(tables: &mut Vec<(u32, Vec<u8>)>, tag: u32, table: Vec<u8>) {
+    tables.retain(|&(other_tag, _)| other_tag != tag);
+    tables.push((tag, table))
+}
+
+/// Builds an `.otf` file whose table directory is positioned at `base` within the final file.
+fn sfnt_at(base: usize, tables: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut tables = tables.to_vec();
+    tables.sort_by_key(|&(tag, _)| tag);
+
+    let mut font = vec![];
+    font.write_u32::<BigEndian>(0x10000).unwrap();
+    font.write_u16::<BigEndian>(tables.len() as u16).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+
+    let mut offset = base + 12 + 16 * tables.len();
+    for &(tag, ref table) in &tables {
+        font.write_u32::<BigEndian>(tag).unwrap();
+        font.write_u32::<BigEndian>(0).unwrap();
+        font.write_u32::<BigEndian>(offset as u32).unwrap();
+        font.write_u32::<BigEndian>(table.len() as u32).unwrap();
+        offset += (table.len() + 3) & !3;
+    }
+    for &(_, ref table) in &tables {
+        font.extend_from_slice(table);
+        while font.len() % 4 != 0 {
+            font.push(0)
+        }
+    }
+    font
+}
+
+/// Builds an `.otf` file out of `(tag, table)` pairs.
+pub fn sfnt(tables: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    sfnt_at(0, tables)
+}
+
+/// Builds a `.ttc` file containing one font per table list.
+pub fn ttc(fonts: &[Vec<(u32, Vec<u8>)>]) -> Vec<u8> {
+    let mut collection = vec![];
+    collection.write_u32::<BigEndian>(ttc::MAGIC_NUMBER).unwrap();
+    collection.write_u16::<BigEndian>(1).unwrap();
+    collection.write_u16::<BigEndian>(0).unwrap();
+    collection.write_u32::<BigEndian>(fonts.len() as u32).unwrap();
+
+    let mut font_data = vec![];
+    let header_size = 12 + 4 * fonts.len();
+    for tables in fonts {
+        let base = header_size + font_data.len();
+        collection.write_u32::<BigEndian>(base as u32).unwrap();
+        font_data.extend_from_slice(&sfnt_at(base, tables));
+    }
+    collection.extend_from_slice(&font_data);
+    collection
+}
+
+/// Builds a `.woff` file out of `(tag, table)` pairs, storing every table uncompressed.
+pub fn woff(tables: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut tables = tables.to_vec();
+    tables.sort_by_key(|&(tag, _)| tag);
+
+    let directory_size = 44 + 20 * tables.len();
+    let table_data_size: usize = tables.iter().map(|&(_, ref table)| (table.len() + 3) & !3).sum();
+    let total_sfnt_size = 12 + 16 * tables.len() + table_data_size;
+
+    let mut font = vec![];
+    font.write_u32::<BigEndian>(woff::MAGIC_NUMBER).unwrap();
+    font.write_u32::<BigEndian>(0x10000).unwrap();
+    font.write_u32::<BigEndian>((directory_size + table_data_size) as u32).unwrap();
+    font.write_u16::<BigEndian>(tables.len() as u16).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    font.write_u32::<BigEndian>(total_sfnt_size as u32).unwrap();
+    font.write_u16::<BigEndian>(1).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    for _ in 0..5 {
+        font.write_u32::<BigEndian>(0).unwrap();
+    }
+
+    let mut offset = directory_size;
+    for &(tag, ref table) in &tables {
+        font.write_u32::<BigEndian>(tag).unwrap();
+        font.write_u32::<BigEndian>(offset as u32).unwrap();
+        font.write_u32::<BigEndian>(table.len() as u32).unwrap();
+        font.write_u32::<BigEndian>(table.len() as u32).unwrap();
+        font.write_u32::<BigEndian>(0).unwrap();
+        offset += (table.len() + 3) & !3;
+    }
+    for &(_, ref table) in &tables {
+        font.extend_from_slice(table);
+        while font.len() % 4 != 0 {
+            font.push(0)
+        }
+    }
+    font
+}
+
+/// Builds a `name` table out of `(platform ID, encoding ID, language ID, name ID, string)`
+/// records, with the strings already encoded.
+pub fn name_table(records: &[(u16, u16, u16, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(records.len() as u16).unwrap();
+    table.write_u16::<BigEndian>(6 + 12 * records.len() as u16).unwrap();
+
+    let mut offset = 0;
+    for &(platform_id, encoding_id, language_id, name_id, ref string) in records {
+        table.write_u16::<BigEndian>(platform_id).unwrap();
+        table.write_u16::<BigEndian>(encoding_id).unwrap();
+        table.write_u16::<BigEndian>(language_id).unwrap();
+        table.write_u16::<BigEndian>(name_id).unwrap();
+        table.write_u16::<BigEndian>(string.len() as u16).unwrap();
+        table.write_u16::<BigEndian>(offset).unwrap();
+        offset += string.len() as u16;
+    }
+    for &(_, _, _, _, ref string) in records {
+        table.extend_from_slice(string)
+    }
+    table
+}
+
+/// Encodes a string as UTF-16BE, as used by most `name` records.
+pub fn utf16_be(string: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    for unit in string.encode_utf16() {
+        bytes.write_u16::<BigEndian>(unit).unwrap();
+    }
+    bytes
+}
+
+/// Builds a simple `glyf` glyph out of contours made only of on-curve points.
+pub fn glyf_simple_glyph(contours: &[&[(i16, i16)]]) -> Vec<u8> {
+    let points: Vec<(i16, i16)> = contours.iter().flat_map(|contour| contour.iter().cloned())
+                                                .collect();
+
+    let mut glyph = vec![];
+    glyph.write_i16::<BigEndian>(contours.len() as i16).unwrap();
+    glyph.write_i16::<BigEndian>(points.iter().map(|point| point.0).min().unwrap_or(0)).unwrap();
+    glyph.write_i16::<BigEndian>(points.iter().map(|point| point.1).min().unwrap_or(0)).unwrap();
+    glyph.write_i16::<BigEndian>(points.iter().map(|point| point.0).max().unwrap_or(0)).unwrap();
+    glyph.write_i16::<BigEndian>(points.iter().map(|point| point.1).max().unwrap_or(0)).unwrap();
+
+    let mut end_point = -1;
+    for contour in contours {
+        end_point += contour.len() as i16;
+        glyph.write_u16::<BigEndian>(end_point as u16).unwrap();
+    }
+    glyph.write_u16::<BigEndian>(0).unwrap();
+
+    // Every point is on the curve, with 16-bit coordinate deltas.
+    for _ in &points {
+        glyph.push(1)
+    }
+    let mut last = (0, 0);
+    for point in &points {
+        glyph.write_i16::<BigEndian>(point.0 - last.0).unwrap();
+        last.0 = point.0;
+    }
+    for point in &points {
+        glyph.write_i16::<BigEndian>(point.1 - last.1).unwrap();
+        last.1 = point.1;
+    }
+    glyph
+}
+
+/// Builds `glyf` and short-format `loca` tables out of the given glyphs, in glyph ID order.
+pub fn glyf_and_loca_tables(glyphs: &[Vec<u8>]) -> (Vec<u8>, Vec<u8>) {
+    let (mut glyf, mut loca) = (vec![], vec![]);
+    for glyph in glyphs {
+        loca.write_u16::<BigEndian>((glyf.len() / 2) as u16).unwrap();
+        glyf.extend_from_slice(glyph);
+        if glyf.len() % 2 != 0 {
+            glyf.push(0)
+        }
+    }
+    loca.write_u16::<BigEndian>((glyf.len() / 2) as u16).unwrap();
+    (glyf, loca)
+}
+
+/// Builds an `fvar` table with the given axis tags and `(subfamily name ID, coordinates,
+/// PostScript name ID)` instances. Each instance must have one coordinate per axis.
+pub fn fvar_table(axis_tags: &[u32], instances: &[(u16, Vec<f32>, u16)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(16).unwrap();
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.write_u16::<BigEndian>(axis_tags.len() as u16).unwrap();
+    table.write_u16::<BigEndian>(20).unwrap();
+    table.write_u16::<BigEndian>(instances.len() as u16).unwrap();
+    table.write_u16::<BigEndian>(6 + 4 * axis_tags.len() as u16).unwrap();
+    for &axis_tag in axis_tags {
+        table.write_u32::<BigEndian>(axis_tag).unwrap();
+        table.write_i32::<BigEndian>(100 << 16).unwrap();
+        table.write_i32::<BigEndian>(400 << 16).unwrap();
+        table.write_i32::<BigEndian>(900 << 16).unwrap();
+        table.write_u16::<BigEndian>(0).unwrap();
+        table.write_u16::<BigEndian>(256).unwrap();
+    }
+    for &(name_id, ref coords, postscript_name_id) in instances {
+        table.write_u16::<BigEndian>(name_id).unwrap();
+        table.write_u16::<BigEndian>(0).unwrap();
+        for &coord in coords {
+            table.write_i32::<BigEndian>((coord * 65536.0) as i32).unwrap();
+        }
+        table.write_u16::<BigEndian>(postscript_name_id).unwrap();
+    }
+    table
+}
+
+/// Builds a `GPOS` or `GSUB` table with a single `DFLT` script whose default language system
+/// enables every feature. Features are `(tag, lookup indices)` pairs, and lookups are
+/// `(lookup type, subtables)` pairs.
+pub fn layout_table(features: &[(u32, &[u16])], lookups: &[(u16, Vec<Vec<u8>>)]) -> Vec<u8> {
+    let mut script_list = vec![];
+    script_list.write_u16::<BigEndian>(1).unwrap();
+    script_list.write_u32::<BigEndian>(tag(b"DFLT")).unwrap();
+    script_list.write_u16::<BigEndian>(8).unwrap();
+    script_list.write_u16::<BigEndian>(4).unwrap();
+    script_list.write_u16::<BigEndian>(0).unwrap();
+    script_list.write_u16::<BigEndian>(0).unwrap();
+    script_list.write_u16::<BigEndian>(0xffff).unwrap();
+    script_list.write_u16::<BigEndian>(features.len() as u16).unwrap();
+    for feature_index in 0..features.len() {
+        script_list.write_u16::<BigEndian>(feature_index as u16).unwrap();
+    }
+
+    let mut feature_list = vec![];
+    feature_list.write_u16::<BigEndian>(features.len() as u16).unwrap();
+    let mut offset = 2 + 6 * features.len();
+    for &(feature_tag, lookup_indices) in features {
+        feature_list.write_u32::<BigEndian>(feature_tag).unwrap();
+        feature_list.write_u16::<BigEndian>(offset as u16).unwrap();
+        offset += 4 + 2 * lookup_indices.len();
+    }
+    for &(_, lookup_indices) in features {
+        feature_list.write_u16::<BigEndian>(0).unwrap();
+        feature_list.write_u16::<BigEndian>(lookup_indices.len() as u16).unwrap();
+        for &lookup_index in lookup_indices {
+            feature_list.write_u16::<BigEndian>(lookup_index).unwrap();
+        }
+    }
+
+    let mut lookup_tables = vec![];
+    for &(lookup_type, ref subtables) in lookups {
+        let mut lookup = vec![];
+        lookup.write_u16::<BigEndian>(lookup_type).unwrap();
+        lookup.write_u16::<BigEndian>(0).unwrap();
+        lookup.write_u16::<BigEndian>(subtables.len() as u16).unwrap();
+        let mut offset = 6 + 2 * subtables.len();
+        for subtable in subtables {
+            lookup.write_u16::<BigEndian>(offset as u16).unwrap();
+            offset += subtable.len();
+        }
+        for subtable in subtables {
+            lookup.extend_from_slice(subtable);
+        }
+        lookup_tables.push(lookup);
+    }
+    let mut lookup_list = vec![];
+    lookup_list.write_u16::<BigEndian>(lookups.len() as u16).unwrap();
+    let mut offset = 2 + 2 * lookups.len();
+    for lookup in &lookup_tables {
+        lookup_list.write_u16::<BigEndian>(offset as u16).unwrap();
+        offset += lookup.len();
+    }
+    for lookup in &lookup_tables {
+        lookup_list.extend_from_slice(lookup);
+    }
+
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(10).unwrap();
+    table.write_u16::<BigEndian>((10 + script_list.len()) as u16).unwrap();
+    table.write_u16::<BigEndian>((10 + script_list.len() + feature_list.len()) as u16).unwrap();
+    table.extend_from_slice(&script_list);
+    table.extend_from_slice(&feature_list);
+    table.extend_from_slice(&lookup_list);
+    table
+}
+
+/// Returns feature settings enabling the features with the given tags.
+pub fn feature_settings(tags: &[u32]) -> FeatureSettings {
+    let mut settings = FeatureSettings::new();
+    for &tag in tags {
+        settings.set(tag, 1)
+    }
+    settings
+}
+
+/// Wraps a lookup subtable of the given type in an extension subtable.
+pub fn layout_extension_subtable(lookup_type: u16, subtable: &[u8]) -> Vec<u8> {
+    let mut extension = vec![];
+    extension.write_u16::<BigEndian>(1).unwrap();
+    extension.write_u16::<BigEndian>(lookup_type).unwrap();
+    extension.write_u32::<BigEndian>(8).unwrap();
+    extension.extend_from_slice(subtable);
+    extension
+}
+
+/// Builds a format 1 coverage table out of sorted glyph IDs.
+pub fn coverage_format_1(glyph_ids: &[u16]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(glyph_ids.len() as u16).unwrap();
+    for &glyph_id in glyph_ids {
+        table.write_u16::<BigEndian>(glyph_id).unwrap();
+    }
+    table
+}
+
+/// Builds a format 2 class definition table out of sorted `(start, end, class)` ranges.
+pub fn class_def_format_2(ranges: &[(u16, u16, u16)]) -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(2).unwrap();
+    table.write_u16::<BigEndian>(ranges.len() as u16).unwrap();
+    for &(start, end, class) in ranges {
+        table.write_u16::<BigEndian>(start).unwrap();
+        table.write_u16::<BigEndian>(end).unwrap();
+        table.write_u16::<BigEndian>(class).unwrap();
+    }
+    table
+}
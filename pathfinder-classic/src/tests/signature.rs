@@ -0,0 +1,36 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use font::Font;
+use tables::dsig;
+use tests::sfnt;
+
+#[test]
+fn font_with_dsig_has_signature() {
+    // A version 1 `DSIG` table with no signatures, as commonly left behind by font tools.
+    let dsig_table = vec![0, 0, 0, 1, 0, 0, 0, 0];
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((dsig::TAG, dsig_table.clone()));
+    let bytes = sfnt::sfnt(&tables);
+
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(font.has_signature());
+    assert_eq!(font.signature_bytes(), Some(&dsig_table[..]));
+    assert_eq!(font.table_bytes(sfnt::tag(b"DSIG")), Some(&dsig_table[..]));
+}
+
+#[test]
+fn font_without_dsig_has_no_signature() {
+    let bytes = sfnt::sfnt(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ])));
+
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(!font.has_signature());
+    assert_eq!(font.signature_bytes(), None);
+    assert!(font.table_bytes(sfnt::tag(b"head")).is_some());
+}
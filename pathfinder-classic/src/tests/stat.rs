@@ -0,0 +1,176 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::{AXIS_VALUE_ELIDABLE_NAME, AxisValue, AxisValueFlags, DesignAxis, Font};
+use tables::{name, stat};
+use tests::sfnt;
+
+fn write_fixed(table: &mut Vec<u8>, value: f32) {
+    table.write_i32::<BigEndian>((value * 65536.0) as i32).unwrap();
+}
+
+/// A weight and a width axis, with values for Regular (elidable) and Bold weights, Condensed
+/// width, Bold Condensed on both axes, and an unnamed light weight.
+fn stat_table() -> Vec<u8> {
+    let mut values = vec![];
+
+    let mut value = vec![];
+    for &field in &[1, 0, 2, 258] {
+        value.write_u16::<BigEndian>(field).unwrap();
+    }
+    write_fixed(&mut value, 400.0);
+    values.push(value);
+
+    let mut value = vec![];
+    for &field in &[2, 0, 0, 259] {
+        value.write_u16::<BigEndian>(field).unwrap();
+    }
+    for &position in &[700.0, 600.0, 800.0] {
+        write_fixed(&mut value, position);
+    }
+    values.push(value);
+
+    let mut value = vec![];
+    for &field in &[3, 1, 0, 260] {
+        value.write_u16::<BigEndian>(field).unwrap();
+    }
+    write_fixed(&mut value, 75.0);
+    write_fixed(&mut value, 100.0);
+    values.push(value);
+
+    let mut value = vec![];
+    for &field in &[4, 2, 0, 261] {
+        value.write_u16::<BigEndian>(field).unwrap();
+    }
+    for &(axis_index, position) in &[(0, 700.0), (1, 75.0)] {
+        value.write_u16::<BigEndian>(axis_index).unwrap();
+        write_fixed(&mut value, position);
+    }
+    values.push(value);
+
+    let mut value = vec![];
+    for &field in &[1, 0, 0, 262] {
+        value.write_u16::<BigEndian>(field).unwrap();
+    }
+    write_fixed(&mut value, 300.0);
+    values.push(value);
+
+    let mut table = vec![];
+    for &field in &[1, 1, 8, 2] {
+        table.write_u16::<BigEndian>(field).unwrap();
+    }
+    table.write_u32::<BigEndian>(20).unwrap();
+    table.write_u16::<BigEndian>(values.len() as u16).unwrap();
+    table.write_u32::<BigEndian>(36).unwrap();
+    table.write_u16::<BigEndian>(270).unwrap();
+
+    for &(tag, name_id, ordering) in &[(sfnt::tag(b"wght"), 256, 0), (sfnt::tag(b"wdth"), 257, 1)] {
+        table.write_u32::<BigEndian>(tag).unwrap();
+        table.write_u16::<BigEndian>(name_id).unwrap();
+        table.write_u16::<BigEndian>(ordering).unwrap();
+    }
+
+    let mut offset = 2 * values.len();
+    for value in &values {
+        table.write_u16::<BigEndian>(offset as u16).unwrap();
+        offset += value.len();
+    }
+    for value in &values {
+        table.extend_from_slice(value);
+    }
+    table
+}
+
+fn font_bytes(stat_table: Option<Vec<u8>>) -> Vec<u8> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some(stat_table) = stat_table {
+        tables.push((stat::TAG, stat_table));
+    }
+    tables.push((name::TAG, sfnt::name_table(&[
+        (3, 1, 0x409, 256, sfnt::utf16_be("Weight")),
+        (3, 1, 0x409, 257, sfnt::utf16_be("Width")),
+        (3, 1, 0x409, 258, sfnt::utf16_be("Regular")),
+        (3, 1, 0x409, 259, sfnt::utf16_be("Bold")),
+        (3, 1, 0x409, 260, sfnt::utf16_be("Condensed")),
+        (3, 1, 0x409, 261, sfnt::utf16_be("Bold Condensed")),
+        (3, 1, 0x409, 270, sfnt::utf16_be("Normal")),
+    ])));
+    sfnt::sfnt(&tables)
+}
+
+#[test]
+fn design_axes_and_axis_values() {
+    let bytes = font_bytes(Some(stat_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.design_axes(), Ok(vec![
+        DesignAxis { tag: sfnt::tag(b"wght"), name_id: 256, ordering: 0 },
+        DesignAxis { tag: sfnt::tag(b"wdth"), name_id: 257, ordering: 1 },
+    ]));
+    assert_eq!(font.axis_values(), Ok(vec![
+        AxisValue {
+            name_id: 258,
+            flags: AXIS_VALUE_ELIDABLE_NAME,
+            locations: vec![(0, 400.0)],
+            range: None,
+            linked_value: None,
+        },
+        AxisValue {
+            name_id: 259,
+            flags: AxisValueFlags::empty(),
+            locations: vec![(0, 700.0)],
+            range: Some((600.0, 800.0)),
+            linked_value: None,
+        },
+        AxisValue {
+            name_id: 260,
+            flags: AxisValueFlags::empty(),
+            locations: vec![(1, 75.0)],
+            range: None,
+            linked_value: Some(100.0),
+        },
+        AxisValue {
+            name_id: 261,
+            flags: AxisValueFlags::empty(),
+            locations: vec![(0, 700.0), (1, 75.0)],
+            range: None,
+            linked_value: None,
+        },
+        AxisValue {
+            name_id: 262,
+            flags: AxisValueFlags::empty(),
+            locations: vec![(0, 300.0)],
+            range: None,
+            linked_value: None,
+        },
+    ]));
+}
+
+#[test]
+fn axis_value_names() {
+    let bytes = font_bytes(Some(stat_table()));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(font.axis_value_names(sfnt::tag(b"wght")),
+               Ok(vec![(400.0, "Regular".to_owned()), (700.0, "Bold".to_owned())]));
+    assert_eq!(font.axis_value_names(sfnt::tag(b"wdth")),
+               Ok(vec![(75.0, "Condensed".to_owned())]));
+    assert_eq!(font.axis_value_names(sfnt::tag(b"opsz")), Ok(vec![]));
+    assert_eq!(font.elided_fallback_name(), Some("Normal".to_owned()));
+}
+
+#[test]
+fn fonts_without_stat_have_no_style_attributes() {
+    let bytes = font_bytes(None);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.design_axes(), Ok(vec![]));
+    assert_eq!(font.axis_values(), Ok(vec![]));
+    assert_eq!(font.axis_value_names(sfnt::tag(b"wght")), Ok(vec![]));
+    assert_eq!(font.elided_fallback_name(), None);
+}
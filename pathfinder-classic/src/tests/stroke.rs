@@ -0,0 +1,62 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use euclid::Point2D;
+use font::Font;
+use stroke::{self, Path, StrokeStyle};
+use tables::{glyf, loca};
+use tests::sfnt;
+
+fn square_glyph_font() -> Vec<u8> {
+    let square = sfnt::glyf_simple_glyph(&[&[(100, 0), (600, 0), (600, 500), (100, 500)]]);
+    let (glyf_table, loca_table) = sfnt::glyf_and_loca_tables(&[vec![], square]);
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((glyf::TAG, glyf_table));
+    tables.push((loca::TAG, loca_table));
+    sfnt::sfnt(&tables)
+}
+
+fn points(points: &[(f32, f32)]) -> Vec<Point2D<f32>> {
+    points.iter().map(|&(x, y)| Point2D::new(x, y)).collect()
+}
+
+#[test]
+fn zero_width_stroke_is_empty_and_fill_is_the_outline() {
+    let bytes = square_glyph_font();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let (fill, stroke) = font.glyph_fill_and_stroke(1, StrokeStyle::new(0.0)).unwrap();
+    assert!(stroke.is_empty());
+    assert_eq!(fill, Path::from_glyph(&font, 1).unwrap());
+    assert_eq!(fill.contours, [
+        points(&[(100.0, 0.0), (600.0, 0.0), (600.0, 500.0), (100.0, 500.0)]),
+    ]);
+
+    let (fill, stroke) = font.glyph_fill_and_stroke(0, StrokeStyle::new(10.0)).unwrap();
+    assert!(fill.is_empty() && stroke.is_empty());
+}
+
+#[test]
+fn stroke_of_a_square_is_a_ring() {
+    let path = Path {
+        contours: vec![points(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)])],
+    };
+
+    let stroke = stroke::stroke_to_fill(&path, &StrokeStyle::new(2.0));
+    let expected = [
+        points(&[(1.0, 1.0), (9.0, 1.0), (9.0, 9.0), (1.0, 9.0)]),
+        points(&[(-1.0, 11.0), (11.0, 11.0), (11.0, -1.0), (-1.0, -1.0)]),
+    ];
+    assert_eq!(stroke.contours.len(), expected.len());
+    for (contour, expected_contour) in stroke.contours.iter().zip(expected.iter()) {
+        assert_eq!(contour.len(), expected_contour.len());
+        for (point, expected_point) in contour.iter().zip(expected_contour.iter()) {
+            assert!((point.x - expected_point.x).abs() < 0.001 &&
+                    (point.y - expected_point.y).abs() < 0.001,
+                    "{:?} != {:?}", point, expected_point);
+        }
+    }
+}
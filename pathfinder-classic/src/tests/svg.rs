@@ -0,0 +1,59 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use font::Font;
+use tables::svg;
+use tests::sfnt;
+
+static DOCUMENT_A: &str = "<svg xmlns=\"http://www.w3.org/2000/svg\"><g id=\"glyph4\"/></svg>";
+static DOCUMENT_B: &[u8] = b"\x1f\x8b\x08\x00\x00\x00\x00\x00";
+
+// Glyphs 2 to 4 share the first document, and glyph 9 has a compressed one.
+fn svg_table() -> Vec<u8> {
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(10).unwrap();
+    table.write_u32::<BigEndian>(0).unwrap();
+
+    let documents_offset = 2 + 12 * 2;
+    table.write_u16::<BigEndian>(2).unwrap();
+    for &(start_glyph_id, end_glyph_id, offset, length) in &[
+        (2, 4, documents_offset, DOCUMENT_A.len()),
+        (9, 9, documents_offset + DOCUMENT_A.len(), DOCUMENT_B.len()),
+    ] {
+        table.write_u16::<BigEndian>(start_glyph_id).unwrap();
+        table.write_u16::<BigEndian>(end_glyph_id).unwrap();
+        table.write_u32::<BigEndian>(offset as u32).unwrap();
+        table.write_u32::<BigEndian>(length as u32).unwrap();
+    }
+    table.extend_from_slice(DOCUMENT_A.as_bytes());
+    table.extend_from_slice(DOCUMENT_B);
+    table
+}
+
+#[test]
+fn svg_documents_are_found_by_glyph_range() {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((svg::TAG, svg_table()));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let document = font.svg_document(4).unwrap().unwrap();
+    assert_eq!(document.bytes, DOCUMENT_A.as_bytes());
+    assert_eq!((document.start_glyph_id, document.end_glyph_id), (2, 4));
+    assert!(!document.is_compressed());
+    assert_eq!(document.decompress(), Ok(DOCUMENT_A.as_bytes().to_vec()));
+    assert_eq!(font.svg_document(2).unwrap(), Some(document));
+
+    let document = font.svg_document(9).unwrap().unwrap();
+    assert_eq!(document.bytes, DOCUMENT_B);
+    assert!(document.is_compressed());
+
+    assert_eq!(font.svg_document(1), Ok(None));
+    assert_eq!(font.svg_document(5), Ok(None));
+    assert_eq!(font.svg_document(10), Ok(None));
+}
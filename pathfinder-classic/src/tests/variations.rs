@@ -0,0 +1,287 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use euclid::Point2D;
+use font::{Font, Point};
+use tables::{fvar, glyf, gvar, loca};
+use tests::sfnt;
+
+const WGHT: u32 = ((b'w' as u32) << 24) | ((b'g' as u32) << 16) | ((b'h' as u32) << 8) |
+    (b't' as u32);
+
+/// A tuple variation: its tuple index and flags, its embedded peak and intermediate region if it
+/// has them, its private point numbers if it has them, and its X and Y deltas.
+struct Tuple {
+    tuple_index: u16,
+    peak: Option<f32>,
+    intermediate: Option<(f32, f32)>,
+    points: Option<Vec<u8>>,
+    x_deltas: Vec<i16>,
+    y_deltas: Vec<i16>,
+}
+
+fn f2dot14(value: f32) -> i16 {
+    (value * 16384.0) as i16
+}
+
+fn packed_point_numbers(points: &[u8]) -> Vec<u8> {
+    let mut data = vec![points.len() as u8, points.len() as u8 - 1];
+    let mut last = 0;
+    for &point in points {
+        data.push(point - last);
+        last = point
+    }
+    data
+}
+
+fn packed_deltas(deltas: &[i16]) -> Vec<u8> {
+    let mut data = vec![];
+    for run in deltas.chunks(64) {
+        data.push(0x40 | (run.len() as u8 - 1));
+        for &delta in run {
+            data.write_i16::<BigEndian>(delta).unwrap();
+        }
+    }
+    data
+}
+
+fn glyph_variation_data(shared_points: Option<&[u8]>, tuples: &[Tuple]) -> Vec<u8> {
+    let (mut headers, mut serialized_data) = (vec![], vec![]);
+    if let Some(shared_points) = shared_points {
+        serialized_data.extend_from_slice(&packed_point_numbers(shared_points));
+    }
+    for tuple in tuples {
+        let mut data = vec![];
+        if let Some(ref points) = tuple.points {
+            data.extend_from_slice(&packed_point_numbers(points));
+        }
+        data.extend_from_slice(&packed_deltas(&tuple.x_deltas));
+        data.extend_from_slice(&packed_deltas(&tuple.y_deltas));
+
+        headers.write_u16::<BigEndian>(data.len() as u16).unwrap();
+        headers.write_u16::<BigEndian>(tuple.tuple_index).unwrap();
+        if let Some(peak) = tuple.peak {
+            headers.write_i16::<BigEndian>(f2dot14(peak)).unwrap();
+        }
+        if let Some((start, end)) = tuple.intermediate {
+            headers.write_i16::<BigEndian>(f2dot14(start)).unwrap();
+            headers.write_i16::<BigEndian>(f2dot14(end)).unwrap();
+        }
+        serialized_data.extend_from_slice(&data);
+    }
+
+    let mut glyph_data = vec![];
+    let shared_point_numbers = if shared_points.is_some() { 0x8000 } else { 0 };
+    glyph_data.write_u16::<BigEndian>(shared_point_numbers | tuples.len() as u16).unwrap();
+    glyph_data.write_u16::<BigEndian>(4 + headers.len() as u16).unwrap();
+    glyph_data.extend_from_slice(&headers);
+    glyph_data.extend_from_slice(&serialized_data);
+    glyph_data
+}
+
+/// Builds a `gvar` table for one axis, whose one shared tuple peaks at -1.
+fn gvar_table(glyphs: &[Vec<u8>]) -> Vec<u8> {
+    let data_offset = 20 + 2 * (glyphs.len() + 1) + 2;
+    let mut table = vec![];
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u16::<BigEndian>(1).unwrap();
+    table.write_u32::<BigEndian>(data_offset as u32 - 2).unwrap();
+    table.write_u16::<BigEndian>(glyphs.len() as u16).unwrap();
+    table.write_u16::<BigEndian>(0).unwrap();
+    table.write_u32::<BigEndian>(data_offset as u32).unwrap();
+
+    let mut data = vec![];
+    for glyph in glyphs {
+        table.write_u16::<BigEndian>((data.len() / 2) as u16).unwrap();
+        data.extend_from_slice(glyph);
+        if data.len() % 2 != 0 {
+            data.push(0)
+        }
+    }
+    table.write_u16::<BigEndian>((data.len() / 2) as u16).unwrap();
+    table.write_i16::<BigEndian>(f2dot14(-1.0)).unwrap();
+    table.extend_from_slice(&data);
+    table
+}
+
+// Glyph 1 widens as the weight increases, and its first contour moves in the other direction as
+// the weight decreases. Glyph 2 has one tuple that touches only some of its points and another
+// with an intermediate region. Glyph 3 is glyph 2 moved right by 1000 units, and by 100 more at
+// the heaviest weight.
+fn variable_font_bytes() -> Vec<u8> {
+    let box_glyph = sfnt::glyf_simple_glyph(&[
+        &[(0, 0), (500, 0), (500, 700), (0, 700)],
+        &[(100, 100), (100, 600), (400, 600)],
+    ]);
+    let pentagon_glyph = sfnt::glyf_simple_glyph(&[
+        &[(0, 0), (200, 0), (200, 100), (100, 200), (0, 100)],
+    ]);
+    let mut composite_glyph = vec![];
+    composite_glyph.write_i16::<BigEndian>(-1).unwrap();
+    for _ in 0..4 {
+        composite_glyph.write_i16::<BigEndian>(0).unwrap();
+    }
+    // ARG_1_AND_2_ARE_WORDS | ARGS_ARE_XY_VALUES.
+    composite_glyph.write_u16::<BigEndian>(0x0003).unwrap();
+    composite_glyph.write_u16::<BigEndian>(2).unwrap();
+    composite_glyph.write_i16::<BigEndian>(1000).unwrap();
+    composite_glyph.write_i16::<BigEndian>(0).unwrap();
+    let (glyf_table, loca_table) = sfnt::glyf_and_loca_tables(&[
+        vec![],
+        box_glyph,
+        pentagon_glyph,
+        composite_glyph,
+    ]);
+
+    let gvar_table = gvar_table(&[
+        vec![],
+        glyph_variation_data(None, &[
+            Tuple {
+                tuple_index: 0x8000,
+                peak: Some(1.0),
+                intermediate: None,
+                points: None,
+                x_deltas: vec![0, 100, 100, 0, 10, 10, 10, 0, 0, 0, 0],
+                y_deltas: vec![0; 11],
+            },
+            Tuple {
+                tuple_index: 0x2000,
+                peak: None,
+                intermediate: None,
+                points: Some(vec![1, 2]),
+                x_deltas: vec![-50, -50],
+                y_deltas: vec![-20, 40],
+            },
+        ]),
+        glyph_variation_data(Some(&[0, 2]), &[
+            Tuple {
+                tuple_index: 0x8000,
+                peak: Some(1.0),
+                intermediate: None,
+                points: None,
+                x_deltas: vec![0, 100],
+                y_deltas: vec![0, 50],
+            },
+            Tuple {
+                tuple_index: 0x2000 | 0x8000 | 0x4000,
+                peak: Some(0.5),
+                intermediate: Some((0.0, 1.0)),
+                points: Some(vec![0, 1, 2, 3, 4]),
+                x_deltas: vec![10; 5],
+                y_deltas: vec![0; 5],
+            },
+        ]),
+        glyph_variation_data(None, &[
+            Tuple {
+                tuple_index: 0x8000,
+                peak: Some(1.0),
+                intermediate: None,
+                points: None,
+                x_deltas: vec![100, 0, 0, 0, 0],
+                y_deltas: vec![0; 5],
+            },
+        ]),
+    ]);
+
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    tables.push((glyf::TAG, glyf_table));
+    tables.push((loca::TAG, loca_table));
+    tables.push((fvar::TAG, sfnt::fvar_table(&[WGHT], &[(257, vec![400.0], 0xffff)])));
+    tables.push((gvar::TAG, gvar_table));
+    sfnt::sfnt(&tables)
+}
+
+fn positions(contours: &[Vec<Point>]) -> Vec<Vec<(i16, i16)>> {
+    contours.iter().map(|contour| {
+        contour.iter().map(|point| (point.position.x, point.position.y)).collect()
+    }).collect()
+}
+
+#[test]
+fn default_location_matches_the_default_instance() {
+    let bytes = variable_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    for glyph_id in 1..4 {
+        let contours = font.glyph_contours(glyph_id).unwrap();
+        assert_eq!(font.glyph_contours_at(glyph_id, &[0.0]).unwrap(), contours);
+        assert_eq!(font.glyph_contours_at(glyph_id, &[]).unwrap(), contours);
+    }
+
+    let contours = font.glyph_contours(1).unwrap();
+    assert_eq!(contours.len(), 2);
+    // Each contour is closed by repeating its first point.
+    assert_eq!(contours.iter().map(|contour| contour.len()).collect::<Vec<_>>(), [5, 4]);
+    assert_eq!(contours[0][1].position, Point2D::new(500, 0));
+}
+
+#[test]
+fn deltas_are_scaled_by_the_distance_to_their_peaks() {
+    let bytes = variable_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(positions(&font.glyph_contours_at(1, &[1.0]).unwrap()), [
+        vec![(0, 0), (600, 0), (600, 700), (0, 700), (0, 0)],
+        vec![(110, 100), (110, 600), (410, 600), (110, 100)],
+    ]);
+    assert_eq!(positions(&font.glyph_contours_at(1, &[0.5]).unwrap()), [
+        vec![(0, 0), (550, 0), (550, 700), (0, 700), (0, 0)],
+        vec![(105, 100), (105, 600), (405, 600), (105, 100)],
+    ]);
+
+    // Coordinates past the ends of the axis are clamped.
+    assert_eq!(font.glyph_contours_at(1, &[2.0]), font.glyph_contours_at(1, &[1.0]));
+}
+
+#[test]
+fn untouched_points_follow_their_neighbors() {
+    let bytes = variable_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    // The first contour of glyph 1 is moved by a shared tuple, and its two untouched points take
+    // the deltas of the touched points nearest them on each axis. The second contour isn't
+    // touched at all.
+    assert_eq!(positions(&font.glyph_contours_at(1, &[-0.5]).unwrap()), [
+        vec![(-25, -10), (475, -10), (475, 720), (-25, 720), (-25, -10)],
+        vec![(100, 100), (100, 600), (400, 600), (100, 100)],
+    ]);
+
+    // Point 3 of glyph 2 lies between the touched points on the X axis, so its delta there is
+    // interpolated.
+    assert_eq!(positions(&font.glyph_contours_at(2, &[1.0]).unwrap()), [
+        vec![(0, 0), (300, 0), (300, 150), (150, 250), (0, 150), (0, 0)],
+    ]);
+}
+
+#[test]
+fn intermediate_regions_peak_inside_the_axis() {
+    let bytes = variable_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(positions(&font.glyph_contours_at(2, &[0.5]).unwrap()), [
+        vec![(10, 0), (260, 0), (260, 125), (135, 225), (10, 125), (10, 0)],
+    ]);
+}
+
+#[test]
+fn component_offsets_vary() {
+    let bytes = variable_font_bytes();
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    assert_eq!(positions(&font.glyph_contours(3).unwrap()), [
+        vec![(1000, 0), (1200, 0), (1200, 100), (1100, 200), (1000, 100), (1000, 0)],
+    ]);
+    assert_eq!(positions(&font.glyph_contours_at(3, &[1.0]).unwrap()), [
+        vec![(1100, 0), (1400, 0), (1400, 150), (1250, 250), (1100, 150), (1100, 0)],
+    ]);
+}
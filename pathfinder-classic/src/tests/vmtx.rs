@@ -0,0 +1,79 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, WriteBytesExt};
+use error::FontError;
+use font::Font;
+use tables::{vhea, vmtx, vorg};
+use tests::sfnt;
+
+fn font_tables(vertical_metrics: Option<(u16, Vec<u8>)>) -> Vec<(u32, Vec<u8>)> {
+    let mut tables = sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[])),
+    ]));
+    if let Some((number_of_long_ver_metrics, vmtx_table)) = vertical_metrics {
+        tables.push((vhea::TAG, sfnt::vhea_table(number_of_long_ver_metrics)));
+        tables.push((vmtx::TAG, vmtx_table));
+    }
+    tables
+}
+
+#[test]
+fn trailing_glyphs_share_the_last_advance_height() {
+    // `vmtx` records have the same layout as `hmtx` ones.
+    let mut vmtx_table = sfnt::hmtx_table(&[(1000, 120), (1000, 80)]);
+    vmtx_table.write_i16::<BigEndian>(300).unwrap();
+    let bytes = sfnt::sfnt(&font_tables(Some((2, vmtx_table))));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(font.has_vertical_metrics());
+
+    let advance_heights: Vec<_> = (0..3).map(|glyph_id| font.advance_height(glyph_id)).collect();
+    assert_eq!(advance_heights, [Ok(1000), Ok(1000), Ok(1000)]);
+    let tsbs: Vec<_> = (0..3).map(|glyph_id| font.top_side_bearing(glyph_id)).collect();
+    assert_eq!(tsbs, [Ok(120), Ok(80), Ok(300)]);
+
+    let metrics = font.vertical_metrics_for_glyph(2).unwrap();
+    assert_eq!((metrics.advance_height, metrics.tsb), (1000, 300));
+    assert_eq!(font.top_side_bearing(3), Err(FontError::UnexpectedEof));
+}
+
+#[test]
+fn fonts_without_vertical_metrics_report_a_missing_table() {
+    let bytes = sfnt::sfnt(&font_tables(None));
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(!font.has_vertical_metrics());
+    assert_eq!(font.advance_height(0), Err(FontError::RequiredTableMissing));
+    assert_eq!(font.vertical_metrics_for_glyph(0).map(|metrics| metrics.tsb),
+               Err(FontError::RequiredTableMissing));
+}
+
+#[test]
+fn vertical_origins_fall_back_to_the_default() {
+    let mut vorg_table = vec![];
+    for &value in &[1, 0] {
+        vorg_table.write_u16::<BigEndian>(value).unwrap();
+    }
+    vorg_table.write_i16::<BigEndian>(880).unwrap();
+    vorg_table.write_u16::<BigEndian>(3).unwrap();
+    for &(glyph_id, vert_origin_y) in &[(2, 900), (5, -40), (9, 700)] {
+        vorg_table.write_u16::<BigEndian>(glyph_id).unwrap();
+        vorg_table.write_i16::<BigEndian>(vert_origin_y).unwrap();
+    }
+    let mut tables = font_tables(None);
+    tables.push((vorg::TAG, vorg_table));
+    let bytes = sfnt::sfnt(&tables);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+
+    let origins: Vec<_> = (0..11).map(|glyph_id| font.vertical_origin_y(glyph_id)).collect();
+    assert_eq!(origins, [
+        Some(880), Some(880), Some(900), Some(880), Some(880), Some(-40),
+        Some(880), Some(880), Some(880), Some(700), Some(880),
+    ]);
+
+    let bytes = sfnt::sfnt(&font_tables(None));
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.vertical_origin_y(0), None);
+}
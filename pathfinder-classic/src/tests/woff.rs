@@ -0,0 +1,51 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, ByteOrder};
+use error::FontError;
+use font::Font;
+use tests::sfnt;
+
+fn woff_bytes() -> Vec<u8> {
+    sfnt::woff(&sfnt::required_tables(sfnt::cmap_table(&[
+        (3, 1, sfnt::cmap_format_4(&[(0x41, 0x5a, -0x40)])),
+    ])))
+}
+
+#[test]
+fn woff_fonts_are_unwrapped() {
+    let bytes = woff_bytes();
+    assert_eq!(Font::collection_size(&bytes), Ok(1));
+
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_for_char('A'), Ok(1));
+    assert_eq!(font.glyph_for_char('Z'), Ok(26));
+    assert_eq!(font.advance_width(1), Ok(500));
+
+    let mut buffer = vec![];
+    assert_eq!(Font::from_collection_index(&bytes, 1, &mut buffer).err(),
+               Some(FontError::FontIndexOutOfBounds));
+}
+
+#[test]
+fn woff_tables_must_not_grow_when_compressed() {
+    // Claim that the first table in the directory, which follows the header, grew when it was
+    // compressed.
+    let mut bytes = woff_bytes();
+    let orig_length = BigEndian::read_u32(&bytes[56..]);
+    BigEndian::write_u32(&mut bytes[52..], orig_length + 4);
+
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::Failed));
+}
+
+#[test]
+fn truncated_woff_fonts_are_rejected() {
+    let mut bytes = woff_bytes();
+    let length = bytes.len();
+    bytes.truncate(length - 8);
+
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::UnexpectedEof));
+}
@@ -0,0 +1,352 @@
+/* Any copyright is dedicated to the Public Domain.
+ * http://creativecommons.org/publicdomain/zero/1.0/ */
+
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use containers::woff2;
+use error::FontError;
+use font::{Font, PointKind};
+use tables::{cmap, glyf, head, hhea, hmtx, loca, maxp, os_2};
+use tests::sfnt;
+
+/// Wraps data in a Brotli stream made of uncompressed meta-blocks.
+fn brotli_stream(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+
+    // A window size of 16 bits.
+    writer.write(0, 1);
+    for block in data.chunks(0x10000) {
+        let nibbles = if block.len() - 1 < 0x10000 {
+            4
+        } else if block.len() - 1 < 0x100000 {
+            5
+        } else {
+            6
+        };
+        writer.write(0, 1);
+        writer.write(nibbles - 4, 2);
+        writer.write((block.len() - 1) as u32, nibbles * 4);
+        writer.write(1, 1);
+        writer.align();
+        writer.bytes.extend_from_slice(block);
+    }
+
+    // An empty last meta-block.
+    writer.write(1, 1);
+    writer.write(1, 1);
+    writer.bytes
+}
+
+/// Writes bits least significant first, as Brotli reads them.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: usize,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: vec![],
+            bit: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bit_count: u32) {
+        for bit_index in 0..bit_count {
+            if self.bit % 8 == 0 {
+                self.bytes.push(0)
+            }
+            let bit = ((value >> bit_index) & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (self.bit % 8);
+            self.bit += 1
+        }
+    }
+
+    fn align(&mut self) {
+        self.bit = self.bytes.len() * 8
+    }
+}
+
+fn write_uint_base_128(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut rest = value >> 7;
+    while rest != 0 {
+        groups.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7
+    }
+    groups.reverse();
+    bytes.extend_from_slice(&groups)
+}
+
+/// Builds a `.woff2` file out of `(tag, transformed original length, table)` entries, in order.
+///
+/// A table with an original length is transformed and the original length is that of the table
+/// it decodes to. The rest are stored as they are.
+fn woff2(tables: &[(u32, Option<u32>, Vec<u8>)]) -> Vec<u8> {
+    let known_tags = [cmap::TAG, head::TAG, hhea::TAG, hmtx::TAG, maxp::TAG];
+
+    let mut directory = vec![];
+    let mut data = vec![];
+    let mut total_sfnt_size = 12 + 16 * tables.len() as u32;
+    for &(tag, orig_length, ref table) in tables {
+        total_sfnt_size += (orig_length.unwrap_or(table.len() as u32) + 3) & !3;
+        let transform_version = match (tag, orig_length) {
+            (glyf::TAG, None) | (loca::TAG, None) => 3,
+            (hmtx::TAG, Some(_)) => 1,
+            _ => 0,
+        };
+        match known_tags.iter().position(|&known_tag| known_tag == tag) {
+            Some(tag_index) => directory.push(tag_index as u8 | (transform_version << 6)),
+            None if tag == glyf::TAG => directory.push(10 | (transform_version << 6)),
+            None if tag == loca::TAG => directory.push(11 | (transform_version << 6)),
+            None => {
+                directory.push(63 | (transform_version << 6));
+                directory.write_u32::<BigEndian>(tag).unwrap();
+            }
+        }
+        match orig_length {
+            None => write_uint_base_128(&mut directory, table.len() as u32),
+            Some(orig_length) => {
+                write_uint_base_128(&mut directory, orig_length);
+                write_uint_base_128(&mut directory, table.len() as u32);
+            }
+        }
+        data.extend_from_slice(table)
+    }
+    let compressed = brotli_stream(&data);
+
+    let mut font = vec![];
+    font.write_u32::<BigEndian>(woff2::MAGIC_NUMBER).unwrap();
+    font.write_u32::<BigEndian>(0x10000).unwrap();
+    font.write_u32::<BigEndian>((48 + directory.len() + compressed.len()) as u32).unwrap();
+    font.write_u16::<BigEndian>(tables.len() as u16).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    font.write_u32::<BigEndian>(total_sfnt_size).unwrap();
+    font.write_u32::<BigEndian>(compressed.len() as u32).unwrap();
+    font.write_u16::<BigEndian>(1).unwrap();
+    font.write_u16::<BigEndian>(0).unwrap();
+    for _ in 0..5 {
+        font.write_u32::<BigEndian>(0).unwrap();
+    }
+    font.extend_from_slice(&directory);
+    font.extend_from_slice(&compressed);
+    font
+}
+
+fn cmap_table() -> Vec<u8> {
+    sfnt::cmap_table(&[(3, 1, sfnt::cmap_format_4(&[(0x41, 0x5a, -0x40)]))])
+}
+
+/// Builds a transformed `glyf` table holding an empty glyph, a simple glyph with the points
+/// (100, 0), (300, 0), (300, 50) off the curve, and (100, 700), and a composite glyph made of the
+/// simple glyph moved by (10, 20).
+fn transformed_glyf_table(mark_empty_glyph_bbox: bool) -> Vec<u8> {
+    let mut n_contour_stream = vec![];
+    for &n_contours in &[0, 1, -1] {
+        n_contour_stream.write_i16::<BigEndian>(n_contours).unwrap();
+    }
+    let n_points_stream = vec![4];
+
+    // Two 4-byte triplets with positive deltas, a 1-byte vertical off-curve triplet, and a 4-byte
+    // triplet with a negative x delta, followed by the instruction length.
+    let flag_stream = vec![127, 127, 0x81, 126];
+    let glyph_stream = vec![0, 100, 0, 0, 0, 200, 0, 0, 50, 0, 200, 0x02, 0x8a, 0];
+
+    let mut composite_stream = vec![];
+    composite_stream.write_u16::<BigEndian>(0x0002).unwrap();
+    composite_stream.write_u16::<BigEndian>(1).unwrap();
+    composite_stream.extend_from_slice(&[10, 20]);
+
+    let mut bbox_stream = vec![if mark_empty_glyph_bbox { 0xa0 } else { 0x20 }, 0, 0, 0];
+    for &value in &[110, 20, 310, 720] {
+        bbox_stream.write_i16::<BigEndian>(value).unwrap();
+    }
+
+    transformed_glyf_table_from_streams(3, &[n_contour_stream, n_points_stream, flag_stream,
+                                            glyph_stream, composite_stream, bbox_stream, vec![]])
+}
+
+/// Builds a transformed `glyf` table with short offsets out of its seven streams.
+fn transformed_glyf_table_from_streams(num_glyphs: u16, streams: &[Vec<u8>]) -> Vec<u8> {
+    let mut table = vec![];
+    for &value in &[0, 0, num_glyphs, 0] {
+        table.write_u16::<BigEndian>(value).unwrap();
+    }
+    for stream in streams {
+        table.write_u32::<BigEndian>(stream.len() as u32).unwrap();
+    }
+    for stream in streams {
+        table.extend_from_slice(stream)
+    }
+    table
+}
+
+/// Builds a font with a single glyph, a contour whose points move horizontally by the given
+/// amounts, in a transformed `glyf` table.
+fn horizontal_glyph_font_bytes(x_deltas: &[i32]) -> Vec<u8> {
+    let mut n_points_stream = vec![253];
+    n_points_stream.write_u16::<BigEndian>(x_deltas.len() as u16).unwrap();
+
+    let (mut flag_stream, mut glyph_stream) = (vec![], vec![]);
+    for &x_delta in x_deltas {
+        flag_stream.push(if x_delta >= 0 { 127 } else { 126 });
+        glyph_stream.write_u16::<BigEndian>(x_delta.abs() as u16).unwrap();
+        glyph_stream.write_u16::<BigEndian>(0).unwrap();
+    }
+    glyph_stream.push(0);
+
+    let glyf_table = transformed_glyf_table_from_streams(1, &[
+        vec![0, 1], n_points_stream, flag_stream, glyph_stream, vec![], vec![0; 4], vec![],
+    ]);
+    let glyf_length = glyf_table.len() as u32;
+    woff2(&[
+        (cmap::TAG, None, cmap_table()),
+        (head::TAG, None, sfnt::head_table()),
+        (hhea::TAG, None, sfnt::hhea_table(1)),
+        (hmtx::TAG, None, sfnt::hmtx_table(&[(500, 0)])),
+        (maxp::TAG, None, sfnt::maxp_table(1)),
+        (os_2::TAG, None, sfnt::os_2_table()),
+        (glyf::TAG, Some(glyf_length), glyf_table),
+        (loca::TAG, Some(4), vec![]),
+    ])
+}
+
+/// Builds a font whose `glyf`, `loca`, and `hmtx` tables are transformed. The left side bearings
+/// of the proportional glyphs are left out.
+fn transformed_font_bytes(mark_empty_glyph_bbox: bool) -> Vec<u8> {
+    let mut hmtx_table = vec![1];
+    for &advance_width in &[500, 600] {
+        hmtx_table.write_u16::<BigEndian>(advance_width).unwrap();
+    }
+    hmtx_table.write_i16::<BigEndian>(5).unwrap();
+
+    woff2(&[
+        (cmap::TAG, None, cmap_table()),
+        (head::TAG, None, sfnt::head_table()),
+        (hhea::TAG, None, sfnt::hhea_table(2)),
+        (maxp::TAG, None, sfnt::maxp_table(3)),
+        (os_2::TAG, None, sfnt::os_2_table()),
+        (glyf::TAG, Some(40), transformed_glyf_table(mark_empty_glyph_bbox)),
+        (loca::TAG, Some(8), vec![]),
+        (hmtx::TAG, Some(10), hmtx_table),
+    ])
+}
+
+#[test]
+fn woff2_fonts_are_unwrapped() {
+    let tables: Vec<_> = sfnt::required_tables(cmap_table()).into_iter().map(|(tag, table)| {
+        (tag, None, table)
+    }).collect();
+    let bytes = woff2(&tables);
+    assert_eq!(Font::collection_size(&bytes), Ok(1));
+
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert_eq!(font.glyph_for_char('A'), Ok(1));
+    assert_eq!(font.glyph_for_char('Z'), Ok(26));
+    assert_eq!(font.advance_width(1), Ok(500));
+    assert_eq!(font.units_per_em(), 1000);
+}
+
+#[test]
+fn transformed_glyf_and_hmtx_tables_are_reconstructed() {
+    let bytes = transformed_font_bytes(false);
+    let mut buffer = vec![];
+    let font = Font::new(&bytes, &mut buffer).unwrap();
+    assert!(!font.has_long_glyph_offsets());
+
+    assert_eq!(font.glyph_contours(0), Ok(vec![]));
+    let contours = font.glyph_contours(1).unwrap();
+    assert_eq!(contours.len(), 1);
+    let points: Vec<_> = contours[0].iter().map(|point| {
+        (point.position.x, point.position.y, point.kind)
+    }).collect();
+    assert_eq!(points, vec![
+        (100, 0, PointKind::OnCurve),
+        (300, 0, PointKind::OnCurve),
+        (300, 50, PointKind::QuadControl),
+        (100, 700, PointKind::OnCurve),
+        (100, 0, PointKind::OnCurve),
+    ]);
+    let bounds = font.glyph_bounds(1).unwrap();
+    assert_eq!((bounds.left, bounds.bottom, bounds.right, bounds.top), (100, 0, 300, 700));
+
+    let composite_points: Vec<_> = font.glyph_contours(2).unwrap()[0].iter().map(|point| {
+        (point.position.x, point.position.y)
+    }).collect();
+    assert_eq!(composite_points, vec![(110, 20), (310, 20), (310, 70), (110, 720), (110, 20)]);
+
+    // The left side bearings of the proportional glyphs come from the bounding boxes.
+    assert_eq!(font.advance_width(1), Ok(600));
+    assert_eq!(font.left_side_bearing(0), Ok(0));
+    assert_eq!(font.left_side_bearing(1), Ok(100));
+    assert_eq!(font.advance_width(2), Ok(600));
+    assert_eq!(font.left_side_bearing(2), Ok(5));
+}
+
+#[test]
+fn empty_glyphs_must_not_have_bounding_boxes() {
+    let bytes = transformed_font_bytes(true);
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::Failed));
+}
+
+#[test]
+fn transformed_glyf_tables_must_have_a_whole_header() {
+    let bytes = woff2(&[
+        (cmap::TAG, None, cmap_table()),
+        (head::TAG, None, sfnt::head_table()),
+        (maxp::TAG, None, sfnt::maxp_table(1)),
+        (glyf::TAG, Some(0), transformed_glyf_table(false)[..20].to_vec()),
+        (loca::TAG, Some(4), vec![]),
+    ]);
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::UnexpectedEof));
+}
+
+#[test]
+fn glyph_coordinates_must_not_overflow() {
+    let bytes = horizontal_glyph_font_bytes(&vec![0xffff; 0x8001]);
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::Failed));
+}
+
+#[test]
+fn reconstructed_glyf_tables_must_fit_short_offsets() {
+    // Every point takes three bytes, so this glyph is too long for short offsets to reach its end.
+    let x_deltas: Vec<_> = (0..0xffff).map(|index| if index % 2 == 0 { 300 } else { -300 })
+                                      .collect();
+    let bytes = horizontal_glyph_font_bytes(&x_deltas);
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::Failed));
+
+    // A glyph that fits is reconstructed.
+    let bytes = horizontal_glyph_font_bytes(&x_deltas[..1000]);
+    let mut buffer = vec![];
+    assert!(Font::new(&bytes, &mut buffer).is_ok());
+}
+
+#[test]
+fn woff2_tables_must_fit_in_the_font_they_make_up() {
+    let mut bytes = transformed_font_bytes(false);
+    BigEndian::write_u32(&mut bytes[16..], 64);
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::Failed));
+}
+
+#[test]
+fn woff2_collections_are_rejected() {
+    let mut bytes = woff2(&[(cmap::TAG, None, cmap_table())]);
+    bytes[4..8].copy_from_slice(b"ttcf");
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::UnknownFormat));
+}
+
+#[test]
+fn truncated_woff2_fonts_are_rejected() {
+    let mut bytes = transformed_font_bytes(false);
+    let length = bytes.len();
+    bytes.truncate(length - 8);
+
+    let mut buffer = vec![];
+    assert_eq!(Font::new(&bytes, &mut buffer).err(), Some(FontError::UnexpectedEof));
+}
@@ -43,10 +43,7 @@ impl Typesetter {
 
     pub fn add_text(&mut self, font: &Font, point_size: f32, string: &str) {
         // TODO(pcwalton): Cache this mapping.
-        let mut chars: Vec<char> = string.chars().collect();
-        chars.push(' ');
-        chars.sort();
-        let codepoint_ranges = CodepointRanges::from_sorted_chars(&chars);
+        let codepoint_ranges: CodepointRanges = string.chars().chain(Some(' ')).collect();
         let glyph_mapping = font.glyph_mapping_for_codepoint_ranges(&codepoint_ranges.ranges)
                                 .unwrap();
 